@@ -0,0 +1,982 @@
+use crate::{
+    core3d::{color::Color, color_rgb::ColorRGB},
+    graphics2d::canvas::Canvas,
+};
+
+use super::image_error::ImageError;
+
+/// Magic bytes every Windows bitmap file starts with
+pub const BMP_SIGNATURE: &[u8] = b"BM";
+const FILE_HEADER_SIZE: u32 = 14;
+const CORE_HEADER_SIZE: u32 = 12;
+const INFO_HEADER_SIZE: u32 = 40;
+/// Version 4 header, needed to declare an alpha channel
+const V4_HEADER_SIZE: u32 = 108;
+const BI_RGB: u32 = 0;
+const BI_RLE8: u32 = 1;
+const BI_RLE4: u32 = 2;
+const BI_BITFIELDS: u32 = 3;
+/// Color space tag `sRGB` of version 4 headers
+const LCS_SRGB: u32 = 0x7352_4742;
+/// 72 dpi, the resolution most tools assume
+const PIXELS_PER_METRE: u32 = 2835;
+const RLE_MAX_COUNT: usize = 255;
+
+/// Order in which the rows of pixels are stored in a file
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum RowOrder {
+    /// Last row first, the traditional layout of bitmaps
+    #[default]
+    BottomUp,
+    TopDown,
+}
+
+/// Encoding of the pixel data
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    #[default]
+    None,
+    /// 8 bit palette indices with run length encoding
+    RLE,
+}
+
+#[derive(Default, Debug)]
+pub struct BMP {
+    pub width: u32,
+    pub height: u32,
+    /// Whether the alpha channel is written, making the file a 32 bit bitmap
+    pub alpha: bool,
+    pub row_order: RowOrder,
+
+    /// RGBA pixels row by row, starting at the top
+    pub pixels: Vec<[u8; 4]>,
+}
+
+impl BMP {
+    /// Creates a new bottom-up bmp
+    #[must_use]
+    pub const fn new(width: u32, height: u32, alpha: bool, pixels: Vec<[u8; 4]>) -> Self {
+        Self {
+            width,
+            height,
+            alpha,
+            row_order: RowOrder::BottomUp,
+            pixels,
+        }
+    }
+
+    /// Creates a new bmp from a canvas, clamping each channel to 0..1 before scaling it to 255
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::bmp::BMP;
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(2, 1);
+    /// canvas.set_pixel_at(0, 0, Color::new(1.0, 0.5, -1.0));
+    /// let bmp = BMP::from_canvas(&canvas, true);
+    /// assert_eq!(vec![[255, 128, 0, 255], [0, 0, 0, 0]], bmp.pixels);
+    /// ```
+    #[must_use]
+    pub fn from_canvas(canvas: &Canvas, alpha: bool) -> Self {
+        Self::new(
+            canvas.width,
+            canvas.height,
+            alpha,
+            canvas
+                .raw_buffer
+                .iter()
+                .map(|c| {
+                    [c.r(), c.g(), c.b(), c.a()]
+                        .map(|c| f32::round(num::clamp(c, 0.0, 1.0) * 255.0) as u8)
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the distinct colors in order of appearance, if there are few enough for an 8 bit palette
+    fn get_palette(&self) -> Option<Vec<[u8; 4]>> {
+        let mut palette = Vec::new();
+        for pixel in &self.pixels {
+            let color = [pixel[0], pixel[1], pixel[2], 0];
+            if !palette.contains(&color) {
+                if palette.len() == 256 {
+                    return None;
+                }
+                palette.push(color);
+            }
+        }
+        Some(palette)
+    }
+
+    /// Returns the pixel data of an uncompressed bitmap, padding each row to a multiple of 4 bytes
+    fn get_uncompressed_rows(&self) -> Vec<u8> {
+        let channels = if self.alpha { 4 } else { 3 };
+        let stride = row_size(self.width, channels * 8);
+        let mut bytes = Vec::with_capacity(stride * self.height as usize);
+        for row in self.get_rows() {
+            for pixel in row {
+                bytes.extend(&[pixel[2], pixel[1], pixel[0], pixel[3]][..channels as usize]);
+            }
+            bytes.resize(bytes.len() + stride - row.len() * channels as usize, 0);
+        }
+        bytes
+    }
+
+    /// Rows in the order they are stored in
+    fn get_rows(&self) -> Vec<&[[u8; 4]]> {
+        if self.width == 0 {
+            return Vec::new();
+        }
+        let rows = self.pixels.chunks(self.width as usize);
+        match self.row_order {
+            RowOrder::BottomUp => rows.rev().collect(),
+            RowOrder::TopDown => rows.collect(),
+        }
+    }
+
+    /// Encodes the image as a bitmap file
+    ///
+    /// Run length encoding needs an 8 bit palette, so images with alpha or with more than 256 colors are
+    /// written uncompressed instead. Compressed bitmaps are always bottom-up as the format requires.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::bmp::{Compression, BMP};
+    /// let bmp = BMP::new(1, 1, false, vec![[255, 128, 0, 255]]);
+    /// let bytes = bmp.to_bytes(Compression::None);
+    /// assert_eq!(14 + 40 + 4, bytes.len());
+    /// assert_eq!([0, 128, 255, 0], bytes[54..]);
+    /// ```
+    #[must_use]
+    pub fn to_bytes(&self, compression: Compression) -> Vec<u8> {
+        let palette = match compression {
+            Compression::RLE if !self.alpha => self.get_palette(),
+            _ => None,
+        };
+        let header_size = if self.alpha {
+            V4_HEADER_SIZE
+        } else {
+            INFO_HEADER_SIZE
+        };
+        let (bits, kind, height, pixel_data) = match &palette {
+            Some(palette) => {
+                let indices = self
+                    .pixels
+                    .iter()
+                    .map(|p| {
+                        palette
+                            .iter()
+                            .position(|c| c[..3] == p[..3])
+                            .unwrap_or_default() as u8
+                    })
+                    .collect::<Vec<_>>();
+                (
+                    8_u16,
+                    BI_RLE8,
+                    self.height as i32,
+                    rle::encode(&indices, self.width),
+                )
+            }
+            None => {
+                let height = match self.row_order {
+                    RowOrder::BottomUp => self.height as i32,
+                    RowOrder::TopDown => -(self.height as i32),
+                };
+                let (bits, kind) = if self.alpha {
+                    (32_u16, BI_BITFIELDS)
+                } else {
+                    (24_u16, BI_RGB)
+                };
+                (bits, kind, height, self.get_uncompressed_rows())
+            }
+        };
+        let palette = palette.unwrap_or_default();
+        let data_offset = FILE_HEADER_SIZE + header_size + 4 * palette.len() as u32;
+
+        let mut bytes = BMP_SIGNATURE.to_vec();
+        bytes.extend((data_offset + pixel_data.len() as u32).to_le_bytes());
+        bytes.extend(0_u32.to_le_bytes());
+        bytes.extend(data_offset.to_le_bytes());
+
+        bytes.extend(header_size.to_le_bytes());
+        bytes.extend((self.width as i32).to_le_bytes());
+        bytes.extend(height.to_le_bytes());
+        bytes.extend(1_u16.to_le_bytes());
+        bytes.extend(bits.to_le_bytes());
+        bytes.extend(kind.to_le_bytes());
+        bytes.extend((pixel_data.len() as u32).to_le_bytes());
+        bytes.extend(PIXELS_PER_METRE.to_le_bytes());
+        bytes.extend(PIXELS_PER_METRE.to_le_bytes());
+        bytes.extend((palette.len() as u32).to_le_bytes());
+        bytes.extend(0_u32.to_le_bytes());
+        if self.alpha {
+            for mask in [0x00FF_0000_u32, 0x0000_FF00, 0x0000_00FF, 0xFF00_0000] {
+                bytes.extend(mask.to_le_bytes());
+            }
+            bytes.extend(LCS_SRGB.to_le_bytes());
+            // Endpoints and gamma are unused for sRGB
+            bytes.resize(bytes.len() + 36 + 12, 0);
+        }
+
+        for color in palette {
+            bytes.extend([color[2], color[1], color[0], 0]);
+        }
+        bytes.extend(pixel_data);
+        bytes
+    }
+}
+
+/// Number of bytes in a row of pixels, which is padded to a multiple of 4 bytes
+const fn row_size(width: u32, bits: u32) -> usize {
+    (width as usize * bits as usize).div_ceil(32) * 4
+}
+
+#[cfg(test)]
+mod tests_bmp {
+    use super::*;
+
+    #[test]
+    fn new() {
+        let bmp = BMP::new(2, 3, true, vec![[0; 4]; 6]);
+        assert_eq!(2, bmp.width);
+        assert_eq!(3, bmp.height);
+        assert!(bmp.alpha);
+        assert_eq!(RowOrder::BottomUp, bmp.row_order);
+    }
+
+    #[test]
+    fn rows_are_padded() {
+        assert_eq!(4, row_size(1, 24));
+        assert_eq!(8, row_size(2, 24));
+        assert_eq!(12, row_size(3, 32));
+        assert_eq!(4, row_size(9, 1));
+        assert_eq!(4, row_size(8, 4));
+        assert_eq!(8, row_size(9, 4));
+
+        let bmp = BMP::new(1, 2, false, vec![[1, 2, 3, 255], [4, 5, 6, 255]]);
+        assert_eq!(vec![6, 5, 4, 0, 3, 2, 1, 0], bmp.get_uncompressed_rows());
+    }
+
+    #[test]
+    fn to_bytes_top_down_with_alpha() {
+        let mut bmp = BMP::new(1, 2, true, vec![[1, 2, 3, 4], [5, 6, 7, 8]]);
+        bmp.row_order = RowOrder::TopDown;
+        let bytes = bmp.to_bytes(Compression::None);
+        assert_eq!(14 + 108 + 8, bytes.len());
+        assert_eq!(&b"BM"[..], &bytes[..2]);
+        assert_eq!(130_u32.to_le_bytes(), bytes[2..6]);
+        assert_eq!(122_u32.to_le_bytes(), bytes[10..14]);
+        assert_eq!((-2_i32).to_le_bytes(), bytes[22..26]);
+        assert_eq!(32_u16.to_le_bytes(), bytes[28..30]);
+        assert_eq!(BI_BITFIELDS.to_le_bytes(), bytes[30..34]);
+        assert_eq!(0xFF00_0000_u32.to_le_bytes(), bytes[66..70]);
+        assert_eq!([3, 2, 1, 4, 7, 6, 5, 8], bytes[122..]);
+    }
+
+    #[test]
+    fn to_bytes_rle() {
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        let bmp = BMP::new(4, 2, false, vec![red, red, red, red, blue, red, blue, blue]);
+        let bytes = bmp.to_bytes(Compression::RLE);
+        assert_eq!(BI_RLE8.to_le_bytes(), bytes[30..34]);
+        assert_eq!(8_u16.to_le_bytes(), bytes[28..30]);
+        assert_eq!(2_u32.to_le_bytes(), bytes[46..50]);
+        // Palette in order of appearance, then the bottom row first
+        assert_eq!([0, 0, 255, 0, 255, 0, 0, 0], bytes[54..62]);
+        assert_eq!([1, 1, 1, 0, 2, 1, 0, 0, 4, 0, 0, 1], bytes[62..]);
+    }
+
+    #[test]
+    fn rle_falls_back_to_uncompressed() {
+        let pixels = (0..=256_u32)
+            .map(|i| [i as u8, (i >> 8) as u8, 0, 255])
+            .collect();
+        let bmp = BMP::new(257, 1, false, pixels);
+        assert_eq!(BI_RGB.to_le_bytes(), bmp.to_bytes(Compression::RLE)[30..34]);
+
+        let bmp = BMP::new(1, 1, true, vec![[0; 4]]);
+        assert_eq!(
+            BI_BITFIELDS.to_le_bytes(),
+            bmp.to_bytes(Compression::RLE)[30..34]
+        );
+    }
+}
+
+mod rle {
+    use super::RLE_MAX_COUNT;
+    use crate::asset_types::image_error::{pixel_count, ImageError};
+
+    /// Encodes palette indices row by row from the bottom, ending each row with an end of line marker
+    pub fn encode(indices: &[u8], width: u32) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        if width == 0 {
+            return vec![0, 1];
+        }
+        for row in indices.chunks(width as usize).rev() {
+            let mut start = 0;
+            while start < row.len() {
+                let run = row[start..]
+                    .iter()
+                    .take(RLE_MAX_COUNT)
+                    .take_while(|&&i| i == row[start])
+                    .count();
+                if run >= 2 {
+                    encoded.extend([run as u8, row[start]]);
+                    start += run;
+                    continue;
+                }
+                let mut end = start + 1;
+                while end < row.len()
+                    && end - start < RLE_MAX_COUNT
+                    && !(end + 1 < row.len() && row[end] == row[end + 1])
+                {
+                    end += 1;
+                }
+                let literal = &row[start..end];
+                // Absolute mode needs at least 3 pixels, shorter literals are written as runs of 1
+                if literal.len() < 3 {
+                    literal.iter().for_each(|&i| encoded.extend([1, i]));
+                } else {
+                    encoded.extend([0, literal.len() as u8]);
+                    encoded.extend(literal);
+                    if literal.len() % 2 == 1 {
+                        encoded.push(0);
+                    }
+                }
+                start = end;
+            }
+            encoded.extend([0, 0]);
+        }
+        // Replace the last end of line with the end of bitmap marker
+        if let Some(last) = encoded.last_mut() {
+            *last = 1;
+        }
+        encoded
+    }
+
+    /// Decodes 8 or 4 bit run length encoded palette indices into rows starting at the bottom
+    ///
+    /// Pixels skipped by delta markers or a premature end keep index 0.
+    pub fn decode(
+        data: &[u8],
+        width: usize,
+        height: usize,
+        bits: u32,
+    ) -> Result<Vec<u8>, ImageError> {
+        let count = pixel_count(width as u64, height as u64)?;
+        let mut indices = vec![0; count];
+        let nibble = |byte: u8, k: usize| {
+            if bits == 4 {
+                if k.is_multiple_of(2) {
+                    byte >> 4
+                } else {
+                    byte & 0x0F
+                }
+            } else {
+                byte
+            }
+        };
+        let (mut x, mut y, mut i) = (0, 0, 0);
+        let mut set = |x: usize, y: usize, index: u8| {
+            if x < width && y < height {
+                indices[y * width + x] = index;
+            }
+        };
+        while let Some(&[count, value]) = data.get(i..i + 2) {
+            i += 2;
+            if count > 0 {
+                for k in 0..usize::from(count) {
+                    set(x + k, y, nibble(value, k));
+                }
+                x += usize::from(count);
+                continue;
+            }
+            match value {
+                0 => (x, y) = (0, y + 1),
+                1 => break,
+                2 => {
+                    let delta = data.get(i..i + 2).ok_or(ImageError::UnexpectedEof)?;
+                    (x, y) = (x + usize::from(delta[0]), y + usize::from(delta[1]));
+                    i += 2;
+                }
+                length => {
+                    let length = usize::from(length);
+                    let size = if bits == 4 {
+                        length.div_ceil(2)
+                    } else {
+                        length
+                    };
+                    let literal = data.get(i..i + size).ok_or(ImageError::UnexpectedEof)?;
+                    for k in 0..length {
+                        let byte = if bits == 4 {
+                            literal[k / 2]
+                        } else {
+                            literal[k]
+                        };
+                        set(x + k, y, nibble(byte, k));
+                    }
+                    x += length;
+                    // Literals are padded to a whole number of 16 bit words
+                    i += size + size % 2;
+                }
+            }
+            if y >= height {
+                break;
+            }
+        }
+        Ok(indices)
+    }
+
+    #[cfg(test)]
+    mod tests_rle {
+        use super::*;
+
+        #[test]
+        fn encode_runs_and_literals() {
+            assert_eq!(vec![4, 7, 0, 1], encode(&[7, 7, 7, 7], 4));
+            assert_eq!(vec![1, 1, 1, 2, 0, 1], encode(&[1, 2], 2));
+            assert_eq!(
+                vec![0, 3, 1, 2, 3, 0, 2, 9, 0, 1],
+                encode(&[1, 2, 3, 9, 9], 5)
+            );
+            assert_eq!(vec![0, 4, 1, 2, 3, 4, 0, 1], encode(&[1, 2, 3, 4], 4));
+            assert_eq!(vec![255, 0, 45, 0, 0, 1], encode(&[0; 300], 300));
+            assert_eq!(vec![0, 1], encode(&[], 0));
+        }
+
+        #[test]
+        fn decode_rle8() {
+            let data = [3, 5, 0, 3, 1, 2, 3, 0, 0, 0, 0, 2, 2, 0, 2, 9, 0, 1];
+            assert_eq!(
+                Ok(vec![5, 5, 5, 1, 2, 3, 0, 0, 9, 9, 0, 0]),
+                decode(&data, 6, 2, 8)
+            );
+        }
+
+        #[test]
+        fn decode_rle4() {
+            let data = [5, 0x12, 0, 3, 0x34, 0x50, 0, 0, 0, 1];
+            assert_eq!(Ok(vec![1, 2, 1, 2, 1, 3, 4, 5]), decode(&data, 8, 1, 4));
+        }
+
+        #[test]
+        fn decode_delta_and_eof() {
+            let data = [0, 2, 1, 1, 1, 7];
+            assert_eq!(Ok(vec![0, 0, 0, 7]), decode(&data, 2, 2, 8));
+            assert_eq!(
+                Err(ImageError::UnexpectedEof),
+                decode(&[0, 4, 1, 2], 4, 1, 8)
+            );
+        }
+
+        #[test]
+        fn decode_absurd_size() {
+            assert!(matches!(
+                decode(&[0, 1], usize::MAX, 2, 8),
+                Err(ImageError::Unsupported(_))
+            ));
+            assert!(matches!(
+                decode(&[0, 1], 100_000, 100_000, 8),
+                Err(ImageError::Unsupported(_))
+            ));
+        }
+
+        #[test]
+        fn round_trip() {
+            let indices = [1, 1, 1, 2, 3, 4, 4, 5, 6, 7, 8, 8, 8, 8, 0, 9, 9, 1, 2, 3];
+            let encoded = encode(&indices, 10);
+            let decoded = decode(&encoded, 10, 2, 8).unwrap();
+            assert_eq!(indices[10..], decoded[..10]);
+            assert_eq!(indices[..10], decoded[10..]);
+        }
+    }
+}
+
+mod decoding {
+    use super::{
+        row_size, RowOrder, BI_BITFIELDS, BI_RGB, BI_RLE4, BI_RLE8, BMP, BMP_SIGNATURE,
+        CORE_HEADER_SIZE, FILE_HEADER_SIZE, INFO_HEADER_SIZE,
+    };
+    use crate::asset_types::image_error::{pixel_count, ImageError};
+
+    fn read_u16(bytes: &[u8], at: usize) -> Result<u16, ImageError> {
+        let b = bytes.get(at..at + 2).ok_or(ImageError::UnexpectedEof)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn read_u32(bytes: &[u8], at: usize) -> Result<u32, ImageError> {
+        let b = bytes.get(at..at + 4).ok_or(ImageError::UnexpectedEof)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// Extracts the channel selected by a bit mask and scales it to 8 bits
+    fn read_masked(value: u32, mask: u32) -> u8 {
+        if mask == 0 {
+            return 0;
+        }
+        let max = u64::from(mask >> mask.trailing_zeros());
+        let channel = u64::from((value & mask) >> mask.trailing_zeros());
+        ((channel * 255 + max / 2) / max) as u8
+    }
+
+    /// Masks of the red, green, blue and alpha channels of 16 and 32 bit pixels
+    fn read_masks(
+        bytes: &[u8],
+        header_size: u32,
+        bits: u16,
+        kind: u32,
+    ) -> Result<[u32; 4], ImageError> {
+        if kind != BI_BITFIELDS {
+            return Ok(match bits {
+                16 => [0x7C00, 0x03E0, 0x001F, 0],
+                _ => [0x00FF_0000, 0x0000_FF00, 0x0000_00FF, 0],
+            });
+        }
+        // Masks follow a plain info header, and are part of all later header versions
+        let at = (FILE_HEADER_SIZE + INFO_HEADER_SIZE) as usize;
+        let alpha = if header_size >= 56 {
+            read_u32(bytes, at + 12)?
+        } else {
+            0
+        };
+        Ok([
+            read_u32(bytes, at)?,
+            read_u32(bytes, at + 4)?,
+            read_u32(bytes, at + 8)?,
+            alpha,
+        ])
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<BMP, ImageError> {
+        if !bytes.starts_with(BMP_SIGNATURE) {
+            return Err(ImageError::InvalidSignature);
+        }
+        let data_offset = read_u32(bytes, 10)? as usize;
+        let header_size = read_u32(bytes, FILE_HEADER_SIZE as usize)?;
+        let at = FILE_HEADER_SIZE as usize + 4;
+
+        let (width, height, bits, kind, colors_used, entry_size) =
+            if header_size == CORE_HEADER_SIZE {
+                let (width, height) = (read_u16(bytes, at)?, read_u16(bytes, at + 2)?);
+                (
+                    i64::from(width),
+                    i64::from(height),
+                    read_u16(bytes, at + 6)?,
+                    BI_RGB,
+                    0,
+                    3,
+                )
+            } else if header_size >= INFO_HEADER_SIZE {
+                (
+                    i64::from(read_u32(bytes, at)? as i32),
+                    i64::from(read_u32(bytes, at + 4)? as i32),
+                    read_u16(bytes, at + 10)?,
+                    read_u32(bytes, at + 12)?,
+                    read_u32(bytes, at + 28)? as usize,
+                    4,
+                )
+            } else {
+                return Err(ImageError::Unsupported(format!(
+                    "header size {header_size}"
+                )));
+            };
+        let row_order = if height < 0 {
+            RowOrder::TopDown
+        } else {
+            RowOrder::BottomUp
+        };
+        let (Ok(width), Ok(height)) = (u32::try_from(width), u32::try_from(height.abs())) else {
+            return Err(ImageError::InvalidData(format!("width {width}")));
+        };
+        pixel_count(u64::from(width), u64::from(height))?;
+        match (kind, bits) {
+            (BI_RGB, 1 | 4 | 8 | 16 | 24 | 32)
+            | (BI_RLE8, 8)
+            | (BI_RLE4, 4)
+            | (BI_BITFIELDS, 16 | 32) => {}
+            _ => {
+                return Err(ImageError::Unsupported(format!(
+                    "compression {kind} with {bits} bits per pixel"
+                )))
+            }
+        }
+        if row_order == RowOrder::TopDown && (kind == BI_RLE8 || kind == BI_RLE4) {
+            return Err(ImageError::InvalidData(
+                "top-down compressed bitmap".to_string(),
+            ));
+        }
+
+        let masks = read_masks(bytes, header_size, bits, kind)?;
+        let palette = if bits <= 8 {
+            let masks_size = if kind == BI_BITFIELDS && header_size == INFO_HEADER_SIZE {
+                12
+            } else {
+                0
+            };
+            let start = (FILE_HEADER_SIZE + header_size) as usize + masks_size;
+            let count = if colors_used == 0 {
+                1 << bits
+            } else {
+                colors_used.min(1 << bits)
+            };
+            (0..count)
+                .map(|i| {
+                    let entry = bytes
+                        .get(start + i * entry_size..start + i * entry_size + 3)
+                        .ok_or(ImageError::UnexpectedEof)?;
+                    Ok([entry[2], entry[1], entry[0], 255])
+                })
+                .collect::<Result<Vec<_>, ImageError>>()?
+        } else {
+            Vec::new()
+        };
+        let from_palette = |index: u8| {
+            palette
+                .get(usize::from(index))
+                .copied()
+                .ok_or_else(|| ImageError::InvalidData(format!("palette index {index}")))
+        };
+
+        let (w, h) = (width as usize, height as usize);
+        let data = bytes.get(data_offset..).ok_or(ImageError::UnexpectedEof)?;
+        let uncompressed = kind != BI_RLE8 && kind != BI_RLE4;
+        // Uncompressed rows must all be there before room is made for their pixels
+        let stride = row_size(width, u32::from(bits));
+        if uncompressed && stride.checked_mul(h).is_none_or(|size| size > data.len()) {
+            return Err(ImageError::UnexpectedEof);
+        }
+        let mut rows = Vec::with_capacity(w * h);
+        if uncompressed {
+            for y in 0..h {
+                let row = data
+                    .get(y * stride..(y + 1) * stride)
+                    .ok_or(ImageError::UnexpectedEof)?;
+                for x in 0..w {
+                    let pixel = match bits {
+                        1 | 4 | 8 => {
+                            let bit = x * usize::from(bits);
+                            let shift = 8 - usize::from(bits) - bit % 8;
+                            from_palette((row[bit / 8] >> shift) & ((1 << bits) - 1) as u8)?
+                        }
+                        24 => [row[x * 3 + 2], row[x * 3 + 1], row[x * 3], 255],
+                        _ => {
+                            let value = if bits == 16 {
+                                u32::from(u16::from_le_bytes([row[x * 2], row[x * 2 + 1]]))
+                            } else {
+                                u32::from_le_bytes([
+                                    row[x * 4],
+                                    row[x * 4 + 1],
+                                    row[x * 4 + 2],
+                                    row[x * 4 + 3],
+                                ])
+                            };
+                            let alpha = if masks[3] == 0 {
+                                255
+                            } else {
+                                read_masked(value, masks[3])
+                            };
+                            [
+                                read_masked(value, masks[0]),
+                                read_masked(value, masks[1]),
+                                read_masked(value, masks[2]),
+                                alpha,
+                            ]
+                        }
+                    };
+                    rows.push(pixel);
+                }
+            }
+        } else {
+            let indices = super::rle::decode(data, w, h, u32::from(bits))?;
+            for index in indices {
+                rows.push(from_palette(index)?);
+            }
+        }
+
+        let pixels = match row_order {
+            RowOrder::TopDown => rows,
+            RowOrder::BottomUp if w > 0 => rows.chunks(w).rev().flatten().copied().collect(),
+            RowOrder::BottomUp => rows,
+        };
+        let mut bmp = BMP::new(width, height, masks[3] != 0, pixels);
+        bmp.row_order = row_order;
+        Ok(bmp)
+    }
+
+    #[cfg(test)]
+    mod tests_decoding {
+        use super::*;
+
+        #[test]
+        fn masked() {
+            assert_eq!(255, read_masked(0x7C00, 0x7C00));
+            assert_eq!(0, read_masked(0x03FF, 0x7C00));
+            assert_eq!(132, read_masked(0x0210, 0x03E0));
+            assert_eq!(0xAB, read_masked(0xAB00_0000, 0xFF00_0000));
+            assert_eq!(0, read_masked(0xFFFF_FFFF, 0));
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for BMP {
+    type Error = ImageError;
+
+    /// Decodes an uncompressed, bit field or run length encoded bitmap of any bit depth
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::bmp::{Compression, BMP};
+    /// let bmp = BMP::new(2, 1, false, vec![[1, 2, 3, 255], [4, 5, 6, 255]]);
+    /// let decoded = BMP::try_from(bmp.to_bytes(Compression::RLE).as_slice()).unwrap();
+    /// assert_eq!(bmp.pixels, decoded.pixels);
+    /// ```
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        decoding::decode(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests_try_from_bytes {
+    use super::*;
+
+    /// Builds a bitmap with a plain info header around the given palette and pixel data
+    fn bitmap(
+        width: i32,
+        height: i32,
+        bits: u16,
+        kind: u32,
+        palette: &[[u8; 4]],
+        data: &[u8],
+    ) -> Vec<u8> {
+        let offset = 14 + 40 + 4 * palette.len() as u32;
+        let mut bytes = b"BM".to_vec();
+        bytes.extend((offset + data.len() as u32).to_le_bytes());
+        bytes.extend(0_u32.to_le_bytes());
+        bytes.extend(offset.to_le_bytes());
+        bytes.extend(40_u32.to_le_bytes());
+        bytes.extend(width.to_le_bytes());
+        bytes.extend(height.to_le_bytes());
+        bytes.extend(1_u16.to_le_bytes());
+        bytes.extend(bits.to_le_bytes());
+        bytes.extend(kind.to_le_bytes());
+        bytes.extend([0; 12]);
+        bytes.extend((palette.len() as u32).to_le_bytes());
+        bytes.extend(0_u32.to_le_bytes());
+        palette.iter().for_each(|c| bytes.extend(c));
+        bytes.extend(data);
+        bytes
+    }
+
+    #[test]
+    fn round_trip() {
+        let pixels = (0..15_u8)
+            .map(|i| [i * 17, 255 - i * 17, i, i * 8])
+            .collect::<Vec<_>>();
+        for alpha in [false, true] {
+            for row_order in [RowOrder::BottomUp, RowOrder::TopDown] {
+                for compression in [Compression::None, Compression::RLE] {
+                    let mut bmp = BMP::new(5, 3, alpha, pixels.clone());
+                    bmp.row_order = row_order;
+                    let decoded = BMP::try_from(bmp.to_bytes(compression).as_slice()).unwrap();
+                    assert_eq!(5, decoded.width);
+                    assert_eq!(3, decoded.height);
+                    assert_eq!(alpha, decoded.alpha);
+                    for (expected, actual) in pixels.iter().zip(&decoded.pixels) {
+                        assert_eq!(expected[..3], actual[..3]);
+                        assert_eq!(if alpha { expected[3] } else { 255 }, actual[3]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn palette_bits() {
+        let palette = [[0, 0, 0, 0], [255, 255, 255, 0]];
+        let bytes = bitmap(
+            10,
+            1,
+            1,
+            BI_RGB,
+            &palette,
+            &[0b1010_0000, 0b0100_0000, 0, 0],
+        );
+        let bmp = BMP::try_from(bytes.as_slice()).unwrap();
+        let white = [255, 255, 255, 255];
+        let black = [0, 0, 0, 255];
+        assert_eq!(
+            vec![white, black, white, black, black, black, black, black, black, white],
+            bmp.pixels
+        );
+
+        let palette = [[0, 0, 255, 0], [0, 255, 0, 0], [255, 0, 0, 0]];
+        let bytes = bitmap(3, 1, 4, BI_RGB, &palette, &[0x01, 0x20, 0, 0]);
+        let bmp = BMP::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(
+            vec![[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]],
+            bmp.pixels
+        );
+    }
+
+    #[test]
+    fn sixteen_bits() {
+        let bytes = bitmap(2, 1, 16, BI_RGB, &[], &[0x00, 0x7C, 0x1F, 0x00]);
+        let bmp = BMP::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(vec![[255, 0, 0, 255], [0, 0, 255, 255]], bmp.pixels);
+        assert!(!bmp.alpha);
+    }
+
+    #[test]
+    fn rle4() {
+        let palette = [[0, 0, 0, 0], [255, 0, 0, 0]];
+        let bytes = bitmap(
+            3,
+            2,
+            4,
+            BI_RLE4,
+            &palette,
+            &[3, 0x10, 0, 0, 0, 3, 0x01, 0x00, 0, 1],
+        );
+        let bmp = BMP::try_from(bytes.as_slice()).unwrap();
+        let blue = [0, 0, 255, 255];
+        let black = [0, 0, 0, 255];
+        assert_eq!(vec![black, blue, black, blue, black, blue], bmp.pixels);
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            Err(ImageError::InvalidSignature),
+            BMP::try_from(&b"PM\0\0"[..]).map(|_| ())
+        );
+        assert_eq!(
+            Err(ImageError::UnexpectedEof),
+            BMP::try_from(&bitmap(2, 2, 24, BI_RGB, &[], &[0; 12])[..]).map(|_| ())
+        );
+        assert!(matches!(
+            BMP::try_from(&bitmap(1, 1, 24, 4, &[], &[0; 4])[..]),
+            Err(ImageError::Unsupported(_))
+        ));
+        assert!(matches!(
+            BMP::try_from(&bitmap(1, -1, 8, BI_RLE8, &[[0; 4]], &[0, 1])[..]),
+            Err(ImageError::InvalidData(_))
+        ));
+        assert!(matches!(
+            BMP::try_from(&bitmap(1, 1, 8, BI_RGB, &[[0; 4]], &[1, 0, 0, 0])[..]),
+            Err(ImageError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn absurd_sizes() {
+        // Huge headers are rejected before any room is made for their pixels
+        assert!(matches!(
+            BMP::try_from(&bitmap(100_000, 100_000, 8, BI_RLE8, &[[0; 4]], &[0, 1])[..]),
+            Err(ImageError::Unsupported(_))
+        ));
+        assert_eq!(
+            Err(ImageError::UnexpectedEof),
+            BMP::try_from(&bitmap(20_000, 20_000, 32, BI_RGB, &[], &[0; 16])[..]).map(|_| ())
+        );
+        assert!(matches!(
+            BMP::try_from(&bitmap(i32::MAX, -i32::MAX, 32, BI_RGB, &[], &[0; 16])[..]),
+            Err(ImageError::Unsupported(_))
+        ));
+    }
+}
+
+impl From<&Canvas> for BMP {
+    /// Creates a new 24 bit bmp from a canvas, dropping the alpha channel
+    fn from(canvas: &Canvas) -> Self {
+        Self::from_canvas(canvas, false)
+    }
+}
+
+impl TryFrom<&BMP> for Canvas {
+    type Error = ImageError;
+
+    /// Creates a new canvas from a bmp, scaling samples to 0..1. Images without alpha are made opaque.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImageError::InvalidData`] if the number of pixels does not match the image size
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::bmp::BMP;
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let bmp = BMP::new(1, 1, false, vec![[255, 51, 0, 0]]);
+    /// let canvas = Canvas::try_from(&bmp).unwrap();
+    /// assert_eq!(Color::new(1.0, 0.2, 0.0), canvas.get_pixel_at(0, 0));
+    /// ```
+    fn try_from(bmp: &BMP) -> Result<Self, Self::Error> {
+        Self::from_buffer(
+            bmp.width,
+            bmp.height,
+            bmp.pixels
+                .iter()
+                .map(|pixel| {
+                    let alpha = if bmp.alpha {
+                        f32::from(pixel[3]) / 255.0
+                    } else {
+                        1.0
+                    };
+                    Color::new_with_alpha(
+                        f32::from(pixel[0]) / 255.0,
+                        f32::from(pixel[1]) / 255.0,
+                        f32::from(pixel[2]) / 255.0,
+                        alpha,
+                    )
+                })
+                .collect(),
+        )
+        .ok_or_else(|| {
+            ImageError::InvalidData("pixel count does not match the image size".to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_canvas {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.set_pixel_at(0, 0, Color::new(1.0, 0.2, 0.0));
+        canvas.set_pixel_at(2, 1, Color::new(0.4, 0.6, 0.8));
+        let bytes = BMP::from(&canvas).to_bytes(Compression::RLE);
+        let decoded = Canvas::try_from(&BMP::try_from(bytes.as_slice()).unwrap()).unwrap();
+        assert_eq!(3, decoded.width);
+        assert_eq!(Color::new(1.0, 0.2, 0.0), decoded.get_pixel_at(0, 0));
+        assert_eq!(Color::new(0.4, 0.6, 0.8), decoded.get_pixel_at(2, 1));
+        assert_eq!(Color::new(0.0, 0.0, 0.0), decoded.get_pixel_at(1, 1));
+    }
+
+    #[test]
+    fn alpha() {
+        let mut canvas = Canvas::new(1, 1);
+        let color = Color::new_with_alpha(0.2, 0.4, 0.6, 0.2);
+        canvas.set_pixel_at(0, 0, color);
+        let bmp = BMP::from_canvas(&canvas, true);
+        let decoded =
+            Canvas::try_from(&BMP::try_from(bmp.to_bytes(Compression::None).as_slice()).unwrap())
+                .unwrap();
+        assert_eq!(color, decoded.get_pixel_at(0, 0));
+        assert_eq!(0.2, decoded.get_pixel_at(0, 0).a());
+    }
+
+    #[test]
+    fn wider_than_u16() {
+        let bmp = BMP::new(70_000, 0, false, vec![]);
+        let canvas = Canvas::try_from(&bmp).unwrap();
+        assert_eq!((bmp.width, bmp.height), (canvas.width, canvas.height));
+    }
+}
@@ -0,0 +1,142 @@
+/// Lookup table for the reflected CRC-32 polynomial `0xEDB8_8320` used by PNG and zlib
+const CRC32_TABLE: [u32; 256] = make_crc32_table();
+
+const fn make_crc32_table() -> [u32; 256] {
+    let mut table = [0_u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 {
+                0xEDB8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+/// Running CRC-32 checksum, allowing the checksum to be fed in several parts
+#[derive(Copy, Clone, Debug)]
+pub struct Crc32 {
+    value: u32,
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crc32 {
+    /// Creates a new checksum with no bytes fed into it
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { value: 0xFFFF_FFFF }
+    }
+
+    /// Feeds more bytes into the checksum
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.value = bytes.iter().fold(self.value, |c, &b| {
+            CRC32_TABLE[((c ^ u32::from(b)) & 0xFF) as usize] ^ (c >> 8)
+        });
+    }
+
+    /// Returns the checksum of all the bytes fed so far
+    #[must_use]
+    pub const fn finish(&self) -> u32 {
+        self.value ^ 0xFFFF_FFFF
+    }
+}
+
+/// Calculates the CRC-32 checksum of the given bytes
+///
+/// # Examples
+///
+/// ```
+/// # use rusty_ray_tracer::asset_types::checksum::crc32;
+/// assert_eq!(0xCBF4_3926, crc32(b"123456789"));
+/// ```
+#[must_use]
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(bytes);
+    crc.finish()
+}
+
+#[cfg(test)]
+mod tests_crc32 {
+    use super::*;
+
+    #[test]
+    fn known_values() {
+        assert_eq!(0, crc32(b""));
+        assert_eq!(0xE8B7_BE43, crc32(b"a"));
+        assert_eq!(0xCBF4_3926, crc32(b"123456789"));
+        assert_eq!(0xAE42_6082, crc32(b"IEND"));
+    }
+
+    #[test]
+    fn incremental() {
+        let mut crc = Crc32::new();
+        crc.update(b"1234");
+        crc.update(b"");
+        crc.update(b"56789");
+        assert_eq!(crc32(b"123456789"), crc.finish());
+    }
+}
+
+/// Largest prime smaller than 2^16, the modulus of the Adler-32 sums
+const ADLER32_MOD: u32 = 65521;
+
+/// Number of bytes that can be summed before the sums have to be reduced to avoid overflowing
+const ADLER32_NMAX: usize = 5552;
+
+/// Calculates the Adler-32 checksum of the given bytes, as used to terminate zlib streams
+///
+/// # Examples
+///
+/// ```
+/// # use rusty_ray_tracer::asset_types::checksum::adler32;
+/// assert_eq!(0x11E6_0398, adler32(b"Wikipedia"));
+/// ```
+#[must_use]
+pub fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes
+        .chunks(ADLER32_NMAX)
+        .fold((1_u32, 0_u32), |(a, b), chunk| {
+            let (a, b) = chunk.iter().fold((a, b), |(a, b), &byte| {
+                let a = a + u32::from(byte);
+                (a, b + a)
+            });
+            (a % ADLER32_MOD, b % ADLER32_MOD)
+        });
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests_adler32 {
+    use super::*;
+
+    #[test]
+    fn known_values() {
+        assert_eq!(1, adler32(b""));
+        assert_eq!(0x0062_0062, adler32(b"a"));
+        assert_eq!(0x11E6_0398, adler32(b"Wikipedia"));
+    }
+
+    #[test]
+    fn long_input_does_not_overflow() {
+        let data = vec![0xFF_u8; 100_000];
+        let (a, b) = data.iter().fold((1_u64, 0_u64), |(a, b), &byte| {
+            let a = (a + u64::from(byte)) % u64::from(ADLER32_MOD);
+            (a, (b + a) % u64::from(ADLER32_MOD))
+        });
+        assert_eq!(((b << 16) | a) as u32, adler32(&data));
+    }
+}
@@ -0,0 +1,939 @@
+use super::{checksum::adler32, image_error::ImageError};
+
+/// Block encoding used when compressing a deflate stream
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// Raw, uncompressed blocks. Fastest to write, but the output is larger than the input
+    Stored,
+    /// LZ77 back references encoded with the fixed Huffman codes from RFC 1951
+    #[default]
+    FixedHuffman,
+}
+
+/// Largest distance a back reference can reach
+const WINDOW_SIZE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+/// How many earlier candidates are checked before settling on the best match so far
+const MAX_CHAIN: usize = 128;
+const HASH_BITS: u32 = 15;
+const NO_POSITION: usize = usize::MAX;
+/// Largest payload of a single stored block
+const MAX_STORED_BLOCK: usize = 65535;
+const END_OF_BLOCK: u16 = 256;
+/// Longest Huffman code allowed by deflate
+const MAX_CODE_LENGTH: usize = 15;
+/// Order in which the code length code lengths of a dynamic block are stored
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Base match lengths for the length symbols 257..=285
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+/// Number of extra bits following each length symbol
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// Base distances for the distance symbols 0..=29
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+/// Number of extra bits following each distance symbol
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Packs values into bytes least significant bit first, as deflate requires
+#[derive(Default, Debug)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bit_buffer: u32,
+    bit_count: u32,
+}
+
+impl BitWriter {
+    fn write_bits(&mut self, value: u32, count: u32) {
+        debug_assert!(count <= 16);
+        self.bit_buffer |= value << self.bit_count;
+        self.bit_count += count;
+        while self.bit_count >= 8 {
+            self.bytes.push(self.bit_buffer as u8);
+            self.bit_buffer >>= 8;
+            self.bit_count -= 8;
+        }
+    }
+
+    /// Huffman codes are defined most significant bit first, so they are reversed before packing
+    fn write_code(&mut self, code: u16, length: u32) {
+        let reversed = code.reverse_bits() >> (16 - length);
+        self.write_bits(u32::from(reversed), length);
+    }
+
+    fn align_to_byte(&mut self) {
+        if self.bit_count > 0 {
+            self.write_bits(0, 8 - self.bit_count);
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.align_to_byte();
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests_bit_writer {
+    use super::*;
+
+    #[test]
+    fn write_bits() {
+        let mut writer = BitWriter::default();
+        writer.write_bits(0b1, 1);
+        writer.write_bits(0b01, 2);
+        writer.write_bits(0b11111, 5);
+        writer.write_bits(0b101, 3);
+        assert_eq!(vec![0b1111_1011, 0b101], writer.finish());
+    }
+
+    #[test]
+    fn write_code() {
+        let mut writer = BitWriter::default();
+        writer.write_code(0b110, 3);
+        writer.write_code(0b0000_0001, 8);
+        assert_eq!(vec![0b0000_0011, 0b100], writer.finish());
+    }
+}
+
+/// A single LZ77 token, either a literal byte or a back reference into the previous output
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Literal(u8),
+    Match { length: u16, distance: u16 },
+}
+
+fn hash3(data: &[u8], i: usize) -> usize {
+    let value = (u32::from(data[i]) << 16) | (u32::from(data[i + 1]) << 8) | u32::from(data[i + 2]);
+    (value.wrapping_mul(0x9E37_79B1) >> (32 - HASH_BITS)) as usize
+}
+
+/// Hash chains linking every position to the previous position starting with the same three bytes
+struct HashChains<'a> {
+    data: &'a [u8],
+    head: Vec<usize>,
+    prev: Vec<usize>,
+}
+
+impl<'a> HashChains<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            head: vec![NO_POSITION; 1 << HASH_BITS],
+            prev: vec![NO_POSITION; WINDOW_SIZE],
+        }
+    }
+
+    fn insert(&mut self, i: usize) {
+        if i + MIN_MATCH <= self.data.len() {
+            let h = hash3(self.data, i);
+            self.prev[i % WINDOW_SIZE] = self.head[h];
+            self.head[h] = i;
+        }
+    }
+
+    /// Returns the length and distance of the longest match for position `i` within the window
+    fn longest_match(&self, i: usize) -> (usize, usize) {
+        let mut best = (0, 0);
+        if i + MIN_MATCH > self.data.len() {
+            return best;
+        }
+        let max_length = MAX_MATCH.min(self.data.len() - i);
+        let mut candidate = self.head[hash3(self.data, i)];
+        let mut chain = 0;
+        while candidate != NO_POSITION && i - candidate <= WINDOW_SIZE && chain < MAX_CHAIN {
+            let length = self.data[candidate..]
+                .iter()
+                .zip(&self.data[i..i + max_length])
+                .take_while(|(a, b)| a == b)
+                .count();
+            if length > best.0 {
+                best = (length, i - candidate);
+                if length == max_length {
+                    break;
+                }
+            }
+            // Slots are reused once the window slides past them, which shows up as a link forwards
+            let next = self.prev[candidate % WINDOW_SIZE];
+            if next >= candidate {
+                break;
+            }
+            candidate = next;
+            chain += 1;
+        }
+        best
+    }
+}
+
+/// Splits the data into literals and back references using hash chains over a sliding window
+fn lz77_tokens(data: &[u8]) -> Vec<Token> {
+    let mut chains = HashChains::new(data);
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        let (length, distance) = chains.longest_match(i);
+        if length >= MIN_MATCH {
+            tokens.push(Token::Match {
+                length: length as u16,
+                distance: distance as u16,
+            });
+            (i..i + length).for_each(|p| chains.insert(p));
+            i += length;
+        } else {
+            tokens.push(Token::Literal(data[i]));
+            chains.insert(i);
+            i += 1;
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests_lz77_tokens {
+    use super::*;
+
+    #[test]
+    fn literals_only() {
+        assert_eq!(
+            vec![
+                Token::Literal(b'a'),
+                Token::Literal(b'b'),
+                Token::Literal(b'c')
+            ],
+            lz77_tokens(b"abc")
+        );
+    }
+
+    #[test]
+    fn repeated_run() {
+        assert_eq!(
+            vec![
+                Token::Literal(b'a'),
+                Token::Match {
+                    length: 9,
+                    distance: 1
+                }
+            ],
+            lz77_tokens(b"aaaaaaaaaa")
+        );
+    }
+
+    #[test]
+    fn repeated_pattern() {
+        assert_eq!(
+            vec![
+                Token::Literal(b'a'),
+                Token::Literal(b'b'),
+                Token::Literal(b'c'),
+                Token::Match {
+                    length: 6,
+                    distance: 3
+                },
+                Token::Literal(b'x'),
+            ],
+            lz77_tokens(b"abcabcabcx")
+        );
+    }
+
+    #[test]
+    fn long_run_is_split() {
+        let tokens = lz77_tokens(&[7_u8; 600]);
+        assert_eq!(Token::Literal(7), tokens[0]);
+        assert!(tokens[1..]
+            .iter()
+            .all(|t| matches!(t, Token::Match { distance: 1, .. })));
+        let total = tokens[1..]
+            .iter()
+            .map(|t| match t {
+                Token::Match { length, .. } => usize::from(*length),
+                Token::Literal(_) => 1,
+            })
+            .sum::<usize>();
+        assert_eq!(599, total);
+    }
+}
+
+/// Returns the fixed Huffman code and its bit length for a literal/length symbol
+const fn fixed_literal_code(symbol: u16) -> (u16, u32) {
+    match symbol {
+        0..=143 => (0x30 + symbol, 8),
+        144..=255 => (0x190 + symbol - 144, 9),
+        256..=279 => (symbol - 256, 7),
+        _ => (0xC0 + symbol - 280, 8),
+    }
+}
+
+fn write_fixed_huffman_block(writer: &mut BitWriter, data: &[u8]) {
+    writer.write_bits(1, 1);
+    writer.write_bits(1, 2);
+    for token in lz77_tokens(data) {
+        match token {
+            Token::Literal(byte) => {
+                let (code, length) = fixed_literal_code(u16::from(byte));
+                writer.write_code(code, length);
+            }
+            Token::Match { length, distance } => {
+                let index = LENGTH_BASE.iter().rposition(|&b| b <= length).unwrap_or(0);
+                let (code, code_length) = fixed_literal_code(257 + index as u16);
+                writer.write_code(code, code_length);
+                writer.write_bits(
+                    u32::from(length - LENGTH_BASE[index]),
+                    u32::from(LENGTH_EXTRA[index]),
+                );
+
+                let index = DISTANCE_BASE
+                    .iter()
+                    .rposition(|&b| b <= distance)
+                    .unwrap_or(0);
+                writer.write_code(index as u16, 5);
+                writer.write_bits(
+                    u32::from(distance - DISTANCE_BASE[index]),
+                    u32::from(DISTANCE_EXTRA[index]),
+                );
+            }
+        }
+    }
+    let (code, length) = fixed_literal_code(END_OF_BLOCK);
+    writer.write_code(code, length);
+}
+
+fn write_stored_blocks(writer: &mut BitWriter, data: &[u8]) {
+    let blocks = data.chunks(MAX_STORED_BLOCK).collect::<Vec<_>>();
+    let blocks = if blocks.is_empty() {
+        vec![&data[..0]]
+    } else {
+        blocks
+    };
+    let last = blocks.len() - 1;
+    for (i, block) in blocks.into_iter().enumerate() {
+        writer.write_bits(u32::from(i == last), 1);
+        writer.write_bits(0, 2);
+        writer.align_to_byte();
+        let length = block.len() as u16;
+        writer.bytes.extend(length.to_le_bytes());
+        writer.bytes.extend((!length).to_le_bytes());
+        writer.bytes.extend(block);
+    }
+}
+
+/// Compresses the data into a raw deflate stream (RFC 1951)
+///
+/// # Examples
+///
+/// ```
+/// # use rusty_ray_tracer::asset_types::deflate::{deflate, Compression};
+/// assert_eq!(vec![0x01, 0x01, 0x00, 0xFE, 0xFF, b'a'], deflate(b"a", Compression::Stored));
+/// assert_eq!(vec![0x4B, 0x04, 0x00], deflate(b"a", Compression::FixedHuffman));
+/// ```
+#[must_use]
+pub fn deflate(data: &[u8], compression: Compression) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+    match compression {
+        Compression::Stored => write_stored_blocks(&mut writer, data),
+        Compression::FixedHuffman => write_fixed_huffman_block(&mut writer, data),
+    }
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests_deflate {
+    use super::*;
+
+    #[test]
+    fn stored() {
+        assert_eq!(
+            vec![0x01, 0x00, 0x00, 0xFF, 0xFF],
+            deflate(b"", Compression::Stored)
+        );
+        assert_eq!(
+            vec![0x01, 0x03, 0x00, 0xFC, 0xFF, b'a', b'b', b'c'],
+            deflate(b"abc", Compression::Stored)
+        );
+    }
+
+    #[test]
+    fn stored_splits_large_input() {
+        let data = vec![1_u8; MAX_STORED_BLOCK + 10];
+        let result = deflate(&data, Compression::Stored);
+        assert_eq!(data.len() + 2 * 5, result.len());
+        assert_eq!([0x00, 0xFF, 0xFF, 0x00, 0x00], result[0..5]);
+        let second = 5 + MAX_STORED_BLOCK;
+        assert_eq!([0x01, 0x0A, 0x00, 0xF5, 0xFF], result[second..second + 5]);
+    }
+
+    #[test]
+    fn fixed_huffman() {
+        assert_eq!(vec![0x03, 0x00], deflate(b"", Compression::FixedHuffman));
+        assert_eq!(
+            vec![0x4B, 0x04, 0x00],
+            deflate(b"a", Compression::FixedHuffman)
+        );
+        assert_eq!(
+            vec![0x4B, 0x84, 0x03, 0x00],
+            deflate(b"aaaaaaaaaa", Compression::FixedHuffman)
+        );
+    }
+
+    #[test]
+    fn fixed_huffman_compresses_repetition() {
+        let data = b"0123456789".repeat(1000);
+        assert!(deflate(&data, Compression::FixedHuffman).len() < data.len() / 20);
+    }
+}
+
+/// Wraps a deflate stream in a zlib container (RFC 1950), as used by PNG `IDAT` chunks
+///
+/// # Examples
+///
+/// ```
+/// # use rusty_ray_tracer::asset_types::deflate::{zlib_compress, Compression};
+/// assert_eq!(
+///     vec![0x78, 0x9C, 0x4B, 0x04, 0x00, 0x00, 0x62, 0x00, 0x62],
+///     zlib_compress(b"a", Compression::FixedHuffman)
+/// );
+/// ```
+#[must_use]
+pub fn zlib_compress(data: &[u8], compression: Compression) -> Vec<u8> {
+    // Compression method 8 with a 32K window; the level hint is informational only
+    let header: [u8; 2] = match compression {
+        Compression::Stored => [0x78, 0x01],
+        Compression::FixedHuffman => [0x78, 0x9C],
+    };
+    let mut result = header.to_vec();
+    result.extend(deflate(data, compression));
+    result.extend(adler32(data).to_be_bytes());
+    result
+}
+
+#[cfg(test)]
+mod tests_zlib_compress {
+    use super::*;
+
+    #[test]
+    fn header_check_bits() {
+        for compression in [Compression::Stored, Compression::FixedHuffman] {
+            let result = zlib_compress(b"", compression);
+            assert_eq!(0, u16::from_be_bytes([result[0], result[1]]) % 31);
+        }
+    }
+
+    #[test]
+    fn stored() {
+        assert_eq!(
+            vec![0x78, 0x01, 0x01, 0x01, 0x00, 0xFE, 0xFF, b'a', 0x00, 0x62, 0x00, 0x62],
+            zlib_compress(b"a", Compression::Stored)
+        );
+    }
+
+    #[test]
+    fn fixed_huffman() {
+        assert_eq!(
+            vec![0x78, 0x9C, 0x4B, 0x04, 0x00, 0x00, 0x62, 0x00, 0x62],
+            zlib_compress(b"a", Compression::FixedHuffman)
+        );
+    }
+}
+
+/// Reads values from bytes least significant bit first, the counterpart of [`BitWriter`]
+#[derive(Debug)]
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    bit_buffer: u32,
+    bit_count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    const fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            position: 0,
+            bit_buffer: 0,
+            bit_count: 0,
+        }
+    }
+
+    fn read_bits(&mut self, count: u32) -> Result<u32, ImageError> {
+        debug_assert!(count <= 16);
+        while self.bit_count < count {
+            let byte = *self
+                .bytes
+                .get(self.position)
+                .ok_or(ImageError::UnexpectedEof)?;
+            self.bit_buffer |= u32::from(byte) << self.bit_count;
+            self.position += 1;
+            self.bit_count += 8;
+        }
+        let value = self.bit_buffer & ((1 << count) - 1);
+        self.bit_buffer >>= count;
+        self.bit_count -= count;
+        Ok(value)
+    }
+
+    /// Drops the bits remaining in the current byte
+    fn align_to_byte(&mut self) {
+        self.bit_buffer = 0;
+        self.bit_count = 0;
+    }
+
+    fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], ImageError> {
+        debug_assert_eq!(0, self.bit_count);
+        let bytes = self
+            .bytes
+            .get(self.position..self.position + count)
+            .ok_or(ImageError::UnexpectedEof)?;
+        self.position += count;
+        Ok(bytes)
+    }
+
+    /// Number of whole bytes consumed so far
+    const fn bytes_consumed(&self) -> usize {
+        self.position - (self.bit_count / 8) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests_bit_reader {
+    use super::*;
+
+    #[test]
+    fn read_bits() {
+        let mut reader = BitReader::new(&[0b1111_1011, 0b101]);
+        assert_eq!(Ok(0b1), reader.read_bits(1));
+        assert_eq!(Ok(0b01), reader.read_bits(2));
+        assert_eq!(Ok(0b11111), reader.read_bits(5));
+        assert_eq!(Ok(0b101), reader.read_bits(3));
+        assert_eq!(Ok(0), reader.read_bits(5));
+        assert_eq!(Err(ImageError::UnexpectedEof), reader.read_bits(1));
+    }
+
+    #[test]
+    fn read_bytes() {
+        let mut reader = BitReader::new(&[0xFF, 1, 2, 3]);
+        assert_eq!(Ok(0b111), reader.read_bits(3));
+        reader.align_to_byte();
+        assert_eq!(1, reader.bytes_consumed());
+        assert_eq!(Ok(&[1_u8, 2][..]), reader.read_bytes(2));
+        assert_eq!(Err(ImageError::UnexpectedEof), reader.read_bytes(2));
+        assert_eq!(3, reader.bytes_consumed());
+    }
+}
+
+/// Canonical Huffman code, decoded one bit at a time from the number of codes of each length
+#[derive(Debug)]
+struct Huffman {
+    counts: [u16; MAX_CODE_LENGTH + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Builds the code from the code length of every symbol, 0 meaning the symbol is unused
+    fn new(lengths: &[u8]) -> Result<Self, ImageError> {
+        let mut counts = [0_u16; MAX_CODE_LENGTH + 1];
+        lengths.iter().for_each(|&l| counts[usize::from(l)] += 1);
+        counts[0] = 0;
+
+        let mut left = 1_i32;
+        for &count in &counts[1..] {
+            left = (left << 1) - i32::from(count);
+            if left < 0 {
+                return Err(ImageError::InvalidData(
+                    "over-subscribed huffman code".to_string(),
+                ));
+            }
+        }
+
+        let mut offsets = [0_u16; MAX_CODE_LENGTH + 1];
+        for length in 1..MAX_CODE_LENGTH {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0_u16; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[usize::from(offsets[usize::from(length)])] = symbol as u16;
+                offsets[usize::from(length)] += 1;
+            }
+        }
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, ImageError> {
+        let mut code = 0_i32;
+        let mut first = 0_i32;
+        let mut index = 0_i32;
+        for &count in &self.counts[1..] {
+            code |= reader.read_bits(1)? as i32;
+            let count = i32::from(count);
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(ImageError::InvalidData("invalid huffman code".to_string()))
+    }
+
+    /// The literal/length and distance codes of fixed Huffman blocks
+    fn fixed() -> (Self, Self) {
+        let lengths = (0..288_u16)
+            .map(|s| fixed_literal_code(s).1 as u8)
+            .collect::<Vec<_>>();
+        (
+            Self::new(&lengths).expect("Fixed literal code is valid"),
+            Self::new(&[5; 30]).expect("Fixed distance code is valid"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests_huffman {
+    use super::*;
+
+    #[test]
+    fn decode() {
+        // Codes: symbol 1 => 0, symbol 0 => 10, symbol 2 => 110, symbol 3 => 111
+        let huffman = Huffman::new(&[2, 1, 3, 3]).unwrap();
+        let mut writer = BitWriter::default();
+        [(0b0, 1), (0b10, 2), (0b110, 3), (0b111, 3)]
+            .iter()
+            .for_each(|&(code, length)| writer.write_code(code, length));
+        let bytes = writer.finish();
+        let mut reader = BitReader::new(&bytes);
+        assert_eq!(Ok(1), huffman.decode(&mut reader));
+        assert_eq!(Ok(0), huffman.decode(&mut reader));
+        assert_eq!(Ok(2), huffman.decode(&mut reader));
+        assert_eq!(Ok(3), huffman.decode(&mut reader));
+    }
+
+    #[test]
+    fn over_subscribed() {
+        assert!(Huffman::new(&[1, 1, 1]).is_err());
+    }
+
+    #[test]
+    fn fixed_matches_encoder() {
+        let (literals, _) = Huffman::fixed();
+        for symbol in [0_u16, 143, 144, 255, 256, 279, 280, 287] {
+            let mut writer = BitWriter::default();
+            let (code, length) = fixed_literal_code(symbol);
+            writer.write_code(code, length);
+            let bytes = writer.finish();
+            assert_eq!(Ok(symbol), literals.decode(&mut BitReader::new(&bytes)));
+        }
+    }
+}
+
+/// Reads the code lengths of a dynamic block and builds its literal/length and distance codes
+fn read_dynamic_huffman(reader: &mut BitReader) -> Result<(Huffman, Huffman), ImageError> {
+    let literal_count = reader.read_bits(5)? as usize + 257;
+    let distance_count = reader.read_bits(5)? as usize + 1;
+    let code_length_count = reader.read_bits(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err(ImageError::InvalidData(
+            "too many huffman codes".to_string(),
+        ));
+    }
+
+    let mut code_length_lengths = [0_u8; 19];
+    for &i in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_length_lengths[i] = reader.read_bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_length_lengths)?;
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (value, repeat) = match code_lengths.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths.last().ok_or_else(|| {
+                    ImageError::InvalidData("repeat without a previous length".to_string())
+                })?;
+                (previous, 3 + reader.read_bits(2)?)
+            }
+            17 => (0, 3 + reader.read_bits(3)?),
+            _ => (0, 11 + reader.read_bits(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+    if lengths.len() > literal_count + distance_count {
+        return Err(ImageError::InvalidData(
+            "code lengths overflow the alphabets".to_string(),
+        ));
+    }
+    if lengths[usize::from(END_OF_BLOCK)] == 0 {
+        return Err(ImageError::InvalidData(
+            "missing end of block code".to_string(),
+        ));
+    }
+
+    Ok((
+        Huffman::new(&lengths[..literal_count])?,
+        Huffman::new(&lengths[literal_count..])?,
+    ))
+}
+
+fn inflate_huffman_block(
+    reader: &mut BitReader,
+    output: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), ImageError> {
+    loop {
+        let symbol = literals.decode(reader)?;
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            END_OF_BLOCK => return Ok(()),
+            257..=285 => {
+                let index = usize::from(symbol - 257);
+                let length = usize::from(LENGTH_BASE[index])
+                    + reader.read_bits(u32::from(LENGTH_EXTRA[index]))? as usize;
+
+                let index = usize::from(distances.decode(reader)?);
+                if index >= DISTANCE_BASE.len() {
+                    return Err(ImageError::InvalidData("invalid distance code".to_string()));
+                }
+                let distance = usize::from(DISTANCE_BASE[index])
+                    + reader.read_bits(u32::from(DISTANCE_EXTRA[index]))? as usize;
+                if distance > output.len() {
+                    return Err(ImageError::InvalidData(
+                        "distance reaches before the start of the output".to_string(),
+                    ));
+                }
+
+                // Byte by byte, as the copy is allowed to overlap the bytes it produces
+                let start = output.len() - distance;
+                (start..start + length).for_each(|i| output.push(output[i]));
+            }
+            _ => return Err(ImageError::InvalidData("invalid length code".to_string())),
+        }
+    }
+}
+
+/// Decompresses a raw deflate stream, returning the data and the number of input bytes it occupied
+fn inflate_stream(data: &[u8]) -> Result<(Vec<u8>, usize), ImageError> {
+    let mut reader = BitReader::new(data);
+    let mut output = Vec::new();
+    loop {
+        let last = reader.read_bits(1)? == 1;
+        match reader.read_bits(2)? {
+            0 => {
+                reader.align_to_byte();
+                let header = reader.read_bytes(4)?;
+                let length = u16::from_le_bytes([header[0], header[1]]);
+                let inverse = u16::from_le_bytes([header[2], header[3]]);
+                if length != !inverse {
+                    return Err(ImageError::InvalidData(
+                        "stored block length mismatch".to_string(),
+                    ));
+                }
+                output.extend(reader.read_bytes(usize::from(length))?);
+            }
+            1 => {
+                let (literals, distances) = Huffman::fixed();
+                inflate_huffman_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = read_dynamic_huffman(&mut reader)?;
+                inflate_huffman_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            _ => return Err(ImageError::InvalidData("invalid block type".to_string())),
+        }
+        if last {
+            return Ok((output, reader.bytes_consumed()));
+        }
+    }
+}
+
+/// Decompresses a raw deflate stream (RFC 1951) with stored, fixed or dynamic Huffman blocks
+///
+/// # Errors
+///
+/// Returns an [`ImageError`] if the stream is truncated or malformed
+///
+/// # Examples
+///
+/// ```
+/// # use rusty_ray_tracer::asset_types::deflate::{deflate, inflate, Compression};
+/// let data = b"Hello Hello Hello".to_vec();
+/// assert_eq!(Ok(data.clone()), inflate(&deflate(&data, Compression::FixedHuffman)));
+/// ```
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, ImageError> {
+    inflate_stream(data).map(|(output, _)| output)
+}
+
+#[cfg(test)]
+mod tests_inflate {
+    use super::*;
+
+    #[test]
+    fn stored() {
+        assert_eq!(Ok(vec![]), inflate(&[0x01, 0x00, 0x00, 0xFF, 0xFF]));
+        assert_eq!(
+            Ok(b"abc".to_vec()),
+            inflate(&[0x01, 0x03, 0x00, 0xFC, 0xFF, b'a', b'b', b'c'])
+        );
+        assert!(inflate(&[0x01, 0x03, 0x00, 0xFD, 0xFF, b'a', b'b', b'c']).is_err());
+    }
+
+    #[test]
+    fn fixed_huffman() {
+        assert_eq!(Ok(b"a".to_vec()), inflate(&[0x4B, 0x04, 0x00]));
+        assert_eq!(
+            Ok(b"aaaaaaaaaa".to_vec()),
+            inflate(&[0x4B, 0x84, 0x03, 0x00])
+        );
+    }
+
+    #[test]
+    fn dynamic_huffman() {
+        // Produced by zlib at level 9, which picks a dynamic block for this text
+        let compressed = [
+            0x75, 0xCB, 0xD1, 0x09, 0x80, 0x30, 0x0C, 0x45, 0xD1, 0x55, 0x32, 0x80, 0x8B, 0x38,
+            0x46, 0xAB, 0x2F, 0x26, 0xD0, 0x9A, 0x62, 0x02, 0x59, 0x5F, 0xFA, 0x25, 0x82, 0x7E,
+            0x9F, 0x7B, 0xD7, 0xA0, 0x2C, 0x4E, 0x21, 0xA0, 0x0A, 0x0F, 0x32, 0xA6, 0xD0, 0x0E,
+            0x5F, 0x48, 0x1F, 0x49, 0xBB, 0xFE, 0xA8, 0x1C, 0x98, 0x90, 0xEA, 0xBB, 0xF5, 0x2F,
+            0x61, 0xB3, 0xA6, 0x2E, 0x27, 0xFC, 0x3D, 0x62, 0xD8, 0x26, 0x33, 0xA8, 0x68, 0x0A,
+            0xBE, 0x01,
+        ];
+        let expected =
+            b"It was the best of times, it was the worst of times, it was the age of wisdom, \
+it was the age of foolishness, it was the epoch of belief";
+        assert_eq!(Ok(expected.to_vec()), inflate(&compressed));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut seed = 7_u32;
+        let data = (0..100_000)
+            .map(|i| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                if i % 3 == 0 {
+                    (seed >> 24) as u8
+                } else {
+                    (i / 7) as u8
+                }
+            })
+            .collect::<Vec<_>>();
+        for compression in [Compression::Stored, Compression::FixedHuffman] {
+            assert_eq!(Ok(data.clone()), inflate(&deflate(&data, compression)));
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        assert_eq!(Err(ImageError::UnexpectedEof), inflate(&[]));
+        assert_eq!(Err(ImageError::UnexpectedEof), inflate(&[0x4B]));
+        assert_eq!(
+            Err(ImageError::UnexpectedEof),
+            inflate(&[0x01, 0x03, 0x00, 0xFC, 0xFF, b'a'])
+        );
+    }
+
+    #[test]
+    fn invalid_block_type() {
+        assert!(matches!(inflate(&[0x07]), Err(ImageError::InvalidData(_))));
+    }
+
+    #[test]
+    fn distance_too_far() {
+        // Fixed block starting with a back reference of length 3, distance 1
+        let mut writer = BitWriter::default();
+        writer.write_bits(1, 1);
+        writer.write_bits(1, 2);
+        let (code, length) = fixed_literal_code(257);
+        writer.write_code(code, length);
+        writer.write_code(0, 5);
+        assert!(matches!(
+            inflate(&writer.finish()),
+            Err(ImageError::InvalidData(_))
+        ));
+    }
+}
+
+/// Decompresses a zlib stream (RFC 1950), verifying its header and Adler-32 checksum
+///
+/// # Errors
+///
+/// Returns an [`ImageError`] if the header, deflate stream or checksum is invalid
+///
+/// # Examples
+///
+/// ```
+/// # use rusty_ray_tracer::asset_types::deflate::zlib_decompress;
+/// assert_eq!(
+///     Ok(b"a".to_vec()),
+///     zlib_decompress(&[0x78, 0x9C, 0x4B, 0x04, 0x00, 0x00, 0x62, 0x00, 0x62])
+/// );
+/// ```
+pub fn zlib_decompress(data: &[u8]) -> Result<Vec<u8>, ImageError> {
+    if data.len() < 2 {
+        return Err(ImageError::UnexpectedEof);
+    }
+    let (cmf, flg) = (data[0], data[1]);
+    if cmf & 0x0F != 8 || cmf >> 4 > 7 || (u16::from(cmf) << 8 | u16::from(flg)) % 31 != 0 {
+        return Err(ImageError::InvalidData("invalid zlib header".to_string()));
+    }
+    if flg & 0x20 != 0 {
+        return Err(ImageError::Unsupported(
+            "zlib preset dictionary".to_string(),
+        ));
+    }
+
+    let (output, consumed) = inflate_stream(&data[2..])?;
+    let trailer = data
+        .get(2 + consumed..2 + consumed + 4)
+        .ok_or(ImageError::UnexpectedEof)?;
+    if u32::from_be_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]) != adler32(&output) {
+        return Err(ImageError::InvalidData(
+            "zlib checksum mismatch".to_string(),
+        ));
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests_zlib_decompress {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let data = b"0123456789".repeat(100);
+        for compression in [Compression::Stored, Compression::FixedHuffman] {
+            assert_eq!(
+                Ok(data.clone()),
+                zlib_decompress(&zlib_compress(&data, compression))
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_header() {
+        assert!(zlib_decompress(&[0x78, 0x9D, 0x03, 0x00, 0, 0, 0, 1]).is_err());
+        assert!(zlib_decompress(&[0x79, 0x9C, 0x03, 0x00, 0, 0, 0, 1]).is_err());
+        assert_eq!(Err(ImageError::UnexpectedEof), zlib_decompress(&[0x78]));
+    }
+
+    #[test]
+    fn checksum_mismatch() {
+        let mut data = zlib_compress(b"abc", Compression::FixedHuffman);
+        *data.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            zlib_decompress(&data),
+            Err(ImageError::InvalidData(_))
+        ));
+    }
+}
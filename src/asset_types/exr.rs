@@ -0,0 +1,740 @@
+use crate::{core3d::color_rgb::ColorRGB, graphics2d::canvas::Canvas};
+
+/// Magic number every OpenEXR file starts with
+pub const EXR_MAGIC: [u8; 4] = [0x76, 0x2F, 0x31, 0x01];
+/// Format version 2 for a single part scanline image
+const EXR_VERSION: u32 = 2;
+/// Version flag required when any attribute or channel name is longer than 31 bytes
+const EXR_LONG_NAMES: u32 = 0x400;
+const EXR_SHORT_NAME_MAX: usize = 31;
+
+/// Converts a float to the nearest half float, rounding ties to even
+///
+/// Values too large for a half become infinity and values too small become zero or subnormals.
+///
+/// # Examples
+///
+/// ```
+/// # use rusty_ray_tracer::asset_types::exr::half_from_f32;
+/// assert_eq!(0x3C00, half_from_f32(1.0));
+/// assert_eq!(0xC000, half_from_f32(-2.0));
+/// assert_eq!(0x7C00, half_from_f32(1e6));
+/// ```
+#[must_use]
+pub fn half_from_f32(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xFF) as i32;
+    let mantissa = bits & 0x007F_FFFF;
+
+    if exponent == 0xFF {
+        // Keep a mantissa bit set so NaN stays NaN after dropping the low bits
+        let nan = if mantissa == 0 {
+            0
+        } else {
+            0x0200 | (mantissa >> 13) as u16
+        };
+        return sign | 0x7C00 | nan;
+    }
+
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1F {
+        return sign | 0x7C00;
+    }
+    if exponent < -10 {
+        return sign;
+    }
+
+    // Number of mantissa bits dropped, with subnormals losing one more per step below the normal range
+    let (kept, shift) = if exponent <= 0 {
+        (mantissa | 0x0080_0000, (14 - exponent) as u32)
+    } else {
+        ((exponent as u32) << 23 | mantissa, 13)
+    };
+    let half = kept >> shift;
+    let remainder = kept & ((1 << shift) - 1);
+    let halfway = 1 << (shift - 1);
+    // A carry out of the mantissa correctly moves on to the next exponent, or to infinity
+    let rounded = half + u32::from(remainder > halfway || (remainder == halfway && half & 1 == 1));
+    sign | rounded as u16
+}
+
+/// Converts a half float to a float, which is always exact
+///
+/// # Examples
+///
+/// ```
+/// # use rusty_ray_tracer::asset_types::exr::f32_from_half;
+/// assert_eq!(1.0, f32_from_half(0x3C00));
+/// assert_eq!(65_504.0, f32_from_half(0x7BFF));
+/// assert!(f32_from_half(0x7E00).is_nan());
+/// ```
+#[must_use]
+pub fn f32_from_half(half: u16) -> f32 {
+    let sign = u32::from(half & 0x8000) << 16;
+    let exponent = u32::from(half >> 10) & 0x1F;
+    let mantissa = u32::from(half & 0x03FF);
+    match exponent {
+        0 => (f32::from(half & 0x03FF) * 2.0_f32.powi(-24)).copysign(f32::from_bits(sign | 1)),
+        0x1F => f32::from_bits(sign | 0x7F80_0000 | mantissa << 13),
+        _ => f32::from_bits(sign | (exponent + 127 - 15) << 23 | mantissa << 13),
+    }
+}
+
+#[cfg(test)]
+mod tests_half {
+    use super::*;
+
+    #[test]
+    fn from_f32() {
+        assert_eq!(0x0000, half_from_f32(0.0));
+        assert_eq!(0x8000, half_from_f32(-0.0));
+        assert_eq!(0x3800, half_from_f32(0.5));
+        assert_eq!(0x7BFF, half_from_f32(65_504.0));
+        assert_eq!(0x0001, half_from_f32(2.0_f32.powi(-24)));
+        assert_eq!(0x0400, half_from_f32(2.0_f32.powi(-14)));
+        assert_eq!(0x0000, half_from_f32(2.0_f32.powi(-26)));
+        assert_eq!(0xFC00, half_from_f32(f32::NEG_INFINITY));
+        assert_eq!(0x7E00, half_from_f32(f32::NAN) & 0x7E00);
+    }
+
+    #[test]
+    fn rounding() {
+        // 1 + 2^-11 is exactly between two halves and rounds to the even one
+        assert_eq!(0x3C00, half_from_f32(1.0 + 2.0_f32.powi(-11)));
+        assert_eq!(0x3C02, half_from_f32(1.0 + 3.0 * 2.0_f32.powi(-11)));
+        assert_eq!(
+            0x3C01,
+            half_from_f32(1.0 + 2.0_f32.powi(-11) + 2.0_f32.powi(-20))
+        );
+        // Rounding up the largest half overflows into infinity
+        assert_eq!(0x7C00, half_from_f32(65_520.0));
+        assert_eq!(0x7BFF, half_from_f32(65_519.0));
+        // Subnormal ties round to even as well
+        assert_eq!(0x0000, half_from_f32(2.0_f32.powi(-25)));
+        assert_eq!(0x0002, half_from_f32(3.0 * 2.0_f32.powi(-25)));
+        // Largest subnormal rounds up into the smallest normal
+        assert_eq!(0x0400, half_from_f32(2.0_f32.powi(-14) - 2.0_f32.powi(-26)));
+    }
+
+    #[test]
+    fn round_trip() {
+        for half in (0..=u16::MAX).filter(|h| h & 0x7C00 != 0x7C00) {
+            assert_eq!(half, half_from_f32(f32_from_half(half)));
+        }
+        assert_eq!(f32::INFINITY, f32_from_half(0x7C00));
+        assert_eq!((-0.0_f32).to_bits(), f32_from_half(0x8000).to_bits());
+        assert_eq!(-(2.0_f32.powi(-24)), f32_from_half(0x8001));
+    }
+}
+
+/// Storage type of the samples of a channel
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PixelType {
+    /// 16 bit floats, enough for colors and the usual choice for compositing
+    #[default]
+    Half,
+    /// 32 bit floats, for data that needs the precision like depth or positions
+    Float,
+}
+
+impl PixelType {
+    const fn code(self) -> u32 {
+        match self {
+            Self::Half => 1,
+            Self::Float => 2,
+        }
+    }
+}
+
+/// Compression applied to each block of scanlines
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    /// Run length encoding of the byte deltas, one scanline per block
+    RLE,
+    /// Zlib compression of the byte deltas, 16 scanlines per block
+    #[default]
+    ZIP,
+}
+
+impl Compression {
+    const fn code(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::RLE => 1,
+            Self::ZIP => 3,
+        }
+    }
+
+    const fn lines_per_block(self) -> u32 {
+        match self {
+            Self::None | Self::RLE => 1,
+            Self::ZIP => 16,
+        }
+    }
+}
+
+/// A single named channel holding one sample per pixel, row by row starting at the top
+#[derive(Clone, Debug, PartialEq)]
+pub struct Channel {
+    pub name: String,
+    pub pixel_type: PixelType,
+    pub samples: Vec<f32>,
+}
+
+#[derive(Default, Debug)]
+pub struct EXR {
+    pub width: u32,
+    pub height: u32,
+    pub compression: Compression,
+
+    /// Channels in the order they were added, they are sorted by name when written
+    pub channels: Vec<Channel>,
+}
+
+impl EXR {
+    /// Creates a new exr without any channels
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image is empty, which the format cannot represent
+    #[must_use]
+    pub const fn new(width: u32, height: u32, compression: Compression) -> Self {
+        assert!(width > 0 && height > 0, "EXR images cannot be empty!");
+        Self {
+            width,
+            height,
+            compression,
+            channels: Vec::new(),
+        }
+    }
+
+    /// Adds a single channel, like `depth.Z` or `A`
+    ///
+    /// Names of the form `layer.channel` group channels into layers in compositing applications.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel already exists or the number of samples does not match the image size
+    pub fn add_channel(&mut self, name: &str, pixel_type: PixelType, samples: Vec<f32>) {
+        assert!(
+            !name.is_empty() && self.channels.iter().all(|c| c.name != name),
+            "EXR channel names must be unique and not empty!"
+        );
+        assert_eq!(
+            self.width as usize * self.height as usize,
+            samples.len(),
+            "EXR channel size does not match the image!"
+        );
+        self.channels.push(Channel {
+            name: name.to_string(),
+            pixel_type,
+            samples,
+        });
+    }
+
+    /// Adds the colors of a canvas as the `R`, `G` and `B` channels of a layer
+    ///
+    /// An empty layer name adds the unprefixed channels used for the main image.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::exr::{Compression, PixelType, EXR};
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let canvas = Canvas::new(4, 2);
+    /// let mut exr = EXR::new(4, 2, Compression::ZIP);
+    /// exr.add_layer("", &canvas, PixelType::Half);
+    /// exr.add_layer("albedo", &canvas, PixelType::Half);
+    /// exr.add_layer("normal", &canvas, PixelType::Float);
+    /// exr.add_channel("depth.Z", PixelType::Float, vec![1.0; 8]);
+    /// assert_eq!(10, exr.channels.len());
+    /// assert_eq!("albedo.G", exr.channels[4].name);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layer already exists or the canvas size does not match the image
+    pub fn add_layer(&mut self, layer: &str, canvas: &Canvas, pixel_type: PixelType) {
+        assert!(
+            canvas.width == self.width && canvas.height == self.height,
+            "EXR layer size does not match the image!"
+        );
+        for (channel, name) in ["R", "G", "B"].iter().enumerate() {
+            let name = if layer.is_empty() {
+                (*name).to_string()
+            } else {
+                format!("{layer}.{name}")
+            };
+            let samples = canvas
+                .raw_buffer
+                .iter()
+                .map(|c| c.get_at(channel))
+                .collect();
+            self.add_channel(&name, pixel_type, samples);
+        }
+    }
+
+    /// Channels in the order the format stores them
+    fn get_sorted_channels(&self) -> Vec<&Channel> {
+        let mut channels = self.channels.iter().collect::<Vec<_>>();
+        channels.sort_by(|a, b| a.name.cmp(&b.name));
+        channels
+    }
+
+    fn get_header(&self, channels: &[&Channel]) -> Vec<u8> {
+        let mut bytes = EXR_MAGIC.to_vec();
+        let long_names = channels.iter().any(|c| c.name.len() > EXR_SHORT_NAME_MAX);
+        let version = if long_names {
+            EXR_VERSION | EXR_LONG_NAMES
+        } else {
+            EXR_VERSION
+        };
+        bytes.extend(version.to_le_bytes());
+
+        let mut channel_list = Vec::new();
+        for channel in channels {
+            channel_list.extend(channel.name.as_bytes());
+            channel_list.push(0);
+            channel_list.extend(channel.pixel_type.code().to_le_bytes());
+            // Perceptually linear flag and three reserved bytes, then the x and y sampling rates
+            channel_list.extend([0, 0, 0, 0]);
+            channel_list.extend(1_u32.to_le_bytes());
+            channel_list.extend(1_u32.to_le_bytes());
+        }
+        channel_list.push(0);
+
+        let window = [0, 0, self.width - 1, self.height - 1]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        write_attribute(&mut bytes, "channels", "chlist", &channel_list);
+        write_attribute(
+            &mut bytes,
+            "compression",
+            "compression",
+            &[self.compression.code()],
+        );
+        write_attribute(&mut bytes, "dataWindow", "box2i", &window);
+        write_attribute(&mut bytes, "displayWindow", "box2i", &window);
+        write_attribute(&mut bytes, "lineOrder", "lineOrder", &[0]);
+        write_attribute(
+            &mut bytes,
+            "pixelAspectRatio",
+            "float",
+            &1.0_f32.to_le_bytes(),
+        );
+        write_attribute(&mut bytes, "screenWindowCenter", "v2f", &[0; 8]);
+        write_attribute(
+            &mut bytes,
+            "screenWindowWidth",
+            "float",
+            &1.0_f32.to_le_bytes(),
+        );
+        bytes.push(0);
+        bytes
+    }
+
+    /// Returns the uncompressed samples of a block, each scanline holding every channel in turn
+    fn get_block(&self, channels: &[&Channel], first_line: u32) -> Vec<u8> {
+        let last_line = (first_line + self.compression.lines_per_block()).min(self.height);
+        let width = self.width as usize;
+        let mut bytes = Vec::new();
+        for y in first_line as usize..last_line as usize {
+            for channel in channels {
+                let row = &channel.samples[y * width..(y + 1) * width];
+                match channel.pixel_type {
+                    PixelType::Half => row
+                        .iter()
+                        .for_each(|&s| bytes.extend(half_from_f32(s).to_le_bytes())),
+                    PixelType::Float => row.iter().for_each(|s| bytes.extend(s.to_le_bytes())),
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Encodes the image as a single part scanline EXR file
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::exr::{Compression, PixelType, EXR, EXR_MAGIC};
+    /// let mut exr = EXR::new(2, 2, Compression::None);
+    /// exr.add_channel("Y", PixelType::Half, vec![0.0, 1.0, 2.0, 3.0]);
+    /// let bytes = exr.to_bytes();
+    /// assert!(bytes.starts_with(&EXR_MAGIC));
+    /// assert!(bytes.ends_with(&[1, 0, 0, 0, 4, 0, 0, 0, 0x00, 0x40, 0x00, 0x42]));
+    /// ```
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let channels = self.get_sorted_channels();
+        let mut bytes = self.get_header(&channels);
+
+        let first_lines = (0..self.height)
+            .step_by(self.compression.lines_per_block() as usize)
+            .collect::<Vec<_>>();
+        let offset_table = bytes.len();
+        bytes.resize(offset_table + first_lines.len() * 8, 0);
+
+        for (block, &first_line) in first_lines.iter().enumerate() {
+            let offset = (bytes.len() as u64).to_le_bytes();
+            bytes[offset_table + block * 8..offset_table + (block + 1) * 8]
+                .copy_from_slice(&offset);
+
+            let data =
+                compression::compress(self.compression, &self.get_block(&channels, first_line));
+            bytes.extend(first_line.to_le_bytes());
+            bytes.extend((data.len() as u32).to_le_bytes());
+            bytes.extend(data);
+        }
+        bytes
+    }
+}
+
+/// Appends a header attribute made of its name, type name, size and value
+fn write_attribute(bytes: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    bytes.extend(name.as_bytes());
+    bytes.push(0);
+    bytes.extend(kind.as_bytes());
+    bytes.push(0);
+    bytes.extend((value.len() as u32).to_le_bytes());
+    bytes.extend(value);
+}
+
+mod compression {
+    use super::Compression;
+    use crate::asset_types::deflate::{self, zlib_compress};
+
+    /// Shortest run worth encoding as a run instead of literals
+    const RLE_MIN_RUN: usize = 3;
+    const RLE_MAX_COUNT: usize = 127;
+
+    /// Splits the bytes into the even and odd halves, then replaces each byte by its difference to the previous one
+    ///
+    /// This groups the similar high and low bytes of neighbouring samples and turns smooth gradients into runs.
+    pub fn predict(bytes: &[u8]) -> Vec<u8> {
+        let mut reordered = bytes.iter().step_by(2).copied().collect::<Vec<_>>();
+        reordered.extend(bytes.iter().skip(1).step_by(2));
+        let mut previous = reordered.first().copied().unwrap_or_default();
+        for byte in reordered.iter_mut().skip(1) {
+            let current = *byte;
+            *byte = current.wrapping_sub(previous).wrapping_add(128);
+            previous = current;
+        }
+        reordered
+    }
+
+    /// Encodes runs as a count followed by the byte, and literals as a negative count followed by the bytes
+    pub fn rle(bytes: &[u8]) -> Vec<u8> {
+        let is_run_at =
+            |i: usize| i + 2 < bytes.len() && bytes[i] == bytes[i + 1] && bytes[i] == bytes[i + 2];
+        let mut encoded = Vec::new();
+        let mut start = 0;
+        while start < bytes.len() {
+            let run = bytes[start..]
+                .iter()
+                .take(RLE_MAX_COUNT + 1)
+                .take_while(|&&b| b == bytes[start])
+                .count();
+            if run >= RLE_MIN_RUN {
+                encoded.extend([(run - 1) as u8, bytes[start]]);
+                start += run;
+            } else {
+                let mut end = start + 1;
+                while end < bytes.len() && end - start < RLE_MAX_COUNT && !is_run_at(end) {
+                    end += 1;
+                }
+                encoded.push(((end - start) as u8).wrapping_neg());
+                encoded.extend(&bytes[start..end]);
+                start = end;
+            }
+        }
+        encoded
+    }
+
+    /// Compresses a block, keeping it uncompressed whenever compression does not make it smaller as readers expect
+    pub fn compress(compression: Compression, bytes: &[u8]) -> Vec<u8> {
+        let compressed = match compression {
+            Compression::None => return bytes.to_vec(),
+            Compression::RLE => rle(&predict(bytes)),
+            Compression::ZIP => zlib_compress(&predict(bytes), deflate::Compression::FixedHuffman),
+        };
+        if compressed.len() < bytes.len() {
+            compressed
+        } else {
+            bytes.to_vec()
+        }
+    }
+
+    /// Reverses [`compress`] given the size of the uncompressed block
+    #[cfg(test)]
+    pub fn decompress(compression: Compression, bytes: &[u8], size: usize) -> Vec<u8> {
+        if bytes.len() == size {
+            return bytes.to_vec();
+        }
+        let predicted = match compression {
+            Compression::None => return bytes.to_vec(),
+            Compression::RLE => unrle(bytes),
+            Compression::ZIP => deflate::zlib_decompress(bytes).unwrap(),
+        };
+        unpredict(&predicted)
+    }
+
+    #[cfg(test)]
+    fn unrle(bytes: &[u8]) -> Vec<u8> {
+        let mut decoded = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            let count = bytes[i] as i8;
+            if count < 0 {
+                let length = usize::from(count.unsigned_abs());
+                decoded.extend(&bytes[i + 1..i + 1 + length]);
+                i += 1 + length;
+            } else {
+                decoded.extend(std::iter::repeat_n(bytes[i + 1], count as usize + 1));
+                i += 2;
+            }
+        }
+        decoded
+    }
+
+    #[cfg(test)]
+    fn unpredict(bytes: &[u8]) -> Vec<u8> {
+        let mut deltas = bytes.to_vec();
+        for i in 1..deltas.len() {
+            deltas[i] = deltas[i].wrapping_add(deltas[i - 1]).wrapping_sub(128);
+        }
+        let (even, odd) = deltas.split_at(deltas.len().div_ceil(2));
+        let mut original = Vec::with_capacity(deltas.len());
+        for (i, &byte) in even.iter().enumerate() {
+            original.push(byte);
+            if let Some(&byte) = odd.get(i) {
+                original.push(byte);
+            }
+        }
+        original
+    }
+
+    #[cfg(test)]
+    mod tests_compression {
+        use super::*;
+        use crate::asset_types::checksum::crc32;
+
+        #[test]
+        fn predict_bytes() {
+            assert_eq!(Vec::<u8>::new(), predict(&[]));
+            assert_eq!(vec![7], predict(&[7]));
+            // Reordered to [1, 3, 5, 2, 4] before taking the deltas
+            assert_eq!(vec![1, 130, 130, 125, 130], predict(&[1, 2, 3, 4, 5]));
+            assert_eq!(vec![10, 128, 128, 128], predict(&[10, 10, 10, 10]));
+            for bytes in [&b"abcdefg"[..], b"", b"\xFF\x00\x80\x7F\x01", b"zz"] {
+                assert_eq!(bytes, unpredict(&predict(bytes)));
+            }
+        }
+
+        #[test]
+        fn rle_runs_and_literals() {
+            assert_eq!(vec![3, 9], rle(&[9, 9, 9, 9]));
+            assert_eq!(vec![0xFE, 1, 2], rle(&[1, 2]));
+            assert_eq!(vec![0xFE, 1, 1], rle(&[1, 1]));
+            assert_eq!(vec![0xFF, 5, 2, 7], rle(&[5, 7, 7, 7]));
+            assert_eq!(vec![2, 7, 0xFE, 1, 2], rle(&[7, 7, 7, 1, 2]));
+            assert_eq!(Vec::<u8>::new(), rle(&[]));
+        }
+
+        #[test]
+        fn rle_limits() {
+            let run = vec![4; 300];
+            assert_eq!(vec![127, 4, 127, 4, 43, 4], rle(&run));
+            assert_eq!(run, unrle(&rle(&run)));
+
+            let literals = (0..=255).collect::<Vec<u8>>();
+            let encoded = rle(&literals);
+            assert_eq!(0x81, encoded[0]);
+            assert_eq!(256 + 3, encoded.len());
+            assert_eq!(literals, unrle(&encoded));
+        }
+
+        #[test]
+        fn compress_falls_back_to_raw() {
+            let noise = (0..64_u32)
+                .map(|i| crc32(&i.to_le_bytes()) as u8)
+                .collect::<Vec<_>>();
+            assert_eq!(noise, compress(Compression::RLE, &noise));
+            assert_eq!(noise, compress(Compression::None, &noise));
+
+            let flat = vec![0x3C; 64];
+            for compression in [Compression::RLE, Compression::ZIP] {
+                let compressed = compress(compression, &flat);
+                assert!(compressed.len() < flat.len());
+                assert_eq!(flat, decompress(compression, &compressed, flat.len()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_exr {
+    use super::*;
+    use crate::core3d::color::Color;
+
+    fn read_u32(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    /// Reads the blocks listed in the offset table back into raw scanline bytes
+    fn read_blocks(exr: &EXR, bytes: &[u8], header_size: usize) -> Vec<u8> {
+        let channels = exr.get_sorted_channels();
+        let blocks = exr.height.div_ceil(exr.compression.lines_per_block()) as usize;
+        let mut raw = Vec::new();
+        for block in 0..blocks {
+            let at = header_size + block * 8;
+            let offset = u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap()) as usize;
+            let first_line = read_u32(bytes, offset);
+            let size = read_u32(bytes, offset + 4) as usize;
+            let expected = exr.get_block(&channels, first_line);
+            let data = &bytes[offset + 8..offset + 8 + size];
+            raw.extend(compression::decompress(
+                exr.compression,
+                data,
+                expected.len(),
+            ));
+        }
+        raw
+    }
+
+    #[test]
+    fn new() {
+        let exr = EXR::new(3, 2, Compression::RLE);
+        assert_eq!(3, exr.width);
+        assert_eq!(2, exr.height);
+        assert!(exr.channels.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "EXR images cannot be empty!")]
+    fn new_empty() {
+        let _ = EXR::new(0, 2, Compression::None);
+    }
+
+    #[test]
+    #[should_panic(expected = "EXR channel names must be unique and not empty!")]
+    fn duplicate_channel() {
+        let mut exr = EXR::new(1, 1, Compression::None);
+        exr.add_channel("Z", PixelType::Float, vec![1.0]);
+        exr.add_channel("Z", PixelType::Float, vec![2.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "EXR layer size does not match the image!")]
+    fn layer_size() {
+        let mut exr = EXR::new(1, 1, Compression::None);
+        exr.add_layer("albedo", &Canvas::new(2, 1), PixelType::Half);
+    }
+
+    #[test]
+    fn header() {
+        let mut exr = EXR::new(2, 1, Compression::ZIP);
+        exr.add_channel("G", PixelType::Half, vec![0.0; 2]);
+        exr.add_channel("B", PixelType::Float, vec![0.0; 2]);
+        let header = exr.get_header(&exr.get_sorted_channels());
+
+        let mut expected = vec![0x76, 0x2F, 0x31, 0x01, 2, 0, 0, 0];
+        expected.extend(b"channels\0chlist\0\x25\0\0\0");
+        expected.extend(b"B\0\x02\0\0\0\0\0\0\0\x01\0\0\0\x01\0\0\0");
+        expected.extend(b"G\0\x01\0\0\0\0\0\0\0\x01\0\0\0\x01\0\0\0\0");
+        expected.extend(b"compression\0compression\0\x01\0\0\0\x03");
+        expected.extend(b"dataWindow\0box2i\0\x10\0\0\0\0\0\0\0\0\0\0\0\x01\0\0\0\0\0\0\0");
+        expected.extend(b"displayWindow\0box2i\0\x10\0\0\0\0\0\0\0\0\0\0\0\x01\0\0\0\0\0\0\0");
+        expected.extend(b"lineOrder\0lineOrder\0\x01\0\0\0\0");
+        expected.extend(b"pixelAspectRatio\0float\0\x04\0\0\0\0\0\x80\x3F");
+        expected.extend(b"screenWindowCenter\0v2f\0\x08\0\0\0\0\0\0\0\0\0\0\0");
+        expected.extend(b"screenWindowWidth\0float\0\x04\0\0\0\0\0\x80\x3F");
+        expected.push(0);
+        assert_eq!(expected, header);
+    }
+
+    #[test]
+    fn long_names() {
+        let mut exr = EXR::new(1, 1, Compression::None);
+        exr.add_channel(&"n".repeat(32), PixelType::Half, vec![0.0]);
+        let bytes = exr.to_bytes();
+        assert_eq!(EXR_VERSION | EXR_LONG_NAMES, read_u32(&bytes, 4));
+    }
+
+    #[test]
+    fn block_layout() {
+        let mut exr = EXR::new(2, 2, Compression::None);
+        exr.add_channel("Z", PixelType::Float, vec![1.0, 2.0, 3.0, 4.0]);
+        exr.add_channel("A", PixelType::Half, vec![0.5, 1.0, 2.0, 0.0]);
+        let channels = exr.get_sorted_channels();
+        assert_eq!(
+            vec![0x00, 0x38, 0x00, 0x3C, 0, 0, 0x80, 0x3F, 0, 0, 0, 0x40],
+            exr.get_block(&channels, 0)
+        );
+        assert_eq!(
+            vec![0x00, 0x40, 0x00, 0x00, 0, 0, 0x40, 0x40, 0, 0, 0x80, 0x40],
+            exr.get_block(&channels, 1)
+        );
+    }
+
+    #[test]
+    fn offsets_and_blocks() {
+        let mut canvas = Canvas::new(5, 37);
+        for (i, color) in canvas.raw_buffer.iter_mut().enumerate() {
+            let i = i as f32;
+            *color = Color::new(i * 0.25, (i * 0.1).sin(), 100.0 - i);
+        }
+        let depth = (0..5 * 37).map(|i| i as f32 * 1.5).collect::<Vec<_>>();
+
+        for compression in [Compression::None, Compression::RLE, Compression::ZIP] {
+            let mut exr = EXR::new(5, 37, compression);
+            exr.add_layer("", &canvas, PixelType::Half);
+            exr.add_layer("albedo", &canvas, PixelType::Half);
+            exr.add_channel("depth.Z", PixelType::Float, depth.clone());
+            let bytes = exr.to_bytes();
+            let header_size = exr.get_header(&exr.get_sorted_channels()).len();
+
+            let expected = (0..37)
+                .step_by(compression.lines_per_block() as usize)
+                .flat_map(|y| exr.get_block(&exr.get_sorted_channels(), y))
+                .collect::<Vec<_>>();
+            assert_eq!(expected, read_blocks(&exr, &bytes, header_size));
+
+            let blocks = 37_u32.div_ceil(compression.lines_per_block()) as usize;
+            let first = header_size + blocks * 8;
+            assert_eq!(
+                first as u64,
+                u64::from_le_bytes(bytes[header_size..header_size + 8].try_into().unwrap())
+            );
+            if compression == Compression::None {
+                assert_eq!(first + 37 * (8 + 5 * (2 * 6 + 4)), bytes.len());
+            }
+        }
+    }
+}
+
+impl From<&Canvas> for EXR {
+    /// Creates a new zip compressed exr with the colors of a canvas as half float `R`, `G` and `B` channels
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::exr::EXR;
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(2, 1);
+    /// canvas.set_pixel_at(1, 0, Color::new(4.0, 2.0, 1.0));
+    /// let exr = EXR::from(&canvas);
+    /// assert_eq!(vec![0.0, 4.0], exr.channels[0].samples);
+    /// assert_eq!("B", exr.channels[2].name);
+    /// ```
+    fn from(canvas: &Canvas) -> Self {
+        let mut exr = Self::new(canvas.width, canvas.height, Compression::ZIP);
+        exr.add_layer("", canvas, PixelType::Half);
+        exr
+    }
+}
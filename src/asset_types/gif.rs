@@ -0,0 +1,748 @@
+use std::collections::HashMap;
+
+use crate::{
+    core3d::{color::Color, color_rgb::ColorRGB},
+    graphics2d::{canvas::Canvas, dither::Dither},
+};
+
+/// Magic bytes of the GIF version supporting animation extensions
+pub const GIF_SIGNATURE: &[u8] = b"GIF89a";
+const EXTENSION_INTRODUCER: u8 = 0x21;
+const GRAPHIC_CONTROL_LABEL: u8 = 0xF9;
+const APPLICATION_LABEL: u8 = 0xFF;
+const IMAGE_SEPARATOR: u8 = 0x2C;
+const TRAILER: u8 = 0x3B;
+/// Disposal method leaving each frame in place, so later frames are drawn on top
+const DISPOSE_NONE: u8 = 1 << 2;
+const MAX_COLORS: usize = 256;
+const MAX_CODE_SIZE: u32 = 12;
+const MAX_SUB_BLOCK: usize = 255;
+
+/// Algorithm choosing the palette shared by all frames
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Quantizer {
+    /// Splits the box of colors at the median of its widest channel until there are enough boxes
+    #[default]
+    MedianCut,
+    /// Merges the least used leaves of an octree of colors until there are few enough
+    Octree,
+}
+
+#[derive(Default, Debug)]
+pub struct GIF {
+    pub width: u16,
+    pub height: u16,
+    /// Time each frame is shown, in hundredths of a second
+    pub delay: u16,
+    /// Number of times the animation is repeated, 0 repeating forever and `None` playing it only once
+    pub repeat: Option<u16>,
+    pub quantizer: Quantizer,
+    /// Hiding of the banding left when mapping the frames to the palette
+    pub dithering: Dither,
+
+    /// RGB pixels of each frame row by row, starting at the top
+    pub frames: Vec<Vec<[u8; 3]>>,
+}
+
+impl GIF {
+    /// Creates a new gif without frames, looping forever
+    #[must_use]
+    pub const fn new(width: u16, height: u16, delay: u16) -> Self {
+        Self {
+            width,
+            height,
+            delay,
+            repeat: Some(0),
+            quantizer: Quantizer::MedianCut,
+            dithering: Dither::None,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Creates a new looping gif from a sequence of canvases of the same size
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::gif::GIF;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let frames = vec![Canvas::new(4, 3), Canvas::new(4, 3)];
+    /// let gif = GIF::from_frames(&frames, 5);
+    /// assert_eq!(2, gif.frames.len());
+    /// assert_eq!(4, gif.width);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the canvases differ in size or are wider or higher than the 65535 pixels a gif can hold
+    #[must_use]
+    pub fn from_frames(frames: &[Canvas], delay: u16) -> Self {
+        let (width, height) = frames.first().map_or((0, 0), |c| {
+            (
+                u16::try_from(c.width).expect("Canvas is too large for a GIF image!"),
+                u16::try_from(c.height).expect("Canvas is too large for a GIF image!"),
+            )
+        });
+        let mut gif = Self::new(width, height, delay);
+        frames.iter().for_each(|frame| gif.add_frame(frame));
+        gif
+    }
+
+    /// Appends a canvas as the next frame, clamping each channel to 0..1 before scaling it to 255
+    ///
+    /// # Panics
+    ///
+    /// Panics if the canvas size does not match the animation
+    pub fn add_frame(&mut self, canvas: &Canvas) {
+        assert!(
+            canvas.width == u32::from(self.width) && canvas.height == u32::from(self.height),
+            "GIF frame size does not match the animation!"
+        );
+        self.frames
+            .push(canvas.raw_buffer.iter().map(|&c| to_rgb(c)).collect());
+    }
+
+    /// Returns the palette for all frames, at most 256 colors
+    fn get_palette(&self) -> Vec<[u8; 3]> {
+        let mut histogram = HashMap::new();
+        for pixel in self.frames.iter().flatten() {
+            *histogram.entry(*pixel).or_insert(0_u64) += 1;
+        }
+        if histogram.len() <= MAX_COLORS {
+            let mut palette = histogram.into_keys().collect::<Vec<_>>();
+            palette.sort_unstable();
+            return palette;
+        }
+        match self.quantizer {
+            Quantizer::MedianCut => quantize::median_cut(&histogram, MAX_COLORS),
+            Quantizer::Octree => quantize::octree(&histogram, MAX_COLORS),
+        }
+    }
+
+    /// Encodes the animation as a GIF file with a global palette
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::gif::{GIF, GIF_SIGNATURE};
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let gif = GIF::from_frames(&[Canvas::new(2, 2)], 10);
+    /// let bytes = gif.to_bytes();
+    /// assert!(bytes.starts_with(GIF_SIGNATURE));
+    /// assert_eq!(Some(&0x3B), bytes.last());
+    /// ```
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut palette = self.get_palette();
+        let colors = palette.iter().map(|&p| to_color(p)).collect::<Vec<_>>();
+        // Color tables hold a power of two of at least two colors
+        let table_bits = palette.len().max(2).next_power_of_two().trailing_zeros();
+        palette.resize(1 << table_bits, [0, 0, 0]);
+
+        let mut bytes = GIF_SIGNATURE.to_vec();
+        bytes.extend(self.width.to_le_bytes());
+        bytes.extend(self.height.to_le_bytes());
+        // Global color table with 8 bits per primary, followed by background color and aspect ratio
+        bytes.extend([0x80 | 0x70 | (table_bits - 1) as u8, 0, 0]);
+        palette.iter().for_each(|color| bytes.extend(color));
+
+        if let Some(repeat) = self.repeat {
+            bytes.extend([EXTENSION_INTRODUCER, APPLICATION_LABEL, 11]);
+            bytes.extend(b"NETSCAPE2.0");
+            bytes.extend([3, 1]);
+            bytes.extend(repeat.to_le_bytes());
+            bytes.push(0);
+        }
+
+        let mut mapper = quantize::Mapper::new(&palette);
+        for frame in &self.frames {
+            bytes.extend([EXTENSION_INTRODUCER, GRAPHIC_CONTROL_LABEL, 4, DISPOSE_NONE]);
+            bytes.extend(self.delay.to_le_bytes());
+            bytes.extend([0, 0]);
+
+            bytes.push(IMAGE_SEPARATOR);
+            bytes.extend([0, 0, 0, 0]);
+            bytes.extend(self.width.to_le_bytes());
+            bytes.extend(self.height.to_le_bytes());
+            bytes.push(0);
+
+            let indices: Vec<u8> = match self.dithering {
+                Dither::None => frame.iter().map(|&p| mapper.map(p)).collect(),
+                // Dithered pixels are palette colors already, so the mapper finds them exactly
+                dither => self
+                    .frame_canvas(frame)
+                    .quantize_palette(&colors, dither)
+                    .raw_buffer
+                    .iter()
+                    .map(|&c| mapper.map(to_rgb(c)))
+                    .collect(),
+            };
+            let min_code_size = table_bits.max(2);
+            bytes.push(min_code_size as u8);
+            for block in lzw::encode(&indices, min_code_size).chunks(MAX_SUB_BLOCK) {
+                bytes.push(block.len() as u8);
+                bytes.extend(block);
+            }
+            bytes.push(0);
+        }
+
+        bytes.push(TRAILER);
+        bytes
+    }
+
+    /// Returns a frame as a canvas, to dither it against the palette
+    fn frame_canvas(&self, frame: &[[u8; 3]]) -> Canvas {
+        Canvas {
+            width: u32::from(self.width),
+            height: u32::from(self.height),
+            raw_buffer: frame.iter().map(|&p| to_color(p)).collect(),
+        }
+    }
+}
+
+/// Scales each channel of an RGB pixel from 255 to 0..1
+fn to_color(pixel: [u8; 3]) -> Color {
+    let [r, g, b] = pixel.map(|c| f32::from(c) / 255.0);
+    Color::new(r, g, b)
+}
+
+/// Clamps each channel of a color to 0..1 and scales it to 255
+fn to_rgb(color: Color) -> [u8; 3] {
+    [color.r(), color.g(), color.b()].map(|c| f32::round(num::clamp(c, 0.0, 1.0) * 255.0) as u8)
+}
+
+mod quantize {
+    use std::collections::HashMap;
+
+    /// Weighted average of a set of colors
+    fn average<'a>(colors: impl Iterator<Item = (&'a [u8; 3], &'a u64)>) -> [u8; 3] {
+        let (mut sum, mut total) = ([0_u64; 3], 0);
+        for (color, &count) in colors {
+            (0..3).for_each(|c| sum[c] += u64::from(color[c]) * count);
+            total += count;
+        }
+        sum.map(|s| ((s + total / 2) / total.max(1)) as u8)
+    }
+
+    /// Widest channel range of a box of colors, and the channel it is in
+    fn widest_channel(colors: &[([u8; 3], u64)]) -> (u8, usize) {
+        (0..3)
+            .map(|c| {
+                let (min, max) = colors
+                    .iter()
+                    .fold((u8::MAX, u8::MIN), |(min, max), (color, _)| {
+                        (min.min(color[c]), max.max(color[c]))
+                    });
+                (max.saturating_sub(min), c)
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Splits the colors into boxes, halving the population of the box with the widest channel range each time
+    pub fn median_cut(histogram: &HashMap<[u8; 3], u64>, max_colors: usize) -> Vec<[u8; 3]> {
+        let mut colors = histogram.iter().map(|(&c, &n)| (c, n)).collect::<Vec<_>>();
+        colors.sort_unstable();
+        let mut boxes = vec![(widest_channel(&colors), colors)];
+        while boxes.len() < max_colors {
+            let Some((i, &((_, channel), _))) = boxes
+                .iter()
+                .enumerate()
+                .filter(|(_, ((width, _), _))| *width > 0)
+                .max_by_key(|(_, ((width, _), _))| *width)
+            else {
+                break;
+            };
+            let (_, mut lower) = boxes.swap_remove(i);
+            lower.sort_by_key(|(color, _)| color[channel]);
+            let half = lower.iter().map(|(_, n)| n).sum::<u64>() / 2;
+            let mut population = 0;
+            let median = lower
+                .iter()
+                .position(|(_, n)| {
+                    population += n;
+                    population >= half
+                })
+                .unwrap_or_default()
+                .min(lower.len() - 2);
+            let upper = lower.split_off(median + 1);
+            boxes.push((widest_channel(&lower), lower));
+            boxes.push((widest_channel(&upper), upper));
+        }
+        boxes
+            .iter()
+            .map(|(_, colors)| average(colors.iter().map(|(color, count)| (color, count))))
+            .collect()
+    }
+
+    #[derive(Default, Clone)]
+    struct OctreeNode {
+        children: [Option<usize>; 8],
+        /// Channel sums and number of the pixels merged into this node while it is a leaf
+        sum: [u64; 3],
+        count: u64,
+        /// Number of pixels in the whole subtree
+        total: u64,
+        depth: usize,
+    }
+
+    /// Child of a node holding the color, chosen by one bit of each channel
+    const fn octant(color: [u8; 3], depth: usize) -> usize {
+        let shift = 7 - depth;
+        (((color[0] >> shift) & 1) << 2
+            | ((color[1] >> shift) & 1) << 1
+            | ((color[2] >> shift) & 1)) as usize
+    }
+
+    /// Builds an octree of the colors, then folds the least used nodes into their parents level by level from the
+    /// bottom until there are few enough leaves
+    pub fn octree(histogram: &HashMap<[u8; 3], u64>, max_colors: usize) -> Vec<[u8; 3]> {
+        let mut nodes = vec![OctreeNode::default()];
+        let mut colors = histogram.iter().collect::<Vec<_>>();
+        colors.sort_unstable();
+        for (&color, &count) in colors {
+            let mut node = 0;
+            nodes[node].total += count;
+            for depth in 0..8 {
+                let child = octant(color, depth);
+                node = match nodes[node].children[child] {
+                    Some(next) => next,
+                    None => {
+                        nodes.push(OctreeNode {
+                            depth: depth + 1,
+                            ..OctreeNode::default()
+                        });
+                        let next = nodes.len() - 1;
+                        nodes[node].children[child] = Some(next);
+                        next
+                    }
+                };
+                nodes[node].total += count;
+            }
+            (0..3).for_each(|c| nodes[node].sum[c] += u64::from(color[c]) * count);
+            nodes[node].count += count;
+        }
+
+        let mut leaves = histogram.len();
+        for depth in (0..8).rev() {
+            if leaves <= max_colors {
+                break;
+            }
+            // All children are leaves by now, as the level below has been folded completely
+            let mut parents = (0..nodes.len())
+                .filter(|&n| {
+                    nodes[n].depth == depth && nodes[n].children.iter().any(Option::is_some)
+                })
+                .collect::<Vec<_>>();
+            parents.sort_by_key(|&n| nodes[n].total);
+            for parent in parents {
+                if leaves <= max_colors {
+                    break;
+                }
+                for child in std::mem::take(&mut nodes[parent].children)
+                    .into_iter()
+                    .flatten()
+                {
+                    let (sum, count) = (nodes[child].sum, std::mem::take(&mut nodes[child].count));
+                    (0..3).for_each(|c| nodes[parent].sum[c] += sum[c]);
+                    nodes[parent].count += count;
+                    leaves -= 1;
+                }
+                leaves += 1;
+            }
+        }
+
+        nodes
+            .iter()
+            .filter(|n| n.count > 0 && n.children.iter().all(Option::is_none))
+            .map(|n| n.sum.map(|s| ((s + n.count / 2) / n.count) as u8))
+            .collect()
+    }
+
+    /// Finds the closest palette color, remembering the answer for colors seen before
+    pub struct Mapper<'a> {
+        palette: &'a [[u8; 3]],
+        cache: HashMap<[u8; 3], u8>,
+    }
+
+    impl<'a> Mapper<'a> {
+        pub fn new(palette: &'a [[u8; 3]]) -> Self {
+            Self {
+                palette,
+                cache: HashMap::new(),
+            }
+        }
+
+        pub fn map(&mut self, color: [u8; 3]) -> u8 {
+            *self.cache.entry(color).or_insert_with(|| {
+                let distance = |p: &[u8; 3]| {
+                    (0..3)
+                        .map(|c| (i32::from(p[c]) - i32::from(color[c])).pow(2))
+                        .sum::<i32>()
+                };
+                self.palette
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, p)| distance(p))
+                    .map_or(0, |(i, _)| i as u8)
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests_quantize {
+        use super::*;
+
+        fn histogram(colors: &[([u8; 3], u64)]) -> HashMap<[u8; 3], u64> {
+            colors.iter().copied().collect()
+        }
+
+        #[test]
+        fn median_cut_splits_widest_channel() {
+            let colors = histogram(&[
+                ([0, 0, 0], 1),
+                ([10, 0, 0], 1),
+                ([200, 0, 0], 1),
+                ([210, 0, 0], 1),
+            ]);
+            let mut palette = median_cut(&colors, 2);
+            palette.sort_unstable();
+            assert_eq!(vec![[5, 0, 0], [205, 0, 0]], palette);
+        }
+
+        #[test]
+        fn median_cut_weights_by_population() {
+            let colors = histogram(&[([0, 0, 0], 100), ([100, 0, 0], 1), ([200, 0, 0], 1)]);
+            let mut palette = median_cut(&colors, 2);
+            palette.sort_unstable();
+            assert_eq!(vec![[0, 0, 0], [150, 0, 0]], palette);
+            assert_eq!(3, median_cut(&colors, 10).len());
+        }
+
+        #[test]
+        fn octree_merges_least_used() {
+            let colors = histogram(&[([0, 0, 0], 10), ([0, 0, 1], 1), ([255, 255, 255], 5)]);
+            let mut palette = octree(&colors, 2);
+            palette.sort_unstable();
+            assert_eq!(vec![[0, 0, 0], [255, 255, 255]], palette);
+
+            let colors = histogram(&[
+                ([0, 0, 0], 1),
+                ([0, 0, 3], 1),
+                ([0, 0, 16], 1),
+                ([255, 0, 0], 1),
+            ]);
+            let mut palette = octree(&colors, 3);
+            palette.sort_unstable();
+            assert_eq!(vec![[0, 0, 2], [0, 0, 16], [255, 0, 0]], palette);
+        }
+
+        #[test]
+        fn octants() {
+            assert_eq!(0, octant([0x7F, 0x7F, 0x7F], 0));
+            assert_eq!(7, octant([0x80, 0x80, 0x80], 0));
+            assert_eq!(4, octant([0x01, 0x00, 0x00], 7));
+            assert_eq!(3, octant([0x00, 0x40, 0x40], 1));
+        }
+
+        #[test]
+        fn mapper_finds_closest() {
+            let palette = [[0, 0, 0], [255, 255, 255], [255, 0, 0]];
+            let mut mapper = Mapper::new(&palette);
+            assert_eq!(0, mapper.map([10, 20, 30]));
+            assert_eq!(1, mapper.map([200, 200, 200]));
+            assert_eq!(2, mapper.map([200, 10, 10]));
+        }
+    }
+}
+
+mod lzw {
+    use std::collections::HashMap;
+
+    use super::MAX_CODE_SIZE;
+
+    /// Packs variable length codes into bytes, least significant bit first
+    struct BitWriter {
+        bytes: Vec<u8>,
+        buffer: u32,
+        count: u32,
+    }
+
+    impl BitWriter {
+        fn write(&mut self, code: u16, size: u32) {
+            self.buffer |= u32::from(code) << self.count;
+            self.count += size;
+            while self.count >= 8 {
+                self.bytes.push(self.buffer as u8);
+                self.buffer >>= 8;
+                self.count -= 8;
+            }
+        }
+
+        fn finish(mut self) -> Vec<u8> {
+            if self.count > 0 {
+                self.bytes.push(self.buffer as u8);
+            }
+            self.bytes
+        }
+    }
+
+    /// Compresses palette indices with the variable code size LZW of GIF, starting with a clear code
+    pub fn encode(indices: &[u8], min_code_size: u32) -> Vec<u8> {
+        let clear = 1_u16 << min_code_size;
+        let end = clear + 1;
+        let mut writer = BitWriter {
+            bytes: Vec::new(),
+            buffer: 0,
+            count: 0,
+        };
+        let mut dictionary = HashMap::new();
+        let mut code_size = min_code_size + 1;
+        let mut next_code = end + 1;
+        writer.write(clear, code_size);
+
+        let Some((&first, rest)) = indices.split_first() else {
+            writer.write(end, code_size);
+            return writer.finish();
+        };
+        let mut prefix = u16::from(first);
+        for &index in rest {
+            if let Some(&code) = dictionary.get(&(prefix, index)) {
+                prefix = code;
+                continue;
+            }
+            writer.write(prefix, code_size);
+            if next_code < 1 << MAX_CODE_SIZE {
+                dictionary.insert((prefix, index), next_code);
+                // The decoder adds its entries one code later, so the size grows once this code is reachable
+                if next_code == 1 << code_size {
+                    code_size += 1;
+                }
+                next_code += 1;
+            } else {
+                writer.write(clear, code_size);
+                dictionary.clear();
+                code_size = min_code_size + 1;
+                next_code = end + 1;
+            }
+            prefix = u16::from(index);
+        }
+        writer.write(prefix, code_size);
+        writer.write(end, code_size);
+        writer.finish()
+    }
+
+    /// Decodes a code stream the way GIF readers do
+    #[cfg(test)]
+    pub fn decode(bytes: &[u8], min_code_size: u32) -> Vec<u8> {
+        let clear = 1_usize << min_code_size;
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let mut code_size = min_code_size + 1;
+        let mut previous: Option<usize> = None;
+        let mut output = Vec::new();
+        let (mut buffer, mut count, mut position) = (0_u32, 0, 0);
+        loop {
+            while count < code_size {
+                buffer |= u32::from(bytes[position]) << count;
+                position += 1;
+                count += 8;
+            }
+            let code = (buffer & ((1 << code_size) - 1)) as usize;
+            buffer >>= code_size;
+            count -= code_size;
+
+            if code == clear {
+                table = (0..clear).map(|i| vec![i as u8]).collect();
+                table.extend([vec![], vec![]]);
+                code_size = min_code_size + 1;
+                previous = None;
+                continue;
+            }
+            if code == clear + 1 {
+                return output;
+            }
+            let entry = match (table.get(code), previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(p)) => {
+                    let mut entry = table[p].clone();
+                    entry.push(table[p][0]);
+                    entry
+                }
+                (None, None) => panic!("invalid first code"),
+            };
+            output.extend(&entry);
+            if let Some(p) = previous {
+                if table.len() < 1 << MAX_CODE_SIZE {
+                    let mut added = table[p].clone();
+                    added.push(entry[0]);
+                    table.push(added);
+                    if table.len() == 1 << code_size && code_size < MAX_CODE_SIZE {
+                        code_size += 1;
+                    }
+                }
+            }
+            previous = Some(code);
+        }
+    }
+
+    #[cfg(test)]
+    mod tests_lzw {
+        use super::*;
+
+        #[test]
+        fn known_stream() {
+            // Sample image from the GIF specification walkthroughs, 4 colors and a 10x10 pattern
+            let rows: [[u8; 10]; 4] = [
+                [1, 1, 1, 1, 1, 2, 2, 2, 2, 2],
+                [1, 1, 1, 0, 0, 0, 0, 2, 2, 2],
+                [2, 2, 2, 0, 0, 0, 0, 1, 1, 1],
+                [2, 2, 2, 2, 2, 1, 1, 1, 1, 1],
+            ];
+            let indices = [0, 0, 0, 1, 1, 2, 2, 3, 3, 3]
+                .iter()
+                .flat_map(|&r| rows[r])
+                .collect::<Vec<_>>();
+            let expected = [
+                0x8C, 0x2D, 0x99, 0x87, 0x2A, 0x1C, 0xDC, 0x33, 0xA0, 0x02, 0x75, 0xEC, 0x95, 0xFA,
+                0xA8, 0xDE, 0x60, 0x8C, 0x04, 0x91, 0x4C, 0x01,
+            ];
+            assert_eq!(expected.to_vec(), encode(&indices, 2));
+            assert_eq!(indices, decode(&expected, 2));
+        }
+
+        #[test]
+        fn empty_and_single() {
+            assert_eq!(Vec::<u8>::new(), decode(&encode(&[], 2), 2));
+            assert_eq!(vec![3], decode(&encode(&[3], 2), 2));
+        }
+
+        #[test]
+        fn code_size_growth_and_reset() {
+            // Long varied input fills the dictionary several times over
+            let indices = (0..100_000_u32)
+                .map(|i| ((i * 7 + i / 13 + (i % 31) * (i % 31)) % 256) as u8)
+                .collect::<Vec<_>>();
+            assert_eq!(indices, decode(&encode(&indices, 8), 8));
+
+            let indices = (0..5000_u32).map(|i| (i % 3) as u8).collect::<Vec<_>>();
+            assert_eq!(indices, decode(&encode(&indices, 2), 2));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_gif {
+    use super::*;
+    use crate::core3d::color::Color;
+
+    #[test]
+    fn new() {
+        let gif = GIF::new(4, 3, 8);
+        assert_eq!(4, gif.width);
+        assert_eq!(3, gif.height);
+        assert_eq!(8, gif.delay);
+        assert_eq!(Some(0), gif.repeat);
+        assert!(gif.frames.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "GIF frame size does not match the animation!")]
+    fn frame_size() {
+        let _ = GIF::from_frames(&[Canvas::new(2, 2), Canvas::new(2, 3)], 10);
+    }
+
+    #[test]
+    fn structure() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set_pixel_at(1, 0, Color::new(1.0, 0.0, 0.0));
+        let mut gif = GIF::new(2, 1, 25);
+        gif.add_frame(&canvas);
+        gif.add_frame(&canvas);
+        gif.repeat = Some(3);
+        let bytes = gif.to_bytes();
+
+        let mut expected = b"GIF89a\x02\0\x01\0\xF0\0\0".to_vec();
+        expected.extend([0, 0, 0, 255, 0, 0]);
+        expected.extend(b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x03\0\0");
+        let frame = [
+            &[0x21, 0xF9, 4, 4, 25, 0, 0, 0][..],
+            &[0x2C, 0, 0, 0, 0, 2, 0, 1, 0, 0],
+            &[2, 2, 0x44, 0x0A, 0],
+        ]
+        .concat();
+        expected.extend(&frame);
+        expected.extend(&frame);
+        expected.push(0x3B);
+        assert_eq!(expected, bytes);
+    }
+
+    #[test]
+    fn without_loop() {
+        let mut gif = GIF::from_frames(&[Canvas::new(1, 1)], 1);
+        gif.repeat = None;
+        let bytes = gif.to_bytes();
+        assert!(!bytes.windows(8).any(|w| w == b"NETSCAPE"));
+    }
+
+    #[test]
+    fn quantized_frames_decode() {
+        let frames = (0..3)
+            .map(|f| {
+                let mut canvas = Canvas::new(40, 30);
+                for (i, color) in canvas.raw_buffer.iter_mut().enumerate() {
+                    let (x, y) = ((i % 40) as f32, (i / 40) as f32);
+                    *color = Color::new(x / 39.0, y / 29.0, f as f32 / 2.0);
+                }
+                canvas
+            })
+            .collect::<Vec<_>>();
+
+        for quantizer in [Quantizer::MedianCut, Quantizer::Octree] {
+            for dithering in [
+                Dither::None,
+                Dither::Bayer,
+                Dither::BlueNoise,
+                Dither::FloydSteinberg,
+            ] {
+                let mut gif = GIF::from_frames(&frames, 4);
+                gif.quantizer = quantizer;
+                gif.dithering = dithering;
+                let palette = gif.get_palette();
+                assert!(palette.len() <= 256);
+
+                let bytes = gif.to_bytes();
+                let table = 3 * 256;
+                let mut at = 13 + table + 19;
+                let mut total_error = 0.0;
+                for frame in &gif.frames {
+                    at += 8 + 10;
+                    assert_eq!(8, bytes[at]);
+                    let mut data = Vec::new();
+                    at += 1;
+                    while bytes[at] != 0 {
+                        let size = usize::from(bytes[at]);
+                        data.extend(&bytes[at + 1..at + 1 + size]);
+                        at += 1 + size;
+                    }
+                    at += 1;
+                    let indices = lzw::decode(&data, 8);
+                    assert_eq!(frame.len(), indices.len());
+                    for (pixel, &index) in frame.iter().zip(&indices) {
+                        let color =
+                            &bytes[13 + 3 * usize::from(index)..16 + 3 * usize::from(index)];
+                        total_error += (0..3)
+                            .map(|c| (f64::from(pixel[c]) - f64::from(color[c])).abs())
+                            .sum::<f64>();
+                    }
+                }
+                assert_eq!(0x3B, bytes[at]);
+                // Average error per channel stays small for a smooth gradient, ordered dithering adding some noise
+                let bound = match dithering {
+                    Dither::Bayer | Dither::BlueNoise => 12.0,
+                    Dither::None | Dither::FloydSteinberg => 8.0,
+                };
+                assert!(total_error / (3.0 * 3.0 * 1200.0) < bound, "{dithering:?}");
+            }
+        }
+    }
+}
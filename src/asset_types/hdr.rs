@@ -0,0 +1,624 @@
+use crate::{
+    core3d::{color::Color, color_rgb::ColorRGB},
+    graphics2d::canvas::Canvas,
+};
+
+use super::image_error::ImageError;
+
+/// Magic line every Radiance file starts with. Some writers use `#?RGBE` instead
+pub const HDR_SIGNATURE: &[u8] = b"#?RADIANCE";
+const HDR_FORMAT: &str = "FORMAT=32-bit_rle_rgbe";
+/// Adaptive run length encoding is only defined for scanlines within this range of widths
+const RLE_MIN_WIDTH: u32 = 8;
+const RLE_MAX_WIDTH: u32 = 0x7FFF;
+/// Shortest run worth encoding as a run instead of literals
+const RLE_MIN_RUN: usize = 4;
+const RLE_MAX_COUNT: usize = 127;
+/// Shift of the last byte an old style run may add to its count, making runs at most 32 bits long
+const FLAT_RUN_MAX_SHIFT: u32 = 24;
+
+/// Packs a linear color into the shared exponent RGBE format, clamping negative channels to 0
+///
+/// # Examples
+///
+/// ```
+/// # use rusty_ray_tracer::asset_types::hdr::rgbe_from_rgb;
+/// assert_eq!([128, 64, 0, 129], rgbe_from_rgb([1.0, 0.5, 0.0]));
+/// assert_eq!([0, 0, 0, 0], rgbe_from_rgb([0.0, -1.0, 0.0]));
+/// ```
+#[must_use]
+pub fn rgbe_from_rgb(rgb: [f32; 3]) -> [u8; 4] {
+    let rgb = rgb.map(|c| c.max(0.0));
+    let max = rgb[0].max(rgb[1]).max(rgb[2]);
+    if max < 1e-32 || !max.is_finite() {
+        return [0, 0, 0, 0];
+    }
+    // Split max into mantissa * 2^exponent with the mantissa in 0.5..1
+    let exponent = max.log2().floor() as i32 + 1;
+    let scale = 256.0 / 2.0_f32.powi(exponent);
+    let [r, g, b] = rgb.map(|c| (c * scale).min(255.0) as u8);
+    [r, g, b, (exponent + 128) as u8]
+}
+
+/// Unpacks an RGBE pixel back into a linear color, reconstructing the middle of each quantization step
+///
+/// # Examples
+///
+/// ```
+/// # use rusty_ray_tracer::asset_types::hdr::rgb_from_rgbe;
+/// assert_eq!([1.003_906_25, 0.503_906_25, 0.0], rgb_from_rgbe([128, 64, 0, 129]));
+/// assert_eq!([0.0, 0.0, 0.0], rgb_from_rgbe([10, 20, 30, 0]));
+/// ```
+#[must_use]
+pub fn rgb_from_rgbe(rgbe: [u8; 4]) -> [f32; 3] {
+    if rgbe[3] == 0 {
+        return [0.0, 0.0, 0.0];
+    }
+    let factor = 2.0_f32.powi(i32::from(rgbe[3]) - (128 + 8));
+    let channel = |c: u8| {
+        if c == 0 {
+            0.0
+        } else {
+            (f32::from(c) + 0.5) * factor
+        }
+    };
+    [channel(rgbe[0]), channel(rgbe[1]), channel(rgbe[2])]
+}
+
+#[cfg(test)]
+mod tests_rgbe {
+    use super::*;
+
+    #[test]
+    fn from_rgb() {
+        assert_eq!([128, 0, 0, 129], rgbe_from_rgb([1.0, 0.0, 0.0]));
+        assert_eq!([250, 125, 0, 138], rgbe_from_rgb([1000.0, 500.0, 0.0]));
+        assert_eq!([131, 0, 0, 119], rgbe_from_rgb([0.001, 0.0, 0.0]));
+        assert_eq!([0, 0, 0, 0], rgbe_from_rgb([f32::NAN, 0.0, 0.0]));
+        assert_eq!([0, 0, 0, 0], rgbe_from_rgb([f32::INFINITY, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn round_trip() {
+        for rgb in [
+            [1.0, 0.5, 0.25],
+            [1000.0, 20.0, 0.3],
+            [0.001, 0.002, 0.003],
+            [65_504.0, 1.0, 0.0],
+        ] {
+            let result = rgb_from_rgbe(rgbe_from_rgb(rgb));
+            let max = rgb[0].max(rgb[1]).max(rgb[2]);
+            for (expected, actual) in rgb.iter().zip(result) {
+                // Precision is relative to the brightest channel, which sets the shared exponent
+                assert!((expected - actual).abs() <= max / 128.0);
+            }
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct HDR {
+    pub width: u32,
+    pub height: u32,
+
+    /// RGBE pixels row by row, starting at the top
+    pub pixels: Vec<[u8; 4]>,
+}
+
+impl HDR {
+    /// Creates a new hdr from RGBE pixels
+    #[must_use]
+    pub const fn new(width: u32, height: u32, pixels: Vec<[u8; 4]>) -> Self {
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Encodes the image as a Radiance file, run length encoding the scanlines when their width allows it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::hdr::HDR;
+    /// let bytes = HDR::new(1, 1, vec![[128, 64, 0, 129]]).to_bytes();
+    /// assert_eq!(
+    ///     b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 1\n\x80\x40\x00\x81".to_vec(),
+    ///     bytes
+    /// );
+    /// ```
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = HDR_SIGNATURE.to_vec();
+        bytes.extend(format!("\n{HDR_FORMAT}\n\n-Y {} +X {}\n", self.height, self.width).bytes());
+        if self.width == 0 {
+            return bytes;
+        }
+        for scanline in self.pixels.chunks(self.width as usize) {
+            if (RLE_MIN_WIDTH..=RLE_MAX_WIDTH).contains(&self.width) {
+                bytes.extend([2, 2, (self.width >> 8) as u8, self.width as u8]);
+                for channel in 0..4 {
+                    let values = scanline.iter().map(|p| p[channel]).collect::<Vec<_>>();
+                    bytes.extend(rle::encode(&values));
+                }
+            } else {
+                bytes.extend(scanline.iter().flatten());
+            }
+        }
+        bytes
+    }
+}
+
+mod rle {
+    use super::{RLE_MAX_COUNT, RLE_MIN_RUN};
+    use crate::asset_types::image_error::ImageError;
+
+    /// Length of the run of identical values at the start of the slice
+    fn run_length(values: &[u8]) -> usize {
+        values
+            .iter()
+            .take(RLE_MAX_COUNT)
+            .take_while(|&&v| v == values[0])
+            .count()
+    }
+
+    /// Encodes one channel of a scanline as runs (count + 128, value) and literals (count, values...)
+    pub fn encode(values: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut i = 0;
+        while i < values.len() {
+            let run = run_length(&values[i..]);
+            if run >= RLE_MIN_RUN {
+                bytes.extend([128 + run as u8, values[i]]);
+                i += run;
+                continue;
+            }
+            let start = i;
+            while i < values.len()
+                && i - start < RLE_MAX_COUNT
+                && run_length(&values[i..]) < RLE_MIN_RUN
+            {
+                i += 1;
+            }
+            bytes.push((i - start) as u8);
+            bytes.extend(&values[start..i]);
+        }
+        bytes
+    }
+
+    /// Decodes one channel of a scanline, returning the values and the number of bytes read
+    pub fn decode(bytes: &[u8], count: usize) -> Result<(Vec<u8>, usize), ImageError> {
+        let mut values = Vec::with_capacity(count);
+        let mut i = 0;
+        while values.len() < count {
+            let code = usize::from(*bytes.get(i).ok_or(ImageError::UnexpectedEof)?);
+            if code > 128 {
+                let value = *bytes.get(i + 1).ok_or(ImageError::UnexpectedEof)?;
+                values.extend(std::iter::repeat_n(value, code - 128));
+                i += 2;
+            } else if code > 0 {
+                let literals = bytes
+                    .get(i + 1..i + 1 + code)
+                    .ok_or(ImageError::UnexpectedEof)?;
+                values.extend(literals);
+                i += 1 + code;
+            } else {
+                return Err(ImageError::InvalidData("zero length RLE run".to_string()));
+            }
+        }
+        if values.len() > count {
+            return Err(ImageError::InvalidData(
+                "RLE run overflows the scanline".to_string(),
+            ));
+        }
+        Ok((values, i))
+    }
+
+    #[cfg(test)]
+    mod tests_rle {
+        use super::*;
+
+        #[test]
+        fn encode_runs_and_literals() {
+            assert_eq!(vec![132, 7], encode(&[7, 7, 7, 7]));
+            assert_eq!(vec![3, 1, 2, 3], encode(&[1, 2, 3]));
+            assert_eq!(
+                vec![2, 1, 2, 133, 9, 1, 3],
+                encode(&[1, 2, 9, 9, 9, 9, 9, 3])
+            );
+            assert_eq!(vec![3, 5, 5, 5], encode(&[5, 5, 5]));
+        }
+
+        #[test]
+        fn encode_long_runs() {
+            let encoded = encode(&[0; 300]);
+            assert_eq!(vec![255, 0, 255, 0, 174, 0], encoded);
+            let literals = (0..200).map(|i| i as u8).collect::<Vec<_>>();
+            let encoded = encode(&literals);
+            assert_eq!(127, encoded[0]);
+            assert_eq!(73, encoded[128]);
+            assert_eq!(200 + 2, encoded.len());
+        }
+
+        #[test]
+        fn decode_round_trip() {
+            let values = [1, 2, 9, 9, 9, 9, 9, 3, 3, 3, 3, 0, 0, 1];
+            let encoded = encode(&values);
+            assert_eq!(
+                Ok((values.to_vec(), encoded.len())),
+                decode(&encoded, values.len())
+            );
+        }
+
+        #[test]
+        fn decode_errors() {
+            assert_eq!(Err(ImageError::UnexpectedEof), decode(&[3, 1, 2], 3));
+            assert!(decode(&[0, 1], 1).is_err());
+            assert!(decode(&[133, 1], 3).is_err());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_hdr {
+    use super::*;
+
+    #[test]
+    fn new() {
+        let hdr = HDR::new(16, 9, vec![[0; 4]; 144]);
+        assert_eq!(16, hdr.width);
+        assert_eq!(9, hdr.height);
+        assert_eq!(144, hdr.pixels.len());
+    }
+
+    #[test]
+    fn to_bytes_flat() {
+        let hdr = HDR::new(2, 1, vec![[1, 2, 3, 4], [5, 6, 7, 8]]);
+        let mut expected = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 2\n".to_vec();
+        expected.extend([1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(expected, hdr.to_bytes());
+    }
+
+    #[test]
+    fn to_bytes_rle() {
+        let hdr = HDR::new(8, 1, vec![[10, 20, 30, 128]; 8]);
+        let mut expected = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 8\n".to_vec();
+        expected.extend([2, 2, 0, 8]);
+        expected.extend([136, 10, 136, 20, 136, 30, 136, 128]);
+        assert_eq!(expected, hdr.to_bytes());
+    }
+}
+
+mod decoding {
+    use super::{rle, FLAT_RUN_MAX_SHIFT, HDR, HDR_FORMAT, RLE_MAX_WIDTH, RLE_MIN_WIDTH};
+    use crate::asset_types::image_error::{pixel_count, ImageError};
+
+    /// Reads one `\n` terminated line, returning it and the remaining bytes
+    fn read_line(bytes: &[u8]) -> Result<(&str, &[u8]), ImageError> {
+        let end = bytes
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or(ImageError::UnexpectedEof)?;
+        let line = std::str::from_utf8(&bytes[..end])
+            .map_err(|_| ImageError::InvalidData("header is not text".to_string()))?;
+        Ok((line, &bytes[end + 1..]))
+    }
+
+    /// Parses the `-Y height +X width` resolution line, the standard top to bottom, left to right orientation
+    fn parse_resolution(line: &str) -> Result<(u32, u32), ImageError> {
+        let parts = line.split_whitespace().collect::<Vec<_>>();
+        match parts.as_slice() {
+            ["-Y", height, "+X", width] => {
+                let parse = |s: &str| {
+                    s.parse::<u32>()
+                        .map_err(|_| ImageError::InvalidData(format!("resolution {line}")))
+                };
+                Ok((parse(width)?, parse(height)?))
+            }
+            [_, _, _, _] => Err(ImageError::Unsupported(format!("orientation {line}"))),
+            _ => Err(ImageError::InvalidData(format!("resolution {line}"))),
+        }
+    }
+
+    /// Reads a scanline stored as plain RGBE pixels, which may contain old style `(1, 1, 1, count)` runs
+    ///
+    /// Consecutive runs hold ever higher bytes of one count, runs longer than the rest of the scanline are cut
+    fn read_flat_scanline(bytes: &[u8], width: usize) -> Result<(Vec<[u8; 4]>, usize), ImageError> {
+        let mut pixels: Vec<[u8; 4]> = Vec::with_capacity(width);
+        let mut i = 0;
+        let mut shift = 0;
+        while pixels.len() < width {
+            let p = bytes.get(i..i + 4).ok_or(ImageError::UnexpectedEof)?;
+            let pixel = [p[0], p[1], p[2], p[3]];
+            i += 4;
+            if pixel[..3] == [1, 1, 1] && !pixels.is_empty() {
+                if shift > FLAT_RUN_MAX_SHIFT {
+                    return Err(ImageError::InvalidData("run count overflow".to_string()));
+                }
+                let count = (u64::from(pixel[3]) << shift).min((width - pixels.len()) as u64);
+                let previous = pixels[pixels.len() - 1];
+                pixels.extend(std::iter::repeat_n(previous, count as usize));
+                shift += 8;
+            } else {
+                pixels.push(pixel);
+                shift = 0;
+            }
+        }
+        pixels.truncate(width);
+        Ok((pixels, i))
+    }
+
+    /// Reads a scanline stored with each channel run length encoded separately
+    fn read_rle_scanline(bytes: &[u8], width: usize) -> Result<(Vec<[u8; 4]>, usize), ImageError> {
+        let mut pixels = vec![[0_u8; 4]; width];
+        let mut i = 4;
+        for channel in 0..4 {
+            let (values, used) = rle::decode(&bytes[i..], width)?;
+            pixels
+                .iter_mut()
+                .zip(values)
+                .for_each(|(p, v)| p[channel] = v);
+            i += used;
+        }
+        Ok((pixels, i))
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<HDR, ImageError> {
+        let (signature, mut rest) = read_line(bytes).map_err(|_| ImageError::InvalidSignature)?;
+        if signature != "#?RADIANCE" && signature != "#?RGBE" {
+            return Err(ImageError::InvalidSignature);
+        }
+        loop {
+            let (line, remaining) = read_line(rest)?;
+            rest = remaining;
+            if line.is_empty() {
+                break;
+            }
+            if line.starts_with("FORMAT=") && line != HDR_FORMAT {
+                return Err(ImageError::Unsupported(line.to_string()));
+            }
+        }
+        let (line, mut rest) = read_line(rest)?;
+        let (width, height) = parse_resolution(line)?;
+        let count = pixel_count(u64::from(width), u64::from(height))?;
+
+        let mut pixels = Vec::with_capacity(count);
+        for _ in 0..height {
+            let is_rle = (RLE_MIN_WIDTH..=RLE_MAX_WIDTH).contains(&width)
+                && rest.len() >= 4
+                && rest[0] == 2
+                && rest[1] == 2
+                && rest[2] & 0x80 == 0;
+            let (scanline, used) = if is_rle {
+                if u32::from(u16::from_be_bytes([rest[2], rest[3]])) != width {
+                    return Err(ImageError::InvalidData(
+                        "scanline width mismatch".to_string(),
+                    ));
+                }
+                read_rle_scanline(rest, width as usize)?
+            } else {
+                read_flat_scanline(rest, width as usize)?
+            };
+            pixels.extend(scanline);
+            rest = &rest[used..];
+        }
+        Ok(HDR::new(width, height, pixels))
+    }
+
+    #[cfg(test)]
+    mod tests_decoding {
+        use super::*;
+        use crate::asset_types::image_error::PIXELS_MAX;
+
+        #[test]
+        fn resolution() {
+            assert_eq!(Ok((640, 480)), parse_resolution("-Y 480 +X 640"));
+            assert!(matches!(
+                parse_resolution("+Y 480 +X 640"),
+                Err(ImageError::Unsupported(_))
+            ));
+            assert!(matches!(
+                parse_resolution("-Y 480"),
+                Err(ImageError::InvalidData(_))
+            ));
+            assert!(matches!(
+                parse_resolution("-Y a +X 640"),
+                Err(ImageError::InvalidData(_))
+            ));
+        }
+
+        #[test]
+        fn old_style_runs() {
+            let bytes = [9, 8, 7, 130, 1, 1, 1, 3, 5, 5, 5, 128];
+            let (pixels, used) = read_flat_scanline(&bytes, 5).unwrap();
+            assert_eq!(12, used);
+            assert_eq!(
+                vec![
+                    [9, 8, 7, 130],
+                    [9, 8, 7, 130],
+                    [9, 8, 7, 130],
+                    [9, 8, 7, 130],
+                    [5, 5, 5, 128]
+                ],
+                pixels
+            );
+        }
+
+        #[test]
+        fn old_style_runs_are_bounded() {
+            let bytes = [9, 8, 7, 130, 1, 1, 1, 255, 1, 1, 1, 255];
+            let (pixels, used) = read_flat_scanline(&bytes, 3).unwrap();
+            assert_eq!(8, used);
+            assert_eq!(vec![[9, 8, 7, 130]; 3], pixels);
+
+            let mut bytes = vec![9, 8, 7, 130];
+            bytes.extend([1, 1, 1, 0].repeat(4));
+            bytes.extend([1, 1, 1, 1]);
+            assert!(matches!(
+                read_flat_scanline(&bytes, 2),
+                Err(ImageError::InvalidData(_))
+            ));
+        }
+
+        #[test]
+        fn absurd_sizes() {
+            let bytes = b"#?RADIANCE\n\n-Y 4000000000 +X 4000000000\n";
+            assert!(matches!(decode(bytes), Err(ImageError::Unsupported(_))));
+            let side = PIXELS_MAX.isqrt() + 1;
+            let bytes = format!("#?RADIANCE\n\n-Y {side} +X {side}\n");
+            assert!(matches!(
+                decode(bytes.as_bytes()),
+                Err(ImageError::Unsupported(_))
+            ));
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for HDR {
+    type Error = ImageError;
+
+    /// Decodes a Radiance RGBE file with flat, old style run length or adaptive run length encoded scanlines
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::hdr::HDR;
+    /// let hdr = HDR::new(9, 2, vec![[1, 2, 3, 128]; 18]);
+    /// let decoded = HDR::try_from(hdr.to_bytes().as_slice()).unwrap();
+    /// assert_eq!(hdr.pixels, decoded.pixels);
+    /// ```
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        decoding::decode(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests_try_from_bytes {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for width in [1, 7, 8, 33, 300] {
+            let pixels = (0..width * 3)
+                .map(|i| {
+                    let v = if i % 5 < 2 { 7 } else { i as u8 };
+                    [v, v / 2, 3, 128 + (i % 3) as u8]
+                })
+                .collect::<Vec<_>>();
+            let hdr = HDR::new(width, 3, pixels);
+            let decoded = HDR::try_from(hdr.to_bytes().as_slice()).unwrap();
+            assert_eq!(width, decoded.width);
+            assert_eq!(3, decoded.height);
+            assert_eq!(hdr.pixels, decoded.pixels);
+        }
+    }
+
+    #[test]
+    fn header_variations() {
+        let mut bytes = b"#?RGBE\n# made by hand\nEXPOSURE=1.0\n\n-Y 1 +X 1\n".to_vec();
+        bytes.extend([1, 2, 3, 4]);
+        let hdr = HDR::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(vec![[1, 2, 3, 4]], hdr.pixels);
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            Err(ImageError::InvalidSignature),
+            HDR::try_from(&b"P3\n1 1\n255\n"[..]).map(|_| ())
+        );
+        assert!(matches!(
+            HDR::try_from(&b"#?RADIANCE\nFORMAT=32-bit_rle_xyze\n\n-Y 1 +X 1\n"[..]),
+            Err(ImageError::Unsupported(_))
+        ));
+        assert_eq!(
+            Err(ImageError::UnexpectedEof),
+            HDR::try_from(&b"#?RADIANCE\n\n-Y 1 +X 2\n\x01\x02\x03\x04"[..]).map(|_| ())
+        );
+        let mut bytes = HDR::new(8, 1, vec![[1, 2, 3, 4]; 8]).to_bytes();
+        bytes.truncate(bytes.len() - 1);
+        assert!(HDR::try_from(bytes.as_slice()).is_err());
+    }
+}
+
+impl From<&Canvas> for HDR {
+    /// Creates a new hdr from a canvas, keeping the full range of each channel
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::hdr::HDR;
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(2, 1);
+    /// canvas.set_pixel_at(1, 0, Color::new(4.0, 2.0, 1.0));
+    /// let hdr = HDR::from(&canvas);
+    /// assert_eq!(vec![[0, 0, 0, 0], [128, 64, 32, 131]], hdr.pixels);
+    /// ```
+    fn from(canvas: &Canvas) -> Self {
+        Self::new(
+            canvas.width,
+            canvas.height,
+            canvas
+                .raw_buffer
+                .iter()
+                .map(|c| rgbe_from_rgb([c.r(), c.g(), c.b()]))
+                .collect(),
+        )
+    }
+}
+
+impl TryFrom<&HDR> for Canvas {
+    type Error = ImageError;
+
+    /// Creates a new opaque canvas from an hdr
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImageError::InvalidData`] if the number of pixels does not match the image size
+    fn try_from(hdr: &HDR) -> Result<Self, Self::Error> {
+        Self::from_buffer(
+            hdr.width,
+            hdr.height,
+            hdr.pixels
+                .iter()
+                .map(|&p| Color::from(rgb_from_rgbe(p)))
+                .collect(),
+        )
+        .ok_or_else(|| {
+            ImageError::InvalidData("pixel count does not match the image size".to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_canvas {
+    use super::*;
+
+    #[test]
+    fn round_trip_keeps_dynamic_range() {
+        let mut canvas = Canvas::new(10, 2);
+        canvas.set_pixel_at(0, 0, Color::new(1000.0, 500.0, 250.0));
+        canvas.set_pixel_at(5, 0, Color::new(0.001, 0.002, 0.004));
+        canvas.set_pixel_at(9, 1, Color::new(3.5, 0.0, 1.25));
+        let bytes = HDR::from(&canvas).to_bytes();
+        let decoded = Canvas::try_from(&HDR::try_from(bytes.as_slice()).unwrap()).unwrap();
+        assert_eq!(10, decoded.width);
+        assert_eq!(2, decoded.height);
+        for (expected, actual) in canvas.raw_buffer.iter().zip(&decoded.raw_buffer) {
+            let max = expected.r().max(expected.g()).max(expected.b());
+            assert!((expected.r() - actual.r()).abs() <= max / 128.0);
+            assert!((expected.g() - actual.g()).abs() <= max / 128.0);
+            assert!((expected.b() - actual.b()).abs() <= max / 128.0);
+            assert_eq!(1.0, actual.a());
+        }
+    }
+
+    #[test]
+    fn wider_than_u16() {
+        let hdr = HDR::new(0, 70_000, vec![]);
+        let canvas = Canvas::try_from(&hdr).unwrap();
+        assert_eq!((hdr.width, hdr.height), (canvas.width, canvas.height));
+    }
+}
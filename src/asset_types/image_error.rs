@@ -0,0 +1,119 @@
+use std::fmt::Display;
+
+/// Upper bound on the number of pixels, protecting the decoders against absurd headers
+pub(crate) const PIXELS_MAX: u64 = 400_000_000;
+
+/// Errors raised while reading, decoding or writing image files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageError {
+    /// The data does not start with the magic bytes of the expected format
+    InvalidSignature,
+    /// The data ended before the image was complete
+    UnexpectedEof,
+    /// The data is malformed, with a description of what was wrong
+    InvalidData(String),
+    /// The data is valid but uses a feature this decoder does not implement
+    Unsupported(String),
+    /// The file could not be read or written, with the reason reported by the system
+    Io(String),
+    /// Neither the contents nor the name of the file identify a supported format
+    UnknownFormat(String),
+}
+
+impl Display for ImageError {
+    /// Returns a human readable description of the error
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::image_error::ImageError;
+    /// assert_eq!("Invalid image data: bad checksum", ImageError::InvalidData("bad checksum".to_string()).to_string());
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidSignature => write!(f, "Invalid image signature"),
+            Self::UnexpectedEof => write!(f, "Unexpected end of image data"),
+            Self::InvalidData(reason) => write!(f, "Invalid image data: {reason}"),
+            Self::Unsupported(feature) => write!(f, "Unsupported image feature: {feature}"),
+            Self::Io(reason) => write!(f, "Image I/O failed: {reason}"),
+            Self::UnknownFormat(name) => write!(f, "Unknown image format: {name}"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<std::io::Error> for ImageError {
+    /// Keeps the description of an I/O error, as the error itself can be neither cloned nor compared
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::image_error::ImageError;
+    /// let error = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+    /// assert_eq!(ImageError::Io("no such file".to_string()), ImageError::from(error));
+    /// ```
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error.to_string())
+    }
+}
+
+/// Returns the number of pixels of an image, or [`ImageError::Unsupported`] if there are more than
+/// [`PIXELS_MAX`]
+pub(crate) fn pixel_count(width: u64, height: u64) -> Result<usize, ImageError> {
+    width
+        .checked_mul(height)
+        .filter(|&count| count <= PIXELS_MAX)
+        .map(|count| count as usize)
+        .ok_or_else(|| ImageError::Unsupported(format!("{width}x{height} image")))
+}
+
+#[cfg(test)]
+mod tests_pixel_count {
+    use super::*;
+
+    #[test]
+    fn pixel_count_is_capped() {
+        assert_eq!(Ok(0), pixel_count(0, 7));
+        assert_eq!(Ok(12), pixel_count(4, 3));
+        assert_eq!(Ok(PIXELS_MAX as usize), pixel_count(PIXELS_MAX, 1));
+        assert_eq!(
+            Err(ImageError::Unsupported("20001x20000 image".to_string())),
+            pixel_count(20_001, 20_000)
+        );
+        assert!(pixel_count(u64::MAX, u64::MAX).is_err());
+    }
+}
+
+#[cfg(test)]
+mod tests_display {
+    use super::*;
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            "Invalid image signature",
+            ImageError::InvalidSignature.to_string()
+        );
+        assert_eq!(
+            "Unexpected end of image data",
+            ImageError::UnexpectedEof.to_string()
+        );
+        assert_eq!(
+            "Invalid image data: bad checksum",
+            ImageError::InvalidData("bad checksum".to_string()).to_string()
+        );
+        assert_eq!(
+            "Unsupported image feature: bit depth 3",
+            ImageError::Unsupported("bit depth 3".to_string()).to_string()
+        );
+        assert_eq!(
+            "Image I/O failed: permission denied",
+            ImageError::Io("permission denied".to_string()).to_string()
+        );
+        assert_eq!(
+            "Unknown image format: render.xyz",
+            ImageError::UnknownFormat("render.xyz".to_string()).to_string()
+        );
+    }
+}
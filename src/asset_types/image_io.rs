@@ -0,0 +1,816 @@
+use std::path::Path;
+
+use crate::{
+    core3d::color_rgb::ColorRGB,
+    graphics2d::{
+        canvas::Canvas, dither::Dither, tone_mapping::ToneMapping,
+        transfer_function::TransferFunction,
+    },
+};
+
+use super::{
+    bmp::{self, BMP, BMP_SIGNATURE},
+    deflate,
+    exr::{PixelType, EXR, EXR_MAGIC},
+    gif::GIF,
+    hdr::HDR,
+    image_error::ImageError,
+    pfm::PFM,
+    png::{BitDepth, ColorType, PNG, PNG_SIGNATURE},
+    ppm::PPM,
+    qoi::{Channels, ColorSpace, QOI, QOI_SIGNATURE},
+    tga::{self, TGA},
+};
+
+/// Number of values of each channel of the formats that are not high dynamic range
+const EIGHT_BIT_LEVELS: u32 = 256;
+
+/// Image file formats known to [`load`] and [`save`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    PPM,
+    PNG,
+    HDR,
+    PFM,
+    /// OpenEXR, which can only be saved
+    EXR,
+    BMP,
+    TGA,
+    QOI,
+    /// GIF, which can only be saved
+    GIF,
+}
+
+impl ImageFormat {
+    /// Detects the format from the magic bytes a file starts with
+    ///
+    /// TGA files carry no signature, so they are only ever recognised by their extension
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::image_io::ImageFormat;
+    /// assert_eq!(Some(ImageFormat::PPM), ImageFormat::from_magic(b"P6\n4 4\n255\n"));
+    /// assert_eq!(Some(ImageFormat::QOI), ImageFormat::from_magic(b"qoif\0\0\0\x04"));
+    /// assert_eq!(None, ImageFormat::from_magic(b"\0\0\x02\0"));
+    /// ```
+    #[must_use]
+    pub fn from_magic(bytes: &[u8]) -> Option<Self> {
+        let followed_by_whitespace = |magic: &[&[u8]]| {
+            magic.iter().any(|m| bytes.starts_with(m))
+                && bytes.get(2).is_some_and(u8::is_ascii_whitespace)
+        };
+        if bytes.starts_with(&PNG_SIGNATURE) {
+            Some(Self::PNG)
+        } else if bytes.starts_with(&EXR_MAGIC) {
+            Some(Self::EXR)
+        } else if bytes.starts_with(QOI_SIGNATURE) {
+            Some(Self::QOI)
+        } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+            Some(Self::GIF)
+        } else if bytes.starts_with(b"#?RADIANCE") || bytes.starts_with(b"#?RGBE") {
+            Some(Self::HDR)
+        } else if followed_by_whitespace(&[b"P2", b"P3", b"P5", b"P6"]) {
+            Some(Self::PPM)
+        } else if followed_by_whitespace(&[b"PF", b"Pf"]) {
+            Some(Self::PFM)
+        } else if bytes.starts_with(BMP_SIGNATURE) {
+            Some(Self::BMP)
+        } else {
+            None
+        }
+    }
+
+    /// Detects the format from the extension of a file name, ignoring case
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::image_io::ImageFormat;
+    /// assert_eq!(Some(ImageFormat::TGA), ImageFormat::from_path("render.TGA"));
+    /// assert_eq!(Some(ImageFormat::PPM), ImageFormat::from_path("out/gray.pgm"));
+    /// assert_eq!(None, ImageFormat::from_path("render"));
+    /// ```
+    #[must_use]
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "ppm" | "pgm" | "pnm" => Some(Self::PPM),
+            "png" => Some(Self::PNG),
+            "hdr" | "rgbe" => Some(Self::HDR),
+            "pfm" => Some(Self::PFM),
+            "exr" => Some(Self::EXR),
+            "bmp" | "dib" => Some(Self::BMP),
+            "tga" => Some(Self::TGA),
+            "qoi" => Some(Self::QOI),
+            "gif" => Some(Self::GIF),
+            _ => None,
+        }
+    }
+
+    /// Returns the usual file extension of the format
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::PPM => "ppm",
+            Self::PNG => "png",
+            Self::HDR => "hdr",
+            Self::PFM => "pfm",
+            Self::EXR => "exr",
+            Self::BMP => "bmp",
+            Self::TGA => "tga",
+            Self::QOI => "qoi",
+            Self::GIF => "gif",
+        }
+    }
+
+    /// Returns whether images of this format can be decoded
+    #[must_use]
+    pub const fn can_load(self) -> bool {
+        !matches!(self, Self::EXR | Self::GIF)
+    }
+
+    /// Returns whether the format stores unbounded linear values, so they are written without tone mapping
+    /// or transfer function
+    #[must_use]
+    pub const fn is_high_dynamic_range(self) -> bool {
+        matches!(self, Self::HDR | Self::PFM | Self::EXR)
+    }
+}
+
+#[cfg(test)]
+mod tests_image_format {
+    use super::*;
+
+    const ALL: [ImageFormat; 9] = [
+        ImageFormat::PPM,
+        ImageFormat::PNG,
+        ImageFormat::HDR,
+        ImageFormat::PFM,
+        ImageFormat::EXR,
+        ImageFormat::BMP,
+        ImageFormat::TGA,
+        ImageFormat::QOI,
+        ImageFormat::GIF,
+    ];
+
+    #[test]
+    fn extension_round_trip() {
+        for format in ALL {
+            let name = format!("image.{}", format.extension());
+            assert_eq!(Some(format), ImageFormat::from_path(name));
+        }
+    }
+
+    #[test]
+    fn magic_of_encoded_images() {
+        let canvas = Canvas::new(3, 2);
+        for format in ALL {
+            let bytes = encode(&canvas, format, &SaveOptions::default()).unwrap();
+            let expected = (format != ImageFormat::TGA).then_some(format);
+            assert_eq!(expected, ImageFormat::from_magic(&bytes), "{format:?}");
+        }
+    }
+
+    #[test]
+    fn magic_needs_whitespace() {
+        assert_eq!(None, ImageFormat::from_magic(b"P3"));
+        assert_eq!(None, ImageFormat::from_magic(b"P3x"));
+        assert_eq!(None, ImageFormat::from_magic(b"Pfoo"));
+        assert_eq!(
+            Some(ImageFormat::PFM),
+            ImageFormat::from_magic(b"Pf 1 1 -1\n")
+        );
+        assert_eq!(Some(ImageFormat::HDR), ImageFormat::from_magic(b"#?RGBE\n"));
+        assert_eq!(None, ImageFormat::from_magic(b""));
+    }
+
+    #[test]
+    fn high_dynamic_range() {
+        let hdr = ALL.into_iter().filter(|f| f.is_high_dynamic_range());
+        assert_eq!(
+            vec![ImageFormat::HDR, ImageFormat::PFM, ImageFormat::EXR],
+            hdr.collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn unknown_extensions() {
+        assert_eq!(None, ImageFormat::from_path("image.jpg"));
+        assert_eq!(None, ImageFormat::from_path(".png.bak"));
+    }
+}
+
+/// Settings applied by [`save`] and [`encode`]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SaveOptions {
+    /// Format to write, `None` picking it from the file extension
+    pub format: Option<ImageFormat>,
+    /// Whether to store the alpha channel, for the formats that have one
+    pub alpha: bool,
+    /// Mapping of the colors to the displayable range, for the formats that are not high dynamic range
+    pub tone_mapping: ToneMapping,
+    /// Encoding of the tone mapped colors, for the formats that are not high dynamic range
+    pub transfer_function: TransferFunction,
+    /// Hiding of the banding when reducing the encoded colors to 8 bits, or to the palette of GIF images, for the
+    /// formats that are not high dynamic range
+    pub dither: Dither,
+}
+
+/// Decodes an image of a known format into a linear canvas
+///
+/// The colors of formats that are not high dynamic range are decoded with the given transfer function,
+/// unless the file declares its own like QOI images do
+///
+/// # Errors
+///
+/// Returns the error of the decoder, or [`ImageError::Unsupported`] if the format cannot be loaded or the
+/// transfer function it is decoded with is not valid
+///
+/// # Examples
+///
+/// ```
+/// # use rusty_ray_tracer::asset_types::image_io::{decode, ImageFormat};
+/// # use rusty_ray_tracer::core3d::color::Color;
+/// # use rusty_ray_tracer::graphics2d::transfer_function::TransferFunction;
+/// let bytes = b"P3\n1 1\n4\n4 2 0\n";
+/// let canvas = decode(bytes, ImageFormat::PPM, TransferFunction::Linear).unwrap();
+/// assert_eq!(Color::new(1.0, 0.5, 0.0), canvas.get_pixel_at(0, 0));
+/// let canvas = decode(bytes, ImageFormat::PPM, TransferFunction::Gamma(2.0)).unwrap();
+/// assert_eq!(Color::new(1.0, 0.25, 0.0), canvas.get_pixel_at(0, 0));
+/// ```
+pub fn decode(
+    bytes: &[u8],
+    format: ImageFormat,
+    transfer_function: TransferFunction,
+) -> Result<Canvas, ImageError> {
+    let (mut canvas, transfer_function) = match format {
+        ImageFormat::PPM => (Canvas::try_from(&PPM::try_from(bytes)?)?, transfer_function),
+        ImageFormat::PNG => (Canvas::try_from(&PNG::try_from(bytes)?)?, transfer_function),
+        ImageFormat::HDR => return Canvas::try_from(&HDR::try_from(bytes)?),
+        ImageFormat::PFM => return Canvas::try_from(&PFM::try_from(bytes)?),
+        ImageFormat::BMP => (Canvas::try_from(&BMP::try_from(bytes)?)?, transfer_function),
+        ImageFormat::TGA => (Canvas::try_from(&TGA::try_from(bytes)?)?, transfer_function),
+        ImageFormat::QOI => {
+            let qoi = QOI::try_from(bytes)?;
+            let transfer_function = match qoi.color_space {
+                ColorSpace::SRGB => TransferFunction::SRGB,
+                ColorSpace::Linear => TransferFunction::Linear,
+            };
+            (Canvas::try_from(&qoi)?, transfer_function)
+        }
+        ImageFormat::EXR | ImageFormat::GIF => {
+            return Err(ImageError::Unsupported(format!(
+                "loading {format:?} images"
+            )))
+        }
+    };
+    check_transfer_function(transfer_function)?;
+    canvas
+        .raw_buffer
+        .iter_mut()
+        .for_each(|c| *c = transfer_function.decode_color(*c));
+    Ok(canvas)
+}
+
+/// Rejects power curves that cannot be applied, rather than filling the image with NaNs
+fn check_transfer_function(transfer_function: TransferFunction) -> Result<(), ImageError> {
+    if transfer_function.is_valid() {
+        Ok(())
+    } else {
+        Err(ImageError::Unsupported(format!(
+            "{transfer_function:?} transfer function"
+        )))
+    }
+}
+
+/// Returns the size of a canvas for the formats storing it in 16 bits
+fn u16_size(canvas: &Canvas, format: ImageFormat) -> Result<(u16, u16), ImageError> {
+    match (u16::try_from(canvas.width), u16::try_from(canvas.height)) {
+        (Ok(width), Ok(height)) => Ok((width, height)),
+        _ => Err(ImageError::Unsupported(format!(
+            "{format:?} images wider or higher than 65535 pixels"
+        ))),
+    }
+}
+
+/// Encodes a linear canvas in the given format
+///
+/// Formats that are not high dynamic range store the colors tone mapped and then encoded with the transfer
+/// function of the options. The [`SaveOptions::format`] is ignored.
+///
+/// # Errors
+///
+/// Returns [`ImageError::Unsupported`] if an EXR image is requested for an empty canvas, a TGA or GIF image
+/// for a canvas wider or higher than the 65535 pixels these formats can hold, or if a format that is not high
+/// dynamic range is requested with a transfer function that is not valid
+///
+/// # Examples
+///
+/// ```
+/// # use rusty_ray_tracer::asset_types::image_io::{encode, ImageFormat, SaveOptions};
+/// # use rusty_ray_tracer::core3d::color::Color;
+/// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+/// let mut canvas = Canvas::new(1, 1);
+/// canvas.set_pixel_at(0, 0, Color::new(2.0, 0.5, -1.0));
+/// let bytes = encode(&canvas, ImageFormat::PPM, &SaveOptions::default());
+/// assert_eq!(Ok(b"P3\n1 1\n255\n255 188 0\n".to_vec()), bytes);
+/// ```
+pub fn encode(
+    canvas: &Canvas,
+    format: ImageFormat,
+    options: &SaveOptions,
+) -> Result<Vec<u8>, ImageError> {
+    match format {
+        ImageFormat::TGA | ImageFormat::GIF => {
+            u16_size(canvas, format)?;
+        }
+        ImageFormat::EXR if canvas.raw_buffer.is_empty() => {
+            return Err(ImageError::Unsupported("empty EXR images".to_string()));
+        }
+        _ => {}
+    }
+    let mapped;
+    let canvas = if format.is_high_dynamic_range() {
+        canvas
+    } else {
+        check_transfer_function(options.transfer_function)?;
+        let encoded = Canvas {
+            width: canvas.width,
+            height: canvas.height,
+            raw_buffer: canvas
+                .raw_buffer
+                .iter()
+                .map(|&c| {
+                    let c = options.tone_mapping.map_color(c);
+                    options.transfer_function.encode_color(c)
+                })
+                .collect(),
+        };
+        // GIF images are dithered against their palette instead
+        mapped = match (options.dither, format) {
+            (Dither::None, _) | (_, ImageFormat::GIF) => encoded,
+            (dither, _) => encoded.quantize_levels(EIGHT_BIT_LEVELS, dither),
+        };
+        &mapped
+    };
+    Ok(match format {
+        ImageFormat::PPM => PPM::from(canvas).to_string().into_bytes(),
+        ImageFormat::PNG => {
+            let color_type = if options.alpha {
+                ColorType::RGBA
+            } else {
+                ColorType::RGB
+            };
+            PNG::from_canvas(canvas, BitDepth::Eight, color_type)
+                .to_bytes(deflate::Compression::FixedHuffman)
+        }
+        ImageFormat::HDR => HDR::from(canvas).to_bytes(),
+        ImageFormat::PFM => PFM::from(canvas).to_bytes(),
+        ImageFormat::EXR => {
+            let mut exr = EXR::from(canvas);
+            if options.alpha {
+                let alpha = canvas.raw_buffer.iter().map(|c| c.a()).collect();
+                exr.add_channel("A", PixelType::Half, alpha);
+            }
+            exr.to_bytes()
+        }
+        ImageFormat::BMP => {
+            BMP::from_canvas(canvas, options.alpha).to_bytes(bmp::Compression::None)
+        }
+        ImageFormat::TGA => TGA::from_canvas(canvas, options.alpha).to_bytes(tga::Compression::RLE),
+        ImageFormat::QOI => {
+            let channels = if options.alpha {
+                Channels::RGBA
+            } else {
+                Channels::RGB
+            };
+            let mut qoi = QOI::from_canvas(canvas, channels);
+            if options.transfer_function == TransferFunction::Linear {
+                qoi.color_space = ColorSpace::Linear;
+            }
+            qoi.to_bytes()
+        }
+        ImageFormat::GIF => {
+            let (width, height) = u16_size(canvas, format)?;
+            let mut gif = GIF::new(width, height, 0);
+            gif.repeat = None;
+            gif.dithering = options.dither;
+            gif.add_frame(canvas);
+            gif.to_bytes()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests_codec {
+    use super::*;
+    use crate::{
+        core3d::color::Color,
+        graphics2d::{fixtures::gradient, tone_mapping::ToneMapOperator},
+    };
+
+    /// The shared gradient with a transparent left column
+    fn transparent_edge() -> Canvas {
+        let mut canvas = gradient(5, 3);
+        for color in canvas.raw_buffer.iter_mut().step_by(5) {
+            color.tuple[3] = 0.0;
+        }
+        canvas
+    }
+
+    fn assert_close(expected: &Canvas, actual: &Canvas, tolerance: f32, alpha: bool) {
+        assert_eq!(
+            (expected.width, expected.height),
+            (actual.width, actual.height)
+        );
+        for (e, a) in expected.raw_buffer.iter().zip(&actual.raw_buffer) {
+            let channels = if alpha { 4 } else { 3 };
+            for channel in 0..channels {
+                assert!(
+                    (e.get_at(channel) - a.get_at(channel)).abs() <= tolerance,
+                    "{e:?} != {a:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let canvas = transparent_edge();
+        // sRGB spends fewer levels on bright values, stretching their rounding error up to 2.3 times
+        for (format, tolerance) in [
+            (ImageFormat::PPM, 1.5 / 255.0),
+            (ImageFormat::PNG, 1.5 / 255.0),
+            (ImageFormat::HDR, 0.01),
+            (ImageFormat::PFM, 0.0),
+            (ImageFormat::BMP, 1.5 / 255.0),
+            (ImageFormat::TGA, 1.5 / 255.0),
+            (ImageFormat::QOI, 1.5 / 255.0),
+        ] {
+            let bytes = encode(&canvas, format, &SaveOptions::default()).unwrap();
+            let decoded = decode(&bytes, format, TransferFunction::default()).unwrap();
+            assert_close(&canvas, &decoded, tolerance, false);
+            assert!(
+                decoded.raw_buffer.iter().all(|c| c.a() == 1.0),
+                "{format:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn round_trip_alpha() {
+        let canvas = transparent_edge();
+        let options = SaveOptions {
+            alpha: true,
+            ..SaveOptions::default()
+        };
+        for format in [
+            ImageFormat::PNG,
+            ImageFormat::BMP,
+            ImageFormat::TGA,
+            ImageFormat::QOI,
+        ] {
+            let bytes = encode(&canvas, format, &options).unwrap();
+            let decoded = decode(&bytes, format, TransferFunction::default()).unwrap();
+            assert_close(&canvas, &decoded, 1.5 / 255.0, true);
+        }
+    }
+
+    #[test]
+    fn unsupported_sizes() {
+        let wide = Canvas::new(70_000, 1);
+        for format in [ImageFormat::TGA, ImageFormat::GIF] {
+            assert!(matches!(
+                encode(&wide, format, &SaveOptions::default()),
+                Err(ImageError::Unsupported(_))
+            ));
+        }
+        assert!(encode(&wide, ImageFormat::QOI, &SaveOptions::default()).is_ok());
+        assert!(matches!(
+            encode(
+                &Canvas::new(0, 0),
+                ImageFormat::EXR,
+                &SaveOptions::default()
+            ),
+            Err(ImageError::Unsupported(_))
+        ));
+        assert!(encode(
+            &Canvas::new(0, 0),
+            ImageFormat::PNG,
+            &SaveOptions::default()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn invalid_gamma() {
+        let options = SaveOptions {
+            transfer_function: TransferFunction::Gamma(-1.0),
+            ..SaveOptions::default()
+        };
+        assert!(matches!(
+            encode(&gradient(5, 3), ImageFormat::PNG, &options),
+            Err(ImageError::Unsupported(_))
+        ));
+        let bytes = encode(&gradient(5, 3), ImageFormat::PPM, &SaveOptions::default()).unwrap();
+        assert!(matches!(
+            decode(&bytes, ImageFormat::PPM, TransferFunction::Gamma(f32::NAN)),
+            Err(ImageError::Unsupported(_))
+        ));
+
+        // Formats that do not apply the transfer function ignore it
+        for format in [ImageFormat::HDR, ImageFormat::PFM, ImageFormat::EXR] {
+            assert!(
+                encode(&gradient(5, 3), format, &options).is_ok(),
+                "{format:?}"
+            );
+        }
+        for format in [ImageFormat::HDR, ImageFormat::PFM, ImageFormat::QOI] {
+            let bytes = encode(&gradient(5, 3), format, &SaveOptions::default()).unwrap();
+            assert!(
+                decode(&bytes, format, options.transfer_function).is_ok(),
+                "{format:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn exr_alpha_channel() {
+        let canvas = gradient(5, 3);
+        let plain = encode(&canvas, ImageFormat::EXR, &SaveOptions::default()).unwrap();
+        let options = SaveOptions {
+            alpha: true,
+            ..SaveOptions::default()
+        };
+        let with_alpha = encode(&canvas, ImageFormat::EXR, &options).unwrap();
+        assert!(!plain.windows(2).any(|w| w == b"A\0"));
+        assert!(with_alpha.windows(2).any(|w| w == b"A\0"));
+    }
+
+    #[test]
+    fn tone_mapping() {
+        let mut canvas = Canvas::new(1, 1);
+        canvas.set_pixel_at(0, 0, Color::new(3.0, 1.0, 0.25));
+        let options = SaveOptions {
+            tone_mapping: ToneMapping::new(ToneMapOperator::Linear, -2.0),
+            transfer_function: TransferFunction::Linear,
+            ..SaveOptions::default()
+        };
+        let ppm = encode(&canvas, ImageFormat::PPM, &options).unwrap();
+        assert_eq!(b"P3\n1 1\n255\n191 64 16\n".to_vec(), ppm);
+        let clamped = SaveOptions {
+            transfer_function: TransferFunction::Linear,
+            ..SaveOptions::default()
+        };
+        let ppm = encode(&canvas, ImageFormat::PPM, &clamped).unwrap();
+        assert_eq!(b"P3\n1 1\n255\n255 255 64\n".to_vec(), ppm);
+
+        // High dynamic range formats keep the linear values
+        let bytes = encode(&canvas, ImageFormat::PFM, &options).unwrap();
+        let pfm = decode(&bytes, ImageFormat::PFM, TransferFunction::SRGB);
+        assert_eq!(Color::new(3.0, 1.0, 0.25), pfm.unwrap().get_pixel_at(0, 0));
+    }
+
+    #[test]
+    fn transfer_function() {
+        let mut canvas = Canvas::new(1, 1);
+        canvas.set_pixel_at(0, 0, Color::new(0.214_041_14, 0.0, 1.0));
+        let ppm = encode(&canvas, ImageFormat::PPM, &SaveOptions::default()).unwrap();
+        assert_eq!(b"P3\n1 1\n255\n128 0 255\n".to_vec(), ppm);
+
+        let gamma = SaveOptions {
+            transfer_function: TransferFunction::Gamma(2.0),
+            ..SaveOptions::default()
+        };
+        canvas.set_pixel_at(0, 0, Color::new(0.25, 0.0, 1.0));
+        let ppm = encode(&canvas, ImageFormat::PPM, &gamma).unwrap();
+        assert_eq!(b"P3\n1 1\n255\n128 0 255\n".to_vec(), ppm);
+        let decoded = decode(&ppm, ImageFormat::PPM, TransferFunction::Gamma(2.0)).unwrap();
+        assert!((decoded.get_pixel_at(0, 0).r() - 0.25).abs() < 0.005);
+    }
+
+    #[test]
+    fn dither() {
+        // Halfway between two 8-bit levels, so plain rounding turns it all into one of them
+        let canvas = Canvas::filled(16, 16, Color::new(100.5 / 255.0, 0.0, 0.0));
+        let plain = SaveOptions {
+            transfer_function: TransferFunction::Linear,
+            ..SaveOptions::default()
+        };
+        let ppm = encode(&canvas, ImageFormat::PPM, &plain).unwrap();
+        let decoded = decode(&ppm, ImageFormat::PPM, TransferFunction::Linear).unwrap();
+        let levels = |canvas: &Canvas| {
+            let mut reds: Vec<u32> = canvas
+                .raw_buffer
+                .iter()
+                .map(|c| (c.r() * 255.0).round() as u32)
+                .collect();
+            reds.sort_unstable();
+            reds.dedup();
+            reds
+        };
+        assert_eq!(1, levels(&decoded).len());
+
+        for dither in [Dither::Bayer, Dither::BlueNoise, Dither::FloydSteinberg] {
+            let options = SaveOptions { dither, ..plain };
+            for format in [ImageFormat::PPM, ImageFormat::PNG] {
+                let bytes = encode(&canvas, format, &options).unwrap();
+                let decoded = decode(&bytes, format, TransferFunction::Linear).unwrap();
+                assert_eq!(vec![100, 101], levels(&decoded), "{dither:?} {format:?}");
+                let mean = decoded.raw_buffer.iter().map(|c| c.r()).sum::<f32>() / 256.0;
+                assert!((mean * 255.0 - 100.5).abs() < 0.05, "{dither:?} {format:?}");
+            }
+        }
+
+        // High dynamic range formats keep the exact values
+        let options = SaveOptions {
+            dither: Dither::FloydSteinberg,
+            ..plain
+        };
+        let pfm = decode(
+            &encode(&canvas, ImageFormat::PFM, &options).unwrap(),
+            ImageFormat::PFM,
+            TransferFunction::Linear,
+        );
+        assert_eq!(canvas.raw_buffer, pfm.unwrap().raw_buffer);
+
+        // GIF images dither against their palette, which only has room for some of the gradient
+        let gradient = gradient(64, 64);
+        let plain_gif = encode(&gradient, ImageFormat::GIF, &plain).unwrap();
+        let mut gif = GIF::new(64, 64, 0);
+        gif.repeat = None;
+        gif.add_frame(&gradient);
+        assert_eq!(gif.to_bytes(), plain_gif);
+        gif.dithering = Dither::FloydSteinberg;
+        assert_eq!(
+            gif.to_bytes(),
+            encode(&gradient, ImageFormat::GIF, &options).unwrap()
+        );
+        assert_ne!(gif.to_bytes(), plain_gif);
+    }
+
+    #[test]
+    fn qoi_declares_color_space() {
+        let mut canvas = Canvas::new(1, 1);
+        canvas.set_pixel_at(0, 0, Color::new(0.5, 0.5, 0.5));
+        let linear = SaveOptions {
+            transfer_function: TransferFunction::Linear,
+            ..SaveOptions::default()
+        };
+        for options in [SaveOptions::default(), linear] {
+            let bytes = encode(&canvas, ImageFormat::QOI, &options).unwrap();
+            // The header wins over the transfer function passed in
+            let decoded = decode(&bytes, ImageFormat::QOI, TransferFunction::Gamma(3.0)).unwrap();
+            assert!(
+                (decoded.get_pixel_at(0, 0).r() - 0.5).abs() < 0.005,
+                "{options:?}"
+            );
+        }
+        let bytes = encode(&canvas, ImageFormat::QOI, &linear).unwrap();
+        let qoi = QOI::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(ColorSpace::Linear, qoi.color_space);
+    }
+
+    #[test]
+    fn save_only_formats() {
+        for format in [ImageFormat::EXR, ImageFormat::GIF] {
+            let bytes = encode(&gradient(5, 3), format, &SaveOptions::default()).unwrap();
+            assert!(matches!(
+                decode(&bytes, format, TransferFunction::default()),
+                Err(ImageError::Unsupported(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert_eq!(
+            Err(ImageError::InvalidSignature),
+            decode(
+                b"P3\n1 1\n255\n0 0 0\n",
+                ImageFormat::PNG,
+                TransferFunction::SRGB
+            )
+            .map(|_| ())
+        );
+        assert_eq!(
+            Err(ImageError::UnexpectedEof),
+            decode(
+                b"P3\n1 1\n255\n0 0",
+                ImageFormat::PPM,
+                TransferFunction::SRGB
+            )
+            .map(|_| ())
+        );
+    }
+}
+
+/// Reads an image file, detecting its format from its contents or, failing that, its extension
+///
+/// Colors of formats that are not high dynamic range are assumed to be sRGB encoded, unless the file says
+/// otherwise, and are converted to linear
+///
+/// # Errors
+///
+/// Returns [`ImageError::Io`] if the file cannot be read, [`ImageError::UnknownFormat`] if the format
+/// cannot be detected, or the error of the decoder
+pub fn load(path: impl AsRef<Path>) -> Result<Canvas, ImageError> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path)?;
+    let format = ImageFormat::from_magic(&bytes)
+        .or_else(|| ImageFormat::from_path(path))
+        .ok_or_else(|| ImageError::UnknownFormat(path.display().to_string()))?;
+    decode(&bytes, format, TransferFunction::default())
+}
+
+/// Writes a canvas to an image file, in the format of the options or else the one of the extension
+///
+/// # Errors
+///
+/// Returns [`ImageError::UnknownFormat`] if no format is given and the extension is not known, the error of
+/// [`encode`] if the canvas cannot be stored in the format, or [`ImageError::Io`] if the file cannot be written
+pub fn save(
+    canvas: &Canvas,
+    path: impl AsRef<Path>,
+    options: &SaveOptions,
+) -> Result<(), ImageError> {
+    let path = path.as_ref();
+    let format = options
+        .format
+        .or_else(|| ImageFormat::from_path(path))
+        .ok_or_else(|| ImageError::UnknownFormat(path.display().to_string()))?;
+    std::fs::write(path, encode(canvas, format, options)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests_files {
+    use super::*;
+    use crate::core3d::color::Color;
+    use std::path::PathBuf;
+
+    /// Path in the temporary directory that is removed again when dropped
+    struct TempPath(PathBuf);
+
+    impl TempPath {
+        fn new(name: &str) -> Self {
+            let name = format!("rusty_ray_tracer_{}_{name}", std::process::id());
+            Self(std::env::temp_dir().join(name))
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn canvas() -> Canvas {
+        let mut canvas = Canvas::new(2, 2);
+        canvas.set_pixel_at(0, 0, Color::new(1.0, 0.0, 0.0));
+        canvas.set_pixel_at(1, 1, Color::new(0.0, 0.0, 1.0));
+        canvas
+    }
+
+    #[test]
+    fn save_and_load_by_extension() {
+        for format in [ImageFormat::PPM, ImageFormat::PNG, ImageFormat::TGA] {
+            let path = TempPath::new(&format!("by_extension.{}", format.extension()));
+            save(&canvas(), &path.0, &SaveOptions::default()).unwrap();
+            let loaded = load(&path.0).unwrap();
+            assert_eq!(Color::new(1.0, 0.0, 0.0), loaded.get_pixel_at(0, 0));
+            assert_eq!(Color::new(0.0, 0.0, 1.0), loaded.get_pixel_at(1, 1));
+        }
+    }
+
+    #[test]
+    fn contents_win_over_extension() {
+        let path = TempPath::new("mislabelled.tga");
+        let options = SaveOptions {
+            format: Some(ImageFormat::QOI),
+            ..SaveOptions::default()
+        };
+        save(&canvas(), &path.0, &options).unwrap();
+        assert!(std::fs::read(&path.0).unwrap().starts_with(QOI_SIGNATURE));
+        let loaded = load(&path.0).unwrap();
+        assert_eq!(Color::new(1.0, 0.0, 0.0), loaded.get_pixel_at(0, 0));
+    }
+
+    #[test]
+    fn unknown_format() {
+        let path = TempPath::new("unknown.xyz");
+        assert!(matches!(
+            save(&canvas(), &path.0, &SaveOptions::default()),
+            Err(ImageError::UnknownFormat(_))
+        ));
+        std::fs::write(&path.0, b"not an image").unwrap();
+        assert!(matches!(load(&path.0), Err(ImageError::UnknownFormat(_))));
+    }
+
+    #[test]
+    fn missing_file() {
+        let path = TempPath::new("missing.png");
+        assert!(matches!(load(&path.0), Err(ImageError::Io(_))));
+    }
+}
@@ -1 +1,13 @@
+pub mod bmp;
+pub mod checksum;
+pub mod deflate;
+pub mod exr;
+pub mod gif;
+pub mod hdr;
+pub mod image_error;
+pub mod image_io;
+pub mod pfm;
+pub mod png;
 pub mod ppm;
+pub mod qoi;
+pub mod tga;
@@ -0,0 +1,473 @@
+use crate::{
+    core3d::{color::Color, color_rgb::ColorRGB},
+    graphics2d::canvas::Canvas,
+};
+
+use super::image_error::ImageError;
+
+/// Byte order of the stored floats, given by the sign of the scale in the header
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+/// Channel layout, given by the `PF` (color) or `Pf` (grayscale) magic
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    #[default]
+    RGB,
+    Grayscale,
+}
+
+impl ColorMode {
+    /// Returns the number of samples per pixel
+    #[must_use]
+    pub const fn channels(self) -> usize {
+        match self {
+            Self::RGB => 3,
+            Self::Grayscale => 1,
+        }
+    }
+
+    const fn magic(self) -> &'static str {
+        match self {
+            Self::RGB => "PF",
+            Self::Grayscale => "Pf",
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct PFM {
+    pub width: u32,
+    pub height: u32,
+    pub mode: ColorMode,
+    pub endianness: Endianness,
+    /// Magnitude of the scale field, informational only as most tools ignore it
+    pub scale: f32,
+
+    /// Samples of all pixels row by row starting at the top, even though the file stores the bottom row first
+    pub samples: Vec<f32>,
+}
+
+impl PFM {
+    /// Creates a new pfm from raw samples with a scale of 1
+    #[must_use]
+    pub const fn new(
+        width: u32,
+        height: u32,
+        mode: ColorMode,
+        endianness: Endianness,
+        samples: Vec<f32>,
+    ) -> Self {
+        Self {
+            width,
+            height,
+            mode,
+            endianness,
+            scale: 1.0,
+            samples,
+        }
+    }
+
+    /// Creates a new pfm from a canvas, storing grayscale images as Rec. 709 luminance
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::pfm::{ColorMode, Endianness, PFM};
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(1, 1);
+    /// canvas.set_pixel_at(0, 0, Color::new(2.0, 2.0, 2.0));
+    /// let pfm = PFM::from_canvas(&canvas, ColorMode::Grayscale, Endianness::Big);
+    /// assert!((pfm.samples[0] - 2.0).abs() < 1e-6);
+    /// ```
+    #[must_use]
+    pub fn from_canvas(canvas: &Canvas, mode: ColorMode, endianness: Endianness) -> Self {
+        Self::new(
+            canvas.width,
+            canvas.height,
+            mode,
+            endianness,
+            canvas
+                .raw_buffer
+                .iter()
+                .flat_map(|c| match mode {
+                    ColorMode::RGB => vec![c.r(), c.g(), c.b()],
+                    ColorMode::Grayscale => vec![c.luminance()],
+                })
+                .collect(),
+        )
+    }
+
+    /// Number of samples in a single row of pixels
+    #[must_use]
+    const fn get_stride(&self) -> usize {
+        self.width as usize * self.mode.channels()
+    }
+
+    /// Encodes the image as a PFM file, writing the rows bottom to top as the format requires
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::pfm::{ColorMode, Endianness, PFM};
+    /// let pfm = PFM::new(1, 1, ColorMode::Grayscale, Endianness::Little, vec![1.0]);
+    /// assert_eq!(b"Pf\n1 1\n-1\n\x00\x00\x80\x3F".to_vec(), pfm.to_bytes());
+    /// ```
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let scale = match self.endianness {
+            Endianness::Little => -self.scale,
+            Endianness::Big => self.scale,
+        };
+        let mut bytes = format!(
+            "{}\n{} {}\n{scale}\n",
+            self.mode.magic(),
+            self.width,
+            self.height
+        )
+        .into_bytes();
+        if self.get_stride() == 0 {
+            return bytes;
+        }
+        for row in self.samples.chunks(self.get_stride()).rev() {
+            for sample in row {
+                match self.endianness {
+                    Endianness::Little => bytes.extend(sample.to_le_bytes()),
+                    Endianness::Big => bytes.extend(sample.to_be_bytes()),
+                }
+            }
+        }
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests_pfm {
+    use super::*;
+
+    #[test]
+    fn new() {
+        let pfm = PFM::new(4, 3, ColorMode::RGB, Endianness::Little, vec![0.0; 36]);
+        assert_eq!(4, pfm.width);
+        assert_eq!(3, pfm.height);
+        assert_eq!(1.0, pfm.scale);
+        assert_eq!(4 * 3, pfm.get_stride());
+    }
+
+    #[test]
+    fn to_bytes_bottom_up() {
+        let pfm = PFM::new(
+            1,
+            2,
+            ColorMode::RGB,
+            Endianness::Big,
+            vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+        );
+        let mut expected = b"PF\n1 2\n1\n".to_vec();
+        [4.0_f32, 5.0, 6.0, 1.0, 2.0, 3.0]
+            .iter()
+            .for_each(|s| expected.extend(s.to_be_bytes()));
+        assert_eq!(expected, pfm.to_bytes());
+    }
+
+    #[test]
+    fn to_bytes_scale() {
+        let mut pfm = PFM::new(1, 1, ColorMode::Grayscale, Endianness::Little, vec![0.0]);
+        pfm.scale = 0.5;
+        assert!(pfm.to_bytes().starts_with(b"Pf\n1 1\n-0.5\n"));
+    }
+}
+
+mod decoding {
+    use super::{ColorMode, Endianness, PFM};
+    use crate::asset_types::image_error::ImageError;
+
+    /// Reads the next whitespace separated header token, returning it and the remaining bytes
+    fn read_token(bytes: &[u8]) -> Result<(&str, &[u8]), ImageError> {
+        let start = bytes
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .ok_or(ImageError::UnexpectedEof)?;
+        let length = bytes[start..]
+            .iter()
+            .position(u8::is_ascii_whitespace)
+            .ok_or(ImageError::UnexpectedEof)?;
+        let token = std::str::from_utf8(&bytes[start..start + length])
+            .map_err(|_| ImageError::InvalidData("header is not text".to_string()))?;
+        Ok((token, &bytes[start + length..]))
+    }
+
+    fn parse<T: std::str::FromStr>(token: &str) -> Result<T, ImageError> {
+        token
+            .parse()
+            .map_err(|_| ImageError::InvalidData(format!("header value {token}")))
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<PFM, ImageError> {
+        let (magic, rest) = read_token(bytes).map_err(|_| ImageError::InvalidSignature)?;
+        let mode = match magic {
+            "PF" => ColorMode::RGB,
+            "Pf" => ColorMode::Grayscale,
+            _ => return Err(ImageError::InvalidSignature),
+        };
+        let (width, rest) = read_token(rest)?;
+        let (height, rest) = read_token(rest)?;
+        let (scale, rest) = read_token(rest)?;
+        let (width, height, scale) = (
+            parse::<u32>(width)?,
+            parse::<u32>(height)?,
+            parse::<f32>(scale)?,
+        );
+        if scale == 0.0 || !scale.is_finite() {
+            return Err(ImageError::InvalidData(format!("scale {scale}")));
+        }
+        let endianness = if scale < 0.0 {
+            Endianness::Little
+        } else {
+            Endianness::Big
+        };
+
+        // A single whitespace character separates the header from the samples
+        let stride = (width as usize)
+            .checked_mul(mode.channels())
+            .ok_or_else(|| ImageError::Unsupported(format!("{width}x{height} image")))?;
+        let size = stride
+            .checked_mul(height as usize)
+            .and_then(|samples| samples.checked_mul(4))
+            .ok_or_else(|| ImageError::Unsupported(format!("{width}x{height} image")))?;
+        if rest.len() <= size {
+            return Err(ImageError::UnexpectedEof);
+        }
+        let data = &rest[1..=size];
+        let rows = data
+            .chunks_exact(4)
+            .map(|b| {
+                let b = [b[0], b[1], b[2], b[3]];
+                match endianness {
+                    Endianness::Little => f32::from_le_bytes(b),
+                    Endianness::Big => f32::from_be_bytes(b),
+                }
+            })
+            .collect::<Vec<_>>();
+        let samples = if stride == 0 {
+            rows
+        } else {
+            rows.chunks(stride).rev().flatten().copied().collect()
+        };
+
+        let mut pfm = PFM::new(width, height, mode, endianness, samples);
+        pfm.scale = scale.abs();
+        Ok(pfm)
+    }
+
+    #[cfg(test)]
+    mod tests_read_token {
+        use super::*;
+
+        #[test]
+        fn tokens() {
+            assert_eq!(Ok(("PF", &b"\n1"[..])), read_token(b"PF\n1"));
+            assert_eq!(Ok(("12", &b" x"[..])), read_token(b" \n 12 x"));
+            assert_eq!(Err(ImageError::UnexpectedEof), read_token(b"12"));
+            assert_eq!(Err(ImageError::UnexpectedEof), read_token(b"   "));
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for PFM {
+    type Error = ImageError;
+
+    /// Decodes a `PF` or `Pf` file of either byte order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::pfm::{ColorMode, Endianness, PFM};
+    /// let pfm = PFM::new(2, 1, ColorMode::RGB, Endianness::Big, vec![1.5, -2.0, 1e30, 0.0, 0.25, 7.0]);
+    /// let decoded = PFM::try_from(pfm.to_bytes().as_slice()).unwrap();
+    /// assert_eq!(pfm.samples, decoded.samples);
+    /// assert_eq!(Endianness::Big, decoded.endianness);
+    /// ```
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        decoding::decode(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests_try_from_bytes {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let samples = [
+            1.0,
+            -2.5,
+            f32::MAX,
+            f32::MIN_POSITIVE,
+            0.1,
+            1e-40,
+            3.0,
+            4.0,
+            5.0,
+        ]
+        .iter()
+        .cycle()
+        .take(3 * 2 * 3)
+        .copied()
+        .collect::<Vec<_>>();
+        for endianness in [Endianness::Little, Endianness::Big] {
+            for mode in [ColorMode::RGB, ColorMode::Grayscale] {
+                let samples = samples[..3 * 2 * mode.channels()].to_vec();
+                let pfm = PFM::new(3, 2, mode, endianness, samples);
+                let decoded = PFM::try_from(pfm.to_bytes().as_slice()).unwrap();
+                assert_eq!(3, decoded.width);
+                assert_eq!(2, decoded.height);
+                assert_eq!(mode, decoded.mode);
+                assert_eq!(endianness, decoded.endianness);
+                assert_eq!(
+                    pfm.samples.iter().map(|s| s.to_bits()).collect::<Vec<_>>(),
+                    decoded
+                        .samples
+                        .iter()
+                        .map(|s| s.to_bits())
+                        .collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn header_on_one_line() {
+        let mut bytes = b"Pf 2 1 -4.0\n".to_vec();
+        bytes.extend(1.0_f32.to_le_bytes());
+        bytes.extend(2.0_f32.to_le_bytes());
+        let pfm = PFM::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(vec![1.0, 2.0], pfm.samples);
+        assert_eq!(4.0, pfm.scale);
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            Err(ImageError::InvalidSignature),
+            PFM::try_from(&b"P6\n1 1\n255\n"[..]).map(|_| ())
+        );
+        assert!(matches!(
+            PFM::try_from(&b"PF\n1 x\n-1\n"[..]),
+            Err(ImageError::InvalidData(_))
+        ));
+        assert!(matches!(
+            PFM::try_from(&b"PF\n1 1\n0\n"[..]),
+            Err(ImageError::InvalidData(_))
+        ));
+        assert_eq!(
+            Err(ImageError::UnexpectedEof),
+            PFM::try_from(&b"Pf\n1 1\n-1\n\x00\x00"[..]).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn absurd_sizes() {
+        assert!(matches!(
+            PFM::try_from(&b"PF\n4294967295 4294967295\n-1\n\x00"[..]),
+            Err(ImageError::Unsupported(_))
+        ));
+        assert_eq!(
+            Err(ImageError::UnexpectedEof),
+            PFM::try_from(&b"PF\n100000 100000\n-1\n\x00\x00\x00\x00"[..]).map(|_| ())
+        );
+    }
+}
+
+impl From<&Canvas> for PFM {
+    /// Creates a new little-endian RGB pfm from a canvas, keeping every channel exactly
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::pfm::PFM;
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(2, 1);
+    /// canvas.set_pixel_at(1, 0, Color::new(-1.0, 250.0, 0.125));
+    /// let pfm = PFM::from(&canvas);
+    /// assert_eq!(vec![0.0, 0.0, 0.0, -1.0, 250.0, 0.125], pfm.samples);
+    /// ```
+    fn from(canvas: &Canvas) -> Self {
+        Self::from_canvas(canvas, ColorMode::RGB, Endianness::Little)
+    }
+}
+
+impl TryFrom<&PFM> for Canvas {
+    type Error = ImageError;
+
+    /// Creates a new opaque canvas from a pfm, spreading grayscale samples over all three channels
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImageError::InvalidData`] if the number of pixels does not match the image size
+    fn try_from(pfm: &PFM) -> Result<Self, Self::Error> {
+        Self::from_buffer(
+            pfm.width,
+            pfm.height,
+            pfm.samples
+                .chunks_exact(pfm.mode.channels())
+                .map(|pixel| match pfm.mode {
+                    ColorMode::RGB => Color::new(pixel[0], pixel[1], pixel[2]),
+                    ColorMode::Grayscale => Color::new(pixel[0], pixel[0], pixel[0]),
+                })
+                .collect(),
+        )
+        .ok_or_else(|| {
+            ImageError::InvalidData("pixel count does not match the image size".to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_canvas {
+    use super::*;
+
+    #[test]
+    fn round_trip_is_lossless() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.set_pixel_at(0, 0, Color::new(1000.0, -0.5, 1e-7));
+        canvas.set_pixel_at(2, 1, Color::new(0.333_333_3, 65_504.0, 0.1));
+        for endianness in [Endianness::Little, Endianness::Big] {
+            let bytes = PFM::from_canvas(&canvas, ColorMode::RGB, endianness).to_bytes();
+            let decoded = Canvas::try_from(&PFM::try_from(bytes.as_slice()).unwrap()).unwrap();
+            assert_eq!(3, decoded.width);
+            assert_eq!(2, decoded.height);
+            for (expected, actual) in canvas.raw_buffer.iter().zip(&decoded.raw_buffer) {
+                assert_eq!(expected.tuple[..3], actual.tuple[..3]);
+                assert_eq!(1.0, actual.a());
+            }
+        }
+    }
+
+    #[test]
+    fn grayscale() {
+        let mut canvas = Canvas::new(1, 1);
+        canvas.set_pixel_at(0, 0, Color::new(1.0, 0.0, 0.0));
+        let pfm = PFM::from_canvas(&canvas, ColorMode::Grayscale, Endianness::Little);
+        let luminance = Color::new(1.0, 0.0, 0.0).luminance();
+        assert_eq!(vec![luminance], pfm.samples);
+        let decoded = Canvas::try_from(&pfm).unwrap();
+        assert_eq!(
+            Color::new(luminance, luminance, luminance),
+            decoded.get_pixel_at(0, 0)
+        );
+    }
+
+    #[test]
+    fn wider_than_u16() {
+        let pfm = PFM::new(70_000, 0, ColorMode::RGB, Endianness::Little, vec![]);
+        let canvas = Canvas::try_from(&pfm).unwrap();
+        assert_eq!((pfm.width, pfm.height), (canvas.width, canvas.height));
+    }
+}
@@ -0,0 +1,1320 @@
+use crate::{
+    core3d::{color::Color, color_rgb::ColorRGB},
+    graphics2d::canvas::Canvas,
+};
+
+use super::{
+    checksum::Crc32,
+    deflate::{zlib_compress, Compression},
+    image_error::ImageError,
+};
+
+/// Every PNG file starts with these eight bytes
+pub const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// Number of bits stored per channel sample
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BitDepth {
+    #[default]
+    Eight,
+    Sixteen,
+}
+
+impl BitDepth {
+    /// Returns the number of bits per sample
+    #[must_use]
+    pub const fn bits(self) -> u8 {
+        match self {
+            Self::Eight => 8,
+            Self::Sixteen => 16,
+        }
+    }
+
+    /// Returns the largest value a sample can hold
+    #[must_use]
+    pub const fn max_value(self) -> u16 {
+        match self {
+            Self::Eight => 0xFF,
+            Self::Sixteen => 0xFFFF,
+        }
+    }
+
+    const fn bytes(self) -> usize {
+        self.bits() as usize / 8
+    }
+}
+
+/// Channel layout of each pixel
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ColorType {
+    #[default]
+    RGB,
+    RGBA,
+}
+
+impl ColorType {
+    /// Returns the number of samples per pixel
+    #[must_use]
+    pub const fn channels(self) -> usize {
+        match self {
+            Self::RGB => 3,
+            Self::RGBA => 4,
+        }
+    }
+
+    /// Returns the color type code written to the `IHDR` chunk
+    #[must_use]
+    pub const fn code(self) -> u8 {
+        match self {
+            Self::RGB => 2,
+            Self::RGBA => 6,
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct PNG {
+    pub width: u32,
+    pub height: u32,
+    pub bit_depth: BitDepth,
+    pub color_type: ColorType,
+
+    /// Samples of all pixels row by row, `color_type.channels()` samples per pixel
+    pub samples: Vec<u16>,
+}
+
+impl PNG {
+    /// Creates a new png from raw samples
+    #[must_use]
+    pub const fn new(
+        width: u32,
+        height: u32,
+        bit_depth: BitDepth,
+        color_type: ColorType,
+        samples: Vec<u16>,
+    ) -> Self {
+        Self {
+            width,
+            height,
+            bit_depth,
+            color_type,
+            samples,
+        }
+    }
+
+    /// Creates a new png from a canvas, clamping each channel to 0..1 before scaling it to the bit depth
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::png::{BitDepth, ColorType, PNG};
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(2, 1);
+    /// canvas.set_pixel_at(0, 0, Color::new(1.0, 0.5, 0.0));
+    /// let png = PNG::from_canvas(&canvas, BitDepth::Sixteen, ColorType::RGBA);
+    /// assert_eq!(vec![65535, 32768, 0, 65535, 0, 0, 0, 0], png.samples);
+    /// ```
+    #[must_use]
+    pub fn from_canvas(canvas: &Canvas, bit_depth: BitDepth, color_type: ColorType) -> Self {
+        let max_value = f32::from(bit_depth.max_value());
+        Self::new(
+            canvas.width,
+            canvas.height,
+            bit_depth,
+            color_type,
+            canvas
+                .raw_buffer
+                .iter()
+                .flat_map(|c| [c.r(), c.g(), c.b(), c.a()])
+                .enumerate()
+                .filter(|(i, _)| color_type == ColorType::RGBA || i % 4 != 3)
+                .map(|(_, c)| num::clamp(c, 0.0, 1.0))
+                .map(|c| f32::round(c * max_value) as u16)
+                .collect(),
+        )
+    }
+
+    /// Number of bytes a whole pixel occupies
+    #[must_use]
+    const fn get_bytes_per_pixel(&self) -> usize {
+        self.color_type.channels() * self.bit_depth.bytes()
+    }
+
+    /// Number of bytes in a single row of pixels, excluding the filter type byte
+    #[must_use]
+    const fn get_stride(&self) -> usize {
+        self.width as usize * self.get_bytes_per_pixel()
+    }
+
+    /// Serializes the samples into big-endian rows of bytes
+    fn get_scanlines(&self) -> Vec<Vec<u8>> {
+        let samples_per_row = self.width as usize * self.color_type.channels();
+        if samples_per_row == 0 {
+            return vec![Vec::new(); self.height as usize];
+        }
+        self.samples
+            .chunks(samples_per_row)
+            .map(|row| match self.bit_depth {
+                BitDepth::Eight => row.iter().map(|&s| s as u8).collect(),
+                BitDepth::Sixteen => row.iter().flat_map(|s| s.to_be_bytes()).collect(),
+            })
+            .collect()
+    }
+
+    /// Encodes the image as a complete PNG file
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::deflate::Compression;
+    /// # use rusty_ray_tracer::asset_types::png::{PNG, PNG_SIGNATURE};
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let bytes = PNG::from(&Canvas::new(4, 4)).to_bytes(Compression::FixedHuffman);
+    /// assert_eq!(PNG_SIGNATURE, bytes[0..8]);
+    /// assert_eq!(b"IHDR", &bytes[12..16]);
+    /// assert_eq!(b"IEND", &bytes[bytes.len() - 8..bytes.len() - 4]);
+    /// ```
+    #[must_use]
+    pub fn to_bytes(&self, compression: Compression) -> Vec<u8> {
+        debug_assert_eq!(
+            self.width as usize * self.height as usize * self.color_type.channels(),
+            self.samples.len()
+        );
+        let mut header = Vec::with_capacity(13);
+        header.extend(self.width.to_be_bytes());
+        header.extend(self.height.to_be_bytes());
+        header.extend([self.bit_depth.bits(), self.color_type.code(), 0, 0, 0]);
+
+        let bpp = self.get_bytes_per_pixel();
+        let mut filtered = Vec::with_capacity((self.get_stride() + 1) * self.height as usize);
+        let mut previous = vec![0_u8; self.get_stride()];
+        for scanline in self.get_scanlines() {
+            let (filter, bytes) = match compression {
+                // Filtering only helps entropy coding, stored blocks are the same size either way
+                Compression::Stored => (FilterType::None, scanline.clone()),
+                Compression::FixedHuffman => filters::select_filter(&scanline, &previous, bpp),
+            };
+            filtered.push(filter as u8);
+            filtered.extend(bytes);
+            previous = scanline;
+        }
+
+        let mut bytes = PNG_SIGNATURE.to_vec();
+        write_chunk(&mut bytes, b"IHDR", &header);
+        write_chunk(&mut bytes, b"IDAT", &zlib_compress(&filtered, compression));
+        write_chunk(&mut bytes, b"IEND", &[]);
+        bytes
+    }
+}
+
+/// Appends a chunk with its length prefix and CRC suffix
+fn write_chunk(bytes: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    bytes.extend((data.len() as u32).to_be_bytes());
+    bytes.extend(chunk_type);
+    bytes.extend(data);
+    let mut crc = Crc32::new();
+    crc.update(chunk_type);
+    crc.update(data);
+    bytes.extend(crc.finish().to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests_png {
+    use super::*;
+    use crate::asset_types::checksum::crc32;
+
+    #[test]
+    fn new() {
+        let png = PNG::new(16, 9, BitDepth::Eight, ColorType::RGB, vec![0_u16; 432]);
+        assert_eq!(16, png.width);
+        assert_eq!(9, png.height);
+        assert_eq!(BitDepth::Eight, png.bit_depth);
+        assert_eq!(ColorType::RGB, png.color_type);
+        assert_eq!(16 * 9 * 3, png.samples.len());
+    }
+
+    #[test]
+    fn get_stride() {
+        assert_eq!(
+            3 * 2,
+            PNG::new(2, 2, BitDepth::Eight, ColorType::RGB, vec![]).get_stride()
+        );
+        assert_eq!(
+            4 * 2,
+            PNG::new(2, 2, BitDepth::Eight, ColorType::RGBA, vec![]).get_stride()
+        );
+        assert_eq!(
+            6 * 2,
+            PNG::new(2, 2, BitDepth::Sixteen, ColorType::RGB, vec![]).get_stride()
+        );
+        assert_eq!(
+            8 * 2,
+            PNG::new(2, 2, BitDepth::Sixteen, ColorType::RGBA, vec![]).get_stride()
+        );
+    }
+
+    #[test]
+    fn get_scanlines() {
+        let png = PNG::new(
+            1,
+            2,
+            BitDepth::Eight,
+            ColorType::RGB,
+            vec![1, 2, 3, 4, 5, 6],
+        );
+        assert_eq!(vec![vec![1, 2, 3], vec![4, 5, 6]], png.get_scanlines());
+
+        let png = PNG::new(
+            1,
+            1,
+            BitDepth::Sixteen,
+            ColorType::RGB,
+            vec![0x0102, 0xFF00, 3],
+        );
+        assert_eq!(vec![vec![1, 2, 0xFF, 0, 0, 3]], png.get_scanlines());
+    }
+
+    #[test]
+    fn write_chunk() {
+        let mut bytes = Vec::new();
+        super::write_chunk(&mut bytes, b"IEND", &[]);
+        assert_eq!(
+            vec![0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82],
+            bytes
+        );
+    }
+
+    #[test]
+    fn to_bytes_stored() {
+        let png = PNG::new(1, 1, BitDepth::Eight, ColorType::RGB, vec![255, 0, 128]);
+        let bytes = png.to_bytes(Compression::Stored);
+        let mut expected = PNG_SIGNATURE.to_vec();
+        expected.extend([0, 0, 0, 13]);
+        expected.extend(b"IHDR");
+        expected.extend([0, 0, 0, 1, 0, 0, 0, 1, 8, 2, 0, 0, 0]);
+        expected.extend(crc32(&expected[12..29]).to_be_bytes());
+        expected.extend([0, 0, 0, 15]);
+        expected.extend(b"IDAT");
+        expected.extend([0x78, 0x01, 0x01, 0x04, 0x00, 0xFB, 0xFF, 0, 255, 0, 128]);
+        expected.extend([0x03, 0x81, 0x01, 0x80]);
+        expected.extend(crc32(&expected[37..56]).to_be_bytes());
+        expected.extend([0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]);
+        assert_eq!(expected, bytes);
+    }
+
+    #[test]
+    fn to_bytes_header() {
+        let png = PNG::new(
+            300,
+            2,
+            BitDepth::Sixteen,
+            ColorType::RGBA,
+            vec![0; 300 * 2 * 4],
+        );
+        let bytes = png.to_bytes(Compression::FixedHuffman);
+        assert_eq!(PNG_SIGNATURE, bytes[0..8]);
+        assert_eq!([0, 0, 0, 13], bytes[8..12]);
+        assert_eq!(b"IHDR", &bytes[12..16]);
+        assert_eq!([0, 0, 1, 44, 0, 0, 0, 2, 16, 6, 0, 0, 0], bytes[16..29]);
+        assert_eq!(crc32(&bytes[12..29]).to_be_bytes(), bytes[29..33]);
+        assert_eq!(b"IDAT", &bytes[37..41]);
+    }
+}
+
+impl From<&Canvas> for PNG {
+    /// Creates a new 8-bit RGB png from a canvas
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::png::{BitDepth, ColorType, PNG};
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let png = PNG::from(&Canvas::new(16, 9));
+    /// assert_eq!(BitDepth::Eight, png.bit_depth);
+    /// assert_eq!(ColorType::RGB, png.color_type);
+    /// assert_eq!(16 * 9 * 3, png.samples.len());
+    /// ```
+    fn from(canvas: &Canvas) -> Self {
+        Self::from_canvas(canvas, BitDepth::Eight, ColorType::RGB)
+    }
+}
+
+#[cfg(test)]
+mod tests_from {
+    use crate::core3d::color::Color;
+
+    use super::*;
+
+    #[test]
+    fn new() {
+        let png = PNG::from(&Canvas::new(16, 9));
+        assert_eq!(16, png.width);
+        assert_eq!(9, png.height);
+        assert_eq!(16 * 9 * 3, png.samples.len());
+        assert!(png.samples.iter().all(|&c| c == 0));
+    }
+
+    #[test]
+    #[allow(clippy::identity_op, clippy::erasing_op)]
+    fn new_with_oor_data() {
+        let mut canvas = Canvas::new(4, 3);
+        canvas.set_pixel_at(0, 0, Color::new(3.0, 0.5, -0.2));
+        canvas.set_pixel_at(1, 1, Color::new(-1.0, 1.5, 0.0));
+        canvas.set_pixel_at(2, 2, Color::new(-0.5, 0.2, 1.5));
+        let png = PNG::from(&canvas);
+        assert_eq!(4 * 3 * 3, png.samples.len());
+        assert_eq!([255, 128, 0], png.samples[0 * 3..0 * 3 + 3]);
+        assert_eq!([0, 255, 0], png.samples[5 * 3..5 * 3 + 3]);
+        assert_eq!([0, 51, 255], png.samples[10 * 3..10 * 3 + 3]);
+    }
+
+    #[test]
+    fn rgba() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set_pixel_at(1, 0, Color::new(0.0, 0.2, 1.0));
+        let png = PNG::from_canvas(&canvas, BitDepth::Eight, ColorType::RGBA);
+        assert_eq!(vec![0, 0, 0, 0, 0, 51, 255, 255], png.samples);
+    }
+
+    #[test]
+    fn sixteen_bit() {
+        let mut canvas = Canvas::new(1, 1);
+        canvas.set_pixel_at(0, 0, Color::new(0.2, 0.5, 1.0));
+        let png = PNG::from_canvas(&canvas, BitDepth::Sixteen, ColorType::RGB);
+        assert_eq!(vec![13107, 32768, 65535], png.samples);
+    }
+}
+
+/// Per scanline filter, predicting each byte from its already encoded neighbours
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FilterType {
+    None = 0,
+    Sub = 1,
+    Up = 2,
+    Average = 3,
+    Paeth = 4,
+}
+
+mod filters {
+    use super::FilterType;
+
+    /// Chooses between the left, up or upper left neighbour, whichever is closest to `left + up - upper_left`
+    pub const fn paeth_predictor(left: u8, up: u8, upper_left: u8) -> u8 {
+        let p = left as i16 + up as i16 - upper_left as i16;
+        let pa = (p - left as i16).abs();
+        let pb = (p - up as i16).abs();
+        let pc = (p - upper_left as i16).abs();
+        if pa <= pb && pa <= pc {
+            left
+        } else if pb <= pc {
+            up
+        } else {
+            upper_left
+        }
+    }
+
+    /// Returns the value the filter predicts for byte `i`, given the previous scanline and bytes per pixel
+    pub fn predict(
+        filter: FilterType,
+        scanline: &[u8],
+        previous: &[u8],
+        i: usize,
+        bpp: usize,
+    ) -> u8 {
+        let left = if i >= bpp { scanline[i - bpp] } else { 0 };
+        let up = previous[i];
+        let upper_left = if i >= bpp { previous[i - bpp] } else { 0 };
+        match filter {
+            FilterType::None => 0,
+            FilterType::Sub => left,
+            FilterType::Up => up,
+            FilterType::Average => ((u16::from(left) + u16::from(up)) / 2) as u8,
+            FilterType::Paeth => paeth_predictor(left, up, upper_left),
+        }
+    }
+
+    /// Filters an unfiltered scanline
+    pub fn filter(filter: FilterType, scanline: &[u8], previous: &[u8], bpp: usize) -> Vec<u8> {
+        (0..scanline.len())
+            .map(|i| scanline[i].wrapping_sub(predict(filter, scanline, previous, i, bpp)))
+            .collect()
+    }
+
+    /// Reverses the filter of a scanline, given the already reconstructed previous scanline
+    pub fn unfilter(filter: FilterType, filtered: &[u8], previous: &[u8], bpp: usize) -> Vec<u8> {
+        let mut scanline = Vec::with_capacity(filtered.len());
+        for (i, &byte) in filtered.iter().enumerate() {
+            let prediction = predict(filter, &scanline, previous, i, bpp);
+            scanline.push(byte.wrapping_add(prediction));
+        }
+        scanline
+    }
+
+    /// Picks the filter with the smallest sum of absolute signed differences, the heuristic suggested by the PNG
+    /// specification
+    pub fn select_filter(scanline: &[u8], previous: &[u8], bpp: usize) -> (FilterType, Vec<u8>) {
+        [
+            FilterType::None,
+            FilterType::Sub,
+            FilterType::Up,
+            FilterType::Average,
+            FilterType::Paeth,
+        ]
+        .into_iter()
+        .map(|f| (f, filter(f, scanline, previous, bpp)))
+        .min_by_key(|(_, bytes)| {
+            bytes
+                .iter()
+                .map(|&b| u32::from((b as i8).unsigned_abs()))
+                .sum::<u32>()
+        })
+        .expect("There is always at least one filter")
+    }
+
+    #[cfg(test)]
+    mod tests_filters {
+        use super::*;
+
+        #[test]
+        fn paeth() {
+            assert_eq!(10, paeth_predictor(10, 20, 20));
+            assert_eq!(20, paeth_predictor(10, 20, 10));
+            assert_eq!(15, paeth_predictor(10, 20, 15));
+            assert_eq!(0, paeth_predictor(0, 0, 0));
+        }
+
+        #[test]
+        fn filter_each() {
+            let previous = [10, 20, 30, 40];
+            let scanline = [11, 22, 33, 44];
+            assert_eq!(
+                vec![11, 22, 33, 44],
+                filter(FilterType::None, &scanline, &previous, 1)
+            );
+            assert_eq!(
+                vec![11, 11, 11, 11],
+                filter(FilterType::Sub, &scanline, &previous, 1)
+            );
+            assert_eq!(
+                vec![11, 22, 22, 22],
+                filter(FilterType::Sub, &scanline, &previous, 2)
+            );
+            assert_eq!(
+                vec![1, 2, 3, 4],
+                filter(FilterType::Up, &scanline, &previous, 1)
+            );
+            assert_eq!(
+                vec![6, 7, 7, 8],
+                filter(FilterType::Average, &scanline, &previous, 1)
+            );
+            assert_eq!(
+                vec![1, 2, 3, 4],
+                filter(FilterType::Paeth, &scanline, &previous, 1)
+            );
+        }
+
+        #[test]
+        fn unfilter_each() {
+            let previous = [10, 20, 30, 40];
+            let scanline = [11, 22, 33, 44];
+            for f in [
+                FilterType::None,
+                FilterType::Sub,
+                FilterType::Up,
+                FilterType::Average,
+                FilterType::Paeth,
+            ] {
+                for bpp in 1..=3 {
+                    let filtered = filter(f, &scanline, &previous, bpp);
+                    assert_eq!(scanline.to_vec(), unfilter(f, &filtered, &previous, bpp));
+                }
+            }
+        }
+
+        #[test]
+        fn filter_wraps() {
+            assert_eq!(vec![0xFF], filter(FilterType::Up, &[0], &[1], 1));
+        }
+
+        #[test]
+        fn select() {
+            assert_eq!(
+                FilterType::None,
+                select_filter(&[0, 0, 0, 0], &[9, 9, 9, 9], 1).0
+            );
+            assert_eq!(
+                FilterType::Up,
+                select_filter(&[9, 8, 7, 6], &[9, 8, 7, 6], 1).0
+            );
+            assert_eq!(
+                FilterType::Sub,
+                select_filter(&[5, 5, 5, 5], &[0, 9, 0, 9], 1).0
+            );
+        }
+    }
+}
+
+impl TryFrom<u8> for FilterType {
+    type Error = ImageError;
+
+    /// Converts the filter type byte at the start of a scanline
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::None),
+            1 => Ok(Self::Sub),
+            2 => Ok(Self::Up),
+            3 => Ok(Self::Average),
+            4 => Ok(Self::Paeth),
+            _ => Err(ImageError::InvalidData(format!("filter type {value}"))),
+        }
+    }
+}
+
+mod decoding {
+    use super::{filters, BitDepth, ColorType, FilterType, PNG, PNG_SIGNATURE};
+    use crate::asset_types::{
+        checksum::Crc32,
+        deflate::zlib_decompress,
+        image_error::{pixel_count, ImageError},
+    };
+
+    /// Origin and spacing of the pixels in each of the seven Adam7 passes, as (x, y, dx, dy)
+    pub const ADAM7_PASSES: [(usize, usize, usize, usize); 7] = [
+        (0, 0, 8, 8),
+        (4, 0, 8, 8),
+        (0, 4, 4, 8),
+        (2, 0, 4, 4),
+        (0, 2, 2, 4),
+        (1, 0, 2, 2),
+        (0, 1, 1, 2),
+    ];
+
+    /// Image properties from the `IHDR` chunk
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct Header {
+        pub width: u32,
+        pub height: u32,
+        pub bit_depth: u8,
+        pub color_type: u8,
+        pub interlaced: bool,
+    }
+
+    impl Header {
+        pub fn parse(data: &[u8]) -> Result<Self, ImageError> {
+            if data.len() != 13 {
+                return Err(ImageError::InvalidData("IHDR length".to_string()));
+            }
+            let header = Self {
+                width: u32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+                height: u32::from_be_bytes([data[4], data[5], data[6], data[7]]),
+                bit_depth: data[8],
+                color_type: data[9],
+                interlaced: data[12] == 1,
+            };
+            if header.width == 0 || header.height == 0 {
+                return Err(ImageError::InvalidData("zero image dimension".to_string()));
+            }
+            pixel_count(u64::from(header.width), u64::from(header.height))?;
+            let valid_depths: &[u8] = match header.color_type {
+                0 => &[1, 2, 4, 8, 16],
+                3 => &[1, 2, 4, 8],
+                2 | 4 | 6 => &[8, 16],
+                _ => {
+                    return Err(ImageError::InvalidData(format!(
+                        "color type {}",
+                        header.color_type
+                    )))
+                }
+            };
+            if !valid_depths.contains(&header.bit_depth) {
+                return Err(ImageError::InvalidData(format!(
+                    "bit depth {} for color type {}",
+                    header.bit_depth, header.color_type
+                )));
+            }
+            if data[10] != 0 || data[11] != 0 || data[12] > 1 {
+                return Err(ImageError::Unsupported(
+                    "compression, filter or interlace method".to_string(),
+                ));
+            }
+            Ok(header)
+        }
+
+        /// Number of samples per pixel
+        pub const fn channels(&self) -> usize {
+            match self.color_type {
+                2 => 3,
+                4 => 2,
+                6 => 4,
+                _ => 1,
+            }
+        }
+
+        /// Bytes per complete pixel, rounded up to one byte as the filters require
+        pub const fn filter_bpp(&self) -> usize {
+            let bits = self.channels() * self.bit_depth as usize;
+            if bits < 8 {
+                1
+            } else {
+                bits / 8
+            }
+        }
+
+        /// Bytes in a scanline of the given number of pixels, excluding the filter type byte
+        pub const fn stride(&self, width: usize) -> usize {
+            (width * self.channels() * self.bit_depth as usize).div_ceil(8)
+        }
+
+        /// Bytes of filtered data in a (sub)image of the given size, or `None` if that does not fit in memory
+        pub fn filtered_size(&self, width: usize, height: usize) -> Option<usize> {
+            if width == 0 || height == 0 {
+                return Some(0);
+            }
+            (self.stride(width) + 1).checked_mul(height)
+        }
+    }
+
+    /// Chunk type and data of a single chunk
+    pub type Chunk<'a> = ([u8; 4], &'a [u8]);
+
+    /// Splits the file into its chunks, verifying the signature and every chunk CRC
+    pub fn read_chunks(bytes: &[u8]) -> Result<Vec<Chunk<'_>>, ImageError> {
+        if bytes.len() < PNG_SIGNATURE.len() || bytes[..PNG_SIGNATURE.len()] != PNG_SIGNATURE {
+            return Err(ImageError::InvalidSignature);
+        }
+        let mut chunks = Vec::new();
+        let mut rest = &bytes[PNG_SIGNATURE.len()..];
+        while !rest.is_empty() {
+            if rest.len() < 12 {
+                return Err(ImageError::UnexpectedEof);
+            }
+            let length = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+            let chunk_type = [rest[4], rest[5], rest[6], rest[7]];
+            let data = rest.get(8..8 + length).ok_or(ImageError::UnexpectedEof)?;
+            let crc = rest
+                .get(8 + length..12 + length)
+                .ok_or(ImageError::UnexpectedEof)?;
+            let mut expected = Crc32::new();
+            expected.update(&chunk_type);
+            expected.update(data);
+            if expected.finish().to_be_bytes() != crc {
+                return Err(ImageError::InvalidData(format!(
+                    "CRC mismatch in {} chunk",
+                    String::from_utf8_lossy(&chunk_type)
+                )));
+            }
+            chunks.push((chunk_type, data));
+            rest = &rest[12 + length..];
+            if &chunk_type == b"IEND" {
+                break;
+            }
+        }
+        Ok(chunks)
+    }
+
+    /// Unpacks the samples of a scanline, most significant bits first for depths below 8
+    pub fn unpack_samples(scanline: &[u8], count: usize, bit_depth: u8) -> Vec<u16> {
+        match bit_depth {
+            16 => scanline
+                .chunks_exact(2)
+                .take(count)
+                .map(|b| u16::from_be_bytes([b[0], b[1]]))
+                .collect(),
+            8 => scanline.iter().take(count).map(|&b| u16::from(b)).collect(),
+            _ => {
+                let per_byte = usize::from(8 / bit_depth);
+                let mask = (1_u8 << bit_depth) - 1;
+                (0..count)
+                    .map(|i| {
+                        let shift = 8 - bit_depth * (1 + (i % per_byte) as u8);
+                        u16::from((scanline[i / per_byte] >> shift) & mask)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Unfilters the scanlines of one (sub)image, returning the raw samples and the number of bytes read
+    pub fn read_subimage(
+        data: &[u8],
+        header: &Header,
+        width: usize,
+        height: usize,
+    ) -> Result<(Vec<u16>, usize), ImageError> {
+        if width == 0 || height == 0 {
+            return Ok((Vec::new(), 0));
+        }
+        let stride = header.stride(width);
+        let bpp = header.filter_bpp();
+        let size = header
+            .filtered_size(width, height)
+            .ok_or_else(|| ImageError::Unsupported(format!("{width}x{height} image")))?;
+        let data = data.get(..size).ok_or(ImageError::UnexpectedEof)?;
+
+        let mut samples = Vec::with_capacity(width * height * header.channels());
+        let mut previous = vec![0_u8; stride];
+        for row in data.chunks_exact(stride + 1) {
+            let filter = FilterType::try_from(row[0])?;
+            let scanline = filters::unfilter(filter, &row[1..], &previous, bpp);
+            samples.extend(unpack_samples(
+                &scanline,
+                width * header.channels(),
+                header.bit_depth,
+            ));
+            previous = scanline;
+        }
+        Ok((samples, size))
+    }
+
+    /// Reads all samples of the image in row order, undoing the Adam7 interlacing if needed
+    pub fn read_samples(data: &[u8], header: &Header) -> Result<Vec<u16>, ImageError> {
+        let (width, height) = (header.width as usize, header.height as usize);
+        if !header.interlaced {
+            return read_subimage(data, header, width, height).map(|(samples, _)| samples);
+        }
+
+        let channels = header.channels();
+        // Check that every pass is present before allocating the whole image
+        let mut size = 0_usize;
+        for (x0, y0, dx, dy) in ADAM7_PASSES {
+            let pass_width = (width + dx - 1).saturating_sub(x0) / dx;
+            let pass_height = (height + dy - 1).saturating_sub(y0) / dy;
+            size = header
+                .filtered_size(pass_width, pass_height)
+                .and_then(|pass_size| size.checked_add(pass_size))
+                .ok_or_else(|| ImageError::Unsupported(format!("{width}x{height} image")))?;
+        }
+        if data.len() < size {
+            return Err(ImageError::UnexpectedEof);
+        }
+        let count = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(channels))
+            .ok_or_else(|| ImageError::Unsupported(format!("{width}x{height} image")))?;
+        let mut samples = vec![0_u16; count];
+        let mut offset = 0;
+        for (x0, y0, dx, dy) in ADAM7_PASSES {
+            let pass_width = (width + dx - 1).saturating_sub(x0) / dx;
+            let pass_height = (height + dy - 1).saturating_sub(y0) / dy;
+            let (pass, size) = read_subimage(&data[offset..], header, pass_width, pass_height)?;
+            offset += size;
+            for (i, pixel) in pass.chunks_exact(channels).enumerate() {
+                let x = x0 + (i % pass_width) * dx;
+                let y = y0 + (i / pass_width) * dy;
+                let index = (y * width + x) * channels;
+                samples[index..index + channels].copy_from_slice(pixel);
+            }
+        }
+        Ok(samples)
+    }
+
+    /// Expands samples of any color type to RGB or RGBA, at 8 bits or 16 bits per sample
+    pub fn expand_samples(
+        samples: &[u16],
+        header: &Header,
+        palette: &[u8],
+        transparency: Option<&[u8]>,
+    ) -> Result<PNG, ImageError> {
+        let has_alpha = matches!(header.color_type, 4 | 6) || transparency.is_some();
+        let color_type = if has_alpha {
+            ColorType::RGBA
+        } else {
+            ColorType::RGB
+        };
+        let bit_depth = if header.bit_depth == 16 {
+            BitDepth::Sixteen
+        } else {
+            BitDepth::Eight
+        };
+        let max_value = bit_depth.max_value();
+        // Gray samples below 8 bits are scaled up so that their maximum becomes 255
+        let scale = |v: u16| match header.bit_depth {
+            1 | 2 | 4 => v * 255 / ((1 << header.bit_depth) - 1),
+            _ => v,
+        };
+        let key = |i: usize| {
+            transparency
+                .and_then(|t| t.get(2 * i..2 * i + 2))
+                .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        };
+
+        let mut expanded =
+            Vec::with_capacity(samples.len() / header.channels() * color_type.channels());
+        for pixel in samples.chunks_exact(header.channels()) {
+            let rgba = match header.color_type {
+                0 => {
+                    let alpha = if key(0) == Some(pixel[0]) {
+                        0
+                    } else {
+                        max_value
+                    };
+                    let gray = scale(pixel[0]);
+                    [gray, gray, gray, alpha]
+                }
+                2 => {
+                    let transparent = (0..3).all(|i| key(i) == Some(pixel[i]));
+                    [
+                        pixel[0],
+                        pixel[1],
+                        pixel[2],
+                        if transparent { 0 } else { max_value },
+                    ]
+                }
+                3 => {
+                    let index = usize::from(pixel[0]);
+                    let rgb = palette.get(3 * index..3 * index + 3).ok_or_else(|| {
+                        ImageError::InvalidData(format!("palette index {index} out of range"))
+                    })?;
+                    let alpha = transparency
+                        .and_then(|t| t.get(index))
+                        .map_or(max_value, |&a| u16::from(a));
+                    [
+                        u16::from(rgb[0]),
+                        u16::from(rgb[1]),
+                        u16::from(rgb[2]),
+                        alpha,
+                    ]
+                }
+                4 => [pixel[0], pixel[0], pixel[0], pixel[1]],
+                _ => [pixel[0], pixel[1], pixel[2], pixel[3]],
+            };
+            expanded.extend(&rgba[..color_type.channels()]);
+        }
+        Ok(PNG::new(
+            header.width,
+            header.height,
+            bit_depth,
+            color_type,
+            expanded,
+        ))
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<PNG, ImageError> {
+        let chunks = read_chunks(bytes)?;
+        let (first_type, first_data) = chunks.first().ok_or(ImageError::UnexpectedEof)?;
+        if first_type != b"IHDR" {
+            return Err(ImageError::InvalidData(
+                "IHDR is not the first chunk".to_string(),
+            ));
+        }
+        let header = Header::parse(first_data)?;
+
+        let mut palette: &[u8] = &[];
+        let mut transparency = None;
+        let mut compressed = Vec::new();
+        let mut ended = false;
+        for (chunk_type, data) in &chunks[1..] {
+            match chunk_type {
+                b"PLTE" => palette = data,
+                b"tRNS" => transparency = Some(*data),
+                b"IDAT" => compressed.extend_from_slice(data),
+                b"IEND" => ended = true,
+                // Ancillary chunks, marked by a lowercase first letter, can be safely ignored
+                _ if chunk_type[0].is_ascii_lowercase() => {}
+                _ => {
+                    return Err(ImageError::Unsupported(format!(
+                        "critical chunk {}",
+                        String::from_utf8_lossy(chunk_type)
+                    )))
+                }
+            }
+        }
+        if !ended {
+            return Err(ImageError::UnexpectedEof);
+        }
+        if header.color_type == 3 && palette.is_empty() {
+            return Err(ImageError::InvalidData("missing PLTE chunk".to_string()));
+        }
+
+        let data = zlib_decompress(&compressed)?;
+        let samples = read_samples(&data, &header)?;
+        expand_samples(&samples, &header, palette, transparency)
+    }
+}
+
+impl TryFrom<&[u8]> for PNG {
+    type Error = ImageError;
+
+    /// Decodes a PNG file of any standard color type and bit depth, interlaced or not.
+    ///
+    /// Grayscale and palette images are expanded to RGB, and to RGBA when they carry alpha or a `tRNS` chunk.
+    /// 16-bit images keep their 16-bit samples, lower bit depths are scaled to 8 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::deflate::Compression;
+    /// # use rusty_ray_tracer::asset_types::png::{BitDepth, ColorType, PNG};
+    /// let png = PNG::new(2, 1, BitDepth::Eight, ColorType::RGB, vec![255, 0, 0, 0, 0, 255]);
+    /// let decoded = PNG::try_from(png.to_bytes(Compression::FixedHuffman).as_slice()).unwrap();
+    /// assert_eq!(png.samples, decoded.samples);
+    /// ```
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        decoding::decode(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests_try_from_bytes {
+    use super::{decoding::ADAM7_PASSES, *};
+
+    /// Assembles a PNG file around raw, already filtered image data
+    fn build_png(header: [u8; 13], extra_chunks: &[(&[u8; 4], &[u8])], raw: &[u8]) -> Vec<u8> {
+        let mut bytes = PNG_SIGNATURE.to_vec();
+        write_chunk(&mut bytes, b"IHDR", &header);
+        for (chunk_type, data) in extra_chunks {
+            write_chunk(&mut bytes, chunk_type, data);
+        }
+        let compressed = zlib_compress(raw, Compression::FixedHuffman);
+        // Split the data across two IDAT chunks to check they are concatenated
+        let (first, second) = compressed.split_at(compressed.len() / 2);
+        write_chunk(&mut bytes, b"IDAT", first);
+        write_chunk(&mut bytes, b"IDAT", second);
+        write_chunk(&mut bytes, b"IEND", &[]);
+        bytes
+    }
+
+    fn ihdr(width: u32, height: u32, bit_depth: u8, color_type: u8, interlace: u8) -> [u8; 13] {
+        let mut header = [0_u8; 13];
+        header[0..4].copy_from_slice(&width.to_be_bytes());
+        header[4..8].copy_from_slice(&height.to_be_bytes());
+        header[8] = bit_depth;
+        header[9] = color_type;
+        header[12] = interlace;
+        header
+    }
+
+    #[test]
+    fn round_trip() {
+        let samples = (0..5 * 3 * 4)
+            .map(|i| (i * 977 % 65536) as u16)
+            .collect::<Vec<_>>();
+        for (bit_depth, color_type) in [
+            (BitDepth::Eight, ColorType::RGB),
+            (BitDepth::Eight, ColorType::RGBA),
+            (BitDepth::Sixteen, ColorType::RGB),
+            (BitDepth::Sixteen, ColorType::RGBA),
+        ] {
+            let samples = samples
+                .iter()
+                .take(5 * 3 * color_type.channels())
+                .map(|s| s & bit_depth.max_value())
+                .collect::<Vec<_>>();
+            let png = PNG::new(5, 3, bit_depth, color_type, samples);
+            for compression in [Compression::Stored, Compression::FixedHuffman] {
+                let decoded = PNG::try_from(png.to_bytes(compression).as_slice()).unwrap();
+                assert_eq!(5, decoded.width);
+                assert_eq!(3, decoded.height);
+                assert_eq!(bit_depth, decoded.bit_depth);
+                assert_eq!(color_type, decoded.color_type);
+                assert_eq!(png.samples, decoded.samples);
+            }
+        }
+    }
+
+    #[test]
+    fn grayscale_low_bit_depths() {
+        // 1 bit: 10110000, 2 bits: 11 10 01 00, 4 bits: 1111 0000 | 0101
+        let cases: [(u8, &[u8], Vec<u16>); 3] = [
+            (1, &[0b1011_0000], vec![255, 0, 255, 255]),
+            (2, &[0b1110_0100], vec![255, 170, 85, 0]),
+            (4, &[0b1111_0000, 0b0101_0000], vec![255, 0, 85, 0]),
+        ];
+        for (bit_depth, row, gray) in cases {
+            let mut raw = vec![0];
+            raw.extend(row);
+            let png = PNG::try_from(build_png(ihdr(4, 1, bit_depth, 0, 0), &[], &raw).as_slice())
+                .unwrap();
+            assert_eq!(ColorType::RGB, png.color_type);
+            assert_eq!(BitDepth::Eight, png.bit_depth);
+            let expected = gray.iter().flat_map(|&g| [g, g, g]).collect::<Vec<_>>();
+            assert_eq!(expected, png.samples);
+        }
+    }
+
+    #[test]
+    fn grayscale_sixteen_with_transparency() {
+        let raw = [0, 0x12, 0x34, 0xFF, 0xFF];
+        let bytes = build_png(ihdr(2, 1, 16, 0, 0), &[(b"tRNS", &[0x12, 0x34])], &raw);
+        let png = PNG::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(ColorType::RGBA, png.color_type);
+        assert_eq!(BitDepth::Sixteen, png.bit_depth);
+        assert_eq!(
+            vec![0x1234, 0x1234, 0x1234, 0, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF],
+            png.samples
+        );
+    }
+
+    #[test]
+    fn gray_alpha() {
+        let raw = [0, 10, 20, 30, 40];
+        let png = PNG::try_from(build_png(ihdr(2, 1, 8, 4, 0), &[], &raw).as_slice()).unwrap();
+        assert_eq!(ColorType::RGBA, png.color_type);
+        assert_eq!(vec![10, 10, 10, 20, 30, 30, 30, 40], png.samples);
+    }
+
+    #[test]
+    fn rgb_with_transparency() {
+        let raw = [0, 1, 2, 3, 4, 5, 6];
+        let bytes = build_png(ihdr(2, 1, 8, 2, 0), &[(b"tRNS", &[0, 4, 0, 5, 0, 6])], &raw);
+        let png = PNG::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(ColorType::RGBA, png.color_type);
+        assert_eq!(vec![1, 2, 3, 255, 4, 5, 6, 0], png.samples);
+    }
+
+    #[test]
+    fn palette() {
+        let plte = [255, 0, 0, 0, 255, 0, 0, 0, 255];
+        // 2 bits per index: 00 01 10 01
+        let raw = [0, 0b0001_1001];
+        let bytes = build_png(ihdr(4, 1, 2, 3, 0), &[(b"PLTE", &plte)], &raw);
+        let png = PNG::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(ColorType::RGB, png.color_type);
+        assert_eq!(
+            vec![255, 0, 0, 0, 255, 0, 0, 0, 255, 0, 255, 0],
+            png.samples
+        );
+
+        let bytes = build_png(
+            ihdr(4, 1, 2, 3, 0),
+            &[(b"PLTE", &plte), (b"tRNS", &[128])],
+            &raw,
+        );
+        let png = PNG::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(ColorType::RGBA, png.color_type);
+        assert_eq!(
+            vec![255, 0, 0, 128, 0, 255, 0, 255, 0, 0, 255, 255, 0, 255, 0, 255],
+            png.samples
+        );
+    }
+
+    #[test]
+    fn palette_index_out_of_range() {
+        let raw = [0, 5];
+        let bytes = build_png(ihdr(1, 1, 8, 3, 0), &[(b"PLTE", &[1, 2, 3])], &raw);
+        assert!(matches!(
+            PNG::try_from(bytes.as_slice()),
+            Err(ImageError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn filtered_scanlines() {
+        // 8-bit RGB, 2x2 with Sub on the first row and Up on the second
+        let raw = [1, 10, 20, 30, 5, 5, 5, 2, 1, 1, 1, 2, 2, 2];
+        let png = PNG::try_from(build_png(ihdr(2, 2, 8, 2, 0), &[], &raw).as_slice()).unwrap();
+        assert_eq!(
+            vec![10, 20, 30, 15, 25, 35, 11, 21, 31, 17, 27, 37],
+            png.samples
+        );
+    }
+
+    #[test]
+    fn interlaced_small() {
+        // 2x2 gray: only passes 1, 6 and 7 contain pixels
+        let raw = [0, 10, 0, 20, 0, 30, 40];
+        let png = PNG::try_from(build_png(ihdr(2, 2, 8, 0, 1), &[], &raw).as_slice()).unwrap();
+        let expected = [10, 20, 30, 40]
+            .iter()
+            .flat_map(|&g| [g, g, g])
+            .collect::<Vec<_>>();
+        assert_eq!(expected, png.samples);
+    }
+
+    #[test]
+    fn interlaced_matches_non_interlaced() {
+        let (width, height) = (11_usize, 9_usize);
+        let pixel = |x: usize, y: usize| [(x * 20) as u8, (y * 25) as u8, ((x + y) * 7) as u8];
+
+        let mut progressive = Vec::new();
+        for y in 0..height {
+            progressive.push(0);
+            (0..width).for_each(|x| progressive.extend(pixel(x, y)));
+        }
+        let mut interlaced = Vec::new();
+        for (x0, y0, dx, dy) in ADAM7_PASSES {
+            for y in (y0..height).step_by(dy) {
+                if x0 < width {
+                    interlaced.push(0);
+                    (x0..width)
+                        .step_by(dx)
+                        .for_each(|x| interlaced.extend(pixel(x, y)));
+                }
+            }
+        }
+
+        let header = |interlace| ihdr(width as u32, height as u32, 8, 2, interlace);
+        let expected = PNG::try_from(build_png(header(0), &[], &progressive).as_slice()).unwrap();
+        let png = PNG::try_from(build_png(header(1), &[], &interlaced).as_slice()).unwrap();
+        assert_eq!(expected.samples, png.samples);
+        assert_eq!(
+            pixel(10, 8).map(u16::from),
+            expected.samples[(8 * 11 + 10) * 3..][..3]
+        );
+    }
+
+    #[test]
+    fn invalid_signature() {
+        assert_eq!(
+            Err(ImageError::InvalidSignature),
+            PNG::try_from(&b"P3\n1 1\n255\n0 0 0\n"[..]).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = PNG::from(&Canvas::new(4, 4)).to_bytes(Compression::FixedHuffman);
+        for length in [8, 20, bytes.len() - 12, bytes.len() - 1] {
+            assert!(PNG::try_from(&bytes[..length]).is_err());
+        }
+    }
+
+    #[test]
+    fn corrupted_crc() {
+        let mut bytes = PNG::from(&Canvas::new(4, 4)).to_bytes(Compression::FixedHuffman);
+        bytes[20] ^= 0xFF;
+        assert!(matches!(
+            PNG::try_from(bytes.as_slice()),
+            Err(ImageError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_bit_depth() {
+        let bytes = build_png(ihdr(1, 1, 16, 3, 0), &[], &[0, 0, 0]);
+        assert!(matches!(
+            PNG::try_from(bytes.as_slice()),
+            Err(ImageError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn absurd_sizes() {
+        for interlace in [0, 1] {
+            let bytes = build_png(ihdr(u32::MAX, u32::MAX, 16, 6, interlace), &[], &[0]);
+            assert!(matches!(
+                PNG::try_from(bytes.as_slice()),
+                Err(ImageError::Unsupported(_))
+            ));
+            let bytes = build_png(ihdr(20_000, 20_000, 16, 6, interlace), &[], &[0, 1, 2]);
+            assert_eq!(
+                Err(ImageError::UnexpectedEof),
+                PNG::try_from(bytes.as_slice()).map(|_| ())
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_chunks() {
+        let raw = [0, 7];
+        let bytes = build_png(ihdr(1, 1, 8, 0, 0), &[(b"tEXt", b"Comment\0hi")], &raw);
+        assert_eq!(
+            vec![7, 7, 7],
+            PNG::try_from(bytes.as_slice()).unwrap().samples
+        );
+
+        let bytes = build_png(ihdr(1, 1, 8, 0, 0), &[(b"ABCD", b"")], &raw);
+        assert!(matches!(
+            PNG::try_from(bytes.as_slice()),
+            Err(ImageError::Unsupported(_))
+        ));
+    }
+}
+
+impl TryFrom<&PNG> for Canvas {
+    type Error = ImageError;
+
+    /// Creates a new canvas from a png, scaling samples to 0..1. Images without alpha are made opaque.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImageError::InvalidData`] if the number of pixels does not match the image size
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::png::{BitDepth, ColorType, PNG};
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let png = PNG::new(1, 1, BitDepth::Eight, ColorType::RGB, vec![255, 51, 0]);
+    /// let canvas = Canvas::try_from(&png).unwrap();
+    /// assert_eq!(Color::new(1.0, 0.2, 0.0), canvas.get_pixel_at(0, 0));
+    /// ```
+    fn try_from(png: &PNG) -> Result<Self, Self::Error> {
+        let max_value = f32::from(png.bit_depth.max_value());
+        let channels = png.color_type.channels();
+        Self::from_buffer(
+            png.width,
+            png.height,
+            png.samples
+                .chunks_exact(channels)
+                .map(|pixel| {
+                    let alpha = if png.color_type == ColorType::RGBA {
+                        f32::from(pixel[3]) / max_value
+                    } else {
+                        1.0
+                    };
+                    Color::new_with_alpha(
+                        f32::from(pixel[0]) / max_value,
+                        f32::from(pixel[1]) / max_value,
+                        f32::from(pixel[2]) / max_value,
+                        alpha,
+                    )
+                })
+                .collect(),
+        )
+        .ok_or_else(|| {
+            ImageError::InvalidData("pixel count does not match the image size".to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_into_canvas {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut canvas = Canvas::new(4, 3);
+        canvas.set_pixel_at(0, 0, Color::new(1.0, 0.0, 0.0));
+        canvas.set_pixel_at(1, 1, Color::new(0.0, 0.2, 0.0));
+        canvas.set_pixel_at(3, 2, Color::new(0.4, 0.6, 1.0));
+        for color_type in [ColorType::RGB, ColorType::RGBA] {
+            let png = PNG::from_canvas(&canvas, BitDepth::Sixteen, color_type);
+            let bytes = png.to_bytes(Compression::FixedHuffman);
+            let decoded = Canvas::try_from(&PNG::try_from(bytes.as_slice()).unwrap()).unwrap();
+            assert_eq!(4, decoded.width);
+            assert_eq!(3, decoded.height);
+            for (expected, actual) in canvas.raw_buffer.iter().zip(&decoded.raw_buffer) {
+                assert!((expected.r() - actual.r()).abs() < 1e-4);
+                assert!((expected.g() - actual.g()).abs() < 1e-4);
+                assert!((expected.b() - actual.b()).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn alpha() {
+        let png = PNG::new(
+            2,
+            1,
+            BitDepth::Eight,
+            ColorType::RGBA,
+            vec![0, 0, 0, 0, 255, 255, 255, 51],
+        );
+        let canvas = Canvas::try_from(&png).unwrap();
+        assert_eq!([0.0, 0.0, 0.0, 0.0], canvas.get_pixel_at(0, 0).tuple);
+        assert_eq!([1.0, 1.0, 1.0, 0.2], canvas.get_pixel_at(1, 0).tuple);
+
+        let png = PNG::new(1, 1, BitDepth::Eight, ColorType::RGB, vec![0, 0, 0]);
+        assert_eq!(1.0, Canvas::try_from(&png).unwrap().get_pixel_at(0, 0).a());
+    }
+
+    #[test]
+    fn wider_than_u16() {
+        let png = PNG::new(70_000, 0, BitDepth::Eight, ColorType::RGB, vec![]);
+        let canvas = Canvas::try_from(&png).unwrap();
+        assert_eq!((png.width, png.height), (canvas.width, canvas.height));
+    }
+}
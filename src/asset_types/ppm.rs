@@ -1,6 +1,11 @@
 use std::fmt::Display;
 
-use crate::{core3d::color_rgb::ColorRGB, graphics2d::canvas::Canvas};
+use crate::{
+    core3d::{color::Color, color_rgb::ColorRGB},
+    graphics2d::canvas::Canvas,
+};
+
+use super::image_error::ImageError;
 
 #[derive(Default, Debug)]
 pub struct PPM {
@@ -101,8 +106,8 @@ impl From<&Canvas> for PPM {
     /// ```
     fn from(canvas: &Canvas) -> Self {
         Self::new(
-            u32::from(canvas.width),
-            u32::from(canvas.height),
+            canvas.width,
+            canvas.height,
             255,
             canvas
                 .raw_buffer
@@ -507,10 +512,10 @@ P3
     #[test]
     fn display_with_long_line_data() {
         let mut canvas = Canvas::new(16, 16);
-        for d in 0..16 {
+        for d in 0..16_u8 {
             canvas.set_pixel_at(
-                d,
-                d,
+                u32::from(d),
+                u32::from(d),
                 Color::new(0.06666 * f32::from(d), 0.06666 * f32::from(15 - d), 0.0),
             );
         }
@@ -558,3 +563,309 @@ P3
         );
     }
 }
+
+mod decoding {
+    use super::{COMPONENTS_PER_COLOR, PPM};
+    use crate::asset_types::image_error::{pixel_count, ImageError};
+
+    /// Reads the next whitespace separated header token, skipping `#` comments up to the end of their line
+    fn read_token(bytes: &[u8]) -> Result<(&str, &[u8]), ImageError> {
+        let mut rest = bytes;
+        loop {
+            let start = rest
+                .iter()
+                .position(|b| !b.is_ascii_whitespace())
+                .ok_or(ImageError::UnexpectedEof)?;
+            rest = &rest[start..];
+            if rest[0] != b'#' {
+                break;
+            }
+            let end = rest
+                .iter()
+                .position(|&b| b == b'\n' || b == b'\r')
+                .ok_or(ImageError::UnexpectedEof)?;
+            rest = &rest[end..];
+        }
+        let length = rest
+            .iter()
+            .position(u8::is_ascii_whitespace)
+            .unwrap_or(rest.len());
+        let token = std::str::from_utf8(&rest[..length])
+            .map_err(|_| ImageError::InvalidData("header is not text".to_string()))?;
+        Ok((token, &rest[length..]))
+    }
+
+    fn parse(token: &str) -> Result<u32, ImageError> {
+        token
+            .parse()
+            .map_err(|_| ImageError::InvalidData(format!("value {token}")))
+    }
+
+    /// Reads `count` plain text samples
+    fn read_ascii_samples(bytes: &[u8], count: usize) -> Result<Vec<u32>, ImageError> {
+        let mut rest = bytes;
+        (0..count)
+            .map(|_| {
+                let (token, remaining) = read_token(rest)?;
+                rest = remaining;
+                parse(token)
+            })
+            .collect()
+    }
+
+    /// Reads `count` raw samples, one byte each or two big-endian bytes when the maximum exceeds 255
+    fn read_binary_samples(
+        bytes: &[u8],
+        count: usize,
+        max_color: u32,
+    ) -> Result<Vec<u32>, ImageError> {
+        // A single whitespace character separates the header from the samples
+        let width = if max_color > 255 { 2 } else { 1 };
+        let data = count
+            .checked_mul(width)
+            .and_then(|size| bytes.get(1..)?.get(..size))
+            .ok_or(ImageError::UnexpectedEof)?;
+        Ok(data
+            .chunks_exact(width)
+            .map(|s| s.iter().fold(0, |acc, &b| acc << 8 | u32::from(b)))
+            .collect())
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<PPM, ImageError> {
+        let (magic, rest) = read_token(bytes).map_err(|_| ImageError::InvalidSignature)?;
+        let (channels, binary) = match magic {
+            "P2" => (1, false),
+            "P3" => (3, false),
+            "P5" => (1, true),
+            "P6" => (3, true),
+            _ => return Err(ImageError::InvalidSignature),
+        };
+        let (width, rest) = read_token(rest)?;
+        let (height, rest) = read_token(rest)?;
+        let (max_color, rest) = read_token(rest)?;
+        let (width, height, max_color) = (parse(width)?, parse(height)?, parse(max_color)?);
+        if max_color == 0 || max_color > 65535 {
+            return Err(ImageError::InvalidData(format!("max color {max_color}")));
+        }
+
+        let count = pixel_count(u64::from(width), u64::from(height))?
+            .checked_mul(channels)
+            .ok_or_else(|| ImageError::Unsupported(format!("{width}x{height} image")))?;
+        let samples = if binary {
+            read_binary_samples(rest, count, max_color)?
+        } else {
+            read_ascii_samples(rest, count)?
+        };
+        if let Some(sample) = samples.iter().find(|&&s| s > max_color) {
+            return Err(ImageError::InvalidData(format!(
+                "sample {sample} exceeds {max_color}"
+            )));
+        }
+        let colors = if channels == 1 {
+            samples
+                .iter()
+                .flat_map(|&s| [s; COMPONENTS_PER_COLOR as usize])
+                .collect()
+        } else {
+            samples
+        };
+        Ok(PPM::new(width, height, max_color, colors))
+    }
+
+    #[cfg(test)]
+    mod tests_read_token {
+        use super::*;
+
+        #[test]
+        fn tokens() {
+            assert_eq!(Ok(("P3", &b"\n1"[..])), read_token(b"P3\n1"));
+            assert_eq!(Ok(("12", &b" x"[..])), read_token(b" \n 12 x"));
+            assert_eq!(Ok(("12", &b""[..])), read_token(b"12"));
+            assert_eq!(Err(ImageError::UnexpectedEof), read_token(b"   "));
+        }
+
+        #[test]
+        fn comments() {
+            assert_eq!(Ok(("4", &b" 3"[..])), read_token(b"# made by hand\n4 3"));
+            assert_eq!(Ok(("4", &b""[..])), read_token(b" #a\n#b 1 2\r\n4"));
+            assert_eq!(Err(ImageError::UnexpectedEof), read_token(b"# no end"));
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for PPM {
+    type Error = ImageError;
+
+    /// Decodes a plain (`P3`) or raw (`P6`) pixmap, as well as the `P2` and `P5` graymaps
+    ///
+    /// Graymaps are spread over all three components so the colors always hold RGB triples
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::ppm::PPM;
+    /// let ppm = PPM::try_from(&b"P6\n2 1\n255\n\xFF\x80\x00\x00\x00\x01"[..]).unwrap();
+    /// assert_eq!((2, 1, 255), (ppm.width, ppm.height, ppm.max_color));
+    /// assert_eq!(vec![255, 128, 0, 0, 0, 1], ppm.colors);
+    /// ```
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        decoding::decode(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests_try_from_bytes {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut canvas = Canvas::new(16, 16);
+        for d in 0..16_u8 {
+            let color = Color::new(0.06666 * f32::from(d), 1.0, 0.5);
+            canvas.set_pixel_at(u32::from(d), u32::from(15 - d), color);
+        }
+        let ppm = PPM::from(&canvas);
+        let decoded = PPM::try_from(ppm.to_string().as_bytes()).unwrap();
+        assert_eq!("P3", decoded.version);
+        assert_eq!(
+            (16, 16, 255),
+            (decoded.width, decoded.height, decoded.max_color)
+        );
+        assert_eq!(ppm.colors, decoded.colors);
+    }
+
+    #[test]
+    fn graymaps() {
+        let plain = PPM::try_from(&b"P2\n# gray\n2 1\n15\n0 15\n"[..]).unwrap();
+        assert_eq!(15, plain.max_color);
+        assert_eq!(vec![0, 0, 0, 15, 15, 15], plain.colors);
+        let raw = PPM::try_from(&b"P5 1 1 1000\n\x01\x02"[..]).unwrap();
+        assert_eq!(vec![258, 258, 258], raw.colors);
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            Err(ImageError::InvalidSignature),
+            PPM::try_from(&b"P4\n1 1\n"[..]).map(|_| ())
+        );
+        assert_eq!(
+            Err(ImageError::InvalidSignature),
+            PPM::try_from(&b""[..]).map(|_| ())
+        );
+        assert!(matches!(
+            PPM::try_from(&b"P3\n1 1\n0\n0 0 0"[..]),
+            Err(ImageError::InvalidData(_))
+        ));
+        assert!(matches!(
+            PPM::try_from(&b"P3\n1 1\n15\n0 16 0"[..]),
+            Err(ImageError::InvalidData(_))
+        ));
+        assert!(matches!(
+            PPM::try_from(&b"P3\n1 1\n255\n0 x 0"[..]),
+            Err(ImageError::InvalidData(_))
+        ));
+        assert_eq!(
+            Err(ImageError::UnexpectedEof),
+            PPM::try_from(&b"P3\n1 1\n255\n0 0"[..]).map(|_| ())
+        );
+        assert_eq!(
+            Err(ImageError::UnexpectedEof),
+            PPM::try_from(&b"P6\n1 1\n255\n\x00\x00"[..]).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn absurd_sizes() {
+        for magic in ["P3", "P6"] {
+            let bytes = format!("{magic} 4294967295 4294967295 65535\n0 0 0");
+            assert!(matches!(
+                PPM::try_from(bytes.as_bytes()),
+                Err(ImageError::Unsupported(_))
+            ));
+            let bytes = format!("{magic} 20000 20000 65535\n0 0 0");
+            assert_eq!(
+                Err(ImageError::UnexpectedEof),
+                PPM::try_from(bytes.as_bytes()).map(|_| ())
+            );
+        }
+    }
+}
+
+impl TryFrom<&PPM> for Canvas {
+    type Error = ImageError;
+
+    /// Creates a new opaque canvas from a ppm, scaling every component by the maximum color
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImageError::InvalidData`] if the number of pixels does not match the image size
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::ppm::PPM;
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let ppm = PPM::try_from(&b"P3\n1 1\n4\n4 2 0\n"[..]).unwrap();
+    /// let canvas = Canvas::try_from(&ppm).unwrap();
+    /// assert_eq!(Color::new(1.0, 0.5, 0.0), canvas.get_pixel_at(0, 0));
+    /// ```
+    fn try_from(ppm: &PPM) -> Result<Self, Self::Error> {
+        let max_color = ppm.max_color as f32;
+        Self::from_buffer(
+            ppm.width,
+            ppm.height,
+            ppm.colors
+                .chunks_exact(COMPONENTS_PER_COLOR as usize)
+                .map(|c| {
+                    Color::new(
+                        c[0] as f32 / max_color,
+                        c[1] as f32 / max_color,
+                        c[2] as f32 / max_color,
+                    )
+                })
+                .collect(),
+        )
+        .ok_or_else(|| {
+            ImageError::InvalidData("pixel count does not match the image size".to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_canvas {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut canvas = Canvas::new(4, 3);
+        canvas.set_pixel_at(0, 0, Color::new(1.0, 0.0, 0.0));
+        canvas.set_pixel_at(3, 2, Color::new(0.2, 0.4, 0.6));
+        let ppm = PPM::from(&canvas);
+        let decoded = Canvas::try_from(&ppm).unwrap();
+        assert_eq!(4, decoded.width);
+        assert_eq!(3, decoded.height);
+        assert_eq!(Color::new(1.0, 0.0, 0.0), decoded.get_pixel_at(0, 0));
+        assert_eq!(Color::new(0.2, 0.4, 0.6), decoded.get_pixel_at(3, 2));
+        assert_eq!(Color::new(0.0, 0.0, 0.0), decoded.get_pixel_at(1, 1));
+        assert_eq!(1.0, decoded.get_pixel_at(1, 1).a());
+    }
+
+    #[test]
+    fn wider_than_u16() {
+        let ppm = PPM::new(70000, 1, 255, vec![255; 3 * 70000]);
+        let canvas = Canvas::try_from(&ppm).unwrap();
+        assert_eq!(70000, canvas.width);
+        assert_eq!(Color::new(1.0, 1.0, 1.0), canvas.get_pixel_at(69999, 0));
+    }
+
+    #[test]
+    fn missing_pixels() {
+        let ppm = PPM::new(2, 2, 255, vec![255; 9]);
+        assert!(matches!(
+            Canvas::try_from(&ppm),
+            Err(ImageError::InvalidData(_))
+        ));
+    }
+}
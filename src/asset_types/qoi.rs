@@ -0,0 +1,519 @@
+use crate::{
+    core3d::{color::Color, color_rgb::ColorRGB},
+    graphics2d::canvas::Canvas,
+};
+
+use super::image_error::ImageError;
+
+/// Magic bytes every QOI file starts with
+pub const QOI_SIGNATURE: &[u8] = b"qoif";
+const QOI_HEADER_SIZE: usize = 14;
+/// Seven zero bytes and a one terminate the stream
+const QOI_END_MARKER: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+const QOI_OP_INDEX: u8 = 0x00;
+const QOI_OP_DIFF: u8 = 0x40;
+const QOI_OP_LUMA: u8 = 0x80;
+const QOI_OP_RUN: u8 = 0xC0;
+const QOI_OP_RGB: u8 = 0xFE;
+const QOI_OP_RGBA: u8 = 0xFF;
+const QOI_MASK: u8 = 0xC0;
+/// Longest run, as the run lengths 63 and 64 would collide with the RGB and RGBA tags
+const QOI_MAX_RUN: u8 = 62;
+
+/// Number of channels declared in the header
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Channels {
+    #[default]
+    RGB,
+    RGBA,
+}
+
+/// Color space declared in the header. It is purely informational and does not change the encoding.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// sRGB color channels with a linear alpha channel
+    #[default]
+    SRGB,
+    Linear,
+}
+
+/// Position of a pixel in the table of recently seen pixels
+const fn index_position(pixel: [u8; 4]) -> usize {
+    let [r, g, b, a] = pixel;
+    (r as usize * 3 + g as usize * 5 + b as usize * 7 + a as usize * 11) % 64
+}
+
+#[derive(Default, Debug)]
+pub struct QOI {
+    pub width: u32,
+    pub height: u32,
+    pub channels: Channels,
+    pub color_space: ColorSpace,
+
+    /// RGBA pixels row by row, starting at the top. Images without alpha keep it at 255.
+    pub pixels: Vec<[u8; 4]>,
+}
+
+impl QOI {
+    /// Creates a new qoi with the sRGB color space
+    #[must_use]
+    pub const fn new(width: u32, height: u32, channels: Channels, pixels: Vec<[u8; 4]>) -> Self {
+        Self {
+            width,
+            height,
+            channels,
+            color_space: ColorSpace::SRGB,
+            pixels,
+        }
+    }
+
+    /// Creates a new qoi from a canvas, clamping each channel to 0..1 before scaling it to 255
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::qoi::{Channels, QOI};
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(2, 1);
+    /// canvas.set_pixel_at(0, 0, Color::new(1.0, 0.5, -1.0));
+    /// let qoi = QOI::from_canvas(&canvas, Channels::RGB);
+    /// assert_eq!(vec![[255, 128, 0, 255], [0, 0, 0, 255]], qoi.pixels);
+    /// ```
+    #[must_use]
+    pub fn from_canvas(canvas: &Canvas, channels: Channels) -> Self {
+        Self::new(
+            canvas.width,
+            canvas.height,
+            channels,
+            canvas
+                .raw_buffer
+                .iter()
+                .map(|c| {
+                    let alpha = match channels {
+                        Channels::RGB => 1.0,
+                        Channels::RGBA => c.a(),
+                    };
+                    [c.r(), c.g(), c.b(), alpha]
+                        .map(|c| f32::round(num::clamp(c, 0.0, 1.0) * 255.0) as u8)
+                })
+                .collect(),
+        )
+    }
+
+    /// Encodes the image as a QOI file
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::qoi::{Channels, QOI};
+    /// let qoi = QOI::new(3, 1, Channels::RGB, vec![[255, 0, 0, 255]; 3]);
+    /// let bytes = qoi.to_bytes();
+    /// assert_eq!(b"qoif\0\0\0\x03\0\0\0\x01\x03\0", &bytes[..14]);
+    /// assert_eq!([0x5A, 0xC1], bytes[14..16]);
+    /// ```
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = QOI_SIGNATURE.to_vec();
+        bytes.extend(self.width.to_be_bytes());
+        bytes.extend(self.height.to_be_bytes());
+        bytes.push(match self.channels {
+            Channels::RGB => 3,
+            Channels::RGBA => 4,
+        });
+        bytes.push(match self.color_space {
+            ColorSpace::SRGB => 0,
+            ColorSpace::Linear => 1,
+        });
+
+        let mut index = [[0_u8; 4]; 64];
+        let mut previous = [0, 0, 0, 255];
+        let mut run = 0;
+        for (i, &pixel) in self.pixels.iter().enumerate() {
+            if pixel == previous {
+                run += 1;
+                if run == QOI_MAX_RUN || i + 1 == self.pixels.len() {
+                    bytes.push(QOI_OP_RUN | (run - 1));
+                    run = 0;
+                }
+                continue;
+            }
+            if run > 0 {
+                bytes.push(QOI_OP_RUN | (run - 1));
+                run = 0;
+            }
+
+            let position = index_position(pixel);
+            if index[position] == pixel {
+                bytes.push(QOI_OP_INDEX | position as u8);
+            } else if pixel[3] == previous[3] {
+                index[position] = pixel;
+                let [dr, dg, db] = [0, 1, 2].map(|c| pixel[c].wrapping_sub(previous[c]) as i8);
+                let (dr_dg, db_dg) = (dr.wrapping_sub(dg), db.wrapping_sub(dg));
+                if [dr, dg, db].iter().all(|d| (-2..=1).contains(d)) {
+                    bytes.push(
+                        QOI_OP_DIFF
+                            | ((dr + 2) as u8) << 4
+                            | ((dg + 2) as u8) << 2
+                            | (db + 2) as u8,
+                    );
+                } else if (-32..=31).contains(&dg)
+                    && (-8..=7).contains(&dr_dg)
+                    && (-8..=7).contains(&db_dg)
+                {
+                    bytes.push(QOI_OP_LUMA | (dg + 32) as u8);
+                    bytes.push(((dr_dg + 8) as u8) << 4 | (db_dg + 8) as u8);
+                } else {
+                    bytes.push(QOI_OP_RGB);
+                    bytes.extend(&pixel[..3]);
+                }
+            } else {
+                index[position] = pixel;
+                bytes.push(QOI_OP_RGBA);
+                bytes.extend(pixel);
+            }
+            previous = pixel;
+        }
+
+        bytes.extend(QOI_END_MARKER);
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests_qoi {
+    use super::*;
+
+    /// Encoded pixel data between the header and the end marker
+    fn ops(qoi: &QOI) -> Vec<u8> {
+        let bytes = qoi.to_bytes();
+        bytes[QOI_HEADER_SIZE..bytes.len() - QOI_END_MARKER.len()].to_vec()
+    }
+
+    #[test]
+    fn new() {
+        let qoi = QOI::new(2, 3, Channels::RGBA, vec![[0; 4]; 6]);
+        assert_eq!(2, qoi.width);
+        assert_eq!(3, qoi.height);
+        assert_eq!(Channels::RGBA, qoi.channels);
+        assert_eq!(ColorSpace::SRGB, qoi.color_space);
+    }
+
+    #[test]
+    fn header() {
+        let mut qoi = QOI::new(0x0102_0304, 5, Channels::RGBA, vec![]);
+        qoi.color_space = ColorSpace::Linear;
+        assert_eq!(
+            b"qoif\x01\x02\x03\x04\0\0\0\x05\x04\x01\0\0\0\0\0\0\0\x01".to_vec(),
+            qoi.to_bytes()
+        );
+    }
+
+    #[test]
+    fn index_positions() {
+        assert_eq!(0, index_position([0, 0, 0, 0]));
+        assert_eq!(53, index_position([0, 0, 0, 255]));
+        assert_eq!((255 * 3 + 255 * 11) % 64, index_position([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn runs() {
+        // The start pixel is opaque black, so the first pixels are already a run
+        assert_eq!(
+            vec![0xC0],
+            ops(&QOI::new(1, 1, Channels::RGB, vec![[0, 0, 0, 255]]))
+        );
+        assert_eq!(
+            vec![0xFD, 0xC0],
+            ops(&QOI::new(63, 1, Channels::RGB, vec![[0, 0, 0, 255]; 63]))
+        );
+    }
+
+    #[test]
+    fn diff_and_luma() {
+        let pixels = vec![[1, 255, 0, 255], [11, 5, 2, 255], [200, 100, 50, 255]];
+        assert_eq!(
+            vec![
+                0x40 | 3 << 4 | 1 << 2 | 2,
+                0x80 | 38,
+                12 << 4 | 4,
+                0xFE,
+                200,
+                100,
+                50
+            ],
+            ops(&QOI::new(3, 1, Channels::RGB, pixels))
+        );
+    }
+
+    #[test]
+    fn index_and_rgba() {
+        let red = [255, 0, 0, 128];
+        let green = [0, 200, 0, 128];
+        let pixels = vec![red, green, red];
+        let mut expected = vec![0xFF, 255, 0, 0, 128, 0xFE, 0, 200, 0];
+        expected.push(index_position(red) as u8);
+        assert_eq!(expected, ops(&QOI::new(3, 1, Channels::RGBA, pixels)));
+    }
+
+    #[test]
+    fn transparent_black_start() {
+        // A zeroed index entry already matches transparent black
+        assert_eq!(
+            vec![0x00],
+            ops(&QOI::new(1, 1, Channels::RGBA, vec![[0; 4]]))
+        );
+    }
+}
+
+mod decoding {
+    use super::{
+        index_position, Channels, ColorSpace, QOI, QOI_HEADER_SIZE, QOI_MASK, QOI_MAX_RUN,
+        QOI_OP_DIFF, QOI_OP_INDEX, QOI_OP_LUMA, QOI_OP_RGB, QOI_OP_RGBA, QOI_OP_RUN, QOI_SIGNATURE,
+    };
+    use crate::asset_types::image_error::{pixel_count, ImageError};
+
+    pub fn decode(bytes: &[u8]) -> Result<QOI, ImageError> {
+        if !bytes.starts_with(QOI_SIGNATURE) {
+            return Err(ImageError::InvalidSignature);
+        }
+        let header = bytes
+            .get(..QOI_HEADER_SIZE)
+            .ok_or(ImageError::UnexpectedEof)?;
+        let width = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+        let height = u32::from_be_bytes([header[8], header[9], header[10], header[11]]);
+        let channels = match header[12] {
+            3 => Channels::RGB,
+            4 => Channels::RGBA,
+            channels => return Err(ImageError::InvalidData(format!("{channels} channels"))),
+        };
+        let color_space = match header[13] {
+            0 => ColorSpace::SRGB,
+            1 => ColorSpace::Linear,
+            color_space => {
+                return Err(ImageError::InvalidData(format!(
+                    "color space {color_space}"
+                )))
+            }
+        };
+        let count = pixel_count(u64::from(width), u64::from(height))?;
+
+        let data = &bytes[QOI_HEADER_SIZE..];
+        // No byte decodes to more pixels than the longest run, so the header cannot reserve more than the data holds
+        let mut pixels = Vec::with_capacity(count.min(data.len() * usize::from(QOI_MAX_RUN)));
+        let mut data = data.iter().copied();
+        let mut next = || data.next().ok_or(ImageError::UnexpectedEof);
+        let mut index = [[0_u8; 4]; 64];
+        let mut pixel = [0, 0, 0, 255];
+        while pixels.len() < count {
+            let op = next()?;
+            match (op, op & QOI_MASK) {
+                (QOI_OP_RGB, _) => pixel = [next()?, next()?, next()?, pixel[3]],
+                (QOI_OP_RGBA, _) => pixel = [next()?, next()?, next()?, next()?],
+                (_, QOI_OP_INDEX) => pixel = index[usize::from(op)],
+                (_, QOI_OP_DIFF) => {
+                    for (c, shift) in [4, 2, 0].iter().enumerate() {
+                        pixel[c] = pixel[c].wrapping_add((op >> shift) & 0x03).wrapping_sub(2);
+                    }
+                }
+                (_, QOI_OP_LUMA) => {
+                    let dg = (op & 0x3F).wrapping_sub(32);
+                    let rb = next()?;
+                    pixel[0] = pixel[0]
+                        .wrapping_add(dg)
+                        .wrapping_add(rb >> 4)
+                        .wrapping_sub(8);
+                    pixel[1] = pixel[1].wrapping_add(dg);
+                    pixel[2] = pixel[2]
+                        .wrapping_add(dg)
+                        .wrapping_add(rb & 0x0F)
+                        .wrapping_sub(8);
+                }
+                _ => {
+                    let run = usize::from(op & !QOI_OP_RUN) + 1;
+                    let run = run.min(count - pixels.len());
+                    pixels.extend(std::iter::repeat_n(pixel, run));
+                    continue;
+                }
+            }
+            index[index_position(pixel)] = pixel;
+            pixels.push(pixel);
+        }
+
+        let mut qoi = QOI::new(width, height, channels, pixels);
+        qoi.color_space = color_space;
+        Ok(qoi)
+    }
+}
+
+impl TryFrom<&[u8]> for QOI {
+    type Error = ImageError;
+
+    /// Decodes a QOI file
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::qoi::{Channels, QOI};
+    /// let qoi = QOI::new(2, 1, Channels::RGBA, vec![[1, 2, 3, 4], [5, 6, 7, 8]]);
+    /// let decoded = QOI::try_from(qoi.to_bytes().as_slice()).unwrap();
+    /// assert_eq!(qoi.pixels, decoded.pixels);
+    /// ```
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        decoding::decode(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests_try_from_bytes {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        // A mix of runs, small and large steps, alpha changes and repeated colors
+        let pixels = (0..2000_u32)
+            .map(|i| match i % 7 {
+                0 | 1 => [(i / 7) as u8, 10, 20, 255],
+                2 => [((i / 7) as u8).wrapping_add(1), 9, 21, 255],
+                3 => [(i * 37) as u8, (i * 91) as u8, (i * 13) as u8, 255],
+                4 => [(i / 5) as u8, (i / 5 + 20) as u8, (i / 5 + 2) as u8, 255],
+                5 => [1, 2, 3, (i / 3) as u8],
+                _ => [(i * 37) as u8, (i * 91) as u8, (i * 13) as u8, 255],
+            })
+            .chain(std::iter::repeat_n([9, 9, 9, 9], 200))
+            .collect::<Vec<_>>();
+        let mut qoi = QOI::new(50, 44, Channels::RGBA, pixels);
+        qoi.color_space = ColorSpace::Linear;
+        let bytes = qoi.to_bytes();
+        assert!(bytes.len() < 4 * qoi.pixels.len());
+        let decoded = QOI::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(50, decoded.width);
+        assert_eq!(44, decoded.height);
+        assert_eq!(Channels::RGBA, decoded.channels);
+        assert_eq!(ColorSpace::Linear, decoded.color_space);
+        assert_eq!(qoi.pixels, decoded.pixels);
+    }
+
+    #[test]
+    fn wrapping_differences() {
+        let pixels = vec![[255, 0, 1, 255], [0, 255, 0, 255], [30, 1, 250, 255]];
+        let qoi = QOI::new(3, 1, Channels::RGB, pixels);
+        assert_eq!(
+            qoi.pixels,
+            QOI::try_from(qoi.to_bytes().as_slice()).unwrap().pixels
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            Err(ImageError::InvalidSignature),
+            QOI::try_from(&b"qoix"[..]).map(|_| ())
+        );
+        assert_eq!(
+            Err(ImageError::UnexpectedEof),
+            QOI::try_from(&b"qoif\0\0"[..]).map(|_| ())
+        );
+        assert!(matches!(
+            QOI::try_from(&b"qoif\0\0\0\x01\0\0\0\x01\x05\0"[..]),
+            Err(ImageError::InvalidData(_))
+        ));
+        assert!(matches!(
+            QOI::try_from(&b"qoif\0\0\0\x01\0\0\0\x01\x03\x02"[..]),
+            Err(ImageError::InvalidData(_))
+        ));
+        assert!(matches!(
+            QOI::try_from(&b"qoif\xFF\0\0\0\xFF\0\0\0\x03\0"[..]),
+            Err(ImageError::Unsupported(_))
+        ));
+        assert_eq!(
+            Err(ImageError::UnexpectedEof),
+            QOI::try_from(&b"qoif\0\0\0\x02\0\0\0\x01\x03\0\xFE\x01"[..]).map(|_| ())
+        );
+        // The largest header accepted, with data for a single pixel
+        assert_eq!(
+            Err(ImageError::UnexpectedEof),
+            QOI::try_from(&b"qoif\0\0\x4E\x20\0\0\x4E\x20\x03\0\xC0"[..]).map(|_| ())
+        );
+    }
+}
+
+impl From<&Canvas> for QOI {
+    /// Creates a new RGB qoi from a canvas, dropping the alpha channel
+    fn from(canvas: &Canvas) -> Self {
+        Self::from_canvas(canvas, Channels::RGB)
+    }
+}
+
+impl TryFrom<&QOI> for Canvas {
+    type Error = ImageError;
+
+    /// Creates a new canvas from a qoi, scaling samples to 0..1. Images without alpha are made opaque.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImageError::InvalidData`] if the number of pixels does not match the image size
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::qoi::{Channels, QOI};
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let qoi = QOI::new(1, 1, Channels::RGB, vec![[255, 51, 0, 255]]);
+    /// let canvas = Canvas::try_from(&qoi).unwrap();
+    /// assert_eq!(Color::new(1.0, 0.2, 0.0), canvas.get_pixel_at(0, 0));
+    /// ```
+    fn try_from(qoi: &QOI) -> Result<Self, Self::Error> {
+        Self::from_buffer(
+            qoi.width,
+            qoi.height,
+            qoi.pixels
+                .iter()
+                .map(|pixel| {
+                    let alpha = if qoi.channels == Channels::RGBA {
+                        f32::from(pixel[3]) / 255.0
+                    } else {
+                        1.0
+                    };
+                    Color::new_with_alpha(
+                        f32::from(pixel[0]) / 255.0,
+                        f32::from(pixel[1]) / 255.0,
+                        f32::from(pixel[2]) / 255.0,
+                        alpha,
+                    )
+                })
+                .collect(),
+        )
+        .ok_or_else(|| {
+            ImageError::InvalidData("pixel count does not match the image size".to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_canvas {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut canvas = Canvas::new(3, 2);
+        let color = Color::new_with_alpha(0.4, 0.6, 0.8, 0.2);
+        canvas.set_pixel_at(0, 0, Color::new(1.0, 0.2, 0.0));
+        canvas.set_pixel_at(2, 1, color);
+        let bytes = QOI::from_canvas(&canvas, Channels::RGBA).to_bytes();
+        let decoded = Canvas::try_from(&QOI::try_from(bytes.as_slice()).unwrap()).unwrap();
+        assert_eq!(3, decoded.width);
+        assert_eq!(2, decoded.height);
+        assert_eq!(Color::new(1.0, 0.2, 0.0), decoded.get_pixel_at(0, 0));
+        assert_eq!(0.2, decoded.get_pixel_at(2, 1).a());
+        assert_eq!(0.0, decoded.get_pixel_at(1, 1).a());
+    }
+
+    #[test]
+    fn without_alpha() {
+        let canvas = Canvas::new(2, 2);
+        let decoded = Canvas::try_from(&QOI::from(&canvas)).unwrap();
+        assert_eq!(Color::new(0.0, 0.0, 0.0), decoded.get_pixel_at(1, 1));
+    }
+}
@@ -0,0 +1,709 @@
+use crate::{
+    core3d::{color::Color, color_rgb::ColorRGB},
+    graphics2d::canvas::Canvas,
+};
+
+use super::{bmp::RowOrder, image_error::ImageError};
+
+const TGA_HEADER_SIZE: usize = 18;
+/// Footer signature marking a TGA 2.0 file, preceded by the extension and developer area offsets
+const TGA_FOOTER_SIGNATURE: &[u8] = b"TRUEVISION-XFILE.\0";
+const COLOR_MAPPED: u8 = 1;
+const TRUE_COLOR: u8 = 2;
+const GRAYSCALE: u8 = 3;
+/// Added to the image type when the pixels are run length encoded
+const RLE_FLAG: u8 = 8;
+/// Descriptor bits giving the origin of the pixels, lower left by default
+const RIGHT_ORIGIN: u8 = 0x10;
+const TOP_ORIGIN: u8 = 0x20;
+const RLE_MAX_COUNT: usize = 128;
+
+/// Encoding of the pixel data
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    #[default]
+    None,
+    /// Run length encoding of whole pixels, never crossing a row
+    RLE,
+}
+
+#[derive(Default, Debug)]
+pub struct TGA {
+    pub width: u16,
+    pub height: u16,
+    /// Whether the alpha channel is written, making the file a 32 bit image
+    pub alpha: bool,
+    pub row_order: RowOrder,
+
+    /// RGBA pixels row by row, starting at the top
+    pub pixels: Vec<[u8; 4]>,
+}
+
+impl TGA {
+    /// Creates a new tga with the origin at the lower left
+    #[must_use]
+    pub const fn new(width: u16, height: u16, alpha: bool, pixels: Vec<[u8; 4]>) -> Self {
+        Self {
+            width,
+            height,
+            alpha,
+            row_order: RowOrder::BottomUp,
+            pixels,
+        }
+    }
+
+    /// Creates a new tga from a canvas, clamping each channel to 0..1 before scaling it to 255
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::tga::TGA;
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(2, 1);
+    /// canvas.set_pixel_at(0, 0, Color::new(1.0, 0.5, -1.0));
+    /// let tga = TGA::from_canvas(&canvas, true);
+    /// assert_eq!(vec![[255, 128, 0, 255], [0, 0, 0, 0]], tga.pixels);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the canvas is wider or higher than the 65535 pixels a tga can hold
+    #[must_use]
+    pub fn from_canvas(canvas: &Canvas, alpha: bool) -> Self {
+        Self::new(
+            u16::try_from(canvas.width).expect("Canvas is too large for a TGA image!"),
+            u16::try_from(canvas.height).expect("Canvas is too large for a TGA image!"),
+            alpha,
+            canvas
+                .raw_buffer
+                .iter()
+                .map(|c| {
+                    [c.r(), c.g(), c.b(), c.a()]
+                        .map(|c| f32::round(num::clamp(c, 0.0, 1.0) * 255.0) as u8)
+                })
+                .collect(),
+        )
+    }
+
+    /// Pixels of each row in the order they are stored in, as BGR or BGRA bytes
+    fn get_rows(&self) -> Vec<Vec<&[u8]>> {
+        if self.width == 0 {
+            return Vec::new();
+        }
+        let channels = if self.alpha { 4 } else { 3 };
+        let rows = self.pixels.chunks(usize::from(self.width));
+        let rows = match self.row_order {
+            RowOrder::BottomUp => rows.rev().collect::<Vec<_>>(),
+            RowOrder::TopDown => rows.collect(),
+        };
+        rows.iter()
+            .map(|row| row.iter().map(|p| &p[..channels]).collect())
+            .collect()
+    }
+
+    /// Encodes the image as a TGA 2.0 file
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::tga::{Compression, TGA};
+    /// let tga = TGA::new(1, 1, false, vec![[255, 128, 0, 255]]);
+    /// let bytes = tga.to_bytes(Compression::None);
+    /// assert_eq!(18 + 3 + 26, bytes.len());
+    /// assert_eq!([0, 128, 255], bytes[18..21]);
+    /// ```
+    #[must_use]
+    pub fn to_bytes(&self, compression: Compression) -> Vec<u8> {
+        let image_type = match compression {
+            Compression::None => TRUE_COLOR,
+            Compression::RLE => TRUE_COLOR + RLE_FLAG,
+        };
+        let (depth, alpha_bits) = if self.alpha { (32, 8) } else { (24, 0) };
+        let origin = match self.row_order {
+            RowOrder::BottomUp => 0,
+            RowOrder::TopDown => TOP_ORIGIN,
+        };
+
+        // No image id and no color map, followed by an origin of 0, 0
+        let mut bytes = vec![0, 0, image_type, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        bytes.extend(self.width.to_le_bytes());
+        bytes.extend(self.height.to_le_bytes());
+        bytes.extend([depth, alpha_bits | origin]);
+
+        for row in self.get_rows() {
+            // Pixels are stored as BGR or BGRA
+            let row = row
+                .iter()
+                .map(|p| {
+                    let mut pixel = p.to_vec();
+                    pixel.swap(0, 2);
+                    pixel
+                })
+                .collect::<Vec<_>>();
+            match compression {
+                Compression::None => row.iter().for_each(|p| bytes.extend(p)),
+                Compression::RLE => rle::encode_row(&row, &mut bytes),
+            }
+        }
+
+        bytes.extend(0_u32.to_le_bytes());
+        bytes.extend(0_u32.to_le_bytes());
+        bytes.extend(TGA_FOOTER_SIGNATURE);
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests_tga {
+    use super::*;
+
+    #[test]
+    fn new() {
+        let tga = TGA::new(2, 3, true, vec![[0; 4]; 6]);
+        assert_eq!(2, tga.width);
+        assert_eq!(3, tga.height);
+        assert!(tga.alpha);
+        assert_eq!(RowOrder::BottomUp, tga.row_order);
+    }
+
+    #[test]
+    fn to_bytes_bottom_up() {
+        let tga = TGA::new(1, 2, false, vec![[1, 2, 3, 255], [4, 5, 6, 255]]);
+        let bytes = tga.to_bytes(Compression::None);
+        assert_eq!(
+            [0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 2, 0, 24, 0],
+            bytes[..18]
+        );
+        assert_eq!([6, 5, 4, 3, 2, 1], bytes[18..24]);
+        assert!(bytes.ends_with(b"\0\0\0\0\0\0\0\0TRUEVISION-XFILE.\0"));
+    }
+
+    #[test]
+    fn to_bytes_top_down_rle_with_alpha() {
+        let mut tga = TGA::new(3, 1, true, vec![[1, 2, 3, 4], [1, 2, 3, 4], [5, 6, 7, 8]]);
+        tga.row_order = RowOrder::TopDown;
+        let bytes = tga.to_bytes(Compression::RLE);
+        assert_eq!(
+            [10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 0, 1, 0, 32, 0x28],
+            bytes[2..18]
+        );
+        assert_eq!([0x81, 3, 2, 1, 4, 0x00, 7, 6, 5, 8], bytes[18..28]);
+    }
+}
+
+mod rle {
+    use super::RLE_MAX_COUNT;
+
+    /// Appends a row of pixels as run and raw packets
+    pub fn encode_row(row: &[Vec<u8>], bytes: &mut Vec<u8>) {
+        let mut start = 0;
+        while start < row.len() {
+            let run = row[start..]
+                .iter()
+                .take(RLE_MAX_COUNT)
+                .take_while(|&p| *p == row[start])
+                .count();
+            if run >= 2 {
+                bytes.push(0x80 | (run - 1) as u8);
+                bytes.extend(&row[start]);
+                start += run;
+                continue;
+            }
+            let mut end = start + 1;
+            while end < row.len()
+                && end - start < RLE_MAX_COUNT
+                && !(end + 1 < row.len() && row[end] == row[end + 1])
+            {
+                end += 1;
+            }
+            bytes.push((end - start - 1) as u8);
+            row[start..end].iter().for_each(|p| bytes.extend(p));
+            start = end;
+        }
+    }
+
+    /// Expands run and raw packets into `count` pixels of `size` bytes, or `None` if the data ends early
+    ///
+    /// Packets are allowed to cross rows, as older writers produce them.
+    pub fn decode(data: &[u8], count: usize, size: usize) -> Option<Vec<u8>> {
+        // A packet holds at most as many pixels as it has bytes times the longest run, whatever the header says
+        let mut pixels = Vec::with_capacity((count * size).min(data.len() * RLE_MAX_COUNT));
+        let mut i = 0;
+        while pixels.len() < count * size {
+            let header = *data.get(i)?;
+            let length = usize::from(header & 0x7F) + 1;
+            if header & 0x80 == 0 {
+                pixels.extend(data.get(i + 1..i + 1 + length * size)?);
+                i += 1 + length * size;
+            } else {
+                let pixel = data.get(i + 1..i + 1 + size)?;
+                (0..length).for_each(|_| pixels.extend(pixel));
+                i += 1 + size;
+            }
+        }
+        pixels.truncate(count * size);
+        Some(pixels)
+    }
+
+    #[cfg(test)]
+    mod tests_rle {
+        use super::*;
+
+        #[test]
+        fn encode() {
+            let mut bytes = Vec::new();
+            let row = [vec![1], vec![1], vec![1], vec![2], vec![3], vec![3]];
+            encode_row(&row, &mut bytes);
+            assert_eq!(vec![0x82, 1, 0x00, 2, 0x81, 3], bytes);
+
+            let mut bytes = Vec::new();
+            encode_row(&vec![vec![9, 9]; 300], &mut bytes);
+            assert_eq!(vec![0xFF, 9, 9, 0xFF, 9, 9, 0xAB, 9, 9], bytes);
+
+            let mut bytes = Vec::new();
+            let row = (0..=255).map(|i| vec![i]).collect::<Vec<_>>();
+            encode_row(&row, &mut bytes);
+            assert_eq!(2 + 256, bytes.len());
+            assert_eq!(0x7F, bytes[0]);
+            assert_eq!(0x7F, bytes[129]);
+        }
+
+        #[test]
+        fn decode_packets() {
+            assert_eq!(
+                Some(vec![1, 2, 1, 2, 1, 2, 3, 4, 5, 6]),
+                decode(&[0x82, 1, 2, 0x01, 3, 4, 5, 6], 5, 2)
+            );
+            assert_eq!(Some(vec![7, 7]), decode(&[0x83, 7], 2, 1));
+            assert_eq!(None, decode(&[0x01, 7], 2, 1));
+        }
+    }
+}
+
+mod decoding {
+    use super::{
+        Compression, COLOR_MAPPED, GRAYSCALE, RIGHT_ORIGIN, RLE_FLAG, TGA, TGA_HEADER_SIZE,
+        TOP_ORIGIN, TRUE_COLOR,
+    };
+    use crate::asset_types::{
+        bmp::RowOrder,
+        image_error::{pixel_count, ImageError},
+    };
+
+    /// Converts a stored pixel or color map entry to RGBA
+    fn read_pixel(bytes: &[u8], alpha: bool) -> [u8; 4] {
+        match bytes.len() {
+            1 => [bytes[0], bytes[0], bytes[0], 255],
+            2 => {
+                let value = u16::from_le_bytes([bytes[0], bytes[1]]);
+                let scale = |c: u16| ((c & 0x1F) * 255 + 15) / 31;
+                let a = if alpha && value & 0x8000 == 0 { 0 } else { 255 };
+                [
+                    scale(value >> 10) as u8,
+                    scale(value >> 5) as u8,
+                    scale(value) as u8,
+                    a,
+                ]
+            }
+            3 => [bytes[2], bytes[1], bytes[0], 255],
+            _ => [
+                bytes[2],
+                bytes[1],
+                bytes[0],
+                if alpha { bytes[3] } else { 255 },
+            ],
+        }
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<TGA, ImageError> {
+        let header = bytes
+            .get(..TGA_HEADER_SIZE)
+            .ok_or(ImageError::UnexpectedEof)?;
+        let (id_length, map_type, image_type) = (header[0], header[1], header[2]);
+        let map_first = usize::from(u16::from_le_bytes([header[3], header[4]]));
+        let map_length = usize::from(u16::from_le_bytes([header[5], header[6]]));
+        let map_entry_bits = header[7];
+        let width = u16::from_le_bytes([header[12], header[13]]);
+        let height = u16::from_le_bytes([header[14], header[15]]);
+        let (depth, descriptor) = (header[16], header[17]);
+        let alpha = descriptor & 0x0F != 0;
+
+        // There is no magic number, so the header fields are the only way to reject other files
+        let compression = if image_type & RLE_FLAG == 0 {
+            Compression::None
+        } else {
+            Compression::RLE
+        };
+        match (image_type & !RLE_FLAG, depth, map_type) {
+            (COLOR_MAPPED, 8 | 16, 1)
+            | (TRUE_COLOR, 15 | 16 | 24 | 32, 0 | 1)
+            | (GRAYSCALE, 8, 0 | 1) => {}
+            (COLOR_MAPPED | TRUE_COLOR | GRAYSCALE, _, 0 | 1) => {
+                return Err(ImageError::Unsupported(format!(
+                    "image type {image_type} with {depth} bits per pixel"
+                )))
+            }
+            _ => return Err(ImageError::InvalidSignature),
+        }
+
+        let mut at = TGA_HEADER_SIZE + usize::from(id_length);
+        let mut palette = Vec::new();
+        if map_type == 1 {
+            let entry_size = usize::from(map_entry_bits).div_ceil(8);
+            if !(2..=4).contains(&entry_size) {
+                return Err(ImageError::Unsupported(format!(
+                    "{map_entry_bits} bit color map"
+                )));
+            }
+            let map = bytes
+                .get(at..at + map_length * entry_size)
+                .ok_or(ImageError::UnexpectedEof)?;
+            palette = map
+                .chunks_exact(entry_size)
+                .map(|e| read_pixel(e, alpha))
+                .collect();
+            at += map_length * entry_size;
+        }
+
+        let size = usize::from(depth).div_ceil(8);
+        let count = pixel_count(u64::from(width), u64::from(height))?;
+        let data = bytes.get(at..).ok_or(ImageError::UnexpectedEof)?;
+        let data = match compression {
+            Compression::None => data.get(..count * size).map(<[u8]>::to_vec),
+            Compression::RLE => super::rle::decode(data, count, size),
+        }
+        .ok_or(ImageError::UnexpectedEof)?;
+
+        let mut pixels = data
+            .chunks_exact(size)
+            .map(|p| {
+                if image_type & !RLE_FLAG != COLOR_MAPPED {
+                    return Ok(read_pixel(p, alpha));
+                }
+                let index = if size == 1 {
+                    usize::from(p[0])
+                } else {
+                    usize::from(u16::from_le_bytes([p[0], p[1]]))
+                };
+                index
+                    .checked_sub(map_first)
+                    .and_then(|i| palette.get(i).copied())
+                    .ok_or_else(|| ImageError::InvalidData(format!("color map index {index}")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if width > 0 && descriptor & RIGHT_ORIGIN != 0 {
+            pixels
+                .chunks_mut(usize::from(width))
+                .for_each(<[[u8; 4]]>::reverse);
+        }
+        let row_order = if descriptor & TOP_ORIGIN == 0 {
+            if width > 0 {
+                pixels = pixels
+                    .chunks(usize::from(width))
+                    .rev()
+                    .flatten()
+                    .copied()
+                    .collect();
+            }
+            RowOrder::BottomUp
+        } else {
+            RowOrder::TopDown
+        };
+
+        let mut tga = TGA::new(width, height, alpha, pixels);
+        tga.row_order = row_order;
+        Ok(tga)
+    }
+
+    #[cfg(test)]
+    mod tests_read_pixel {
+        use super::*;
+
+        #[test]
+        fn depths() {
+            assert_eq!([7, 7, 7, 255], read_pixel(&[7], false));
+            assert_eq!([255, 0, 255, 255], read_pixel(&[0x1F, 0x7C], false));
+            assert_eq!([0, 132, 0, 0], read_pixel(&[0x00, 0x02], true));
+            assert_eq!([3, 2, 1, 255], read_pixel(&[1, 2, 3], true));
+            assert_eq!([3, 2, 1, 255], read_pixel(&[1, 2, 3, 4], false));
+            assert_eq!([3, 2, 1, 4], read_pixel(&[1, 2, 3, 4], true));
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for TGA {
+    type Error = ImageError;
+
+    /// Decodes an uncompressed or run length encoded true color, grayscale or color mapped image
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::tga::{Compression, TGA};
+    /// let tga = TGA::new(2, 1, false, vec![[1, 2, 3, 255], [4, 5, 6, 255]]);
+    /// let decoded = TGA::try_from(tga.to_bytes(Compression::RLE).as_slice()).unwrap();
+    /// assert_eq!(tga.pixels, decoded.pixels);
+    /// ```
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        decoding::decode(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests_try_from_bytes {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let pixels = (0..15_u8)
+            .map(|i| [i * 17, 255 - i * 17, i / 4, i * 8])
+            .collect::<Vec<_>>();
+        for alpha in [false, true] {
+            for row_order in [RowOrder::BottomUp, RowOrder::TopDown] {
+                for compression in [Compression::None, Compression::RLE] {
+                    let mut tga = TGA::new(5, 3, alpha, pixels.clone());
+                    tga.row_order = row_order;
+                    let decoded = TGA::try_from(tga.to_bytes(compression).as_slice()).unwrap();
+                    assert_eq!(5, decoded.width);
+                    assert_eq!(3, decoded.height);
+                    assert_eq!(alpha, decoded.alpha);
+                    assert_eq!(row_order, decoded.row_order);
+                    for (expected, actual) in pixels.iter().zip(&decoded.pixels) {
+                        assert_eq!(expected[..3], actual[..3]);
+                        assert_eq!(if alpha { expected[3] } else { 255 }, actual[3]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn grayscale_rle_right_origin() {
+        let mut bytes = vec![
+            0,
+            0,
+            GRAYSCALE + RLE_FLAG,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            2,
+            0,
+            2,
+            0,
+            8,
+            RIGHT_ORIGIN,
+        ];
+        // Packets crossing from the bottom row into the top row
+        bytes.extend([0x00, 10, 0x82, 20]);
+        let tga = TGA::try_from(bytes.as_slice()).unwrap();
+        let gray = |v| [v, v, v, 255];
+        assert_eq!(vec![gray(20), gray(20), gray(20), gray(10)], tga.pixels);
+    }
+
+    #[test]
+    fn color_mapped() {
+        let mut bytes = vec![
+            2,
+            1,
+            COLOR_MAPPED,
+            1,
+            0,
+            2,
+            0,
+            24,
+            0,
+            0,
+            0,
+            0,
+            3,
+            0,
+            1,
+            0,
+            8,
+            TOP_ORIGIN,
+        ];
+        bytes.extend(b"id");
+        bytes.extend([0, 0, 255, 255, 0, 0]);
+        bytes.extend([1, 2, 2]);
+        let tga = TGA::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(
+            vec![[255, 0, 0, 255], [0, 0, 255, 255], [0, 0, 255, 255]],
+            tga.pixels
+        );
+
+        bytes[TGA_HEADER_SIZE + 2 + 6] = 0;
+        assert!(matches!(
+            TGA::try_from(bytes.as_slice()),
+            Err(ImageError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            Err(ImageError::UnexpectedEof),
+            TGA::try_from(&[0; 17][..]).map(|_| ())
+        );
+        let mut bytes = vec![
+            0, 0, TRUE_COLOR, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 2, 0, 24, 0,
+        ];
+        assert_eq!(
+            Err(ImageError::UnexpectedEof),
+            TGA::try_from(bytes.as_slice()).map(|_| ())
+        );
+        bytes[16] = 12;
+        assert!(matches!(
+            TGA::try_from(bytes.as_slice()),
+            Err(ImageError::Unsupported(_))
+        ));
+        bytes[2] = 42;
+        assert_eq!(
+            Err(ImageError::InvalidSignature),
+            TGA::try_from(bytes.as_slice()).map(|_| ())
+        );
+        assert_eq!(
+            Err(ImageError::InvalidSignature),
+            TGA::try_from(&b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0"[..]).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn absurd_sizes() {
+        // Huge headers are rejected before any room is made for their pixels
+        let mut bytes = vec![
+            0,
+            0,
+            TRUE_COLOR | RLE_FLAG,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF,
+            32,
+            0,
+        ];
+        bytes.extend([0xFF, 1, 2, 3, 4]);
+        assert!(matches!(
+            TGA::try_from(bytes.as_slice()),
+            Err(ImageError::Unsupported(_))
+        ));
+        bytes[12..16].copy_from_slice(&[0x20, 0x4E, 0x20, 0x4E]);
+        assert_eq!(
+            Err(ImageError::UnexpectedEof),
+            TGA::try_from(bytes.as_slice()).map(|_| ())
+        );
+        bytes[2] = TRUE_COLOR;
+        assert_eq!(
+            Err(ImageError::UnexpectedEof),
+            TGA::try_from(bytes.as_slice()).map(|_| ())
+        );
+    }
+}
+
+impl From<&Canvas> for TGA {
+    /// Creates a new 24 bit tga from a canvas, dropping the alpha channel
+    fn from(canvas: &Canvas) -> Self {
+        Self::from_canvas(canvas, false)
+    }
+}
+
+impl TryFrom<&TGA> for Canvas {
+    type Error = ImageError;
+
+    /// Creates a new canvas from a tga, scaling samples to 0..1. Images without alpha are made opaque.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImageError::InvalidData`] if the number of pixels does not match the image size
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::asset_types::tga::TGA;
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let tga = TGA::new(1, 1, false, vec![[255, 51, 0, 0]]);
+    /// let canvas = Canvas::try_from(&tga).unwrap();
+    /// assert_eq!(Color::new(1.0, 0.2, 0.0), canvas.get_pixel_at(0, 0));
+    /// ```
+    fn try_from(tga: &TGA) -> Result<Self, Self::Error> {
+        Self::from_buffer(
+            u32::from(tga.width),
+            u32::from(tga.height),
+            tga.pixels
+                .iter()
+                .map(|pixel| {
+                    let alpha = if tga.alpha {
+                        f32::from(pixel[3]) / 255.0
+                    } else {
+                        1.0
+                    };
+                    Color::new_with_alpha(
+                        f32::from(pixel[0]) / 255.0,
+                        f32::from(pixel[1]) / 255.0,
+                        f32::from(pixel[2]) / 255.0,
+                        alpha,
+                    )
+                })
+                .collect(),
+        )
+        .ok_or_else(|| {
+            ImageError::InvalidData("pixel count does not match the image size".to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_canvas {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.set_pixel_at(0, 0, Color::new(1.0, 0.2, 0.0));
+        canvas.set_pixel_at(2, 1, Color::new(0.4, 0.6, 0.8));
+        let bytes = TGA::from(&canvas).to_bytes(Compression::RLE);
+        let decoded = Canvas::try_from(&TGA::try_from(bytes.as_slice()).unwrap()).unwrap();
+        assert_eq!(3, decoded.width);
+        assert_eq!(Color::new(1.0, 0.2, 0.0), decoded.get_pixel_at(0, 0));
+        assert_eq!(Color::new(0.4, 0.6, 0.8), decoded.get_pixel_at(2, 1));
+        assert_eq!(Color::new(0.0, 0.0, 0.0), decoded.get_pixel_at(1, 1));
+    }
+
+    #[test]
+    fn alpha() {
+        let mut canvas = Canvas::new(1, 1);
+        let color = Color::new_with_alpha(0.2, 0.4, 0.6, 0.2);
+        canvas.set_pixel_at(0, 0, color);
+        let tga = TGA::from_canvas(&canvas, true);
+        let decoded =
+            Canvas::try_from(&TGA::try_from(tga.to_bytes(Compression::None).as_slice()).unwrap())
+                .unwrap();
+        assert_eq!(0.2, decoded.get_pixel_at(0, 0).a());
+    }
+
+    #[test]
+    fn pixel_count_mismatch() {
+        let tga = TGA::new(2, 2, false, vec![[0, 0, 0, 0]; 3]);
+        assert!(matches!(
+            Canvas::try_from(&tga),
+            Err(ImageError::InvalidData(_))
+        ));
+    }
+}
@@ -30,26 +30,26 @@ impl Color {
         }
     }
 
-    // /// Creates a new Color
-    // ///
-    // /// # Examples
-    // ///
-    // /// ```
-    // /// # use crate::rusty_ray_tracer::core3d::color::Color;
-    // /// # use crate::rusty_ray_tracer::core3d::color_rgb::ColorRGB;
-    // ///
-    // /// let color = Color::nnew_with_alphaw(1.0, 2.0, 3.0, 4.0);
-    // /// assert_eq!(1.0, color.tuple[0]);
-    // /// assert_eq!(2.0, color.tuple[1]);
-    // /// assert_eq!(3.0, color.tuple[2]);
-    // /// assert_eq!(4.0, color.tuple[3]);
-    // /// ```
-    // #[must_use]
-    // pub const fn new_with_alpha(r: f32, g: f32, b: f32, a: f32) -> Color {
-    //     Color {
-    //         tuple: [r, g, b, a],
-    //     }
-    // }
+    /// Creates a new Color with a straight, not premultiplied, alpha
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crate::rusty_ray_tracer::core3d::color::Color;
+    /// # use crate::rusty_ray_tracer::core3d::color_rgb::ColorRGB;
+    ///
+    /// let color = Color::new_with_alpha(1.0, 2.0, 3.0, 0.5);
+    /// assert_eq!(1.0, color.tuple[0]);
+    /// assert_eq!(2.0, color.tuple[1]);
+    /// assert_eq!(3.0, color.tuple[2]);
+    /// assert_eq!(0.5, color.a());
+    /// ```
+    #[must_use]
+    pub const fn new_with_alpha(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self {
+            tuple: [r, g, b, a],
+        }
+    }
 }
 
 #[cfg(test)]
@@ -62,6 +62,14 @@ mod tests_color {
         assert_eq!([1.0, 2.0, 3.0, 1.0], color.tuple);
     }
 
+    #[test]
+    fn new_with_alpha() {
+        let color = Color::new_with_alpha(1.0, 2.0, 3.0, 0.25);
+        assert_eq!([1.0, 2.0, 3.0, 0.25], color.tuple);
+        let transparent = Color::new_with_alpha(0.0, 0.0, 0.0, 0.0);
+        assert_eq!(Color::default(), transparent);
+    }
+
     #[test]
     #[allow(clippy::clone_on_copy)]
     fn copy_clone() {
@@ -98,21 +106,20 @@ impl From<[f32; 3]> for Color {
     }
 }
 
-// impl From<[f32; 4]> for Color {
-//     /// Creates a new Color from an array of scaler values
-//     ///
-//     /// # Examples
-//     ///
-//     /// ```
-//     /// # use crate::rusty_ray_tracer::core3d::tuple::Tuple;
-//     /// # use crate::rusty_ray_tracer::core3d::color::Color;
-//     /// let color = Color::from([1.0, 2.0, 3.0, 4.0]);
-//     /// assert_eq!([1.0, 2.0, 3.0, 4.0], color.tuple);
-//     /// ```
-//     fn from(arr: [f32; 4]) -> Self {
-//         Color::new_with_alpha(arr[0], arr[1], arr[2], arr[3])
-//     }
-// }
+impl From<[f32; 4]> for Color {
+    /// Creates a new Color from an array of scaler values, the last one being alpha
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crate::rusty_ray_tracer::core3d::color::Color;
+    /// let color = Color::from([1.0, 2.0, 3.0, 0.5]);
+    /// assert_eq!([1.0, 2.0, 3.0, 0.5], color.tuple);
+    /// ```
+    fn from(arr: [f32; 4]) -> Self {
+        Self::new_with_alpha(arr[0], arr[1], arr[2], arr[3])
+    }
+}
 
 // impl From<Tuple> for Color {
 //     /// Creates a new Color from a Tuple
@@ -271,7 +278,7 @@ mod tests_colorrgba {
         assert_eq!(3.0, color.r());
         assert_eq!(2.0, color.g());
         assert_eq!(1.0, color.b());
-        // assert_eq!(1.0, color.a());
+        assert_eq!(1.0, color.a());
     }
 
     #[test]
@@ -280,7 +287,7 @@ mod tests_colorrgba {
         assert_eq!(1.0, color.r());
         assert_eq!(2.0, color.g());
         assert_eq!(3.0, color.b());
-        // assert_eq!(1.0, color.a());
+        assert_eq!(1.0, color.a());
     }
 }
 
@@ -443,22 +450,27 @@ impl Add for Color {
 
     /// Performs the `+` operation.
     ///
+    /// Alpha is coverage rather than light, so it is not summed: the result covers as much as the more opaque of
+    /// the two colors
+    ///
     /// # Example
     ///
     /// ```
     /// # use crate::rusty_ray_tracer::core3d::coordinates4::Coordinates4;
     /// # use crate::rusty_ray_tracer::core3d::color::Color;
+    /// # use crate::rusty_ray_tracer::core3d::color_rgb::ColorRGB;
     /// let a = Color::new(1.23, 4.56, 7.89);
     /// let b = Color::new(1.11, 2.22, 3.33);
     /// let expected = Color::new(2.34, 6.78, 11.22);
     /// assert_eq!(expected, a + b);
+    ///
+    /// let translucent = Color::new_with_alpha(0.5, 0.5, 0.5, 0.25);
+    /// assert_eq!(0.25, (translucent + translucent).a());
     /// ```
     #[must_use]
     fn add(self, rhs: Self) -> Self {
         let result = Self::zip_for_each_collect(self, rhs, |a, b| a + b);
-        // HACK: Alpha is not supported, Alpha requires blending and does not directly translate to Addition or Subtraction
-        // let a = result.a().clamp(0.0, 1.0);
-        Self::new(result.r(), result.g(), result.b())
+        Self::new_with_alpha(result.r(), result.g(), result.b(), self.a().max(rhs.a()))
     }
 }
 
@@ -497,6 +509,18 @@ mod tests_add {
         assert_eq!(a + (b + c), (a + b) + c);
         assert_eq!(c + (a + b), (c + a) + b);
     }
+
+    #[test]
+    fn alpha() {
+        let translucent = Color::new_with_alpha(1.0, 0.5, 0.0, 0.25);
+        let opaque = Color::new(0.0, 0.5, 1.0);
+        assert_eq!(
+            Color::new_with_alpha(2.0, 1.0, 0.0, 0.25),
+            translucent + translucent
+        );
+        assert_eq!(Color::new(1.0, 1.0, 1.0), translucent + opaque);
+        assert_eq!(translucent, translucent + Color::default());
+    }
 }
 
 impl Sub for Color {
@@ -505,6 +529,8 @@ impl Sub for Color {
 
     /// Performs the `-` operation.
     ///
+    /// Like `+`, the result covers as much as the more opaque of the two colors
+    ///
     /// # Example
     ///
     /// ```
@@ -518,9 +544,7 @@ impl Sub for Color {
     #[must_use]
     fn sub(self, rhs: Self) -> Self::Output {
         let result = Self::zip_for_each_collect(self, rhs, |a, b| a - b);
-        // HACK: Alpha is not supported, Alpha requires blending and does not directly translate to Addition or Subtraction
-        // let a = result.a().clamp(0.0, 1.0);
-        Self::new(result.r(), result.g(), result.b())
+        Self::new_with_alpha(result.r(), result.g(), result.b(), self.a().max(rhs.a()))
     }
 }
 
@@ -576,6 +600,18 @@ mod tests_sub {
         assert_eq!(c_ab, c - (a - b));
         assert_eq!(ca_b, (c - a) - b);
     }
+
+    #[test]
+    fn alpha() {
+        let translucent = Color::new_with_alpha(1.0, 0.5, 0.0, 0.25);
+        let opaque = Color::new(0.0, 0.5, 1.0);
+        assert_eq!(
+            Color::new_with_alpha(0.0, 0.0, 0.0, 0.25),
+            translucent - translucent
+        );
+        assert_eq!(Color::new(1.0, 0.0, -1.0), translucent - opaque);
+        assert_eq!(translucent, translucent - Color::default());
+    }
 }
 
 impl Mul<f32> for Color {
@@ -583,19 +619,23 @@ impl Mul<f32> for Color {
 
     /// Performs the `*` operation.
     ///
+    /// Scaling changes the light of the color, not how much it covers, so alpha is kept
+    ///
     /// # Example
     ///
     /// ```
     /// # use crate::rusty_ray_tracer::core3d::coordinates4::Coordinates4;
     /// # use crate::rusty_ray_tracer::core3d::color::Color;
+    /// # use crate::rusty_ray_tracer::core3d::color_rgb::ColorRGB;
     /// let result = Color::new(1.11, -2.22, 3.33) * 100.1;
     /// let expected = Color::new(111.111, -222.222, 333.333);
     /// assert_eq!(expected, result);
+    ///
+    /// let translucent = Color::new_with_alpha(0.5, 0.5, 0.5, 0.25);
+    /// assert_eq!(0.25, (translucent * 0.5).a());
     /// ```
     fn mul(self, rhs: f32) -> Self::Output {
-        let mut result = self;
-        result.iter_mut().for_each(|x| *x *= rhs);
-        Self::new(result.r(), result.g(), result.b())
+        Self::new_with_alpha(self.r() * rhs, self.g() * rhs, self.b() * rhs, self.a())
     }
 }
 
@@ -616,6 +656,19 @@ mod tests_mul {
         let b = 1.0;
         assert_eq!(a * b, a);
     }
+
+    #[test]
+    fn alpha() {
+        let translucent = Color::new_with_alpha(1.0, 0.5, 0.0, 0.25);
+        assert_eq!(
+            Color::new_with_alpha(0.5, 0.25, 0.0, 0.25),
+            translucent * 0.5
+        );
+        assert_eq!(
+            Color::new_with_alpha(0.0, 0.0, 0.0, 0.25),
+            translucent * 0.0
+        );
+    }
 }
 
 impl Mul for Color {
@@ -623,6 +676,8 @@ impl Mul for Color {
 
     /// Performs the Hadamard product (or Schur product) `*` operation.
     ///
+    /// The product filters coverage like light, so alpha is multiplied as well
+    ///
     /// # Example
     ///
     /// ```
@@ -671,4 +726,136 @@ mod tests_product {
         assert_eq!(a * (b * c), (a * b) * c);
         assert_eq!(c * (a * b), (c * a) * b);
     }
+
+    #[test]
+    fn alpha() {
+        let translucent = Color::new_with_alpha(1.0, 0.5, 0.0, 0.25);
+        let half = Color::new_with_alpha(0.5, 0.5, 0.5, 0.5);
+        assert_eq!(
+            Color::new_with_alpha(0.5, 0.25, 0.0, 0.125),
+            translucent * half
+        );
+        assert_eq!(translucent, translucent * Color::new(1.0, 1.0, 1.0));
+    }
+}
+
+impl Color {
+    /// Multiplies the color channels by alpha, the form in which colors can be blended by adding them
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// let color = Color::new_with_alpha(1.0, 0.5, 0.0, 0.5).premultiply();
+    /// assert_eq!([0.5, 0.25, 0.0, 0.5], color.tuple);
+    /// ```
+    #[must_use]
+    pub fn premultiply(self) -> Self {
+        let a = self.a();
+        Self::new_with_alpha(self.r() * a, self.g() * a, self.b() * a, a)
+    }
+
+    /// Divides premultiplied color channels by alpha, turning fully transparent colors into transparent black
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// let color = Color::new_with_alpha(0.5, 0.25, 0.0, 0.5).unpremultiply();
+    /// assert_eq!([1.0, 0.5, 0.0, 0.5], color.tuple);
+    /// ```
+    #[must_use]
+    pub fn unpremultiply(self) -> Self {
+        let a = self.a();
+        if a == 0.0 {
+            return Self::default();
+        }
+        Self::new_with_alpha(self.r() / a, self.g() / a, self.b() / a, a)
+    }
+
+    /// Composites this straight alpha color over a background with the Porter-Duff `over` operator
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// let red = Color::new_with_alpha(1.0, 0.0, 0.0, 0.5);
+    /// assert_eq!(Color::new(0.5, 0.0, 0.5), red.over(Color::new(0.0, 0.0, 1.0)));
+    /// assert_eq!(red, red.over(Color::default()));
+    /// ```
+    #[must_use]
+    pub fn over(self, background: Self) -> Self {
+        let a = self.a() + background.a() * (1.0 - self.a());
+        if a == 0.0 {
+            return Self::default();
+        }
+        let source = self.premultiply();
+        let destination = background.premultiply();
+        let blend = |s: f32, d: f32| (s + d * (1.0 - self.a())) / a;
+        Self::new_with_alpha(
+            blend(source.r(), destination.r()),
+            blend(source.g(), destination.g()),
+            blend(source.b(), destination.b()),
+            a,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests_alpha {
+    use super::*;
+
+    #[test]
+    fn premultiply_round_trip() {
+        let color = Color::new_with_alpha(0.2, 0.4, 0.8, 0.25);
+        assert_eq!(
+            Color::new_with_alpha(0.05, 0.1, 0.2, 0.25),
+            color.premultiply()
+        );
+        assert_eq!(color, color.premultiply().unpremultiply());
+        let opaque = Color::new(0.2, 0.4, 0.8);
+        assert_eq!(opaque, opaque.premultiply());
+        assert_eq!(opaque, opaque.unpremultiply());
+    }
+
+    #[test]
+    fn transparent() {
+        let invisible = Color::new_with_alpha(0.2, 0.4, 0.8, 0.0);
+        assert_eq!(Color::default(), invisible.premultiply());
+        assert_eq!(Color::default(), invisible.unpremultiply());
+        assert_eq!(Color::default(), invisible.over(Color::default()));
+        let background = Color::new_with_alpha(0.1, 0.2, 0.3, 0.6);
+        assert_eq!(background, invisible.over(background));
+    }
+
+    #[test]
+    fn over_opaque() {
+        let foreground = Color::new(0.1, 0.2, 0.3);
+        assert_eq!(foreground, foreground.over(Color::new(0.9, 0.9, 0.9)));
+        assert_eq!(foreground, foreground.over(Color::default()));
+    }
+
+    #[test]
+    fn over_translucent() {
+        let foreground = Color::new_with_alpha(1.0, 0.0, 0.0, 0.5);
+        let background = Color::new_with_alpha(0.0, 0.0, 1.0, 0.5);
+        let result = foreground.over(background);
+        assert_eq!(0.75, result.a());
+        assert_eq!(
+            Color::new_with_alpha(2.0 / 3.0, 0.0, 1.0 / 3.0, 0.75),
+            result
+        );
+    }
+
+    #[test]
+    fn over_is_associative() {
+        let a = Color::new_with_alpha(1.0, 0.5, 0.0, 0.3);
+        let b = Color::new_with_alpha(0.0, 0.5, 1.0, 0.6);
+        let c = Color::new_with_alpha(0.2, 0.2, 0.2, 0.9);
+        let left = a.over(b).over(c);
+        let right = a.over(b.over(c));
+        for i in 0..4 {
+            assert!((left.get_at(i) - right.get_at(i)).abs() < 1e-6);
+        }
+    }
 }
@@ -57,6 +57,21 @@ where
     fn b(&self) -> Self::Item {
         self.get_at(2)
     }
+
+    /// Gets the alpha component, the opacity from 0 for transparent to 1 for opaque
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crate::rusty_ray_tracer::core3d::color_rgb::ColorRGB;
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// assert_eq!(1.0, Color::new(1.23, 4.56, 7.89).a());
+    /// assert_eq!(0.5, Color::new_with_alpha(1.23, 4.56, 7.89, 0.5).a());
+    /// ```
+    #[must_use]
+    fn a(&self) -> Self::Item {
+        self.get_at(3)
+    }
 }
 
 #[cfg(test)]
@@ -123,5 +138,6 @@ mod tests {
         assert_eq!(1.23, color.r());
         assert_eq!(4.56, color.g());
         assert_eq!(7.89, color.b());
+        assert_eq!(10.11, color.a());
     }
 }
@@ -0,0 +1,637 @@
+use super::{color::Color, color_rgb::ColorRGB};
+
+/// Linear sRGB to CIE XYZ matrix for the D65 white point
+const RGB_TO_XYZ: [[f32; 3]; 3] = [
+    [0.412_456_4, 0.357_576_1, 0.180_437_5],
+    [0.212_672_9, 0.715_152_2, 0.072_175_0],
+    [0.019_333_9, 0.119_192, 0.950_304_1],
+];
+/// CIE XYZ to linear sRGB matrix for the D65 white point
+const XYZ_TO_RGB: [[f32; 3]; 3] = [
+    [3.240_454_2, -1.537_138_5, -0.498_531_4],
+    [-0.969_266, 1.876_010_8, 0.041_556_0],
+    [0.055_643_4, -0.204_025_9, 1.057_225_2],
+];
+/// CIE XYZ coordinates of the D65 reference white
+pub const D65_WHITE: XYZ = XYZ::new(0.950_47, 1.0, 1.088_83);
+/// Boundary between the cube root and the linear segment of the CIELAB curve
+const LAB_DELTA: f32 = 6.0 / 29.0;
+
+fn multiply(matrix: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    matrix.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+/// Hue in degrees in 0..360 of a color with the given largest channel and chroma
+fn hue(rgb: [f32; 3], max: f32, chroma: f32) -> f32 {
+    let [r, g, b] = rgb;
+    if chroma == 0.0 {
+        return 0.0;
+    }
+    let sector = if max == r {
+        ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    };
+    (sector * 60.0).rem_euclid(360.0)
+}
+
+/// Color with the given hue in degrees and chroma, offset by the smallest channel
+fn from_hue(hue: f32, chroma: f32, min: f32) -> Color {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+    let [r, g, b] = match sector as u32 {
+        0 => [chroma, x, 0.0],
+        1 => [x, chroma, 0.0],
+        2 => [0.0, chroma, x],
+        3 => [0.0, x, chroma],
+        4 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    };
+    Color::new(r + min, g + min, b + min)
+}
+
+/// Interpolates between two hues in degrees along the shorter way around the circle
+fn lerp_hue(from: f32, to: f32, t: f32) -> f32 {
+    let difference = (to - from + 180.0).rem_euclid(360.0) - 180.0;
+    (from + difference * t).rem_euclid(360.0)
+}
+
+/// Color as hue, saturation and value, the brightness of its largest channel
+///
+/// Conversions work on the channel values as they are and ignore alpha
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct HSV {
+    /// Hue in degrees in 0..360
+    pub hue: f32,
+    pub saturation: f32,
+    pub value: f32,
+}
+
+impl HSV {
+    /// Creates a new hsv color
+    #[must_use]
+    pub const fn new(hue: f32, saturation: f32, value: f32) -> Self {
+        Self {
+            hue,
+            saturation,
+            value,
+        }
+    }
+
+    /// Interpolates towards another color, turning the hue the shorter way around
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color_space::HSV;
+    /// let halfway = HSV::new(350.0, 1.0, 1.0).lerp(HSV::new(30.0, 0.0, 0.5), 0.5);
+    /// assert_eq!(HSV::new(10.0, 0.5, 0.75), halfway);
+    /// ```
+    #[must_use]
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        Self::new(
+            lerp_hue(self.hue, other.hue, t),
+            self.saturation + (other.saturation - self.saturation) * t,
+            self.value + (other.value - self.value) * t,
+        )
+    }
+}
+
+impl From<Color> for HSV {
+    /// Converts the channels of a color to hue, saturation and value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::core3d::color_space::HSV;
+    /// assert_eq!(HSV::new(30.0, 1.0, 1.0), HSV::from(Color::new(1.0, 0.5, 0.0)));
+    /// assert_eq!(HSV::new(0.0, 0.0, 0.25), HSV::from(Color::new(0.25, 0.25, 0.25)));
+    /// ```
+    fn from(color: Color) -> Self {
+        let rgb = [color.r(), color.g(), color.b()];
+        let max = rgb[0].max(rgb[1]).max(rgb[2]);
+        let min = rgb[0].min(rgb[1]).min(rgb[2]);
+        let chroma = max - min;
+        let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+        Self::new(hue(rgb, max, chroma), saturation, max)
+    }
+}
+
+impl From<HSV> for Color {
+    /// Creates a new opaque color from hue, saturation and value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::core3d::color_space::HSV;
+    /// assert_eq!(Color::new(0.0, 0.5, 1.0), Color::from(HSV::new(210.0, 1.0, 1.0)));
+    /// ```
+    fn from(hsv: HSV) -> Self {
+        let chroma = hsv.value * hsv.saturation;
+        from_hue(hsv.hue, chroma, hsv.value - chroma)
+    }
+}
+
+/// Color as hue, saturation and lightness, the average of its largest and smallest channels
+///
+/// Conversions work on the channel values as they are and ignore alpha
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct HSL {
+    /// Hue in degrees in 0..360
+    pub hue: f32,
+    pub saturation: f32,
+    pub lightness: f32,
+}
+
+impl HSL {
+    /// Creates a new hsl color
+    #[must_use]
+    pub const fn new(hue: f32, saturation: f32, lightness: f32) -> Self {
+        Self {
+            hue,
+            saturation,
+            lightness,
+        }
+    }
+
+    /// Interpolates towards another color, turning the hue the shorter way around
+    #[must_use]
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        Self::new(
+            lerp_hue(self.hue, other.hue, t),
+            self.saturation + (other.saturation - self.saturation) * t,
+            self.lightness + (other.lightness - self.lightness) * t,
+        )
+    }
+}
+
+impl From<Color> for HSL {
+    /// Converts the channels of a color to hue, saturation and lightness
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::core3d::color_space::HSL;
+    /// assert_eq!(HSL::new(30.0, 1.0, 0.5), HSL::from(Color::new(1.0, 0.5, 0.0)));
+    /// assert_eq!(HSL::new(240.0, 0.5, 0.25), HSL::from(Color::new(0.125, 0.125, 0.375)));
+    /// ```
+    fn from(color: Color) -> Self {
+        let rgb = [color.r(), color.g(), color.b()];
+        let max = rgb[0].max(rgb[1]).max(rgb[2]);
+        let min = rgb[0].min(rgb[1]).min(rgb[2]);
+        let chroma = max - min;
+        let lightness = (max + min) / 2.0;
+        let saturation = if chroma == 0.0 {
+            0.0
+        } else {
+            chroma / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+        Self::new(hue(rgb, max, chroma), saturation, lightness)
+    }
+}
+
+impl From<HSL> for Color {
+    /// Creates a new opaque color from hue, saturation and lightness
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::core3d::color_space::HSL;
+    /// assert_eq!(Color::new(0.75, 0.25, 0.25), Color::from(HSL::new(0.0, 0.5, 0.5)));
+    /// ```
+    fn from(hsl: HSL) -> Self {
+        let chroma = (1.0 - (2.0 * hsl.lightness - 1.0).abs()) * hsl.saturation;
+        from_hue(hsl.hue, chroma, hsl.lightness - chroma / 2.0)
+    }
+}
+
+#[cfg(test)]
+mod tests_hsv_hsl {
+    use super::*;
+
+    const PRIMARIES: [([f32; 3], f32); 6] = [
+        ([1.0, 0.0, 0.0], 0.0),
+        ([1.0, 1.0, 0.0], 60.0),
+        ([0.0, 1.0, 0.0], 120.0),
+        ([0.0, 1.0, 1.0], 180.0),
+        ([0.0, 0.0, 1.0], 240.0),
+        ([1.0, 0.0, 1.0], 300.0),
+    ];
+
+    #[test]
+    fn primaries() {
+        for (rgb, hue) in PRIMARIES {
+            let color = Color::from(rgb);
+            assert_eq!(HSV::new(hue, 1.0, 1.0), HSV::from(color));
+            assert_eq!(HSL::new(hue, 1.0, 0.5), HSL::from(color));
+            assert_eq!(color, Color::from(HSV::new(hue, 1.0, 1.0)));
+            assert_eq!(color, Color::from(HSL::new(hue, 1.0, 0.5)));
+        }
+    }
+
+    #[test]
+    fn grays() {
+        for value in [0.0, 0.2, 1.0] {
+            let gray = Color::new(value, value, value);
+            assert_eq!(HSV::new(0.0, 0.0, value), HSV::from(gray));
+            assert_eq!(HSL::new(0.0, 0.0, value), HSL::from(gray));
+            assert_eq!(gray, Color::from(HSV::new(123.0, 0.0, value)));
+            assert_eq!(gray, Color::from(HSL::new(123.0, 0.0, value)));
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for i in 0..1000 {
+            let channel = |k: u32| ((i * k) % 101) as f32 / 100.0;
+            let color = Color::new(channel(7), channel(13), channel(29));
+            let hsv = Color::from(HSV::from(color));
+            let hsl = Color::from(HSL::from(color));
+            for c in 0..3 {
+                assert!(
+                    (hsv.get_at(c) - color.get_at(c)).abs() < 1e-5,
+                    "{color:?} {hsv:?}"
+                );
+                assert!(
+                    (hsl.get_at(c) - color.get_at(c)).abs() < 1e-5,
+                    "{color:?} {hsl:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn hue_wraps() {
+        assert_eq!(
+            Color::from(HSV::new(30.0, 1.0, 1.0)),
+            Color::from(HSV::new(390.0, 1.0, 1.0))
+        );
+        assert_eq!(
+            Color::from(HSL::new(330.0, 1.0, 0.5)),
+            Color::from(HSL::new(-30.0, 1.0, 0.5))
+        );
+        // Magenta to red wraps around instead of passing through the other hues
+        let hue = HSV::from(Color::new(1.0, 0.0, 0.1)).hue;
+        assert!((hue - 354.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn lerp_hue() {
+        assert_eq!(10.0, super::lerp_hue(350.0, 30.0, 0.5));
+        assert_eq!(350.0, super::lerp_hue(30.0, 350.0, 1.0));
+        assert_eq!(85.0, super::lerp_hue(0.0, 170.0, 0.5));
+        assert_eq!(
+            HSL::new(120.0, 0.5, 0.5),
+            HSL::new(60.0, 0.0, 0.0).lerp(HSL::new(180.0, 1.0, 1.0), 0.5)
+        );
+    }
+}
+
+/// CIE 1931 XYZ tristimulus values of a linear sRGB color, relative to the D65 white point
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct XYZ {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl XYZ {
+    /// Creates new tristimulus values
+    #[must_use]
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl From<Color> for XYZ {
+    /// Converts a linear color to tristimulus values, with `y` its luminance
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::core3d::color_space::XYZ;
+    /// let xyz = XYZ::from(Color::new(0.0, 1.0, 0.0));
+    /// assert_eq!(0.715_152_2, xyz.y);
+    /// ```
+    fn from(color: Color) -> Self {
+        let [x, y, z] = multiply(&RGB_TO_XYZ, [color.r(), color.g(), color.b()]);
+        Self::new(x, y, z)
+    }
+}
+
+impl Color {
+    /// Returns the luminance of a linear color, the `y` of its tristimulus values
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// assert_eq!(0.715_152_2, Color::new(0.0, 1.0, 0.0).luminance());
+    /// assert!((Color::new(1.0, 1.0, 1.0).luminance() - 1.0).abs() < 1e-6);
+    /// ```
+    #[must_use]
+    pub fn luminance(self) -> f32 {
+        XYZ::from(self).y
+    }
+}
+
+impl From<XYZ> for Color {
+    /// Creates a new opaque linear color from tristimulus values, which may lie outside the sRGB gamut
+    fn from(xyz: XYZ) -> Self {
+        Self::from(multiply(&XYZ_TO_RGB, [xyz.x, xyz.y, xyz.z]))
+    }
+}
+
+/// CIELAB coordinates, a space where distances roughly match the perceived difference between colors
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Lab {
+    /// Lightness from 0 for black to 100 for white
+    pub l: f32,
+    /// Position between green at negative and red at positive values
+    pub a: f32,
+    /// Position between blue at negative and yellow at positive values
+    pub b: f32,
+}
+
+impl Lab {
+    /// Creates new CIELAB coordinates
+    #[must_use]
+    pub const fn new(l: f32, a: f32, b: f32) -> Self {
+        Self { l, a, b }
+    }
+
+    /// Interpolates towards another color, so equal steps look about equally large
+    #[must_use]
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        Self::new(
+            self.l + (other.l - self.l) * t,
+            self.a + (other.a - self.a) * t,
+            self.b + (other.b - self.b) * t,
+        )
+    }
+
+    /// Returns the CIE76 difference, the euclidean distance between both colors
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color_space::Lab;
+    /// assert_eq!(5.0, Lab::new(50.0, 3.0, 0.0).delta_e_76(Lab::new(50.0, 0.0, 4.0)));
+    /// ```
+    #[must_use]
+    pub fn delta_e_76(self, other: Self) -> f32 {
+        ((self.l - other.l).powi(2) + (self.a - other.a).powi(2) + (self.b - other.b).powi(2))
+            .sqrt()
+    }
+
+    /// Returns the CIEDE2000 difference, where a value of about 1 is just noticeable
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color_space::Lab;
+    /// let difference = Lab::new(50.0, 2.6772, -79.7751).delta_e(Lab::new(50.0, 0.0, -82.7485));
+    /// assert!((difference - 2.0425).abs() < 1e-3);
+    /// ```
+    #[must_use]
+    pub fn delta_e(self, other: Self) -> f32 {
+        const POW25_7: f32 = 6_103_515_625.0;
+        let chroma_weight = |c: f32| (c.powi(7) / (c.powi(7) + POW25_7)).sqrt();
+        let hue = |a: f32, b: f32| {
+            if a == 0.0 && b == 0.0 {
+                0.0
+            } else {
+                b.atan2(a).to_degrees().rem_euclid(360.0)
+            }
+        };
+
+        let mean_chroma = (self.a.hypot(self.b) + other.a.hypot(other.b)) / 2.0;
+        let g = 0.5 * (1.0 - chroma_weight(mean_chroma));
+        let (a1, a2) = ((1.0 + g) * self.a, (1.0 + g) * other.a);
+        let (c1, c2) = (a1.hypot(self.b), a2.hypot(other.b));
+        let (h1, h2) = (hue(a1, self.b), hue(a2, other.b));
+
+        let delta_l = other.l - self.l;
+        let delta_c = c2 - c1;
+        let delta_h = if c1 * c2 == 0.0 {
+            0.0
+        } else if (h2 - h1).abs() <= 180.0 {
+            h2 - h1
+        } else if h2 > h1 {
+            h2 - h1 - 360.0
+        } else {
+            h2 - h1 + 360.0
+        };
+        let delta_big_h = 2.0 * (c1 * c2).sqrt() * (delta_h / 2.0).to_radians().sin();
+
+        let mean_l = (self.l + other.l) / 2.0;
+        let mean_c = (c1 + c2) / 2.0;
+        let mean_h = if c1 * c2 == 0.0 {
+            h1 + h2
+        } else if (h1 - h2).abs() <= 180.0 {
+            (h1 + h2) / 2.0
+        } else if h1 + h2 < 360.0 {
+            (h1 + h2 + 360.0) / 2.0
+        } else {
+            (h1 + h2 - 360.0) / 2.0
+        };
+
+        let cos = |degrees: f32| degrees.to_radians().cos();
+        let t = 1.0 - 0.17 * cos(mean_h - 30.0)
+            + 0.24 * cos(2.0 * mean_h)
+            + 0.32 * cos(3.0 * mean_h + 6.0)
+            - 0.20 * cos(4.0 * mean_h - 63.0);
+        let delta_theta = 30.0 * (-((mean_h - 275.0) / 25.0).powi(2)).exp();
+        let rotation = -2.0 * chroma_weight(mean_c) * (2.0 * delta_theta).to_radians().sin();
+        let s_l = 1.0 + 0.015 * (mean_l - 50.0).powi(2) / (20.0 + (mean_l - 50.0).powi(2)).sqrt();
+        let s_c = 1.0 + 0.045 * mean_c;
+        let s_h = 1.0 + 0.015 * mean_c * t;
+
+        let (l, c, h) = (delta_l / s_l, delta_c / s_c, delta_big_h / s_h);
+        (l * l + c * c + h * h + rotation * c * h).sqrt()
+    }
+}
+
+impl From<XYZ> for Lab {
+    /// Converts tristimulus values relative to the D65 white point
+    fn from(xyz: XYZ) -> Self {
+        let f = |t: f32| {
+            if t > LAB_DELTA.powi(3) {
+                t.cbrt()
+            } else {
+                t / (3.0 * LAB_DELTA * LAB_DELTA) + 4.0 / 29.0
+            }
+        };
+        let (fx, fy, fz) = (
+            f(xyz.x / D65_WHITE.x),
+            f(xyz.y / D65_WHITE.y),
+            f(xyz.z / D65_WHITE.z),
+        );
+        Self::new(116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+    }
+}
+
+impl From<Lab> for XYZ {
+    /// Converts CIELAB coordinates to tristimulus values relative to the D65 white point
+    fn from(lab: Lab) -> Self {
+        let f_inverse = |t: f32| {
+            if t > LAB_DELTA {
+                t.powi(3)
+            } else {
+                3.0 * LAB_DELTA * LAB_DELTA * (t - 4.0 / 29.0)
+            }
+        };
+        let fy = (lab.l + 16.0) / 116.0;
+        Self::new(
+            D65_WHITE.x * f_inverse(fy + lab.a / 500.0),
+            D65_WHITE.y * f_inverse(fy),
+            D65_WHITE.z * f_inverse(fy - lab.b / 200.0),
+        )
+    }
+}
+
+impl From<Color> for Lab {
+    /// Converts a linear color to CIELAB coordinates
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::core3d::color_space::Lab;
+    /// let lab = Lab::from(Color::new(0.18, 0.18, 0.18));
+    /// assert!((lab.l - 49.5).abs() < 0.1);
+    /// assert!(lab.a.abs() < 1e-3 && lab.b.abs() < 1e-3);
+    /// ```
+    fn from(color: Color) -> Self {
+        Self::from(XYZ::from(color))
+    }
+}
+
+impl From<Lab> for Color {
+    /// Creates a new opaque linear color from CIELAB coordinates
+    fn from(lab: Lab) -> Self {
+        Self::from(XYZ::from(lab))
+    }
+}
+
+/// Returns the CIEDE2000 difference between two linear colors, where a value of about 1 is just noticeable
+///
+/// # Examples
+///
+/// ```
+/// # use rusty_ray_tracer::core3d::color::Color;
+/// # use rusty_ray_tracer::core3d::color_space::delta_e;
+/// assert_eq!(0.0, delta_e(Color::new(0.3, 0.2, 0.1), Color::new(0.3, 0.2, 0.1)));
+/// assert!(delta_e(Color::new(0.0, 0.0, 0.0), Color::new(1.0, 1.0, 1.0)) > 99.0);
+/// ```
+#[must_use]
+pub fn delta_e(a: Color, b: Color) -> f32 {
+    Lab::from(a).delta_e(Lab::from(b))
+}
+
+#[cfg(test)]
+mod tests_xyz_lab {
+    use super::*;
+
+    #[test]
+    fn white_point() {
+        let xyz = XYZ::from(Color::new(1.0, 1.0, 1.0));
+        assert!((xyz.x - D65_WHITE.x).abs() < 1e-4);
+        assert!((xyz.y - D65_WHITE.y).abs() < 1e-4);
+        assert!((xyz.z - D65_WHITE.z).abs() < 1e-4);
+        let lab = Lab::from(Color::new(1.0, 1.0, 1.0));
+        assert!((lab.l - 100.0).abs() < 1e-3);
+        assert!(lab.a.abs() < 1e-2 && lab.b.abs() < 1e-2);
+        assert_eq!(
+            Lab::new(0.0, 0.0, 0.0),
+            Lab::from(Color::new(0.0, 0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn primaries() {
+        // Reference values for the sRGB primaries under D65
+        for (rgb, expected) in [
+            ([1.0, 0.0, 0.0], [53.2408, 80.0925, 67.2032]),
+            ([0.0, 1.0, 0.0], [87.7347, -86.1827, 83.1793]),
+            ([0.0, 0.0, 1.0], [32.2970, 79.1875, -107.8602]),
+        ] {
+            let lab = Lab::from(Color::from(rgb));
+            assert!((lab.l - expected[0]).abs() < 0.01, "{lab:?}");
+            assert!((lab.a - expected[1]).abs() < 0.01, "{lab:?}");
+            assert!((lab.b - expected[2]).abs() < 0.01, "{lab:?}");
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for i in 0..1000 {
+            let channel = |k: u32| ((i * k) % 101) as f32 / 100.0;
+            let color = Color::new(channel(7), channel(13), channel(29));
+            let xyz = Color::from(XYZ::from(color));
+            let lab = Color::from(Lab::from(color));
+            for c in 0..3 {
+                assert!(
+                    (xyz.get_at(c) - color.get_at(c)).abs() < 1e-5,
+                    "{color:?} {xyz:?}"
+                );
+                assert!(
+                    (lab.get_at(c) - color.get_at(c)).abs() < 1e-4,
+                    "{color:?} {lab:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn lerp() {
+        let mid = Lab::new(0.0, -10.0, 20.0).lerp(Lab::new(100.0, 10.0, 0.0), 0.25);
+        assert_eq!(Lab::new(25.0, -5.0, 15.0), mid);
+    }
+
+    #[test]
+    fn delta_e_76() {
+        let a = Lab::new(10.0, 20.0, 30.0);
+        assert_eq!(0.0, a.delta_e_76(a));
+        assert_eq!(12.0, a.delta_e_76(Lab::new(22.0, 20.0, 30.0)));
+    }
+
+    #[test]
+    fn delta_e_2000() {
+        // Pairs from the CIEDE2000 test data of Sharma, Wu and Dalal
+        for (first, second, expected) in [
+            ([50.0, 2.6772, -79.7751], [50.0, 0.0, -82.7485], 2.0425),
+            ([50.0, 3.1571, -77.2803], [50.0, 0.0, -82.7485], 2.8615),
+            ([50.0, 0.0, 0.0], [50.0, -1.0, 2.0], 2.3669),
+            ([50.0, 2.5, 0.0], [73.0, 25.0, -18.0], 27.1492),
+            (
+                [60.2574, -34.0099, 36.2677],
+                [60.4626, -34.1751, 39.4387],
+                1.2644,
+            ),
+        ] {
+            let first = Lab::new(first[0], first[1], first[2]);
+            let second = Lab::new(second[0], second[1], second[2]);
+            let difference = first.delta_e(second);
+            assert!(
+                (difference - expected).abs() < 1e-3,
+                "{difference} != {expected}"
+            );
+            assert!((second.delta_e(first) - expected).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn delta_e_colors() {
+        let gray = Color::new(0.5, 0.5, 0.5);
+        assert!(delta_e(gray, Color::new(0.502, 0.5, 0.5)) < 1.0);
+        assert!(delta_e(gray, Color::new(0.6, 0.5, 0.5)) > 3.0);
+    }
+}
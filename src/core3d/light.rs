@@ -0,0 +1,119 @@
+use std::f32::consts::PI;
+
+use super::{color::Color, point::Point, spectrum::blackbody_efficacy};
+
+/// Total amount of light a source emits in all directions
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LightPower {
+    /// Luminous flux in lumens, as printed on lamp packaging
+    Lumens(f32),
+    /// Radiant flux in watts, of which only the visible share lights the scene
+    Watts(f32),
+}
+
+impl LightPower {
+    /// Returns the luminous flux in lumens of a blackbody source at a temperature in Kelvin
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::light::LightPower;
+    /// assert_eq!(800.0, LightPower::Lumens(800.0).lumens(2700.0));
+    /// assert!(LightPower::Watts(60.0).lumens(2700.0) < 1000.0);
+    /// ```
+    #[must_use]
+    pub fn lumens(self, kelvin: f32) -> f32 {
+        match self {
+            Self::Lumens(lumens) => lumens,
+            Self::Watts(watts) => watts * blackbody_efficacy(kelvin),
+        }
+    }
+}
+
+/// Light source without size shining equally in all directions
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PointLight {
+    pub position: Point,
+    /// Linear color whose luminance is the luminous intensity in candela
+    pub intensity: Color,
+}
+
+impl PointLight {
+    /// Creates a new point light
+    #[must_use]
+    pub const fn new(position: Point, intensity: Color) -> Self {
+        Self {
+            position,
+            intensity,
+        }
+    }
+
+    /// Creates a new point light emitting the light of a blackbody at a temperature in Kelvin, as fixtures are
+    /// specified
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color_space::XYZ;
+    /// # use rusty_ray_tracer::core3d::light::{LightPower, PointLight};
+    /// # use rusty_ray_tracer::core3d::point::Point;
+    /// let bulb = PointLight::from_temperature(Point::new(0.0, 3.0, 0.0), 2700.0, LightPower::Lumens(800.0));
+    /// let candela = XYZ::from(bulb.intensity).y;
+    /// assert!((candela - 800.0 / (4.0 * std::f32::consts::PI)).abs() < 0.1);
+    /// ```
+    #[must_use]
+    pub fn from_temperature(position: Point, kelvin: f32, power: LightPower) -> Self {
+        let candela = power.lumens(kelvin) / (4.0 * PI);
+        Self::new(position, Color::from_temperature(kelvin) * candela)
+    }
+}
+
+#[cfg(test)]
+mod tests_point_light {
+    use super::*;
+    use crate::core3d::{color_rgb::ColorRGB, color_space::XYZ};
+
+    #[test]
+    fn new() {
+        let light = PointLight::new(Point::new(1.0, 2.0, 3.0), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(Point::new(1.0, 2.0, 3.0), light.position);
+        assert_eq!(Color::new(1.0, 1.0, 1.0), light.intensity);
+    }
+
+    #[test]
+    fn lumens() {
+        let light = PointLight::from_temperature(
+            Point::new(0.0, 0.0, 0.0),
+            4000.0,
+            LightPower::Lumens(4.0 * PI),
+        );
+        assert!((XYZ::from(light.intensity).y - 1.0).abs() < 1e-3);
+        let color = Color::from_temperature(4000.0);
+        assert!((light.intensity.r() / light.intensity.b() - color.r() / color.b()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn watts() {
+        let efficacy = blackbody_efficacy(3000.0);
+        assert_eq!(100.0 * efficacy, LightPower::Watts(100.0).lumens(3000.0));
+        let radiant = PointLight::from_temperature(
+            Point::new(0.0, 0.0, 0.0),
+            3000.0,
+            LightPower::Watts(100.0),
+        );
+        let luminous = PointLight::from_temperature(
+            Point::new(0.0, 0.0, 0.0),
+            3000.0,
+            LightPower::Lumens(100.0 * efficacy),
+        );
+        assert_eq!(luminous, radiant);
+    }
+
+    #[test]
+    fn hotter_is_more_efficient() {
+        // The same power gives more light as the filament heats up, until it glows blue
+        let lumens = [2000.0, 3000.0, 4000.0, 5000.0, 6000.0]
+            .map(|kelvin| LightPower::Watts(1.0).lumens(kelvin));
+        assert!(lumens.windows(2).all(|w| w[0] < w[1]));
+    }
+}
@@ -1,9 +1,12 @@
 pub mod array_base;
 pub mod color;
 pub mod color_rgb;
+pub mod color_space;
 pub mod coordinates4;
 pub mod dot_product;
+pub mod light;
 pub mod matrix;
 pub mod point;
+pub mod spectrum;
 pub mod tuple;
 pub mod vector;
@@ -0,0 +1,800 @@
+use std::{
+    ops::{Add, Mul},
+    sync::OnceLock,
+};
+
+use super::{color::Color, color_rgb::ColorRGB, color_space::XYZ};
+
+/// Shortest wavelength in nanometers carried by spectral light transport
+pub const LAMBDA_MIN: f32 = 360.0;
+/// Longest wavelength in nanometers carried by spectral light transport
+pub const LAMBDA_MAX: f32 = 830.0;
+/// Number of wavelengths traced together along each path
+pub const SPECTRUM_SAMPLES: usize = 4;
+
+/// Piecewise gaussian with different widths left and right of its mean
+fn lobe(lambda: f32, mean: f32, left: f32, right: f32) -> f32 {
+    let sigma = if lambda < mean { left } else { right };
+    let t = (lambda - mean) / sigma;
+    (-0.5 * t * t).exp()
+}
+
+/// Returns the CIE 1931 2° color matching functions at a wavelength in nanometers
+///
+/// Uses the multi-lobe fit of Wyman, Sloan and Shirley, which stays within a few percent of the tabulated
+/// functions
+///
+/// # Examples
+///
+/// ```
+/// # use rusty_ray_tracer::core3d::spectrum::color_matching;
+/// let xyz = color_matching(555.0);
+/// assert!((xyz.y - 1.0).abs() < 0.01);
+/// ```
+#[must_use]
+pub fn color_matching(lambda: f32) -> XYZ {
+    XYZ::new(
+        1.056 * lobe(lambda, 599.8, 37.9, 31.0) + 0.362 * lobe(lambda, 442.0, 16.0, 26.7)
+            - 0.065 * lobe(lambda, 501.1, 20.4, 26.2),
+        0.821 * lobe(lambda, 568.8, 46.9, 40.5) + 0.286 * lobe(lambda, 530.9, 16.3, 31.1),
+        1.217 * lobe(lambda, 437.0, 11.8, 36.0) + 0.681 * lobe(lambda, 459.0, 26.0, 13.8),
+    )
+}
+
+/// Integrates a function over the visible range in steps of one nanometer
+fn integrate(f: impl Fn(f32) -> f32) -> f32 {
+    let steps = (LAMBDA_MAX - LAMBDA_MIN) as u32;
+    (0..steps).map(|i| f(LAMBDA_MIN + i as f32 + 0.5)).sum()
+}
+
+/// Integral of the luminance matching function, so a constant spectrum of 1 has a luminance of 1
+fn y_integral() -> f32 {
+    static Y_INTEGRAL: OnceLock<f32> = OnceLock::new();
+    *Y_INTEGRAL.get_or_init(|| integrate(|lambda| color_matching(lambda).y))
+}
+
+/// Wavelengths carried along a path, the first chosen at random and the others spread evenly after it
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SampledWavelengths {
+    /// Wavelengths in nanometers
+    pub lambda: [f32; SPECTRUM_SAMPLES],
+    /// Probability density of each wavelength, 0 for wavelengths no longer carried
+    pub pdf: [f32; SPECTRUM_SAMPLES],
+}
+
+impl SampledWavelengths {
+    /// Samples wavelengths uniformly over the visible range from a random number in 0..1
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::spectrum::SampledWavelengths;
+    /// let wavelengths = SampledWavelengths::sample_uniform(0.0);
+    /// assert_eq!([360.0, 477.5, 595.0, 712.5], wavelengths.lambda);
+    /// ```
+    #[must_use]
+    pub fn sample_uniform(u: f32) -> Self {
+        let range = LAMBDA_MAX - LAMBDA_MIN;
+        let hero = LAMBDA_MIN + u * range;
+        let spacing = range / SPECTRUM_SAMPLES as f32;
+        let lambda = std::array::from_fn(|i| {
+            let lambda = hero + i as f32 * spacing;
+            if lambda > LAMBDA_MAX {
+                lambda - range
+            } else {
+                lambda
+            }
+        });
+        Self {
+            lambda,
+            pdf: [1.0 / range; SPECTRUM_SAMPLES],
+        }
+    }
+
+    /// Stops carrying all but the first wavelength, as needed once a path is split by dispersion
+    ///
+    /// The remaining wavelength now stands for all of them, so its density is divided by their count
+    pub fn terminate_secondary(&mut self) {
+        if self.is_secondary_terminated() {
+            return;
+        }
+        self.pdf[0] /= SPECTRUM_SAMPLES as f32;
+        self.pdf[1..].fill(0.0);
+    }
+
+    /// Returns whether only the first wavelength is still carried
+    #[must_use]
+    pub fn is_secondary_terminated(&self) -> bool {
+        self.pdf[1..].iter().all(|&pdf| pdf == 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests_sampled_wavelengths {
+    use super::*;
+
+    #[test]
+    fn sample_uniform() {
+        for i in 0..100 {
+            let wavelengths = SampledWavelengths::sample_uniform(i as f32 / 100.0);
+            assert!(wavelengths
+                .lambda
+                .iter()
+                .all(|lambda| (LAMBDA_MIN..=LAMBDA_MAX).contains(lambda)));
+            let mut sorted = wavelengths.lambda;
+            sorted.sort_by(f32::total_cmp);
+            for pair in sorted.windows(2) {
+                assert!((pair[1] - pair[0] - 117.5).abs() < 1e-3);
+            }
+            assert!(wavelengths.pdf.iter().all(|&pdf| pdf == 1.0 / 470.0));
+        }
+    }
+
+    #[test]
+    fn wraps_around() {
+        let wavelengths = SampledWavelengths::sample_uniform(0.5);
+        assert_eq!([595.0, 712.5, 830.0, 477.5], wavelengths.lambda);
+    }
+
+    #[test]
+    fn terminate_secondary() {
+        let mut wavelengths = SampledWavelengths::sample_uniform(0.25);
+        assert!(!wavelengths.is_secondary_terminated());
+        wavelengths.terminate_secondary();
+        assert!(wavelengths.is_secondary_terminated());
+        assert_eq!([1.0 / 1880.0, 0.0, 0.0, 0.0], wavelengths.pdf);
+        wavelengths.terminate_secondary();
+        assert_eq!([1.0 / 1880.0, 0.0, 0.0, 0.0], wavelengths.pdf);
+    }
+}
+
+/// Values of a spectral quantity at the wavelengths of a [`SampledWavelengths`]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SampledSpectrum {
+    pub values: [f32; SPECTRUM_SAMPLES],
+}
+
+/// Smooth spectra whose mix reproduces any linear sRGB color
+fn rgb_basis(lambda: f32) -> [f32; 3] {
+    [
+        lobe(lambda, 610.0, 40.0, 40.0),
+        lobe(lambda, 545.0, 35.0, 35.0),
+        lobe(lambda, 455.0, 30.0, 30.0),
+    ]
+}
+
+fn invert(m: [[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    let cofactor = |r: usize, c: usize| {
+        let (r1, r2) = ((r + 1) % 3, (r + 2) % 3);
+        let (c1, c2) = ((c + 1) % 3, (c + 2) % 3);
+        m[r1][c1] * m[r2][c2] - m[r1][c2] * m[r2][c1]
+    };
+    let determinant = (0..3).map(|c| m[0][c] * cofactor(0, c)).sum::<f32>();
+    std::array::from_fn(|r| std::array::from_fn(|c| cofactor(c, r) / determinant))
+}
+
+/// Matrix turning a linear sRGB color into the weights of the [`rgb_basis`] spectra
+fn rgb_to_basis() -> &'static [[f32; 3]; 3] {
+    static RGB_TO_BASIS: OnceLock<[[f32; 3]; 3]> = OnceLock::new();
+    RGB_TO_BASIS.get_or_init(|| {
+        // Column j holds the color of basis spectrum j
+        let colors = [0, 1, 2].map(|j| {
+            let xyz = [0, 1, 2].map(|k| {
+                integrate(|lambda| {
+                    let cmf = color_matching(lambda);
+                    rgb_basis(lambda)[j] * [cmf.x, cmf.y, cmf.z][k]
+                }) / y_integral()
+            });
+            Color::from(XYZ::new(xyz[0], xyz[1], xyz[2]))
+        });
+        invert(std::array::from_fn(|i| colors.map(|c| c.get_at(i))))
+    })
+}
+
+impl SampledSpectrum {
+    /// Creates a new spectrum with the same value at every wavelength
+    #[must_use]
+    pub const fn constant(value: f32) -> Self {
+        Self {
+            values: [value; SPECTRUM_SAMPLES],
+        }
+    }
+
+    /// Creates a new spectrum at the given wavelengths from a linear color, such as a reflectance or an
+    /// emission
+    ///
+    /// The spectrum is a smooth mix whose color is exactly the given one. Very saturated colors can
+    /// dip below 0 at some wavelengths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::core3d::spectrum::{SampledSpectrum, SampledWavelengths};
+    /// let wavelengths = SampledWavelengths::sample_uniform(0.0);
+    /// let red = SampledSpectrum::from_rgb(Color::new(1.0, 0.0, 0.0), &wavelengths);
+    /// // 712.5 nm is red, 477.5 nm is blue
+    /// assert!(red.values[3] > red.values[1]);
+    /// ```
+    #[must_use]
+    pub fn from_rgb(color: Color, wavelengths: &SampledWavelengths) -> Self {
+        let matrix = rgb_to_basis();
+        let rgb = [color.r(), color.g(), color.b()];
+        let weights = matrix.map(|row| row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2]);
+        Self {
+            values: wavelengths.lambda.map(|lambda| {
+                let basis = rgb_basis(lambda);
+                weights[0] * basis[0] + weights[1] * basis[1] + weights[2] * basis[2]
+            }),
+        }
+    }
+
+    /// Estimates the tristimulus values of the spectrum from its samples, one sample of a path's estimate
+    #[must_use]
+    pub fn to_xyz(&self, wavelengths: &SampledWavelengths) -> XYZ {
+        let mut xyz = [0.0_f32; 3];
+        for ((value, lambda), pdf) in self
+            .values
+            .iter()
+            .zip(wavelengths.lambda)
+            .zip(wavelengths.pdf)
+        {
+            if pdf == 0.0 {
+                continue;
+            }
+            let cmf = color_matching(lambda);
+            for (sum, c) in xyz.iter_mut().zip([cmf.x, cmf.y, cmf.z]) {
+                *sum += c * value / pdf;
+            }
+        }
+        let scale = 1.0 / (SPECTRUM_SAMPLES as f32 * y_integral());
+        XYZ::new(xyz[0] * scale, xyz[1] * scale, xyz[2] * scale)
+    }
+
+    /// Estimates the linear color of the spectrum from its samples, averaging these over many paths
+    /// converges to the color of the whole spectrum
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::core3d::spectrum::{SampledSpectrum, SampledWavelengths};
+    /// let color = Color::new(0.2, 0.5, 0.8);
+    /// let mut sum = Color::new(0.0, 0.0, 0.0);
+    /// for i in 0..100 {
+    ///     let wavelengths = SampledWavelengths::sample_uniform((i as f32 + 0.5) / 100.0);
+    ///     sum = sum + SampledSpectrum::from_rgb(color, &wavelengths).to_color(&wavelengths) * 0.01;
+    /// }
+    /// assert!((sum.r() - 0.2).abs() < 1e-3 && (sum.b() - 0.8).abs() < 1e-3);
+    /// # use rusty_ray_tracer::core3d::color_rgb::ColorRGB;
+    /// ```
+    #[must_use]
+    pub fn to_color(&self, wavelengths: &SampledWavelengths) -> Color {
+        Color::from(self.to_xyz(wavelengths))
+    }
+}
+
+impl Add for SampledSpectrum {
+    type Output = Self;
+
+    /// Adds the values at each wavelength
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            values: std::array::from_fn(|i| self.values[i] + rhs.values[i]),
+        }
+    }
+}
+
+impl Mul for SampledSpectrum {
+    type Output = Self;
+
+    /// Multiplies the values at each wavelength, as when light is reflected by a surface
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::spectrum::SampledSpectrum;
+    /// let light = SampledSpectrum { values: [1.0, 2.0, 3.0, 4.0] };
+    /// let reflectance = SampledSpectrum { values: [0.5, 0.5, 0.0, 1.0] };
+    /// assert_eq!([0.5, 1.0, 0.0, 4.0], (light * reflectance).values);
+    /// ```
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self {
+            values: std::array::from_fn(|i| self.values[i] * rhs.values[i]),
+        }
+    }
+}
+
+impl Mul<f32> for SampledSpectrum {
+    type Output = Self;
+
+    /// Scales the values at each wavelength
+    fn mul(self, rhs: f32) -> Self::Output {
+        Self {
+            values: self.values.map(|v| v * rhs),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_spectrum {
+    use super::*;
+
+    #[test]
+    fn color_matching_peaks() {
+        assert!((color_matching(599.8).x - 1.056).abs() < 0.02);
+        assert!((color_matching(555.0).y - 1.0).abs() < 0.01);
+        assert!((color_matching(445.0).z - 1.78).abs() < 0.03);
+        let far_red = color_matching(LAMBDA_MAX);
+        assert!(far_red.x < 1e-3 && far_red.y < 1e-3 && far_red.z < 1e-3);
+    }
+
+    #[test]
+    fn luminance_integral() {
+        // The tabulated function integrates to 106.857 nm
+        assert!((y_integral() - 106.857).abs() < 1.0);
+    }
+
+    #[test]
+    fn equal_energy_white() {
+        let mut xyz = [0.0; 3];
+        for i in 0..50 {
+            let wavelengths = SampledWavelengths::sample_uniform((i as f32 + 0.5) / 50.0);
+            let sample = SampledSpectrum::constant(1.0).to_xyz(&wavelengths);
+            xyz[0] += sample.x / 50.0;
+            xyz[1] += sample.y / 50.0;
+            xyz[2] += sample.z / 50.0;
+        }
+        for c in xyz {
+            assert!((c - 1.0).abs() < 0.02, "{xyz:?}");
+        }
+    }
+
+    #[test]
+    fn rgb_round_trip() {
+        for rgb in [
+            [1.0, 1.0, 1.0],
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [0.9, 0.6, 0.1],
+            [3.0, 2.0, 1.0],
+        ] {
+            let color = Color::from(rgb);
+            let mut sum = [0.0_f32; 3];
+            for i in 0..470 {
+                let wavelengths = SampledWavelengths::sample_uniform((i as f32 + 0.5) / 470.0);
+                let estimate =
+                    SampledSpectrum::from_rgb(color, &wavelengths).to_color(&wavelengths);
+                for (s, c) in sum
+                    .iter_mut()
+                    .zip([estimate.r(), estimate.g(), estimate.b()])
+                {
+                    *s += c / 470.0;
+                }
+            }
+            for (s, c) in sum.iter().zip(rgb) {
+                assert!((s - c).abs() < 1e-3, "{rgb:?} {sum:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn terminated_wavelengths_keep_the_estimate() {
+        let color = Color::new(0.3, 0.6, 0.9);
+        let mut sum = [0.0_f32; 3];
+        for i in 0..470 {
+            let mut wavelengths = SampledWavelengths::sample_uniform((i as f32 + 0.5) / 470.0);
+            wavelengths.terminate_secondary();
+            let estimate = SampledSpectrum::from_rgb(color, &wavelengths).to_color(&wavelengths);
+            for (s, c) in sum
+                .iter_mut()
+                .zip([estimate.r(), estimate.g(), estimate.b()])
+            {
+                *s += c / 470.0;
+            }
+        }
+        for (s, c) in sum.iter().zip([0.3, 0.6, 0.9]) {
+            assert!((s - c).abs() < 0.01, "{sum:?}");
+        }
+    }
+
+    #[test]
+    fn monochromatic_light() {
+        let spectrum = SampledSpectrum {
+            values: [1.0, 0.0, 0.0, 0.0],
+        };
+        let red = spectrum.to_color(&SampledWavelengths::sample_uniform(340.0 / 470.0));
+        assert!(red.r() > red.g() && red.r() > red.b());
+        let blue = spectrum.to_color(&SampledWavelengths::sample_uniform(100.0 / 470.0));
+        assert!(blue.b() > blue.r() && blue.b() > blue.g());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = SampledSpectrum {
+            values: [1.0, 2.0, 3.0, 4.0],
+        };
+        assert_eq!([2.0, 4.0, 6.0, 8.0], (a + a).values);
+        assert_eq!([1.0, 4.0, 9.0, 16.0], (a * a).values);
+        assert_eq!([0.5, 1.0, 1.5, 2.0], (a * 0.5).values);
+        assert_eq!([0.25; 4], SampledSpectrum::constant(0.25).values);
+    }
+}
+
+/// Luminous efficacy in lumens per watt of light at 555 nm, where the eye is most sensitive
+pub const MAX_LUMINOUS_EFFICACY: f32 = 683.0;
+
+/// Returns the spectral radiance in W·sr⁻¹·m⁻²·nm⁻¹ of a blackbody at a temperature in Kelvin, at a
+/// wavelength in nanometers
+///
+/// # Examples
+///
+/// ```
+/// # use rusty_ray_tracer::core3d::spectrum::planck;
+/// // The sun's surface peaks in the green
+/// assert!(planck(500.0, 5778.0) > planck(400.0, 5778.0));
+/// assert!(planck(500.0, 5778.0) > planck(700.0, 5778.0));
+/// assert_eq!(0.0, planck(500.0, 0.0));
+/// ```
+#[must_use]
+pub fn planck(lambda: f32, kelvin: f32) -> f32 {
+    const PLANCK: f64 = 6.626_070_15e-34;
+    const LIGHT_SPEED: f64 = 299_792_458.0;
+    const BOLTZMANN: f64 = 1.380_649e-23;
+    if kelvin <= 0.0 {
+        return 0.0;
+    }
+    let meters = f64::from(lambda) * 1e-9;
+    let exponent = PLANCK * LIGHT_SPEED / (meters * BOLTZMANN * f64::from(kelvin));
+    let radiance = 2.0 * PLANCK * LIGHT_SPEED * LIGHT_SPEED / (meters.powi(5) * exponent.exp_m1());
+    (radiance * 1e-9) as f32
+}
+
+/// Returns the share of a blackbody's radiated power the eye sees, in lumens per watt
+///
+/// # Examples
+///
+/// ```
+/// # use rusty_ray_tracer::core3d::spectrum::blackbody_efficacy;
+/// // An incandescent filament turns most of its power into heat
+/// assert!(blackbody_efficacy(2700.0) < 20.0);
+/// assert!(blackbody_efficacy(6500.0) > 90.0);
+/// ```
+#[must_use]
+pub fn blackbody_efficacy(kelvin: f32) -> f32 {
+    const STEFAN_BOLTZMANN: f32 = 5.670_374e-8;
+    if kelvin <= 0.0 {
+        return 0.0;
+    }
+    let radiance = STEFAN_BOLTZMANN * kelvin.powi(4) / std::f32::consts::PI;
+    let visible = integrate(|lambda| color_matching(lambda).y * planck(lambda, kelvin));
+    MAX_LUMINOUS_EFFICACY * visible / radiance
+}
+
+/// Tristimulus values of a blackbody, scaled to a luminance of 1, or black when it emits no visible light
+fn blackbody_xyz(kelvin: f32) -> XYZ {
+    let mut xyz = [0.0_f32; 3];
+    for i in 0..(LAMBDA_MAX - LAMBDA_MIN) as u32 {
+        let lambda = LAMBDA_MIN + i as f32 + 0.5;
+        let cmf = color_matching(lambda);
+        let radiance = planck(lambda, kelvin);
+        for (sum, c) in xyz.iter_mut().zip([cmf.x, cmf.y, cmf.z]) {
+            *sum += c * radiance;
+        }
+    }
+    if !(xyz[1] > 0.0 && xyz[1].is_finite()) {
+        return XYZ::new(0.0, 0.0, 0.0);
+    }
+    XYZ::new(xyz[0] / xyz[1], 1.0, xyz[2] / xyz[1])
+}
+
+impl Color {
+    /// Creates a new linear color of a blackbody at a temperature in Kelvin, scaled to a luminance of 1
+    ///
+    /// Channels falling outside of the sRGB gamut, as blue does for the reddest flames, are clamped to 0 before
+    /// scaling. Temperatures at which no visible light is computed, including zero and below, give black
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::core3d::color_rgb::ColorRGB;
+    /// let tungsten = Color::from_temperature(2700.0);
+    /// assert!(tungsten.r() > tungsten.g() && tungsten.g() > tungsten.b());
+    /// let sky = Color::from_temperature(12000.0);
+    /// assert!(sky.b() > sky.r());
+    /// ```
+    #[must_use]
+    pub fn from_temperature(kelvin: f32) -> Self {
+        let color = Self::from(blackbody_xyz(kelvin));
+        let [r, g, b] = [color.r(), color.g(), color.b()].map(|c| c.max(0.0));
+        let clamped = Self::new(r, g, b);
+        let luminance = clamped.luminance();
+        if luminance > 0.0 {
+            clamped * (1.0 / luminance)
+        } else {
+            clamped
+        }
+    }
+}
+
+/// Emission spectrum of a blackbody at a fixed temperature, normalized once to a luminance of 1 so that
+/// sampling it per path only evaluates Planck's law at the sampled wavelengths
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Blackbody {
+    kelvin: f32,
+    scale: f32,
+}
+
+impl Blackbody {
+    /// Creates a new blackbody at a temperature in Kelvin
+    ///
+    /// Temperatures too low to emit visible light, including zero and below, give a black spectrum
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::spectrum::{Blackbody, SampledWavelengths};
+    /// let tungsten = Blackbody::new(2700.0);
+    /// let wavelengths = SampledWavelengths::sample_uniform(0.5);
+    /// assert!(tungsten.sample(&wavelengths).values.iter().all(|&v| v > 0.0));
+    /// assert_eq!([0.0; 4], Blackbody::new(0.0).sample(&wavelengths).values);
+    /// ```
+    #[must_use]
+    pub fn new(kelvin: f32) -> Self {
+        let visible = integrate(|lambda| color_matching(lambda).y * planck(lambda, kelvin));
+        let scale = if visible > 0.0 && visible.is_finite() {
+            y_integral() / visible
+        } else {
+            0.0
+        };
+        Self { kelvin, scale }
+    }
+
+    /// Returns the temperature in Kelvin
+    #[must_use]
+    pub const fn kelvin(&self) -> f32 {
+        self.kelvin
+    }
+
+    /// Returns the spectrum at the given wavelengths
+    #[must_use]
+    pub fn sample(&self, wavelengths: &SampledWavelengths) -> SampledSpectrum {
+        if self.scale == 0.0 {
+            return SampledSpectrum::default();
+        }
+        SampledSpectrum {
+            values: wavelengths
+                .lambda
+                .map(|lambda| planck(lambda, self.kelvin) * self.scale),
+        }
+    }
+}
+
+impl SampledSpectrum {
+    /// Creates a new spectrum at the given wavelengths of a blackbody at a temperature in Kelvin, with a
+    /// luminance of 1
+    ///
+    /// This integrates the whole blackbody spectrum on every call, create a [`Blackbody`] once instead when
+    /// sampling the same temperature repeatedly
+    #[must_use]
+    pub fn from_temperature(kelvin: f32, wavelengths: &SampledWavelengths) -> Self {
+        Blackbody::new(kelvin).sample(wavelengths)
+    }
+}
+
+#[cfg(test)]
+mod tests_blackbody {
+    use super::*;
+
+    /// Chromaticity coordinates of a color
+    fn chromaticity(xyz: XYZ) -> (f32, f32) {
+        let sum = xyz.x + xyz.y + xyz.z;
+        (xyz.x / sum, xyz.y / sum)
+    }
+
+    #[test]
+    fn planck_peak() {
+        // Wien's displacement law puts the peak at 2 897 771.955 nm·K / T
+        let peak = (300..1500)
+            .map(|lambda| lambda as f32)
+            .max_by(|a, b| planck(*a, 5000.0).total_cmp(&planck(*b, 5000.0)))
+            .unwrap();
+        assert!((peak - 579.6).abs() <= 1.0);
+    }
+
+    #[test]
+    fn planck_radiance() {
+        // Radiance of the sun's surface at 500 nm, about 2.6e13 W·sr⁻¹·m⁻²·m⁻¹
+        assert!((planck(500.0, 5778.0) / 2.6e4 - 1.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn planckian_locus() {
+        for (kelvin, x, y) in [
+            (2000.0, 0.5267, 0.4133),
+            (3000.0, 0.4369, 0.4041),
+            (5000.0, 0.3451, 0.3516),
+            (10000.0, 0.2807, 0.2884),
+        ] {
+            let (cx, cy) = chromaticity(blackbody_xyz(kelvin));
+            assert!(
+                (cx - x).abs() < 0.005 && (cy - y).abs() < 0.005,
+                "{kelvin} {cx} {cy}"
+            );
+        }
+    }
+
+    #[test]
+    fn from_temperature() {
+        for kelvin in [3000.0, 5000.0, 6500.0, 9000.0] {
+            let color = Color::from_temperature(kelvin);
+            assert!((XYZ::from(color).y - 1.0).abs() < 1e-3, "{kelvin}");
+        }
+        // Close to the D65 white point
+        let daylight = Color::from_temperature(6504.0);
+        assert!((daylight.r() - daylight.b()).abs() < 0.1, "{daylight:?}");
+        // Too red for sRGB blue, keeping the luminance once it is clamped
+        for kelvin in [500.0, 1000.0] {
+            let color = Color::from_temperature(kelvin);
+            assert_eq!(0.0, color.b(), "{kelvin}");
+            assert!((color.luminance() - 1.0).abs() < 1e-5, "{kelvin}");
+        }
+    }
+
+    #[test]
+    fn warmer_is_redder() {
+        let ratios = (1000..=12000)
+            .step_by(500)
+            .map(|kelvin| {
+                let color = Color::from_temperature(kelvin as f32);
+                color.r() / (color.b() + color.g())
+            })
+            .collect::<Vec<_>>();
+        assert!(ratios.windows(2).all(|w| w[0] > w[1]));
+    }
+
+    #[test]
+    fn efficacy() {
+        // Peaks at about 95 lm/W near 6600 K
+        let efficacy = blackbody_efficacy(6600.0);
+        assert!((efficacy - 95.0).abs() < 3.0, "{efficacy}");
+        assert!(blackbody_efficacy(3000.0) < efficacy);
+        assert!(blackbody_efficacy(15000.0) < efficacy);
+        assert_eq!(0.0, blackbody_efficacy(0.0));
+    }
+
+    #[test]
+    fn no_visible_light() {
+        let wavelengths = SampledWavelengths::sample_uniform(0.5);
+        for kelvin in [0.0, 50.0, -100.0, f32::NAN] {
+            assert_eq!(
+                [0.0; 4],
+                SampledSpectrum::from_temperature(kelvin, &wavelengths).values,
+                "{kelvin}"
+            );
+            assert_eq!(XYZ::new(0.0, 0.0, 0.0), blackbody_xyz(kelvin), "{kelvin}");
+            assert_eq!(Color::new(0.0, 0.0, 0.0), Color::from_temperature(kelvin));
+        }
+    }
+
+    #[test]
+    fn blackbody_matches_from_temperature() {
+        let blackbody = Blackbody::new(3200.0);
+        assert_eq!(3200.0, blackbody.kelvin());
+        for u in [0.1, 0.5, 0.9] {
+            let wavelengths = SampledWavelengths::sample_uniform(u);
+            assert_eq!(
+                SampledSpectrum::from_temperature(3200.0, &wavelengths),
+                blackbody.sample(&wavelengths)
+            );
+        }
+    }
+
+    #[test]
+    fn spectrum_matches_color() {
+        let blackbody = Blackbody::new(4000.0);
+        let mut sum = Color::new(0.0, 0.0, 0.0);
+        for i in 0..470 {
+            let wavelengths = SampledWavelengths::sample_uniform((i as f32 + 0.5) / 470.0);
+            let spectrum = blackbody.sample(&wavelengths);
+            sum = sum + spectrum.to_color(&wavelengths) * (1.0 / 470.0);
+        }
+        let expected = Color::from_temperature(4000.0);
+        for i in 0..3 {
+            assert!((sum.get_at(i) - expected.get_at(i)).abs() < 1e-3, "{sum:?}");
+        }
+    }
+}
+
+/// Model of how the index of refraction of a transparent material changes with the wavelength
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Dispersion {
+    /// Cauchy's equation `n = a + b / λ²` with the wavelength in micrometers
+    Cauchy { a: f32, b: f32 },
+    /// Sellmeier's equation `n² = 1 + Σ b λ² / (λ² - c)` with the wavelength in micrometers
+    Sellmeier { b: [f32; 3], c: [f32; 3] },
+}
+
+impl Dispersion {
+    /// Schott N-BK7 crown glass, the usual glass of lenses and prisms
+    pub const BK7: Self = Self::Sellmeier {
+        b: [1.039_612, 0.231_792_34, 1.010_469_5],
+        c: [0.006_000_699, 0.020_017_914, 103.560_65],
+    };
+    /// Fused silica
+    pub const FUSED_SILICA: Self = Self::Sellmeier {
+        b: [0.696_166_3, 0.407_942_6, 0.897_479_4],
+        c: [0.004_679_148, 0.013_512_063, 97.934],
+    };
+    /// Diamond, whose strong dispersion gives gemstones their fire
+    pub const DIAMOND: Self = Self::Sellmeier {
+        b: [4.3356, 0.3306, 0.0],
+        c: [0.011_236, 0.030_625, 0.0],
+    };
+
+    /// Returns the index of refraction at a wavelength in nanometers
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::spectrum::Dispersion;
+    /// let glass = Dispersion::Cauchy { a: 1.5046, b: 0.0042 };
+    /// assert!((glass.ior(500.0) - 1.5214).abs() < 1e-4);
+    /// assert!(Dispersion::DIAMOND.ior(400.0) > Dispersion::DIAMOND.ior(700.0));
+    /// ```
+    #[must_use]
+    pub fn ior(&self, lambda: f32) -> f32 {
+        let micrometers = lambda / 1000.0;
+        let squared = micrometers * micrometers;
+        match self {
+            Self::Cauchy { a, b } => a + b / squared,
+            Self::Sellmeier { b, c } => (1.0
+                + b.iter()
+                    .zip(c)
+                    .map(|(b, c)| b * squared / (squared - c))
+                    .sum::<f32>())
+            .sqrt(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_dispersion {
+    use super::*;
+
+    #[test]
+    fn reference_indices() {
+        // Refractive indices at the yellow helium d line
+        assert!((Dispersion::BK7.ior(587.6) - 1.5168).abs() < 1e-4);
+        assert!((Dispersion::FUSED_SILICA.ior(587.6) - 1.4585).abs() < 1e-4);
+        assert!((Dispersion::DIAMOND.ior(587.6) - 2.417).abs() < 2e-3);
+    }
+
+    #[test]
+    fn cauchy() {
+        let glass = Dispersion::Cauchy { a: 1.5, b: 0.01 };
+        assert!((glass.ior(1000.0) - 1.51).abs() < 1e-6);
+        assert!((glass.ior(500.0) - 1.54).abs() < 1e-6);
+    }
+
+    #[test]
+    fn normal_dispersion() {
+        for material in [
+            Dispersion::BK7,
+            Dispersion::FUSED_SILICA,
+            Dispersion::DIAMOND,
+        ] {
+            let indices = (400..=700)
+                .step_by(10)
+                .map(|lambda| material.ior(lambda as f32))
+                .collect::<Vec<_>>();
+            assert!(indices.windows(2).all(|w| w[0] > w[1]), "{material:?}");
+        }
+        // Diamond spreads colors much more than glass
+        let spread = |m: Dispersion| m.ior(430.8) - m.ior(686.7);
+        assert!(spread(Dispersion::DIAMOND) > 2.0 * spread(Dispersion::BK7));
+    }
+}
@@ -1,13 +1,15 @@
-use std::vec;
+use std::{fmt::Display, vec};
 
+use super::pixel::Pixel;
 use crate::core3d::color::Color;
 
+/// Image of `width` by `height` pixels stored row by row, holding full colors unless another pixel type is given
 #[derive(Default, Debug)]
-pub struct Canvas {
-    pub width: u16,
-    pub height: u16,
+pub struct Canvas<P = Color> {
+    pub width: u32,
+    pub height: u32,
 
-    pub raw_buffer: Vec<Color>,
+    pub raw_buffer: Vec<P>,
 }
 
 impl Canvas {
@@ -22,12 +24,74 @@ impl Canvas {
     /// assert_eq!(20, canvas.height);
     /// ```
     #[must_use]
-    pub fn new(width: u16, height: u16) -> Self {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self::filled(width, height, Color::default())
+    }
+}
+
+impl<P: Pixel> Canvas<P> {
+    /// Creates a new Canvas with dimensions width, height with every pixel set to the same value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let depth = Canvas::filled(640, 480, f32::INFINITY);
+    /// assert_eq!(f32::INFINITY, depth.get_pixel_at(639, 479));
+    /// ```
+    #[must_use]
+    pub fn filled(width: u32, height: u32, pixel: P) -> Self {
         let size = width as usize * height as usize;
         Self {
             width,
             height,
-            raw_buffer: vec::from_elem(Color::default(), size),
+            raw_buffer: vec::from_elem(pixel, size),
+        }
+    }
+
+    /// Creates a new Canvas with dimensions width, height from its pixels stored row by row, or `None` if their
+    /// number does not match the dimensions
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let canvas = Canvas::from_buffer(2, 1, vec![[255_u8, 0, 0], [0, 0, 255]]).unwrap();
+    /// assert_eq!([0, 0, 255], canvas.get_pixel_at(1, 0));
+    /// assert!(Canvas::from_buffer(2, 2, vec![0.0_f32; 3]).is_none());
+    /// ```
+    #[must_use]
+    pub fn from_buffer(width: u32, height: u32, raw_buffer: Vec<P>) -> Option<Self> {
+        let size = (width as usize).checked_mul(height as usize)?;
+        (raw_buffer.len() == size).then_some(Self {
+            width,
+            height,
+            raw_buffer,
+        })
+    }
+
+    /// Creates a new canvas of another pixel type holding the pixels of this one, converted through [`Color`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(2, 1);
+    /// canvas.set_pixel_at(1, 0, Color::new(1.0, 0.2, 0.0));
+    /// let bytes: Canvas<[u8; 3]> = canvas.convert();
+    /// assert_eq!([255, 51, 0], bytes.get_pixel_at(1, 0));
+    /// ```
+    #[must_use]
+    pub fn convert<Q: Pixel>(&self) -> Canvas<Q> {
+        Canvas {
+            width: self.width,
+            height: self.height,
+            raw_buffer: self
+                .raw_buffer
+                .iter()
+                .map(|&p| Q::from_color(p.to_color()))
+                .collect(),
         }
     }
 }
@@ -45,6 +109,43 @@ mod tests_canvas {
         let canvas = Canvas::new(960, 540);
         assert_eq!(960, canvas.width);
         assert_eq!(540, canvas.height);
+
+        let canvas = Canvas::new(100_000, 1);
+        assert_eq!(100_000, canvas.width);
+        assert_eq!(100_000, canvas.raw_buffer.len());
+    }
+
+    #[test]
+    fn filled() {
+        let canvas = Canvas::filled(3, 2, [1.0_f32, 0.5, 0.0]);
+        assert_eq!(6, canvas.raw_buffer.len());
+        assert!(canvas.raw_buffer.iter().all(|&p| p == [1.0, 0.5, 0.0]));
+
+        let depth: Canvas<f32> = Canvas::filled(2, 2, 0.0);
+        assert_eq!(0.0, depth.get_pixel_at(1, 1));
+    }
+
+    #[test]
+    fn from_buffer() {
+        let canvas = Canvas::from_buffer(2, 2, vec![0.0_f32, 1.0, 2.0, 3.0]).unwrap();
+        assert_eq!(2.0, canvas.get_pixel_at(0, 1));
+        assert!(Canvas::from_buffer(2, 2, vec![0.0_f32; 5]).is_none());
+        assert!(Canvas::<f32>::from_buffer(u32::MAX, u32::MAX, Vec::new()).is_none());
+        assert!(Canvas::<f32>::from_buffer(0, 5, Vec::new()).is_some());
+    }
+
+    #[test]
+    fn convert() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set_pixel_at(0, 0, Color::new_with_alpha(0.2, 0.4, 0.6, 0.5));
+        let rgba: Canvas<[u8; 4]> = canvas.convert();
+        assert_eq!([51, 102, 153, 128], rgba.get_pixel_at(0, 0));
+        assert_eq!([0, 0, 0, 0], rgba.get_pixel_at(1, 0));
+        let rgb: Canvas<[f32; 3]> = rgba.convert();
+        assert_eq!((2, 1), (rgb.width, rgb.height));
+        assert!((rgb.get_pixel_at(0, 0)[2] - 0.6).abs() < 1e-6);
+        let back: Canvas = rgb.convert();
+        assert_eq!(1.0, back.get_pixel_at(1, 0).tuple[3]);
     }
 
     #[test]
@@ -55,7 +156,7 @@ mod tests_canvas {
 }
 
 /// Calculates the raw buffer index from given 2d coordinates
-const fn get_2dbuffer_index(x: u16, y: u16, width: u16, height: u16) -> Option<usize> {
+const fn get_2dbuffer_index(x: u32, y: u32, width: u32, height: u32) -> Option<usize> {
     if x >= width || y >= height {
         debug_assert!(x < width);
         debug_assert!(y < height);
@@ -83,9 +184,9 @@ mod tests_buffer {
     }
 }
 
-impl Canvas {
+impl<P: Pixel> Canvas<P> {
     /// Calculates the raw buffer index from given 2d coordinates
-    const fn get_2dbuffer_index(&self, x: u16, y: u16) -> Option<usize> {
+    const fn get_2dbuffer_index(&self, x: u32, y: u32) -> Option<usize> {
         get_2dbuffer_index(x, y, self.width, self.height)
     }
 
@@ -111,7 +212,7 @@ impl Canvas {
     /// }
     /// ```
     #[must_use]
-    pub fn get_pixel_at(&self, x: u16, y: u16) -> Color {
+    pub fn get_pixel_at(&self, x: u32, y: u32) -> P {
         let idx = self
             .get_2dbuffer_index(x, y)
             .expect("Canvas Coordinates out of range!");
@@ -139,11 +240,33 @@ impl Canvas {
     ///     }
     /// }
     /// ```
-    pub fn set_pixel_at(&mut self, x: u16, y: u16, color: Color) {
+    pub fn set_pixel_at(&mut self, x: u32, y: u32, pixel: P) {
         let idx = self
             .get_2dbuffer_index(x, y)
             .expect("Canvas Coordinates out of range!");
-        self.raw_buffer[idx] = color;
+        self.raw_buffer[idx] = pixel;
+    }
+}
+
+impl Canvas {
+    /// Composites a straight alpha color over the pixel at coordinates x, y
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(1, 1);
+    /// canvas.blend_pixel_at(0, 0, Color::new_with_alpha(1.0, 0.0, 0.0, 0.5));
+    /// assert_eq!(Color::new_with_alpha(1.0, 0.0, 0.0, 0.5), canvas.get_pixel_at(0, 0));
+    /// canvas.blend_pixel_at(0, 0, Color::new_with_alpha(0.0, 0.0, 1.0, 0.5));
+    /// assert_eq!(0.75, canvas.get_pixel_at(0, 0).tuple[3]);
+    /// ```
+    pub fn blend_pixel_at(&mut self, x: u32, y: u32, color: Color) {
+        let idx = self
+            .get_2dbuffer_index(x, y)
+            .expect("Canvas Coordinates out of range!");
+        self.raw_buffer[idx] = color.over(self.raw_buffer[idx]);
     }
 }
 
@@ -183,4 +306,394 @@ mod tests_pixels {
         assert_eq!(blue, canvas.get_pixel_at(0, 7));
         assert_eq!(white, canvas.get_pixel_at(7, 7));
     }
+
+    #[test]
+    fn blend_pixels() {
+        let mut canvas = Canvas::new(2, 1);
+        assert_eq!(0.0, canvas.get_pixel_at(0, 0).tuple[3]);
+
+        let translucent = Color::new_with_alpha(0.2, 0.4, 0.6, 0.5);
+        canvas.blend_pixel_at(0, 0, translucent);
+        assert_eq!(translucent, canvas.get_pixel_at(0, 0));
+        assert_eq!(Color::default(), canvas.get_pixel_at(1, 0));
+
+        canvas.set_pixel_at(1, 0, Color::new(1.0, 1.0, 1.0));
+        canvas.blend_pixel_at(1, 0, translucent);
+        assert_eq!(Color::new(0.6, 0.7, 0.8), canvas.get_pixel_at(1, 0));
+
+        canvas.blend_pixel_at(1, 0, Color::new(0.1, 0.2, 0.3));
+        assert_eq!(Color::new(0.1, 0.2, 0.3), canvas.get_pixel_at(1, 0));
+    }
+}
+
+/// Error returned when accessing a pixel outside of a canvas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfBounds {
+    pub x: i64,
+    pub y: i64,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Display for OutOfBounds {
+    /// Returns a human readable description of the error
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::graphics2d::canvas::OutOfBounds;
+    /// let error = OutOfBounds { x: 8, y: -1, width: 8, height: 4 };
+    /// assert_eq!("Pixel (8, -1) is outside of the 8x4 canvas", error.to_string());
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Pixel ({}, {}) is outside of the {}x{} canvas",
+            self.x, self.y, self.width, self.height
+        )
+    }
+}
+
+impl std::error::Error for OutOfBounds {}
+
+/// How coordinates outside of a canvas are mapped back onto it when sampling
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum EdgeMode {
+    /// Repeats the pixels on the edges
+    #[default]
+    Clamp,
+    /// Tiles the canvas, continuing on the opposite edge
+    Wrap,
+    /// Reflects the canvas at its edges, so -1 reads the first pixel again
+    Mirror,
+    /// Reads a fixed color everywhere outside of the canvas
+    Border(Color),
+}
+
+impl EdgeMode {
+    /// Maps a coordinate along an axis of `size` pixels onto the canvas, or `None` if it reads the border
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::graphics2d::canvas::EdgeMode;
+    /// assert_eq!(Some(0), EdgeMode::Clamp.resolve(-3, 4));
+    /// assert_eq!(Some(1), EdgeMode::Wrap.resolve(-3, 4));
+    /// assert_eq!(Some(2), EdgeMode::Mirror.resolve(-3, 4));
+    /// assert_eq!(None, EdgeMode::Border(Default::default()).resolve(-3, 4));
+    /// ```
+    #[must_use]
+    pub fn resolve(self, coordinate: i32, size: u32) -> Option<u32> {
+        let size = i64::from(size);
+        let coordinate = i64::from(coordinate);
+        if size == 0 {
+            return None;
+        }
+        let resolved = match self {
+            Self::Clamp => coordinate.clamp(0, size - 1),
+            Self::Wrap => coordinate.rem_euclid(size),
+            Self::Mirror => {
+                let period = coordinate.rem_euclid(2 * size);
+                if period < size {
+                    period
+                } else {
+                    2 * size - 1 - period
+                }
+            }
+            Self::Border(_) => {
+                if !(0..size).contains(&coordinate) {
+                    return None;
+                }
+                coordinate
+            }
+        };
+        Some(resolved as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests_edge_mode {
+    use super::*;
+
+    fn resolve_all(mode: EdgeMode) -> Vec<Option<u32>> {
+        (-5..8).map(|x| mode.resolve(x, 3)).collect()
+    }
+
+    #[test]
+    fn clamp() {
+        assert_eq!(
+            [0, 0, 0, 0, 0, 0, 1, 2, 2, 2, 2, 2, 2].map(Some).to_vec(),
+            resolve_all(EdgeMode::Clamp)
+        );
+    }
+
+    #[test]
+    fn wrap() {
+        assert_eq!(
+            [1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1].map(Some).to_vec(),
+            resolve_all(EdgeMode::Wrap)
+        );
+    }
+
+    #[test]
+    fn mirror() {
+        assert_eq!(
+            [1, 2, 2, 1, 0, 0, 1, 2, 2, 1, 0, 0, 1].map(Some).to_vec(),
+            resolve_all(EdgeMode::Mirror)
+        );
+        assert_eq!(Some(0), EdgeMode::Mirror.resolve(i32::MIN, 1));
+        assert_eq!(Some(0), EdgeMode::Mirror.resolve(i32::MAX, 2));
+        assert_eq!(
+            Some(2_147_483_647),
+            EdgeMode::Clamp.resolve(i32::MAX, u32::MAX)
+        );
+    }
+
+    #[test]
+    fn border() {
+        let mut expected = vec![None; 13];
+        expected[5..8].copy_from_slice(&[Some(0), Some(1), Some(2)]);
+        assert_eq!(expected, resolve_all(EdgeMode::Border(Color::default())));
+    }
+
+    #[test]
+    fn empty_axis() {
+        for mode in [EdgeMode::Clamp, EdgeMode::Wrap, EdgeMode::Mirror] {
+            assert_eq!(None, mode.resolve(0, 0), "{mode:?}");
+        }
+    }
+}
+
+impl<P: Pixel> Canvas<P> {
+    /// Returns the width and height as signed coordinates, saturating for canvases too large to address
+    pub(super) fn signed_size(&self) -> (i32, i32) {
+        (
+            i32::try_from(self.width).unwrap_or(i32::MAX),
+            i32::try_from(self.height).unwrap_or(i32::MAX),
+        )
+    }
+
+    /// Calculates the raw buffer index of signed coordinates, or `None` outside of the canvas
+    fn checked_index(&self, x: i64, y: i64) -> Option<usize> {
+        let x = u32::try_from(x).ok().filter(|&x| x < self.width)?;
+        let y = u32::try_from(y).ok().filter(|&y| y < self.height)?;
+        Some(y as usize * self.width as usize + x as usize)
+    }
+
+    fn out_of_bounds(&self, x: i64, y: i64) -> OutOfBounds {
+        OutOfBounds {
+            x,
+            y,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// Get the pixel color at coordinates x, y, or `None` outside of the canvas
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let canvas = Canvas::new(2, 2);
+    /// assert_eq!(Some(Color::default()), canvas.try_get_pixel(1, 1));
+    /// assert_eq!(None, canvas.try_get_pixel(2, 1));
+    /// ```
+    #[must_use]
+    pub fn try_get_pixel(&self, x: u32, y: u32) -> Option<P> {
+        self.checked_index(i64::from(x), i64::from(y))
+            .map(|idx| self.raw_buffer[idx])
+    }
+
+    /// Sets the pixel color at coordinates x, y, failing outside of the canvas
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OutOfBounds`] without changing the canvas if the coordinates lie outside of it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(2, 2);
+    /// assert!(canvas.try_set_pixel(1, 1, Color::new(1.0, 0.0, 0.0)).is_ok());
+    /// assert!(canvas.try_set_pixel(1, 2, Color::new(1.0, 0.0, 0.0)).is_err());
+    /// ```
+    pub fn try_set_pixel(&mut self, x: u32, y: u32, pixel: P) -> Result<(), OutOfBounds> {
+        let (x, y) = (i64::from(x), i64::from(y));
+        let idx = self
+            .checked_index(x, y)
+            .ok_or_else(|| self.out_of_bounds(x, y))?;
+        self.raw_buffer[idx] = pixel;
+        Ok(())
+    }
+
+    /// Get the pixel color at signed coordinates x, y, or `None` outside of the canvas
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let canvas = Canvas::new(2, 2);
+    /// assert_eq!(Some(Color::default()), canvas.get_pixel_clipped(0, 1));
+    /// assert_eq!(None, canvas.get_pixel_clipped(-1, 1));
+    /// ```
+    #[must_use]
+    pub fn get_pixel_clipped(&self, x: i32, y: i32) -> Option<P> {
+        self.checked_index(i64::from(x), i64::from(y))
+            .map(|idx| self.raw_buffer[idx])
+    }
+
+    /// Sets the pixel color at signed coordinates x, y, silently skipping pixels outside of the canvas
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(2, 2);
+    /// canvas.set_pixel_clipped(-1, 0, Color::new(1.0, 0.0, 0.0));
+    /// canvas.set_pixel_clipped(1, 0, Color::new(1.0, 0.0, 0.0));
+    /// assert_eq!(Color::new(1.0, 0.0, 0.0), canvas.get_pixel_at(1, 0));
+    /// ```
+    pub fn set_pixel_clipped(&mut self, x: i32, y: i32, pixel: P) {
+        if let Some(idx) = self.checked_index(i64::from(x), i64::from(y)) {
+            self.raw_buffer[idx] = pixel;
+        }
+    }
+
+    /// Get the pixel color at signed coordinates x, y, mapping coordinates outside of the canvas back onto it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::{Canvas, EdgeMode};
+    /// let mut canvas = Canvas::new(2, 1);
+    /// canvas.set_pixel_at(0, 0, Color::new(1.0, 0.0, 0.0));
+    /// assert_eq!(Color::new(1.0, 0.0, 0.0), canvas.sample_pixel(-5, 0, EdgeMode::Clamp));
+    /// assert_eq!(Color::default(), canvas.sample_pixel(-5, 0, EdgeMode::Wrap));
+    /// ```
+    #[must_use]
+    pub fn sample_pixel(&self, x: i32, y: i32, mode: EdgeMode) -> P {
+        match (mode.resolve(x, self.width), mode.resolve(y, self.height)) {
+            (Some(x), Some(y)) => self.get_pixel_at(x, y),
+            _ => match mode {
+                EdgeMode::Border(color) => P::from_color(color),
+                _ => P::default(),
+            },
+        }
+    }
+}
+
+impl Canvas {
+    /// Composites a straight alpha color over the pixel at signed coordinates x, y, silently skipping pixels
+    /// outside of the canvas
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::filled(2, 1, Color::new(1.0, 1.0, 1.0));
+    /// canvas.blend_pixel_clipped(-1, 0, Color::new(1.0, 0.0, 0.0));
+    /// canvas.blend_pixel_clipped(1, 0, Color::new_with_alpha(1.0, 0.0, 0.0, 0.5));
+    /// assert_eq!(Color::new(1.0, 1.0, 1.0), canvas.get_pixel_at(0, 0));
+    /// assert_eq!(Color::new(1.0, 0.5, 0.5), canvas.get_pixel_at(1, 0));
+    /// ```
+    pub fn blend_pixel_clipped(&mut self, x: i32, y: i32, color: Color) {
+        if let Some(idx) = self.checked_index(i64::from(x), i64::from(y)) {
+            self.raw_buffer[idx] = color.over(self.raw_buffer[idx]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_checked_pixels {
+    use super::*;
+
+    const RED: Color = Color::new(1.0, 0.0, 0.0);
+
+    #[test]
+    fn try_get() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.set_pixel_at(2, 1, RED);
+        assert_eq!(Some(RED), canvas.try_get_pixel(2, 1));
+        assert_eq!(Some(Color::default()), canvas.try_get_pixel(0, 0));
+        assert_eq!(None, canvas.try_get_pixel(3, 0));
+        assert_eq!(None, canvas.try_get_pixel(0, 2));
+        assert_eq!(None, canvas.try_get_pixel(u32::MAX, u32::MAX));
+    }
+
+    #[test]
+    fn try_set() {
+        let mut canvas = Canvas::new(3, 2);
+        assert_eq!(Ok(()), canvas.try_set_pixel(1, 1, RED));
+        assert_eq!(RED, canvas.get_pixel_at(1, 1));
+        assert_eq!(
+            Err(OutOfBounds {
+                x: 3,
+                y: 1,
+                width: 3,
+                height: 2
+            }),
+            canvas.try_set_pixel(3, 1, RED)
+        );
+        assert_eq!(1, canvas.raw_buffer.iter().filter(|&&c| c == RED).count());
+    }
+
+    #[test]
+    fn clipped() {
+        let mut canvas = Canvas::new(3, 2);
+        for (x, y) in [(-1, 0), (0, -1), (3, 0), (0, 2), (i32::MIN, i32::MAX)] {
+            canvas.set_pixel_clipped(x, y, RED);
+            canvas.blend_pixel_clipped(x, y, RED);
+            assert_eq!(None, canvas.get_pixel_clipped(x, y));
+        }
+        assert!(!canvas.raw_buffer.contains(&RED));
+
+        canvas.set_pixel_clipped(2, 1, RED);
+        assert_eq!(Some(RED), canvas.get_pixel_clipped(2, 1));
+        canvas.blend_pixel_clipped(0, 0, Color::new_with_alpha(1.0, 0.0, 0.0, 0.5));
+        assert_eq!(0.5, canvas.get_pixel_at(0, 0).tuple[3]);
+    }
+
+    #[test]
+    fn sample() {
+        let mut canvas = Canvas::new(2, 2);
+        canvas.set_pixel_at(0, 0, RED);
+        assert_eq!(RED, canvas.sample_pixel(-1, -1, EdgeMode::Clamp));
+        assert_eq!(RED, canvas.sample_pixel(2, 2, EdgeMode::Wrap));
+        assert_eq!(RED, canvas.sample_pixel(-1, 3, EdgeMode::Mirror));
+        assert_eq!(
+            Color::default(),
+            canvas.sample_pixel(2, 0, EdgeMode::Mirror)
+        );
+        let blue = Color::new(0.0, 0.0, 1.0);
+        assert_eq!(blue, canvas.sample_pixel(0, -1, EdgeMode::Border(blue)));
+        assert_eq!(RED, canvas.sample_pixel(0, 0, EdgeMode::Border(blue)));
+    }
+
+    #[test]
+    fn sample_empty_canvas() {
+        let canvas = Canvas::new(0, 0);
+        assert_eq!(Color::default(), canvas.sample_pixel(0, 0, EdgeMode::Clamp));
+        let blue = Color::new(0.0, 0.0, 1.0);
+        assert_eq!(blue, canvas.sample_pixel(0, 0, EdgeMode::Border(blue)));
+    }
+
+    #[test]
+    fn other_pixel_types() {
+        let mut depth = Canvas::filled(2, 2, f32::INFINITY);
+        assert_eq!(Ok(()), depth.try_set_pixel(1, 0, 2.5));
+        assert_eq!(Some(2.5), depth.try_get_pixel(1, 0));
+        depth.set_pixel_clipped(-1, 0, 1.0);
+        assert_eq!(2.5, depth.sample_pixel(5, -3, EdgeMode::Clamp));
+        let white = Color::new(1.0, 1.0, 1.0);
+        let border = depth.sample_pixel(-1, 0, EdgeMode::Border(white));
+        assert!((border - 1.0).abs() < 1e-5);
+    }
 }
@@ -0,0 +1,506 @@
+use std::fmt::Display;
+
+use super::{
+    canvas::{Canvas, EdgeMode},
+    pixel::Pixel,
+};
+use crate::core3d::{color::Color, color_space::delta_e};
+
+/// Standard deviation in pixels of the Gaussian window SSIM compares local statistics in
+const SSIM_SIGMA: f32 = 1.5;
+/// Stabilizes the SSIM luminance term for dark areas, `(0.01 * L)²` for a dynamic range `L` of 1
+const SSIM_C1: f32 = 0.0001;
+/// Stabilizes the SSIM contrast term for flat areas, `(0.03 * L)²` for a dynamic range `L` of 1
+const SSIM_C2: f32 = 0.0009;
+/// Heatmap colors from no difference to the largest difference, spaced evenly
+const HEATMAP: [Color; 5] = [
+    Color::new(0.0, 0.0, 0.0),
+    Color::new(0.0, 0.0, 1.0),
+    Color::new(0.0, 1.0, 0.0),
+    Color::new(1.0, 1.0, 0.0),
+    Color::new(1.0, 0.0, 0.0),
+];
+
+/// Error returned when comparing canvases of different sizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeMismatch {
+    pub expected: (u32, u32),
+    pub actual: (u32, u32),
+}
+
+impl Display for SizeMismatch {
+    /// Returns a human readable description of the error
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::graphics2d::diff::SizeMismatch;
+    /// let error = SizeMismatch { expected: (8, 4), actual: (4, 8) };
+    /// assert_eq!("Expected a 8x4 canvas, got 4x8", error.to_string());
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Expected a {}x{} canvas, got {}x{}",
+            self.expected.0, self.expected.1, self.actual.0, self.actual.1
+        )
+    }
+}
+
+impl std::error::Error for SizeMismatch {}
+
+/// Whole image metrics of how much a canvas differs from a reference, all four channels including alpha
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageDiff {
+    /// Number of pixels with a channel differing by more than the epsilon compared with
+    pub differing_pixels: usize,
+    /// Largest absolute difference of any channel of any pixel
+    pub max_difference: f32,
+    /// Root mean square difference of all channels
+    pub rmse: f32,
+    /// Peak signal to noise ratio in decibels for a peak of 1, infinite for identical canvases
+    pub psnr: f32,
+    /// Mean structural similarity of the luminance, 1 for identical canvases
+    pub ssim: f32,
+}
+
+impl<P: Pixel> Canvas<P> {
+    /// Returns an error unless both canvases have the same size
+    fn check_size<Q: Pixel>(&self, other: &Canvas<Q>) -> Result<(), SizeMismatch> {
+        if (self.width, self.height) == (other.width, other.height) {
+            Ok(())
+        } else {
+            Err(SizeMismatch {
+                expected: (self.width, self.height),
+                actual: (other.width, other.height),
+            })
+        }
+    }
+
+    /// Returns the colors of both canvases pixel by pixel
+    fn color_pairs<'a, Q: Pixel>(
+        &'a self,
+        other: &'a Canvas<Q>,
+    ) -> impl Iterator<Item = (Color, Color)> + 'a {
+        self.raw_buffer
+            .iter()
+            .zip(&other.raw_buffer)
+            .map(|(a, b)| (a.to_color(), b.to_color()))
+    }
+
+    /// Compares the canvas to a reference of the same size, counting the pixels differing by more than `epsilon`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SizeMismatch`] if the canvases have different sizes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let reference = Canvas::filled(4, 4, Color::new(0.5, 0.5, 0.5));
+    /// let mut render = Canvas::filled(4, 4, Color::new(0.5, 0.5, 0.5));
+    /// render.set_pixel_at(1, 2, Color::new(0.52, 0.5, 0.5));
+    /// render.set_pixel_at(3, 3, Color::new(0.9, 0.5, 0.5));
+    ///
+    /// let diff = render.compare(&reference, 0.05).unwrap();
+    /// assert_eq!(1, diff.differing_pixels);
+    /// assert!((diff.max_difference - 0.4).abs() < 1e-6);
+    /// assert!(diff.psnr > 20.0 && diff.ssim < 1.0);
+    /// assert!(render.compare(&Canvas::new(4, 3), 0.05).is_err());
+    /// ```
+    pub fn compare<Q: Pixel>(
+        &self,
+        reference: &Canvas<Q>,
+        epsilon: f32,
+    ) -> Result<ImageDiff, SizeMismatch> {
+        let rmse = self.rmse(reference)?;
+        let mut differing_pixels = 0;
+        let mut max_difference: f32 = 0.0;
+        for (a, b) in self.color_pairs(reference) {
+            let difference = a
+                .tuple
+                .iter()
+                .zip(b.tuple)
+                .fold(0.0_f32, |max, (a, b)| max.max((a - b).abs()));
+            if difference > epsilon {
+                differing_pixels += 1;
+            }
+            max_difference = max_difference.max(difference);
+        }
+        Ok(ImageDiff {
+            differing_pixels,
+            max_difference,
+            rmse,
+            psnr: -20.0 * rmse.log10(),
+            ssim: self.ssim(reference)?,
+        })
+    }
+
+    /// Returns the root mean square difference of all channels to a reference of the same size, 0 for empty
+    /// canvases
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SizeMismatch`] if the canvases have different sizes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let a = Canvas::filled(2, 2, Color::new_with_alpha(0.5, 0.5, 0.5, 0.5));
+    /// let b = Canvas::filled(2, 2, Color::new_with_alpha(0.0, 0.0, 0.0, 0.0));
+    /// assert_eq!(Ok(0.5), a.rmse(&b));
+    /// ```
+    pub fn rmse<Q: Pixel>(&self, reference: &Canvas<Q>) -> Result<f32, SizeMismatch> {
+        self.check_size(reference)?;
+        if self.raw_buffer.is_empty() {
+            return Ok(0.0);
+        }
+        let sum: f64 = self
+            .color_pairs(reference)
+            .flat_map(|(a, b)| a.tuple.into_iter().zip(b.tuple))
+            .map(|(a, b)| f64::from(a - b).powi(2))
+            .sum();
+        Ok((sum / (self.raw_buffer.len() * 4) as f64).sqrt() as f32)
+    }
+
+    /// Returns the peak signal to noise ratio in decibels to a reference of the same size, for a peak value of 1
+    ///
+    /// Higher is closer, identical canvases give infinity
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SizeMismatch`] if the canvases have different sizes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let a = Canvas::filled(2, 2, Color::new_with_alpha(0.1, 0.1, 0.1, 0.1));
+    /// let b = Canvas::filled(2, 2, Color::new_with_alpha(0.0, 0.0, 0.0, 0.0));
+    /// assert!((a.psnr(&b).unwrap() - 20.0).abs() < 1e-4);
+    /// assert_eq!(Ok(f32::INFINITY), a.psnr(&a));
+    /// ```
+    pub fn psnr<Q: Pixel>(&self, reference: &Canvas<Q>) -> Result<f32, SizeMismatch> {
+        Ok(-20.0 * self.rmse(reference)?.log10())
+    }
+
+    /// Returns the mean structural similarity of the luminance to a reference of the same size, comparing mean,
+    /// contrast and structure within Gaussian windows
+    ///
+    /// Unlike RMSE it follows how noticeable differences are, 1 meaning identical and lower values less similar.
+    /// Empty canvases give 1
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SizeMismatch`] if the canvases have different sizes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut stripes = Canvas::filled(8, 8, Color::new(0.0, 0.0, 0.0));
+    /// for x in (0..8).step_by(2) {
+    ///     stripes.fill_rect((x, 0), 1, 8, Color::new(1.0, 1.0, 1.0));
+    /// }
+    /// let gray = Canvas::filled(8, 8, Color::new(0.5, 0.5, 0.5));
+    /// assert_eq!(Ok(1.0), stripes.ssim(&stripes));
+    /// assert!(stripes.ssim(&gray).unwrap() < 0.1);
+    /// ```
+    pub fn ssim<Q: Pixel>(&self, reference: &Canvas<Q>) -> Result<f32, SizeMismatch> {
+        self.check_size(reference)?;
+        if self.raw_buffer.is_empty() {
+            return Ok(1.0);
+        }
+        // Packs the luminances, their squares and their product into color channels to blur them all at once
+        let (moments, products): (Vec<Color>, Vec<Color>) = self
+            .color_pairs(reference)
+            .map(|(a, b)| {
+                let (x, y) = (f32::from_color(a), f32::from_color(b));
+                (
+                    Color::from([x, y, x * x, y * y]),
+                    Color::from([x * y, 0.0, 0.0, 0.0]),
+                )
+            })
+            .unzip();
+        let blur = |raw_buffer| {
+            let canvas = Canvas {
+                width: self.width,
+                height: self.height,
+                raw_buffer,
+            };
+            canvas
+                .gaussian_blur_channels(SSIM_SIGMA, EdgeMode::Mirror)
+                .raw_buffer
+        };
+        let sum: f64 = blur(moments)
+            .into_iter()
+            .zip(blur(products))
+            .map(|(moments, products)| {
+                let [mean_x, mean_y, square_x, square_y] = moments.tuple;
+                let variance_x = square_x - mean_x * mean_x;
+                let variance_y = square_y - mean_y * mean_y;
+                let covariance = products.tuple[0] - mean_x * mean_y;
+                let similarity = ((2.0 * mean_x * mean_y + SSIM_C1) * (2.0 * covariance + SSIM_C2))
+                    / ((mean_x * mean_x + mean_y * mean_y + SSIM_C1)
+                        * (variance_x + variance_y + SSIM_C2));
+                f64::from(similarity)
+            })
+            .sum();
+        Ok((sum / self.raw_buffer.len() as f64) as f32)
+    }
+
+    /// Creates a new single channel canvas holding the perceived CIEDE2000 difference of each pixel to a
+    /// reference of the same size, where a value of about 1 is just noticeable
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SizeMismatch`] if the canvases have different sizes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut render = Canvas::filled(2, 1, Color::new(0.2, 0.2, 0.2));
+    /// render.set_pixel_at(1, 0, Color::new(0.2, 0.3, 0.2));
+    /// let differences = render.delta_e_map(&Canvas::filled(2, 1, Color::new(0.2, 0.2, 0.2))).unwrap();
+    /// assert_eq!(0.0, differences.get_pixel_at(0, 0));
+    /// assert!(differences.get_pixel_at(1, 0) > 5.0);
+    /// ```
+    pub fn delta_e_map<Q: Pixel>(
+        &self,
+        reference: &Canvas<Q>,
+    ) -> Result<Canvas<f32>, SizeMismatch> {
+        self.check_size(reference)?;
+        Ok(Canvas {
+            width: self.width,
+            height: self.height,
+            raw_buffer: self
+                .color_pairs(reference)
+                .map(|(a, b)| delta_e(a, b))
+                .collect(),
+        })
+    }
+
+    /// Creates a new canvas showing where the canvas differs from a reference of the same size, from black for
+    /// identical pixels over blue, green and yellow to red for a CIEDE2000 difference of `max_delta_e` or more
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SizeMismatch`] if the canvases have different sizes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut render = Canvas::filled(2, 1, Color::new(0.2, 0.2, 0.2));
+    /// render.set_pixel_at(1, 0, Color::new(1.0, 1.0, 1.0));
+    /// let heatmap = render.diff_heatmap(&Canvas::filled(2, 1, Color::new(0.2, 0.2, 0.2)), 10.0).unwrap();
+    /// assert_eq!(Color::new(0.0, 0.0, 0.0), heatmap.get_pixel_at(0, 0));
+    /// assert_eq!(Color::new(1.0, 0.0, 0.0), heatmap.get_pixel_at(1, 0));
+    /// ```
+    pub fn diff_heatmap<Q: Pixel>(
+        &self,
+        reference: &Canvas<Q>,
+        max_delta_e: f32,
+    ) -> Result<Canvas, SizeMismatch> {
+        let differences = self.delta_e_map(reference)?;
+        Ok(Canvas {
+            width: self.width,
+            height: self.height,
+            raw_buffer: differences
+                .raw_buffer
+                .into_iter()
+                .map(|difference| heatmap_color(difference / max_delta_e))
+                .collect(),
+        })
+    }
+}
+
+/// Returns the heatmap color of a value in 0..1, clamping values outside
+fn heatmap_color(value: f32) -> Color {
+    // Differences so large that they overflowed are as different as it gets
+    let value = if value.is_nan() {
+        1.0
+    } else {
+        value.clamp(0.0, 1.0)
+    };
+    let position = value * (HEATMAP.len() - 1) as f32;
+    let index = (position as usize).min(HEATMAP.len() - 2);
+    let t = position - index as f32;
+    let (from, to) = (HEATMAP[index].tuple, HEATMAP[index + 1].tuple);
+    let [r, g, b, _] = std::array::from_fn::<f32, 4, _>(|i| from[i] + (to[i] - from[i]) * t);
+    Color::new(r, g, b)
+}
+
+#[cfg(test)]
+mod tests_size_mismatch {
+    use super::*;
+
+    #[test]
+    fn every_metric() {
+        let a = Canvas::new(3, 2);
+        let b = Canvas::new(2, 3);
+        let error = SizeMismatch {
+            expected: (3, 2),
+            actual: (2, 3),
+        };
+        assert_eq!(Err(error), a.rmse(&b));
+        assert_eq!(Err(error), a.psnr(&b));
+        assert_eq!(Err(error), a.ssim(&b));
+        assert_eq!(Err(error), a.compare(&b, 0.0));
+        assert_eq!(error, a.delta_e_map(&b).unwrap_err());
+        assert_eq!(error, a.diff_heatmap(&b, 1.0).unwrap_err());
+    }
+}
+
+#[cfg(test)]
+mod tests_metrics {
+    use super::*;
+    use crate::graphics2d::fixtures::gradient;
+
+    /// Deterministic noise in -amount..amount
+    fn noisy(canvas: &Canvas, amount: f32, seed: u32) -> Canvas {
+        let mut state = seed;
+        let raw_buffer = canvas
+            .raw_buffer
+            .iter()
+            .map(|&color| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let noise = ((state >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0) * amount;
+                let [r, g, b, a] = color.tuple;
+                Color::new_with_alpha(r + noise, g + noise, b + noise, a)
+            })
+            .collect();
+        Canvas {
+            width: canvas.width,
+            height: canvas.height,
+            raw_buffer,
+        }
+    }
+
+    #[test]
+    fn identical() {
+        let canvas = gradient(16, 16);
+        let diff = canvas.compare(&canvas, 0.0).unwrap();
+        assert_eq!(
+            ImageDiff {
+                differing_pixels: 0,
+                max_difference: 0.0,
+                rmse: 0.0,
+                psnr: f32::INFINITY,
+                ssim: 1.0,
+            },
+            diff
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let canvas = Canvas::new(0, 5);
+        let diff = canvas.compare(&canvas, 0.0).unwrap();
+        assert_eq!(0.0, diff.rmse);
+        assert_eq!(1.0, diff.ssim);
+        assert!(canvas
+            .diff_heatmap(&canvas, 1.0)
+            .unwrap()
+            .raw_buffer
+            .is_empty());
+    }
+
+    #[test]
+    fn rmse() {
+        let a = Canvas::filled(2, 1, Color::new(0.0, 0.0, 0.0));
+        let mut b = Canvas::filled(2, 1, Color::new(0.0, 0.0, 0.0));
+        b.set_pixel_at(0, 0, Color::new(0.8, 0.0, 0.0));
+        // One channel of eight differs
+        assert!((a.rmse(&b).unwrap() - 0.08_f32.sqrt()).abs() < 1e-6);
+        assert_eq!(a.rmse(&b), b.rmse(&a));
+        assert!((a.psnr(&b).unwrap() - 10.9691).abs() < 1e-3);
+    }
+
+    #[test]
+    fn noise_tolerance() {
+        let reference = gradient(16, 16);
+        let slightly = noisy(&reference, 0.01, 1);
+        let heavily = noisy(&reference, 0.2, 2);
+        let slight = slightly.compare(&reference, 0.01).unwrap();
+        let heavy = heavily.compare(&reference, 0.01).unwrap();
+        assert_eq!(0, slight.differing_pixels);
+        assert!(heavy.differing_pixels > 200);
+        assert!(slight.rmse < heavy.rmse);
+        assert!(slight.psnr > 40.0);
+        assert!(heavy.psnr < 25.0);
+        assert!(slight.ssim > 0.99);
+        assert!(heavy.ssim < slight.ssim);
+    }
+
+    #[test]
+    fn ssim_follows_structure() {
+        let reference = gradient(16, 16);
+        // A uniform brightness shift has a larger RMSE than noise but keeps the structure
+        let shifted = Canvas {
+            width: reference.width,
+            height: reference.height,
+            raw_buffer: reference
+                .raw_buffer
+                .iter()
+                .map(|&c| c + Color::new(0.05, 0.05, 0.05))
+                .collect(),
+        };
+        let noise = noisy(&reference, 0.05, 3);
+        assert!(shifted.rmse(&reference).unwrap() > noise.rmse(&reference).unwrap());
+        assert!(shifted.ssim(&reference).unwrap() > noise.ssim(&reference).unwrap());
+    }
+
+    #[test]
+    fn other_pixel_types() {
+        let bytes = Canvas::from_buffer(2, 1, vec![[255_u8, 0, 0, 255], [0, 0, 0, 255]]).unwrap();
+        let mut colors = Canvas::filled(2, 1, Color::new(0.0, 0.0, 0.0));
+        colors.set_pixel_at(0, 0, Color::new(1.0, 0.0, 0.0));
+        assert_eq!(Ok(0.0), bytes.rmse(&colors));
+        assert_eq!(0, colors.compare(&bytes, 0.0).unwrap().differing_pixels);
+    }
+}
+
+#[cfg(test)]
+mod tests_heatmap {
+    use super::*;
+
+    #[test]
+    fn ramp() {
+        assert_eq!(Color::new(0.0, 0.0, 0.0), heatmap_color(0.0));
+        assert_eq!(Color::new(0.0, 0.0, 0.5), heatmap_color(0.125));
+        assert_eq!(Color::new(0.0, 1.0, 0.0), heatmap_color(0.5));
+        assert_eq!(Color::new(1.0, 0.5, 0.0), heatmap_color(0.875));
+        assert_eq!(Color::new(1.0, 0.0, 0.0), heatmap_color(1.0));
+        assert_eq!(Color::new(1.0, 0.0, 0.0), heatmap_color(50.0));
+        assert_eq!(Color::new(0.0, 0.0, 0.0), heatmap_color(-1.0));
+        assert_eq!(Color::new(1.0, 0.0, 0.0), heatmap_color(f32::NAN));
+    }
+
+    #[test]
+    fn delta_e() {
+        let reference = Canvas::filled(3, 1, Color::new(0.2, 0.2, 0.2));
+        let mut render = Canvas::filled(3, 1, Color::new(0.2, 0.2, 0.2));
+        render.set_pixel_at(1, 0, Color::new(0.21, 0.2, 0.2));
+        render.set_pixel_at(2, 0, Color::new(0.0, 0.0, 0.0));
+        let differences = render.delta_e_map(&reference).unwrap();
+        assert_eq!(0.0, differences.get_pixel_at(0, 0));
+        assert!(differences.get_pixel_at(1, 0) > 0.0);
+        assert!(differences.get_pixel_at(1, 0) < differences.get_pixel_at(2, 0));
+
+        let heatmap = render.diff_heatmap(&reference, 100.0).unwrap();
+        assert_eq!(Color::new(0.0, 0.0, 0.0), heatmap.get_pixel_at(0, 0));
+        let slight = heatmap.get_pixel_at(1, 0);
+        assert!(slight.tuple[2] > 0.0 && slight.tuple[0] == 0.0);
+        assert!(heatmap.get_pixel_at(2, 0).tuple[1] > 0.0);
+    }
+}
@@ -0,0 +1,462 @@
+use std::sync::OnceLock;
+
+use super::canvas::Canvas;
+use crate::core3d::color::Color;
+
+/// Number of bits of each coordinate indexing the Bayer matrix, giving 8x8 thresholds
+const BAYER_BITS: u32 = 3;
+/// Width and height of the tiled blue noise threshold map
+const BLUE_NOISE_SIZE: usize = 32;
+/// Standard deviation in pixels of the Gaussian measuring how clustered the blue noise points are
+const BLUE_NOISE_SIGMA: f32 = 1.5;
+/// Share of pixels set in the initial blue noise pattern
+const BLUE_NOISE_INITIAL: usize = BLUE_NOISE_SIZE * BLUE_NOISE_SIZE / 10;
+
+/// Way of hiding the banding left when reducing colors to a few levels or a palette
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Dither {
+    /// Rounds every pixel to the closest color, showing bands in smooth gradients
+    #[default]
+    None,
+    /// Offsets pixels by an 8x8 Bayer matrix before rounding, a regular crosshatch pattern
+    Bayer,
+    /// Offsets pixels by a tiled blue noise map before rounding, an even grain without visible pattern
+    BlueNoise,
+    /// Spreads the rounding error of each pixel over the neighbours that are not rounded yet
+    FloydSteinberg,
+}
+
+/// Returns the threshold of the Bayer matrix in 0..1 at a pixel, the matrix repeating every 8 pixels
+///
+/// Each bit of the coordinates picks a cell of the 2x2 matrix `[[0, 2], [3, 1]]`, the lowest bits weighing most
+fn bayer(x: u32, y: u32) -> f32 {
+    let rank = (0..BAYER_BITS).fold(0, |rank, bit| {
+        let cell = (((x ^ y) >> bit) & 1) << 1 | ((y >> bit) & 1);
+        rank | cell << (2 * (BAYER_BITS - 1 - bit))
+    });
+    (rank as f32 + 0.5) / (1 << (2 * BAYER_BITS)) as f32
+}
+
+/// Returns the blue noise threshold map in 0..1 row by row, created once by Ulichney's void-and-cluster method
+///
+/// Points are ranked so that each prefix of them is spread as evenly as possible over the tiling map
+fn blue_noise() -> &'static [f32] {
+    static BLUE_NOISE: OnceLock<Vec<f32>> = OnceLock::new();
+    BLUE_NOISE.get_or_init(|| {
+        const N: usize = BLUE_NOISE_SIZE * BLUE_NOISE_SIZE;
+        // Energy one point adds at each offset, wrapping around the edges so the map tiles seamlessly
+        let kernel: Vec<f32> = (0..N)
+            .map(|i| {
+                let wrap = |d: usize| d.min(BLUE_NOISE_SIZE - d) as f32;
+                let (dx, dy) = (wrap(i % BLUE_NOISE_SIZE), wrap(i / BLUE_NOISE_SIZE));
+                (-(dx * dx + dy * dy) / (2.0 * BLUE_NOISE_SIGMA * BLUE_NOISE_SIGMA)).exp()
+            })
+            .collect();
+        let offset = |a: usize, b: usize| {
+            let dx =
+                (a % BLUE_NOISE_SIZE + BLUE_NOISE_SIZE - b % BLUE_NOISE_SIZE) % BLUE_NOISE_SIZE;
+            let dy =
+                (a / BLUE_NOISE_SIZE + BLUE_NOISE_SIZE - b / BLUE_NOISE_SIZE) % BLUE_NOISE_SIZE;
+            dy * BLUE_NOISE_SIZE + dx
+        };
+        let toggle = |points: &mut [bool], energy: &mut [f32], at: usize| {
+            points[at] = !points[at];
+            let sign = if points[at] { 1.0 } else { -1.0 };
+            for (i, e) in energy.iter_mut().enumerate() {
+                *e += sign * kernel[offset(i, at)];
+            }
+        };
+        // The tightest cluster is the set point with the most energy, the largest void the empty one with the least
+        let tightest = |points: &[bool], energy: &[f32]| {
+            (0..N)
+                .filter(|&i| points[i])
+                .max_by(|&a, &b| energy[a].total_cmp(&energy[b]))
+                .unwrap()
+        };
+        let largest_void = |points: &[bool], energy: &[f32]| {
+            (0..N)
+                .filter(|&i| !points[i])
+                .min_by(|&a, &b| energy[a].total_cmp(&energy[b]))
+                .unwrap()
+        };
+
+        // Start from a few random points, moving points from clusters to voids until they are spread evenly
+        let mut points = vec![false; N];
+        let mut energy = vec![0.0; N];
+        let mut state = 0x2545_f491_u32;
+        while points.iter().filter(|&&p| p).count() < BLUE_NOISE_INITIAL {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let at = state as usize % N;
+            if !points[at] {
+                toggle(&mut points, &mut energy, at);
+            }
+        }
+        loop {
+            let cluster = tightest(&points, &energy);
+            toggle(&mut points, &mut energy, cluster);
+            let void = largest_void(&points, &energy);
+            toggle(&mut points, &mut energy, void);
+            if void == cluster {
+                break;
+            }
+        }
+
+        // Ranks the initial points by removing the tightest cluster first, then fills the voids in order
+        let mut ranks = vec![0; N];
+        let (mut removed, mut removed_energy) = (points.clone(), energy.clone());
+        for rank in (0..BLUE_NOISE_INITIAL).rev() {
+            let cluster = tightest(&removed, &removed_energy);
+            toggle(&mut removed, &mut removed_energy, cluster);
+            ranks[cluster] = rank;
+        }
+        for rank in BLUE_NOISE_INITIAL..N {
+            let void = largest_void(&points, &energy);
+            toggle(&mut points, &mut energy, void);
+            ranks[void] = rank;
+        }
+        ranks
+            .into_iter()
+            .map(|rank| (rank as f32 + 0.5) / N as f32)
+            .collect()
+    })
+}
+
+/// Returns the threshold in 0..1 a pixel is offset by for ordered dithering
+fn threshold(dither: Dither, x: u32, y: u32) -> f32 {
+    match dither {
+        Dither::Bayer => bayer(x, y),
+        Dither::BlueNoise => {
+            let (x, y) = (x as usize % BLUE_NOISE_SIZE, y as usize % BLUE_NOISE_SIZE);
+            blue_noise()[y * BLUE_NOISE_SIZE + x]
+        }
+        Dither::None | Dither::FloydSteinberg => 0.5,
+    }
+}
+
+#[cfg(test)]
+mod tests_thresholds {
+    use super::*;
+
+    fn ranks(thresholds: &[f32]) -> Vec<usize> {
+        let size = thresholds.len() as f32;
+        let mut ranks: Vec<usize> = thresholds
+            .iter()
+            .map(|t| (t * size - 0.5).round() as usize)
+            .collect();
+        ranks.sort_unstable();
+        ranks
+    }
+
+    #[test]
+    fn bayer_matrix() {
+        let first_row: Vec<f32> = (0..8).map(|x| bayer(x, 0) * 64.0 - 0.5).collect();
+        assert_eq!(vec![0.0, 32.0, 8.0, 40.0, 2.0, 34.0, 10.0, 42.0], first_row);
+        assert_eq!(21.0, bayer(7, 7) * 64.0 - 0.5);
+        let matrix: Vec<f32> = (0..64).map(|i| bayer(i % 8, i / 8)).collect();
+        assert_eq!((0..64).collect::<Vec<_>>(), ranks(&matrix));
+        assert_eq!(bayer(3, 5), bayer(11, 21));
+    }
+
+    #[test]
+    fn blue_noise_ranks() {
+        let map = blue_noise();
+        assert_eq!(BLUE_NOISE_SIZE * BLUE_NOISE_SIZE, map.len());
+        assert_eq!((0..map.len()).collect::<Vec<_>>(), ranks(map));
+        assert_eq!(
+            threshold(Dither::BlueNoise, 5, 7),
+            threshold(Dither::BlueNoise, 37, 71)
+        );
+    }
+
+    #[test]
+    fn blue_noise_is_even() {
+        let map = blue_noise();
+        // Every block holds about as many low as high thresholds
+        for block_y in (0..BLUE_NOISE_SIZE).step_by(8) {
+            for block_x in (0..BLUE_NOISE_SIZE).step_by(8) {
+                let sum: f32 = (0..64)
+                    .map(|i| map[(block_y + i / 8) * BLUE_NOISE_SIZE + block_x + i % 8])
+                    .sum();
+                assert!((sum / 64.0 - 0.5).abs() < 0.05, "{block_x}, {block_y}");
+            }
+        }
+        // Neighbours differ more than they would in white noise, where the mean difference is a third
+        let difference: f32 = (0..map.len())
+            .map(|i| (map[i] - map[(i + 1) % map.len()]).abs())
+            .sum::<f32>()
+            / map.len() as f32;
+        assert!(difference > 0.36, "{difference}");
+    }
+}
+
+impl Canvas {
+    /// Creates a new canvas with the color channels replaced by colors from `nearest`, dithered, keeping alpha
+    ///
+    /// Ordered dithering offsets each channel by up to half of `spread`, the distance between neighbouring colors
+    fn quantize_with(
+        &self,
+        dither: Dither,
+        spread: f32,
+        nearest: impl Fn([f32; 3]) -> [f32; 3],
+    ) -> Self {
+        let width = self.width as usize;
+        let mut errors = vec![
+            [0.0_f32; 3];
+            if dither == Dither::FloydSteinberg {
+                self.raw_buffer.len()
+            } else {
+                0
+            }
+        ];
+        let raw_buffer = self
+            .raw_buffer
+            .iter()
+            .enumerate()
+            .map(|(i, color)| {
+                let [r, g, b, a] = color.tuple;
+                let (x, y) = ((i % width) as u32, (i / width) as u32);
+                let rgb = [r, g, b].map(|c| c.clamp(0.0, 1.0));
+                let quantized = match dither {
+                    Dither::None => nearest(rgb),
+                    Dither::Bayer | Dither::BlueNoise => {
+                        let offset = (threshold(dither, x, y) - 0.5) * spread;
+                        nearest(rgb.map(|c| c + offset))
+                    }
+                    Dither::FloydSteinberg => {
+                        let value =
+                            std::array::from_fn(|c| (rgb[c] + errors[i][c]).clamp(0.0, 1.0));
+                        let quantized = nearest(value);
+                        let error: [f32; 3] = std::array::from_fn(|c| value[c] - quantized[c]);
+                        let x = i % width;
+                        let below = i + width;
+                        let mut spread = |target: usize, weight: f32| {
+                            if let Some(e) = errors.get_mut(target) {
+                                (0..3).for_each(|c| e[c] += error[c] * weight);
+                            }
+                        };
+                        if x + 1 < width {
+                            spread(i + 1, 7.0 / 16.0);
+                        }
+                        if x > 0 {
+                            spread(below - 1, 3.0 / 16.0);
+                        }
+                        spread(below, 5.0 / 16.0);
+                        if x + 1 < width {
+                            spread(below + 1, 1.0 / 16.0);
+                        }
+                        quantized
+                    }
+                };
+                let [r, g, b] = quantized;
+                Color::new_with_alpha(r, g, b, a)
+            })
+            .collect();
+        Self {
+            width: self.width,
+            height: self.height,
+            raw_buffer,
+        }
+    }
+
+    /// Creates a new canvas with each color channel clamped to 0..1 and reduced to `levels` evenly spaced values,
+    /// `2^bits` levels matching an export with that many bits per channel. Alpha is kept
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer than two levels
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// # use rusty_ray_tracer::graphics2d::dither::Dither;
+    /// let canvas = Canvas::filled(8, 8, Color::new(0.25, 0.25, 0.25));
+    /// let rounded = canvas.quantize_levels(2, Dither::None);
+    /// assert!(rounded.raw_buffer.iter().all(|&c| c == Color::new(0.0, 0.0, 0.0)));
+    ///
+    /// // A quarter of the pixels light up, keeping the average brightness
+    /// let dithered = canvas.quantize_levels(2, Dither::Bayer);
+    /// let lit = dithered.raw_buffer.iter().filter(|&&c| c == Color::new(1.0, 1.0, 1.0)).count();
+    /// assert_eq!(16, lit);
+    /// ```
+    #[must_use]
+    pub fn quantize_levels(&self, levels: u32, dither: Dither) -> Self {
+        assert!(levels >= 2, "Quantization needs at least two levels!");
+        let steps = (levels - 1) as f32;
+        self.quantize_with(dither, 1.0 / steps, |rgb| {
+            rgb.map(|c| (c.clamp(0.0, 1.0) * steps).round() / steps)
+        })
+    }
+
+    /// Creates a new canvas with each color replaced by the closest color of a palette, keeping alpha
+    ///
+    /// Ordered dithering assumes the palette colors are about as far apart as in an evenly spaced palette of the
+    /// same size
+    ///
+    /// # Panics
+    ///
+    /// Panics if the palette is empty
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// # use rusty_ray_tracer::graphics2d::dither::Dither;
+    /// let palette = [Color::new(0.0, 0.0, 0.0), Color::new(1.0, 0.0, 0.0), Color::new(1.0, 1.0, 1.0)];
+    /// let canvas = Canvas::filled(4, 4, Color::new(0.9, 0.2, 0.1));
+    /// let mapped = canvas.quantize_palette(&palette, Dither::None);
+    /// assert!(mapped.raw_buffer.iter().all(|&c| c == Color::new(1.0, 0.0, 0.0)));
+    /// ```
+    #[must_use]
+    pub fn quantize_palette(&self, palette: &[Color], dither: Dither) -> Self {
+        assert!(
+            !palette.is_empty(),
+            "Quantization needs at least one palette color!"
+        );
+        let spread = 1.0 / ((palette.len() as f32).cbrt().max(2.0) - 1.0);
+        self.quantize_with(dither, spread, |rgb| {
+            let distance = |color: &Color| {
+                (0..3)
+                    .map(|c| (color.tuple[c] - rgb[c]).powi(2))
+                    .sum::<f32>()
+            };
+            let closest = palette
+                .iter()
+                .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+                .unwrap();
+            [closest.tuple[0], closest.tuple[1], closest.tuple[2]]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_quantize {
+    use super::*;
+    use crate::{core3d::color_rgb::ColorRGB, graphics2d::fixtures::gradient};
+
+    const DITHERS: [Dither; 4] = [
+        Dither::None,
+        Dither::Bayer,
+        Dither::BlueNoise,
+        Dither::FloydSteinberg,
+    ];
+
+    fn mean_red(canvas: &Canvas) -> f32 {
+        canvas.raw_buffer.iter().map(ColorRGB::r).sum::<f32>() / canvas.raw_buffer.len() as f32
+    }
+
+    #[test]
+    fn only_allowed_levels() {
+        let mut canvas = gradient(64, 4);
+        for color in &mut canvas.raw_buffer {
+            color.tuple[3] = 0.5;
+        }
+        for dither in DITHERS {
+            let quantized = canvas.quantize_levels(4, dither);
+            for color in &quantized.raw_buffer {
+                for c in [color.r(), color.g(), color.b()] {
+                    assert!(
+                        [0.0, 1.0 / 3.0, 2.0 / 3.0, 1.0].contains(&c),
+                        "{dither:?} {c}"
+                    );
+                }
+                assert_eq!(0.5, color.a());
+            }
+        }
+    }
+
+    #[test]
+    fn exact_levels_unchanged() {
+        let canvas = Canvas::filled(8, 8, Color::new(0.0, 0.5, 1.0));
+        for dither in DITHERS {
+            let quantized = canvas.quantize_levels(3, dither);
+            assert_eq!(canvas.raw_buffer, quantized.raw_buffer, "{dither:?}");
+        }
+    }
+
+    #[test]
+    fn dithering_keeps_average() {
+        for value in [0.1, 0.3, 0.5, 0.8] {
+            let canvas = Canvas::filled(32, 32, Color::new(value, 0.0, 0.0));
+            let rounded = canvas.quantize_levels(2, Dither::None);
+            assert!((mean_red(&rounded) - value).abs() > 0.05);
+            for dither in [Dither::Bayer, Dither::BlueNoise, Dither::FloydSteinberg] {
+                let dithered = canvas.quantize_levels(2, dither);
+                assert!(
+                    (mean_red(&dithered) - value).abs() < 0.02,
+                    "{dither:?} {value}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn clamps() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set_pixel_at(0, 0, Color::new(-1.0, 5.0, 0.5));
+        canvas.set_pixel_at(1, 0, Color::new(f32::INFINITY, 0.0, 0.0));
+        for dither in DITHERS {
+            let quantized = canvas.quantize_levels(256, dither);
+            assert_eq!(0.0, quantized.get_pixel_at(0, 0).r());
+            assert_eq!(1.0, quantized.get_pixel_at(0, 0).g());
+            assert_eq!(1.0, quantized.get_pixel_at(1, 0).r());
+        }
+    }
+
+    #[test]
+    fn bit_depths() {
+        let canvas = gradient(64, 4);
+        for bits in 1..=8 {
+            let levels = 1 << bits;
+            let quantized = canvas.quantize_levels(levels, Dither::None);
+            let steps = (levels - 1) as f32;
+            assert!(quantized
+                .raw_buffer
+                .iter()
+                .all(|c| (c.r() * steps).fract() == 0.0));
+        }
+    }
+
+    #[test]
+    #[should_panic = "Quantization needs at least two levels!"]
+    fn one_level() {
+        let _ = Canvas::new(1, 1).quantize_levels(1, Dither::None);
+    }
+
+    #[test]
+    fn palette() {
+        let palette = [Color::new(0.0, 0.0, 0.0), Color::new(1.0, 1.0, 1.0)];
+        let canvas = Canvas::filled(16, 16, Color::new_with_alpha(0.25, 0.25, 0.25, 0.75));
+        for dither in [Dither::Bayer, Dither::BlueNoise, Dither::FloydSteinberg] {
+            let mapped = canvas.quantize_palette(&palette, dither);
+            assert!(mapped
+                .raw_buffer
+                .iter()
+                .all(|c| c.r() == 0.0 || c.r() == 1.0));
+            assert!(mapped.raw_buffer.iter().all(|c| c.a() == 0.75));
+            assert!((mean_red(&mapped) - 0.25).abs() < 0.02, "{dither:?}");
+        }
+        let rounded = canvas.quantize_palette(&palette, Dither::None);
+        assert_eq!(0.0, mean_red(&rounded));
+    }
+
+    #[test]
+    #[should_panic = "Quantization needs at least one palette color!"]
+    fn empty_palette() {
+        let _ = Canvas::new(1, 1).quantize_palette(&[], Dither::None);
+    }
+
+    #[test]
+    fn empty() {
+        for dither in DITHERS {
+            assert!(Canvas::new(0, 4)
+                .quantize_levels(2, dither)
+                .raw_buffer
+                .is_empty());
+        }
+    }
+}
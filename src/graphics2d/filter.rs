@@ -0,0 +1,741 @@
+use super::{
+    canvas::{Canvas, EdgeMode},
+    pixel::Pixel,
+};
+use crate::core3d::color::Color;
+
+/// Horizontal Sobel operator, responding to changes from left to right
+const SOBEL_X: [[f32; 3]; 3] = [[-1.0, 0.0, 1.0], [-2.0, 0.0, 2.0], [-1.0, 0.0, 1.0]];
+/// Vertical Sobel operator, responding to changes from top to bottom
+const SOBEL_Y: [[f32; 3]; 3] = [[-1.0, -2.0, -1.0], [0.0, 0.0, 0.0], [1.0, 2.0, 1.0]];
+
+/// Grid of weights a convolution multiplies the pixels around each pixel with, centered on that pixel
+#[derive(Clone, Debug, PartialEq)]
+pub struct Kernel {
+    width: u32,
+    height: u32,
+    /// Weights stored row by row
+    weights: Vec<f32>,
+}
+
+impl Kernel {
+    /// Creates a new kernel of `width` by `height` weights stored row by row
+    ///
+    /// Returns `None` if either size is even, leaving the kernel without a center, or if the number of weights
+    /// does not match the size
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::graphics2d::filter::Kernel;
+    /// assert!(Kernel::new(3, 1, vec![0.25, 0.5, 0.25]).is_some());
+    /// assert!(Kernel::new(2, 1, vec![0.5, 0.5]).is_none());
+    /// assert!(Kernel::new(3, 3, vec![1.0]).is_none());
+    /// ```
+    #[must_use]
+    pub fn new(width: u32, height: u32, weights: Vec<f32>) -> Option<Self> {
+        let odd = width % 2 == 1 && height % 2 == 1;
+        (odd && weights.len() as u64 == u64::from(width) * u64::from(height)).then_some(Self {
+            width,
+            height,
+            weights,
+        })
+    }
+
+    /// Creates a new 3x3 kernel boosting the difference of each pixel to its four neighbours by `amount`
+    ///
+    /// The weights sum up to 1, so flat areas keep their color
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::graphics2d::filter::Kernel;
+    /// let kernel = Kernel::sharpen(1.0);
+    /// assert_eq!(Kernel::new(3, 3, vec![0.0, -1.0, 0.0, -1.0, 5.0, -1.0, 0.0, -1.0, 0.0]), Some(kernel));
+    /// ```
+    #[must_use]
+    pub fn sharpen(amount: f32) -> Self {
+        Self {
+            width: 3,
+            height: 3,
+            weights: vec![
+                0.0,
+                -amount,
+                0.0,
+                -amount,
+                4.0f32.mul_add(amount, 1.0),
+                -amount,
+                0.0,
+                -amount,
+                0.0,
+            ],
+        }
+    }
+
+    /// Returns the number of weights per row
+    #[must_use]
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the number of rows
+    #[must_use]
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the weights stored row by row
+    #[must_use]
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+}
+
+/// Returns the normalized weights of a Gaussian with standard deviation `sigma`, cut off after three deviations
+/// or `max_radius` pixels, whichever comes first
+///
+/// A deviation of zero or less gives the single weight 1, leaving the image unchanged
+fn gaussian_weights(sigma: f32, max_radius: u32) -> Vec<f32> {
+    if sigma <= 0.0 || !sigma.is_finite() {
+        return vec![1.0];
+    }
+    let radius = (3.0 * sigma).ceil().min(max_radius as f32) as i64;
+    let weights: Vec<f32> = (-radius..=radius)
+        .map(|i| (-(i as f32).powi(2) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let sum: f32 = weights.iter().sum();
+    weights.into_iter().map(|w| w / sum).collect()
+}
+
+#[cfg(test)]
+mod tests_kernel {
+    use super::*;
+
+    #[test]
+    fn new() {
+        let kernel = Kernel::new(3, 1, vec![1.0, 2.0, 3.0]).unwrap();
+        assert_eq!((3, 1), (kernel.width(), kernel.height()));
+        assert_eq!([1.0, 2.0, 3.0], kernel.weights());
+        assert_eq!(None, Kernel::new(1, 2, vec![1.0, 2.0]));
+        assert_eq!(None, Kernel::new(0, 0, vec![]));
+        assert_eq!(None, Kernel::new(1, 1, vec![1.0, 2.0]));
+    }
+
+    #[test]
+    fn sharpen() {
+        let kernel = Kernel::sharpen(0.5);
+        assert_eq!(3.0, kernel.weights()[4]);
+        assert!((kernel.weights().iter().sum::<f32>() - 1.0).abs() < 1e-6);
+        assert_eq!(
+            Kernel::new(3, 3, [0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0].to_vec()),
+            Some(Kernel::sharpen(0.0))
+        );
+    }
+
+    #[test]
+    fn gaussian() {
+        let weights = gaussian_weights(1.0, u32::MAX);
+        assert_eq!(7, weights.len());
+        assert!((weights.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+        assert!(weights.windows(2).take(3).all(|w| w[0] < w[1]));
+        assert_eq!(weights[2], weights[4]);
+        assert_eq!(vec![1.0], gaussian_weights(0.0, u32::MAX));
+        assert_eq!(vec![1.0], gaussian_weights(-1.0, u32::MAX));
+        assert_eq!(vec![1.0], gaussian_weights(f32::NAN, u32::MAX));
+        assert_eq!(5, gaussian_weights(1.0, 2).len());
+        assert_eq!(9, gaussian_weights(1e30, 4).len());
+    }
+}
+
+impl<P: Pixel> Canvas<P> {
+    /// Creates a new canvas of the same size by computing each pixel from its signed coordinates
+    fn map_pixels<Q: Pixel>(&self, mut pixel: impl FnMut(i32, i32) -> Q) -> Canvas<Q> {
+        let (width, height) = self.signed_size();
+        Canvas {
+            width: self.width,
+            height: self.height,
+            raw_buffer: (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(|(x, y)| pixel(x, y))
+                .collect(),
+        }
+    }
+}
+
+impl Canvas {
+    /// Runs a filter on the canvas with premultiplied alpha and returns its result with straight alpha again, so
+    /// the color of transparent pixels does not bleed into their neighbours
+    fn premultiplied(&self, mode: EdgeMode, filter: impl FnOnce(&Self, EdgeMode) -> Self) -> Self {
+        let premultiplied = Self {
+            width: self.width,
+            height: self.height,
+            raw_buffer: self
+                .raw_buffer
+                .iter()
+                .map(|color| color.premultiply())
+                .collect(),
+        };
+        let mode = match mode {
+            EdgeMode::Border(color) => EdgeMode::Border(color.premultiply()),
+            mode => mode,
+        };
+        let mut filtered = filter(&premultiplied, mode);
+        filtered
+            .raw_buffer
+            .iter_mut()
+            .for_each(|color| *color = color.unpremultiply());
+        filtered
+    }
+
+    /// Convolves all four channels independently as plain numbers, such as premultiplied colors
+    fn convolve_channels(&self, kernel: &Kernel, mode: EdgeMode) -> Self {
+        let half_width = (kernel.width / 2) as i32;
+        let half_height = (kernel.height / 2) as i32;
+        self.map_pixels(|x, y| {
+            let mut sum = [0.0; 4];
+            for (row, weights) in kernel.weights.chunks(kernel.width as usize).enumerate() {
+                let sample_y = y.saturating_add(row as i32 - half_height);
+                for (column, &weight) in weights.iter().enumerate() {
+                    if weight == 0.0 {
+                        continue;
+                    }
+                    let sample_x = x.saturating_add(column as i32 - half_width);
+                    let sample = self.sample_pixel(sample_x, sample_y, mode);
+                    for (total, channel) in sum.iter_mut().zip(sample.tuple) {
+                        *total += weight * channel;
+                    }
+                }
+            }
+            Color::from(sum)
+        })
+    }
+
+    /// Creates a new canvas by replacing each pixel with the sum of the pixels around it multiplied by the weights
+    /// of a kernel, all four channels including alpha
+    ///
+    /// The kernel is applied as it is laid out, without flipping it. Pixels outside of the canvas are read
+    /// according to the edge mode. Colors are weighted by their alpha, so transparent pixels add no color
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::{Canvas, EdgeMode};
+    /// # use rusty_ray_tracer::graphics2d::filter::Kernel;
+    /// let mut canvas = Canvas::new(3, 1);
+    /// canvas.set_pixel_at(2, 0, Color::new(1.0, 1.0, 1.0));
+    /// let shift_left = Kernel::new(3, 1, vec![0.0, 0.0, 1.0]).unwrap();
+    /// let shifted = canvas.convolve(&shift_left, EdgeMode::Border(Color::new(0.0, 0.0, 0.0)));
+    /// assert_eq!(Color::new(1.0, 1.0, 1.0), shifted.get_pixel_at(1, 0));
+    /// assert_eq!(Color::new(0.0, 0.0, 0.0), shifted.get_pixel_at(2, 0));
+    /// ```
+    #[must_use]
+    pub fn convolve(&self, kernel: &Kernel, mode: EdgeMode) -> Self {
+        self.premultiplied(mode, |canvas, mode| canvas.convolve_channels(kernel, mode))
+    }
+
+    /// Convolves all four channels as plain numbers with a row of weights horizontally and then vertically, much
+    /// faster than the equivalent square kernel
+    fn convolve_separable_channels(&self, weights: &[f32], mode: EdgeMode) -> Self {
+        let size = weights.len() as u32;
+        let horizontal =
+            Kernel::new(size, 1, weights.to_vec()).expect("Separable kernel has an even size!");
+        let vertical =
+            Kernel::new(1, size, weights.to_vec()).expect("Separable kernel has an even size!");
+        self.convolve_channels(&horizontal, mode)
+            .convolve_channels(&vertical, mode)
+    }
+
+    /// Convolves premultiplied colors with a separable kernel
+    fn convolve_separable(&self, weights: &[f32], mode: EdgeMode) -> Self {
+        self.premultiplied(mode, |canvas, mode| {
+            canvas.convolve_separable_channels(weights, mode)
+        })
+    }
+
+    /// Blurs all four channels independently with a Gaussian, for canvases packing other data than colors
+    pub(super) fn gaussian_blur_channels(&self, sigma: f32, mode: EdgeMode) -> Self {
+        let weights = gaussian_weights(sigma, self.width.max(self.height));
+        self.convolve_separable_channels(&weights, mode)
+    }
+
+    /// Creates a new canvas with each pixel averaged with the pixels up to `radius` away horizontally and
+    /// vertically
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::{Canvas, EdgeMode};
+    /// let mut canvas = Canvas::filled(3, 3, Color::new(0.0, 0.0, 0.0));
+    /// canvas.set_pixel_at(1, 1, Color::new(9.0, 9.0, 9.0));
+    /// let blurred = canvas.box_blur(1, EdgeMode::Clamp);
+    /// assert_eq!(Color::new(1.0, 1.0, 1.0), blurred.get_pixel_at(0, 2));
+    /// ```
+    #[must_use]
+    pub fn box_blur(&self, radius: u16, mode: EdgeMode) -> Self {
+        let size = 2 * usize::from(radius) + 1;
+        self.convolve_separable(&vec![1.0 / size as f32; size], mode)
+    }
+
+    /// Creates a new canvas blurred with a Gaussian of standard deviation `sigma` in pixels
+    ///
+    /// A deviation of zero or less leaves the canvas unchanged. The kernel reaches at most as far as the canvas is
+    /// wide or high, as further pixels lie outside of it for every pixel
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::core3d::color_rgb::ColorRGB;
+    /// # use rusty_ray_tracer::graphics2d::canvas::{Canvas, EdgeMode};
+    /// let mut canvas = Canvas::filled(9, 1, Color::new(0.0, 0.0, 0.0));
+    /// canvas.set_pixel_at(4, 0, Color::new(1.0, 1.0, 1.0));
+    /// let blurred = canvas.gaussian_blur(1.0, EdgeMode::Clamp);
+    /// assert!(blurred.get_pixel_at(3, 0).r() > blurred.get_pixel_at(2, 0).r());
+    /// assert!(blurred.get_pixel_at(4, 0).r() < 1.0);
+    /// ```
+    #[must_use]
+    pub fn gaussian_blur(&self, sigma: f32, mode: EdgeMode) -> Self {
+        self.convolve_separable(&gaussian_weights(sigma, self.width.max(self.height)), mode)
+    }
+
+    /// Creates a new canvas with the difference of each pixel to its four neighbours boosted by `amount`,
+    /// making edges crisper
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::{Canvas, EdgeMode};
+    /// let mut canvas = Canvas::filled(3, 1, Color::new(0.0, 0.0, 0.0));
+    /// canvas.set_pixel_at(1, 0, Color::new(0.5, 0.5, 0.5));
+    /// let sharpened = canvas.sharpen(0.5, EdgeMode::Clamp);
+    /// assert_eq!(Color::new(1.0, 1.0, 1.0), sharpened.get_pixel_at(1, 0));
+    /// ```
+    #[must_use]
+    pub fn sharpen(&self, amount: f32, mode: EdgeMode) -> Self {
+        self.convolve(&Kernel::sharpen(amount), mode)
+    }
+
+    /// Creates a new single channel canvas holding the strength of the edges, the magnitude of the luminance
+    /// gradient found by the Sobel operator
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::{Canvas, EdgeMode};
+    /// let mut canvas = Canvas::new(4, 3);
+    /// for y in 0..3 {
+    ///     canvas.set_pixel_at(2, y, Color::new(1.0, 1.0, 1.0));
+    ///     canvas.set_pixel_at(3, y, Color::new(1.0, 1.0, 1.0));
+    /// }
+    /// let edges = canvas.sobel(EdgeMode::Clamp);
+    /// assert_eq!(0.0, edges.get_pixel_at(0, 1));
+    /// assert!(edges.get_pixel_at(1, 1) > 3.9);
+    /// ```
+    #[must_use]
+    pub fn sobel(&self, mode: EdgeMode) -> Canvas<f32> {
+        let luminance: Canvas<f32> = self.convert();
+        luminance.map_pixels(|x, y| {
+            let (mut gx, mut gy) = (0.0, 0.0);
+            for (dy, (row_x, row_y)) in (-1..=1).zip(SOBEL_X.iter().zip(SOBEL_Y)) {
+                for (dx, (weight_x, weight_y)) in (-1..=1).zip(row_x.iter().zip(row_y)) {
+                    let sample =
+                        luminance.sample_pixel(x.saturating_add(dx), y.saturating_add(dy), mode);
+                    gx += weight_x * sample;
+                    gy += weight_y * sample;
+                }
+            }
+            f32::hypot(gx, gy)
+        })
+    }
+
+    /// Creates a new canvas with each channel of each pixel replaced by the median of that channel within the
+    /// pixels up to `radius` away, removing speckles such as fireflies while keeping edges
+    ///
+    /// The medians are taken of the colors premultiplied by their alpha
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::{Canvas, EdgeMode};
+    /// let mut canvas = Canvas::filled(3, 3, Color::new(0.0, 0.0, 0.0));
+    /// canvas.set_pixel_at(1, 1, Color::new(100.0, 0.0, 0.0));
+    /// let cleaned = canvas.median(1, EdgeMode::Clamp);
+    /// assert_eq!(Color::new(0.0, 0.0, 0.0), cleaned.get_pixel_at(1, 1));
+    /// ```
+    #[must_use]
+    pub fn median(&self, radius: u16, mode: EdgeMode) -> Self {
+        let radius = i32::from(radius);
+        let mut channels: [Vec<f32>; 4] = Default::default();
+        self.premultiplied(mode, |canvas, mode| {
+            canvas.map_pixels(|x, y| {
+                channels.iter_mut().for_each(Vec::clear);
+                for sample_y in y.saturating_sub(radius)..=y.saturating_add(radius) {
+                    for sample_x in x.saturating_sub(radius)..=x.saturating_add(radius) {
+                        let sample = canvas.sample_pixel(sample_x, sample_y, mode);
+                        for (channel, value) in channels.iter_mut().zip(sample.tuple) {
+                            channel.push(value);
+                        }
+                    }
+                }
+                Color::from(channels.each_mut().map(|channel| {
+                    let middle = channel.len() / 2;
+                    *channel.select_nth_unstable_by(middle, f32::total_cmp).1
+                }))
+            })
+        })
+    }
+
+    /// Creates a new canvas with glare around its highlights, as bright lights bleed in camera lenses
+    ///
+    /// The luminance above `threshold` is blurred with a Gaussian of standard deviation `sigma` in pixels and
+    /// added back scaled by `strength`. Light outside of the canvas is taken as black and alpha is kept
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::core3d::color_rgb::ColorRGB;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(20, 20);
+    /// canvas.set_pixel_at(15, 15, Color::new(50.0, 50.0, 50.0));
+    /// canvas.set_pixel_at(0, 0, Color::new(0.5, 0.5, 0.5));
+    /// let glowing = canvas.bloom(1.0, 1.5, 1.0);
+    /// assert!(glowing.get_pixel_at(16, 15).r() > 0.1);
+    /// assert_eq!(Color::new(0.5, 0.5, 0.5), glowing.get_pixel_at(0, 0));
+    /// ```
+    #[must_use]
+    pub fn bloom(&self, threshold: f32, sigma: f32, strength: f32) -> Self {
+        let highlights = Self {
+            width: self.width,
+            height: self.height,
+            raw_buffer: self
+                .raw_buffer
+                .iter()
+                .map(|&color| {
+                    let luminance = f32::from_color(color);
+                    if luminance <= threshold {
+                        return Color::new(0.0, 0.0, 0.0);
+                    }
+                    let [r, g, b, _] = color.tuple.map(|c| c * (luminance - threshold) / luminance);
+                    Color::new(r, g, b)
+                })
+                .collect(),
+        };
+        let glow = highlights.gaussian_blur(sigma, EdgeMode::Border(Color::new(0.0, 0.0, 0.0)));
+        Self {
+            width: self.width,
+            height: self.height,
+            raw_buffer: self
+                .raw_buffer
+                .iter()
+                .zip(glow.raw_buffer)
+                .map(|(&color, glow)| {
+                    let [r, g, b, a] = color.tuple;
+                    let [gr, gg, gb, _] = glow.tuple;
+                    Color::new_with_alpha(
+                        strength.mul_add(gr, r),
+                        strength.mul_add(gg, g),
+                        strength.mul_add(gb, b),
+                        a,
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_convolution {
+    use super::*;
+    use crate::{core3d::color_rgb::ColorRGB, graphics2d::fixtures::gradient};
+
+    #[test]
+    fn identity() {
+        let canvas = gradient(5, 4);
+        let identity = Kernel::new(1, 1, vec![1.0]).unwrap();
+        assert_eq!(
+            canvas.raw_buffer,
+            canvas.convolve(&identity, EdgeMode::Wrap).raw_buffer
+        );
+        assert_eq!(
+            canvas.raw_buffer,
+            canvas.gaussian_blur(0.0, EdgeMode::Wrap).raw_buffer
+        );
+        assert_eq!(
+            canvas.raw_buffer,
+            canvas.box_blur(0, EdgeMode::Wrap).raw_buffer
+        );
+        assert_eq!(
+            canvas.raw_buffer,
+            canvas.sharpen(0.0, EdgeMode::Wrap).raw_buffer
+        );
+    }
+
+    #[test]
+    fn not_flipped() {
+        let canvas = gradient(5, 4);
+        let kernel = Kernel::new(1, 3, vec![1.0, 0.0, 0.0]).unwrap();
+        let shifted = canvas.convolve(&kernel, EdgeMode::Clamp);
+        assert_eq!(Color::new(0.75, 1.0 / 3.0, 0.5), shifted.get_pixel_at(3, 2));
+        assert_eq!(Color::new(0.75, 0.0, 0.5), shifted.get_pixel_at(3, 0));
+    }
+
+    #[test]
+    fn edge_modes() {
+        let canvas = gradient(5, 4);
+        let kernel = Kernel::new(3, 1, vec![1.0, 0.0, 0.0]).unwrap();
+        let red = Color::new(1.0, 0.0, 0.0);
+        assert_eq!(
+            Color::new(0.0, 2.0 / 3.0, 0.5),
+            canvas.convolve(&kernel, EdgeMode::Clamp).get_pixel_at(0, 2)
+        );
+        assert_eq!(
+            Color::new(1.0, 2.0 / 3.0, 0.5),
+            canvas.convolve(&kernel, EdgeMode::Wrap).get_pixel_at(0, 2)
+        );
+        let mirrored = canvas.convolve(&kernel, EdgeMode::Mirror);
+        assert_eq!(Color::new(0.0, 2.0 / 3.0, 0.5), mirrored.get_pixel_at(0, 2));
+        assert_eq!(
+            red,
+            canvas
+                .convolve(&kernel, EdgeMode::Border(red))
+                .get_pixel_at(0, 2)
+        );
+    }
+
+    #[test]
+    fn alpha() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set_pixel_at(0, 0, Color::new(1.0, 1.0, 1.0));
+        let kernel = Kernel::new(3, 1, vec![0.0, 0.5, 0.5]).unwrap();
+        let blurred = canvas.convolve(&kernel, EdgeMode::Clamp);
+        assert_eq!(
+            Color::new_with_alpha(1.0, 1.0, 1.0, 0.5),
+            blurred.get_pixel_at(0, 0)
+        );
+        assert_eq!(Color::default(), blurred.get_pixel_at(1, 0));
+    }
+
+    #[test]
+    fn transparent_pixels_do_not_bleed() {
+        // Red hidden behind zero alpha next to opaque green
+        let mut canvas = Canvas::filled(4, 1, Color::new_with_alpha(1.0, 0.0, 0.0, 0.0));
+        canvas.set_pixel_at(2, 0, Color::new(0.0, 1.0, 0.0));
+        canvas.set_pixel_at(3, 0, Color::new(0.0, 1.0, 0.0));
+        for blurred in [
+            canvas.box_blur(1, EdgeMode::Clamp),
+            canvas.gaussian_blur(1.0, EdgeMode::Clamp),
+        ] {
+            let edge = blurred.get_pixel_at(1, 0);
+            assert!(edge.a() > 0.0 && edge.a() < 1.0);
+            assert_eq!([0.0, 1.0, 0.0], [edge.r(), edge.g(), edge.b()]);
+        }
+        let border = EdgeMode::Border(Color::new_with_alpha(0.0, 0.0, 1.0, 0.0));
+        let blurred = canvas.box_blur(1, border);
+        // Transparent border pixels above and below as well as to the right
+        assert_eq!(
+            Color::new_with_alpha(0.0, 1.0, 0.0, 2.0 / 9.0),
+            blurred.get_pixel_at(3, 0)
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let canvas = Canvas::new(0, 0);
+        assert!(canvas
+            .gaussian_blur(2.0, EdgeMode::Clamp)
+            .raw_buffer
+            .is_empty());
+        assert!(canvas.median(2, EdgeMode::Clamp).raw_buffer.is_empty());
+        assert!(canvas.sobel(EdgeMode::Clamp).raw_buffer.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests_blur {
+    use super::*;
+    use crate::core3d::color_rgb::ColorRGB;
+
+    #[test]
+    fn box_blur_keeps_flat_areas() {
+        let canvas = Canvas::filled(6, 5, Color::new(0.25, 0.5, 2.0));
+        for mode in [EdgeMode::Clamp, EdgeMode::Wrap, EdgeMode::Mirror] {
+            assert_eq!(canvas.raw_buffer, canvas.box_blur(2, mode).raw_buffer);
+            assert_eq!(
+                canvas.raw_buffer,
+                canvas.gaussian_blur(1.5, mode).raw_buffer
+            );
+        }
+    }
+
+    #[test]
+    fn box_blur_averages() {
+        let mut canvas = Canvas::filled(5, 5, Color::new(0.0, 0.0, 0.0));
+        canvas.set_pixel_at(2, 2, Color::new(25.0, 0.0, 0.0));
+        let blurred = canvas.box_blur(2, EdgeMode::Border(Color::new(0.0, 0.0, 0.0)));
+        assert!(blurred
+            .raw_buffer
+            .iter()
+            .all(|&c| c == Color::new(1.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn huge_sigma() {
+        let color = Color::new(0.25, 0.5, 0.75);
+        let canvas = Canvas::filled(5, 3, color);
+        for sigma in [1e7, 1e30, f32::MAX] {
+            let blurred = canvas.gaussian_blur(sigma, EdgeMode::Clamp);
+            assert!(blurred.raw_buffer.iter().all(|&c| c == color), "{sigma}");
+            let glowing = canvas.bloom(0.1, sigma, 1.0);
+            assert!(
+                glowing.raw_buffer.iter().all(|c| c.r().is_finite()),
+                "{sigma}"
+            );
+        }
+    }
+
+    #[test]
+    fn gaussian_keeps_energy() {
+        let mut canvas = Canvas::filled(21, 21, Color::new(0.0, 0.0, 0.0));
+        canvas.set_pixel_at(10, 10, Color::new(1.0, 2.0, 3.0));
+        let blurred = canvas.gaussian_blur(2.0, EdgeMode::Border(Color::new(0.0, 0.0, 0.0)));
+        let total: f32 = blurred.raw_buffer.iter().map(ColorRGB::g).sum();
+        assert!((total - 2.0).abs() < 1e-4);
+        assert!(blurred.get_pixel_at(10, 10).g() < 0.1);
+        // The kernel is round, so pixels at the same distance get the same share
+        assert_eq!(blurred.get_pixel_at(12, 10), blurred.get_pixel_at(10, 8));
+        assert!(blurred.get_pixel_at(12, 10).g() > blurred.get_pixel_at(12, 12).g());
+    }
+
+    #[test]
+    fn sharpen() {
+        let mut canvas = Canvas::filled(3, 3, Color::new(0.5, 0.5, 0.5));
+        canvas.set_pixel_at(1, 1, Color::new(1.0, 1.0, 1.0));
+        let sharpened = canvas.sharpen(1.0, EdgeMode::Clamp);
+        assert_eq!(Color::new(3.0, 3.0, 3.0), sharpened.get_pixel_at(1, 1));
+        assert_eq!(Color::new(0.0, 0.0, 0.0), sharpened.get_pixel_at(1, 0));
+        assert_eq!(Color::new(0.5, 0.5, 0.5), sharpened.get_pixel_at(0, 0));
+    }
+}
+
+#[cfg(test)]
+mod tests_sobel {
+    use super::*;
+
+    #[test]
+    fn flat() {
+        let canvas = Canvas::filled(4, 4, Color::new(0.3, 0.6, 0.9));
+        let edges = canvas.sobel(EdgeMode::Clamp);
+        assert!(edges.raw_buffer.iter().all(|&e| e.abs() < 1e-5));
+    }
+
+    #[test]
+    fn vertical_and_horizontal_edges() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.fill_rect((0, 2), 4, 2, Color::new(1.0, 1.0, 1.0));
+        let edges = canvas.sobel(EdgeMode::Clamp);
+        assert!((edges.get_pixel_at(0, 1) - 4.0).abs() < 1e-4);
+        assert!((edges.get_pixel_at(3, 2) - 4.0).abs() < 1e-4);
+        assert_eq!(0.0, edges.get_pixel_at(2, 0));
+    }
+
+    #[test]
+    fn diagonal_edge() {
+        let mut canvas = Canvas::new(4, 4);
+        for y in 0..4 {
+            for x in 0..4 {
+                if x > y {
+                    canvas.set_pixel_at(x, y, Color::new(1.0, 1.0, 1.0));
+                }
+            }
+        }
+        let edges = canvas.sobel(EdgeMode::Clamp);
+        assert!(edges.get_pixel_at(1, 1) > 1.0);
+        assert!(edges.get_pixel_at(0, 3) < 1e-5);
+    }
+}
+
+#[cfg(test)]
+mod tests_median {
+    use super::*;
+
+    #[test]
+    fn removes_fireflies() {
+        let mut canvas = Canvas::filled(5, 5, Color::new(0.2, 0.2, 0.2));
+        canvas.set_pixel_at(1, 1, Color::new(1000.0, 0.0, 0.0));
+        canvas.set_pixel_at(3, 3, Color::new(0.0, 0.0, 1000.0));
+        let cleaned = canvas.median(1, EdgeMode::Mirror);
+        assert!(cleaned
+            .raw_buffer
+            .iter()
+            .all(|&c| c == Color::new(0.2, 0.2, 0.2)));
+    }
+
+    #[test]
+    fn keeps_edges() {
+        let mut canvas = Canvas::new(6, 6);
+        canvas.fill_rect((3, 0), 3, 6, Color::new(1.0, 1.0, 1.0));
+        assert_eq!(
+            canvas.raw_buffer,
+            canvas.median(1, EdgeMode::Clamp).raw_buffer
+        );
+        assert_eq!(
+            canvas.raw_buffer,
+            canvas.median(2, EdgeMode::Clamp).raw_buffer
+        );
+    }
+
+    #[test]
+    fn each_channel() {
+        let mut canvas = Canvas::new(3, 1);
+        canvas.set_pixel_at(0, 0, Color::new(1.0, 5.0, 0.0));
+        canvas.set_pixel_at(1, 0, Color::new(2.0, 4.0, 0.0));
+        canvas.set_pixel_at(2, 0, Color::new(3.0, 6.0, 0.0));
+        let filtered = canvas.median(1, EdgeMode::Clamp);
+        assert_eq!(Color::new(2.0, 5.0, 0.0), filtered.get_pixel_at(1, 0));
+    }
+}
+
+#[cfg(test)]
+mod tests_bloom {
+    use super::*;
+    use crate::core3d::color_rgb::ColorRGB;
+
+    #[test]
+    fn dark_images_unchanged() {
+        let mut canvas = Canvas::filled(4, 4, Color::new(0.5, 0.9, 0.1));
+        canvas.set_pixel_at(0, 0, Color::new_with_alpha(1.0, 1.0, 1.0, 0.5));
+        assert_eq!(canvas.raw_buffer, canvas.bloom(1.0, 2.0, 1.0).raw_buffer);
+    }
+
+    #[test]
+    fn spreads_highlights() {
+        let mut canvas = Canvas::new(15, 15);
+        canvas.set_pixel_at(7, 7, Color::new(101.0, 101.0, 101.0));
+        let glowing = canvas.bloom(1.0, 2.0, 0.5);
+        assert!(glowing.get_pixel_at(7, 7).r() > 101.0);
+        assert!(glowing.get_pixel_at(9, 7).r() > glowing.get_pixel_at(11, 7).r());
+        assert!(glowing.get_pixel_at(11, 7).r() > 0.0);
+        // Only the excess over the threshold glows, at half strength
+        let added: f32 = glowing.raw_buffer.iter().map(ColorRGB::r).sum::<f32>() - 101.0;
+        assert!((added - 50.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn keeps_hue_and_alpha() {
+        let mut canvas = Canvas::new(9, 9);
+        canvas.set_pixel_at(4, 4, Color::new(40.0, 20.0, 0.0));
+        canvas.set_pixel_at(5, 4, Color::new_with_alpha(0.0, 0.0, 0.0, 0.25));
+        let glowing = canvas.bloom(1.0, 1.0, 1.0);
+        let glow = glowing.get_pixel_at(4, 5);
+        assert!((glow.r() / glow.g() - 2.0).abs() < 1e-4);
+        assert_eq!(0.0, glow.b());
+        assert_eq!(0.25, glowing.get_pixel_at(5, 4).a());
+        assert_eq!(0.0, glow.a());
+    }
+}
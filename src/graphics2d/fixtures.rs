@@ -0,0 +1,26 @@
+use super::canvas::Canvas;
+use crate::core3d::color::Color;
+
+/// Returns a canvas of single channel pixels numbered row by row, starting at 0 in the top left corner
+pub fn numbered(width: u32, height: u32) -> Canvas<f32> {
+    Canvas::from_buffer(
+        width,
+        height,
+        (0..width * height).map(|i| i as f32).collect(),
+    )
+    .unwrap()
+}
+
+/// Returns an opaque canvas with red rising from 0 to 1 left to right, green rising from 0 to 1 top to bottom
+/// and blue at 0.5
+pub fn gradient(width: u32, height: u32) -> Canvas {
+    let ramp = |value: u32, size: u32| value as f32 / (size.max(2) - 1) as f32;
+    Canvas::from_buffer(
+        width,
+        height,
+        (0..width * height)
+            .map(|i| Color::new(ramp(i % width, width), ramp(i / width, height), 0.5))
+            .collect(),
+    )
+    .unwrap()
+}
@@ -0,0 +1,349 @@
+use super::canvas::Canvas;
+use crate::core3d::color::Color;
+
+/// Width of a glyph in font pixels
+pub const GLYPH_WIDTH: u16 = 5;
+/// Height of a glyph in font pixels
+pub const GLYPH_HEIGHT: u16 = 7;
+/// Horizontal distance between the starts of two characters in font pixels, leaving a column of spacing
+pub const ADVANCE_X: u16 = GLYPH_WIDTH + 1;
+/// Vertical distance between the tops of two lines in font pixels, leaving a row of spacing
+pub const ADVANCE_Y: u16 = GLYPH_HEIGHT + 1;
+
+/// Glyphs of the printable ASCII characters from space to tilde, one row per byte with the leftmost pixel in
+/// bit 4
+const GLYPHS: [[u8; GLYPH_HEIGHT as usize]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // space
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04], // !
+    [0x0A, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00], // "
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A], // #
+    [0x04, 0x0F, 0x14, 0x0E, 0x05, 0x1E, 0x04], // $
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03], // %
+    [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D], // &
+    [0x04, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00], // '
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02], // (
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08], // )
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00], // *
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00], // +
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08], // ,
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00], // -
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C], // .
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00], // /
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E], // 0
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E], // 1
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F], // 2
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E], // 3
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02], // 4
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E], // 5
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E], // 6
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08], // 7
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E], // 8
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C], // 9
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00], // :
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08], // ;
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02], // <
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00], // =
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08], // >
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04], // ?
+    [0x0E, 0x11, 0x17, 0x15, 0x17, 0x10, 0x0E], // @
+    [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // A
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E], // B
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E], // C
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C], // D
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F], // E
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10], // F
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F], // G
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // H
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // I
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C], // J
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11], // K
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F], // L
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11], // M
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11], // N
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // O
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10], // P
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D], // Q
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11], // R
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E], // S
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // T
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // U
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04], // V
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A], // W
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11], // X
+    [0x11, 0x11, 0x0A, 0x04, 0x04, 0x04, 0x04], // Y
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F], // Z
+    [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E], // [
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00], // \
+    [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E], // ]
+    [0x04, 0x0A, 0x11, 0x00, 0x00, 0x00, 0x00], // ^
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F], // _
+    [0x08, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00], // `
+    [0x00, 0x00, 0x0E, 0x01, 0x0F, 0x11, 0x0F], // a
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1E], // b
+    [0x00, 0x00, 0x0E, 0x10, 0x10, 0x11, 0x0E], // c
+    [0x01, 0x01, 0x0D, 0x13, 0x11, 0x11, 0x0F], // d
+    [0x00, 0x00, 0x0E, 0x11, 0x1F, 0x10, 0x0E], // e
+    [0x06, 0x09, 0x08, 0x1C, 0x08, 0x08, 0x08], // f
+    [0x00, 0x00, 0x0F, 0x11, 0x0F, 0x01, 0x0E], // g
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x11], // h
+    [0x04, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x0E], // i
+    [0x02, 0x00, 0x06, 0x02, 0x02, 0x12, 0x0C], // j
+    [0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12], // k
+    [0x0C, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // l
+    [0x00, 0x00, 0x1A, 0x15, 0x15, 0x11, 0x11], // m
+    [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11], // n
+    [0x00, 0x00, 0x0E, 0x11, 0x11, 0x11, 0x0E], // o
+    [0x00, 0x00, 0x1E, 0x11, 0x1E, 0x10, 0x10], // p
+    [0x00, 0x00, 0x0D, 0x13, 0x0F, 0x01, 0x01], // q
+    [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10], // r
+    [0x00, 0x00, 0x0E, 0x10, 0x0E, 0x01, 0x1E], // s
+    [0x08, 0x08, 0x1C, 0x08, 0x08, 0x09, 0x06], // t
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0D], // u
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0A, 0x04], // v
+    [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0A], // w
+    [0x00, 0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11], // x
+    [0x00, 0x00, 0x11, 0x11, 0x0F, 0x01, 0x0E], // y
+    [0x00, 0x00, 0x1F, 0x02, 0x04, 0x08, 0x1F], // z
+    [0x02, 0x04, 0x04, 0x08, 0x04, 0x04, 0x02], // {
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // |
+    [0x08, 0x04, 0x04, 0x02, 0x04, 0x04, 0x08], // }
+    [0x00, 0x00, 0x08, 0x15, 0x02, 0x00, 0x00], // ~
+];
+
+/// Returns the rows of the glyph of a character, characters outside of printable ASCII showing as `?`
+///
+/// # Examples
+///
+/// ```
+/// # use rusty_ray_tracer::graphics2d::font::glyph;
+/// assert_eq!([0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E], glyph('1'));
+/// assert_eq!(glyph('?'), glyph('é'));
+/// ```
+#[must_use]
+pub fn glyph(character: char) -> [u8; GLYPH_HEIGHT as usize] {
+    let index = match character {
+        ' '..='~' => character as usize - ' ' as usize,
+        _ => '?' as usize - ' ' as usize,
+    };
+    GLYPHS[index]
+}
+
+/// Splits a text into the lines drawn by [`Canvas::draw_text`], dropping the carriage return of `\r\n` endings
+fn text_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+}
+
+/// Returns the width and height in canvas pixels a text takes when drawn at a scale
+///
+/// # Examples
+///
+/// ```
+/// # use rusty_ray_tracer::graphics2d::font::text_size;
+/// assert_eq!((17, 7), text_size("abc", 1));
+/// assert_eq!((58, 30), text_size("frame\n12", 2));
+/// assert_eq!((0, 0), text_size("", 1));
+/// ```
+#[must_use]
+pub fn text_size(text: &str, scale: u16) -> (u32, u32) {
+    if text.is_empty() {
+        return (0, 0);
+    }
+    let columns = text_lines(text)
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let rows = text_lines(text).count();
+    let scale = u64::from(scale);
+    let width = (columns as u64 * u64::from(ADVANCE_X)).saturating_sub(1);
+    let height = rows as u64 * u64::from(ADVANCE_Y) - 1;
+    let clamp = |size: u64| u32::try_from(size.saturating_mul(scale)).unwrap_or(u32::MAX);
+    (clamp(width), clamp(height))
+}
+
+#[cfg(test)]
+mod tests_font {
+    use super::*;
+
+    #[test]
+    fn glyphs_fit() {
+        for glyph in GLYPHS {
+            assert!(glyph.iter().all(|&row| row < 1 << GLYPH_WIDTH));
+        }
+        assert_eq!([0; 7], glyph(' '));
+        for c in '!'..='~' {
+            assert_ne!([0; 7], glyph(c), "{c}");
+        }
+    }
+
+    #[test]
+    fn glyphs_distinct() {
+        for a in ' '..='~' {
+            for b in ' '..='~' {
+                if a != b {
+                    assert_ne!(glyph(a), glyph(b), "{a} {b}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_characters() {
+        assert_eq!(glyph('?'), glyph('\t'));
+        assert_eq!(glyph('?'), glyph('\u{7f}'));
+        assert_eq!(glyph('?'), glyph('€'));
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!((5, 7), text_size("W", 1));
+        assert_eq!((15, 21), text_size("W", 3));
+        assert_eq!((29, 15), text_size("12\nframe", 1));
+        assert_eq!((5, 15), text_size("a\n", 1));
+        assert_eq!((5, 15), text_size("a\r\n", 1));
+        assert_eq!((11, 15), text_size("a\r\nbc", 1));
+        assert_eq!(u32::MAX, text_size(&"\n".repeat(10_000), u16::MAX).1);
+    }
+}
+
+impl Canvas {
+    /// Draws a line of text from its top left corner with the embedded monospace 5x7 font, each font pixel
+    /// covering a square of `scale` canvas pixels
+    ///
+    /// A newline or `\r\n` starts a new line below the first, pixels outside of the canvas are skipped
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let white = Color::new(1.0, 1.0, 1.0);
+    /// let mut canvas = Canvas::new(32, 16);
+    /// canvas.draw_text(1, 1, "spp 64", white, 1);
+    /// // The stem of the `p`
+    /// assert_eq!(white, canvas.get_pixel_at(7, 7));
+    /// assert_eq!(Color::default(), canvas.get_pixel_at(0, 0));
+    /// ```
+    pub fn draw_text(&mut self, x: i32, y: i32, text: &str, color: Color, scale: u16) {
+        let clamp = |x: i64| i32::try_from(x).unwrap_or(if x < 0 { i32::MIN } else { i32::MAX });
+        let scale_i64 = i64::from(scale);
+        for (line_index, line) in text_lines(text).enumerate() {
+            let top = i64::from(y) + line_index as i64 * i64::from(ADVANCE_Y) * scale_i64;
+            if top >= i64::from(self.height) {
+                break;
+            }
+            for (column, character) in line.chars().enumerate() {
+                let left = i64::from(x) + column as i64 * i64::from(ADVANCE_X) * scale_i64;
+                if left >= i64::from(self.width) {
+                    break;
+                }
+                for (row, bits) in glyph(character).iter().enumerate() {
+                    for bit in 0..GLYPH_WIDTH {
+                        if bits & (1 << (GLYPH_WIDTH - 1 - bit)) != 0 {
+                            self.fill_rect(
+                                (
+                                    clamp(left + i64::from(bit) * scale_i64),
+                                    clamp(top + row as i64 * scale_i64),
+                                ),
+                                u32::from(scale),
+                                u32::from(scale),
+                                color,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_draw_text {
+    use super::*;
+
+    const WHITE: Color = Color::new(1.0, 1.0, 1.0);
+
+    /// Reads back the glyph drawn at a position
+    fn read_glyph(canvas: &Canvas, x: u32, y: u32, scale: u32) -> [u8; 7] {
+        std::array::from_fn(|row| {
+            (0..GLYPH_WIDTH).fold(0, |bits, bit| {
+                let pixel = canvas.get_pixel_at(x + u32::from(bit) * scale, y + row as u32 * scale);
+                (bits << 1) | u8::from(pixel == WHITE)
+            })
+        })
+    }
+
+    #[test]
+    fn draws_glyphs() {
+        let mut canvas = Canvas::new(20, 10);
+        canvas.draw_text(2, 1, "F0", WHITE, 1);
+        assert_eq!(glyph('F'), read_glyph(&canvas, 2, 1, 1));
+        assert_eq!(glyph('0'), read_glyph(&canvas, 8, 1, 1));
+        let lit = canvas.raw_buffer.iter().filter(|&&c| c == WHITE).count();
+        let expected = [glyph('F'), glyph('0')]
+            .iter()
+            .flatten()
+            .map(|row| row.count_ones() as usize)
+            .sum::<usize>();
+        assert_eq!(expected, lit);
+    }
+
+    #[test]
+    fn scaled() {
+        let mut canvas = Canvas::new(20, 20);
+        canvas.draw_text(0, 0, "T", WHITE, 2);
+        assert_eq!(glyph('T'), read_glyph(&canvas, 0, 0, 2));
+        assert_eq!(glyph('T'), read_glyph(&canvas, 1, 1, 2));
+        let lit = canvas.raw_buffer.iter().filter(|&&c| c == WHITE).count();
+        assert_eq!(4 * (5 + 6), lit);
+    }
+
+    #[test]
+    fn new_lines() {
+        let mut canvas = Canvas::new(10, 20);
+        canvas.draw_text(0, 0, "1\n2", WHITE, 1);
+        assert_eq!(glyph('1'), read_glyph(&canvas, 0, 0, 1));
+        assert_eq!(glyph('2'), read_glyph(&canvas, 0, 8, 1));
+    }
+
+    #[test]
+    fn clipped() {
+        let mut canvas = Canvas::new(8, 8);
+        canvas.draw_text(-3, -2, "#", WHITE, 1);
+        // Only the last two columns of the glyph, from its third row on, fall inside
+        assert_eq!(WHITE, canvas.get_pixel_at(0, 1));
+        canvas.draw_text(6, 6, "Long text running off the canvas", WHITE, 3);
+        canvas.draw_text(i32::MAX, i32::MAX, "far away", WHITE, 1);
+        canvas.draw_text(0, 0, "", WHITE, 1);
+        assert_eq!(WHITE, canvas.get_pixel_at(7, 7));
+    }
+
+    #[test]
+    fn extreme_positions() {
+        let mut canvas = Canvas::new(8, 8);
+        canvas.draw_text(0, 0, &"M\n".repeat(5000), WHITE, u16::MAX);
+        assert!(canvas.raw_buffer.iter().all(|&c| c == WHITE));
+
+        let mut canvas = Canvas::new(8, 8);
+        canvas.draw_text(i32::MIN, i32::MIN, &"#\n".repeat(5000), WHITE, u16::MAX);
+        canvas.draw_text(i32::MAX - 3, i32::MAX - 3, "##\n##", WHITE, u16::MAX);
+        canvas.draw_text(0, i32::MAX, "#", WHITE, 1);
+        assert!(!canvas.raw_buffer.contains(&WHITE));
+    }
+
+    #[test]
+    fn carriage_returns() {
+        let mut canvas = Canvas::new(12, 20);
+        canvas.draw_text(0, 0, "1\r\n2", WHITE, 1);
+        assert_eq!(glyph('1'), read_glyph(&canvas, 0, 0, 1));
+        assert_eq!(glyph('2'), read_glyph(&canvas, 0, 8, 1));
+        assert_eq!([0; 7], read_glyph(&canvas, 6, 0, 1));
+    }
+
+    #[test]
+    fn scale_zero_draws_nothing() {
+        let mut canvas = Canvas::new(8, 8);
+        canvas.draw_text(0, 0, "8", WHITE, 0);
+        assert!(!canvas.raw_buffer.contains(&WHITE));
+    }
+}
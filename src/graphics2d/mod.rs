@@ -1 +1,15 @@
 pub mod canvas;
+pub mod diff;
+pub mod dither;
+pub mod filter;
+#[cfg(test)]
+pub(crate) mod fixtures;
+pub mod font;
+pub mod pixel;
+pub mod raster;
+pub mod resize;
+pub mod statistics;
+pub mod tile;
+pub mod tone_mapping;
+pub mod transfer_function;
+pub mod transform;
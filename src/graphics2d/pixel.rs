@@ -0,0 +1,136 @@
+use std::fmt::Debug;
+
+use crate::core3d::{color::Color, color_rgb::ColorRGB};
+
+/// Value stored for each pixel of a [`Canvas`](super::canvas::Canvas), convertible to and from a linear color
+///
+/// Smaller pixel types save memory for large images and for outputs that do not need four float channels,
+/// such as depth buffers
+pub trait Pixel: Copy + Default + Debug + PartialEq {
+    /// Returns the linear color of the pixel
+    fn to_color(self) -> Color;
+
+    /// Creates a new pixel from a linear color, dropping what the pixel type cannot store
+    fn from_color(color: Color) -> Self;
+}
+
+/// Floating point RGBA, the full color
+impl Pixel for Color {
+    fn to_color(self) -> Color {
+        self
+    }
+
+    fn from_color(color: Color) -> Self {
+        color
+    }
+}
+
+/// Floating point RGB without alpha, opaque when converted to a color
+impl Pixel for [f32; 3] {
+    fn to_color(self) -> Color {
+        Color::from(self)
+    }
+
+    fn from_color(color: Color) -> Self {
+        [color.r(), color.g(), color.b()]
+    }
+}
+
+/// Single floating point channel for depth, masks and other data, shown as gray
+///
+/// Colors are stored as their luminance
+impl Pixel for f32 {
+    fn to_color(self) -> Color {
+        Color::new(self, self, self)
+    }
+
+    fn from_color(color: Color) -> Self {
+        color.luminance()
+    }
+}
+
+/// Scales a channel in 0..1 to a byte, clamping it first
+fn to_byte(channel: f32) -> u8 {
+    f32::round(num::clamp(channel, 0.0, 1.0) * 255.0) as u8
+}
+
+/// 8-bit RGB, opaque when converted to a color
+///
+/// Channels are stored linearly in 256 levels after clamping them to 0..1
+impl Pixel for [u8; 3] {
+    fn to_color(self) -> Color {
+        let [r, g, b] = self.map(|c| f32::from(c) / 255.0);
+        Color::new(r, g, b)
+    }
+
+    fn from_color(color: Color) -> Self {
+        [color.r(), color.g(), color.b()].map(to_byte)
+    }
+}
+
+/// 8-bit RGBA with straight alpha
+///
+/// Channels are stored linearly in 256 levels after clamping them to 0..1
+impl Pixel for [u8; 4] {
+    fn to_color(self) -> Color {
+        Color::from(self.map(|c| f32::from(c) / 255.0))
+    }
+
+    fn from_color(color: Color) -> Self {
+        [color.r(), color.g(), color.b(), color.a()].map(to_byte)
+    }
+}
+
+#[cfg(test)]
+mod tests_pixel {
+    use super::*;
+
+    #[test]
+    fn color() {
+        let color = Color::new_with_alpha(2.0, -1.0, 0.5, 0.25);
+        assert_eq!(color, Color::from_color(color));
+        assert_eq!(color, color.to_color());
+    }
+
+    #[test]
+    fn rgb_f32() {
+        let pixel = <[f32; 3]>::from_color(Color::new_with_alpha(2.0, -1.0, 0.5, 0.25));
+        assert_eq!([2.0, -1.0, 0.5], pixel);
+        assert_eq!(Color::new(2.0, -1.0, 0.5), pixel.to_color());
+        assert_eq!([0.0; 3], <[f32; 3]>::default());
+    }
+
+    #[test]
+    fn gray_f32() {
+        assert_eq!(Color::new(0.5, 0.5, 0.5), 0.5_f32.to_color());
+        assert!((f32::from_color(Color::new(0.5, 0.5, 0.5)) - 0.5).abs() < 1e-5);
+        let green = f32::from_color(Color::new(0.0, 1.0, 0.0));
+        assert!((green - 0.7152).abs() < 1e-3);
+    }
+
+    #[test]
+    fn rgb_u8() {
+        let pixel = <[u8; 3]>::from_color(Color::new(2.0, -1.0, 0.5));
+        assert_eq!([255, 0, 128], pixel);
+        assert_eq!(Color::new(1.0, 0.0, 0.2), [255_u8, 0, 51].to_color());
+    }
+
+    #[test]
+    fn rgba_u8() {
+        let pixel = <[u8; 4]>::from_color(Color::new_with_alpha(1.0, 0.2, 0.0, 0.2));
+        assert_eq!([255, 51, 0, 51], pixel);
+        assert_eq!(Color::new_with_alpha(1.0, 0.2, 0.0, 0.2), pixel.to_color());
+        assert_eq!(Color::default(), <[u8; 4]>::default().to_color());
+    }
+
+    #[test]
+    fn round_trips() {
+        for i in 0..=255_u8 {
+            assert_eq!(
+                [i, 0, 255 - i],
+                <[u8; 3]>::from_color([i, 0, 255 - i].to_color())
+            );
+            assert_eq!([0, i, 0, i], <[u8; 4]>::from_color([0, i, 0, i].to_color()));
+        }
+    }
+}
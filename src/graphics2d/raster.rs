@@ -0,0 +1,782 @@
+use std::mem::swap;
+
+use super::canvas::Canvas;
+use crate::core3d::{color::Color, color_rgb::ColorRGB};
+
+/// Signed edge function of point `p` against the edge from `a` to `b`, twice the area of the triangle they form
+fn edge(a: (f32, f32), b: (f32, f32), p: (f32, f32)) -> f32 {
+    (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0)
+}
+
+/// Fractional part of a coordinate
+fn fpart(x: f32) -> f32 {
+    x - x.floor()
+}
+
+/// Saturates a coordinate to the range of `i32`, which still lies outside of the canvas when it was outside before
+fn saturate(value: i64) -> i32 {
+    value.clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32
+}
+
+/// Smallest integer not below `numerator / denominator`, for a positive denominator
+fn div_ceil(numerator: i128, denominator: i128) -> i128 {
+    -(-numerator).div_euclid(denominator)
+}
+
+/// Range of offsets `d`, both included, for which `start + step * d` lies within `0..size`
+///
+/// The range is empty, with its start above its end, when no offset lands on the canvas
+fn offsets_inside(start: i64, step: i64, size: i64) -> (i64, i64) {
+    match step {
+        1 => (-start, size - 1 - start),
+        -1 => (start - size + 1, start),
+        _ if (0..size).contains(&start) => (i64::MIN, i64::MAX),
+        _ => (1, 0),
+    }
+}
+
+/// Offset along the major axis of the first octant of a circle of the given radius, for the pixel `y` steps
+/// along its minor axis
+///
+/// The midpoint circle algorithm keeps the largest `x` whose midpoint towards `x - 1` lies inside the circle,
+/// `(x - 1/2)² + y² <= r²`
+fn octant_x(radius: i64, y: i64) -> i64 {
+    let (radius, y) = (i128::from(radius), i128::from(y));
+    ((4 * (radius * radius - y * y)).isqrt() as i64 + 1) / 2
+}
+
+/// Largest `y` for which [`octant_x`] is at least `x`, or -1 when there is none
+fn octant_y(radius: i64, x: i64) -> i64 {
+    if x <= 0 {
+        return radius;
+    }
+    if x > radius {
+        return -1;
+    }
+    let (radius, x) = (i128::from(radius), i128::from(x));
+    ((4 * radius * radius - (2 * x - 1) * (2 * x - 1)) / 4).isqrt() as i64
+}
+
+impl Canvas {
+    /// Composites a color covering part of the pixel at signed coordinates x, y, ignoring pixels outside of the
+    /// canvas
+    fn plot_coverage(&mut self, x: i32, y: i32, color: Color, coverage: f32) {
+        let alpha = color.a() * coverage;
+        self.blend_pixel_clipped(
+            x,
+            y,
+            Color::new_with_alpha(color.r(), color.g(), color.b(), alpha),
+        );
+    }
+
+    /// Sets the pixels of row y from x0 to x1 inclusive, clipped to the canvas
+    fn fill_span(&mut self, y: i32, x0: i32, x1: i32, color: Color) {
+        let (width, height) = self.signed_size();
+        if !(0..height).contains(&y) {
+            return;
+        }
+        let start = x0.max(0);
+        let end = x1.min(width - 1);
+        if start > end {
+            return;
+        }
+        let row = y as usize * self.width as usize;
+        self.raw_buffer[row + start as usize..=row + end as usize].fill(color);
+    }
+
+    /// Returns whether a box spanning the given coordinates lies entirely outside of the canvas
+    fn is_outside(&self, x0: i64, y0: i64, x1: i64, y1: i64) -> bool {
+        let (width, height) = self.signed_size();
+        x0.max(x1) < 0
+            || y0.max(y1) < 0
+            || x0.min(x1) >= i64::from(width)
+            || y0.min(y1) >= i64::from(height)
+    }
+
+    /// Draws a one pixel wide line between two pixels, both included, with Bresenham's algorithm
+    ///
+    /// Coordinates may lie outside of the canvas, the line is clipped to it before being walked
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let white = Color::new(1.0, 1.0, 1.0);
+    /// let mut canvas = Canvas::new(4, 4);
+    /// canvas.draw_line((0, 0), (3, 3), white);
+    /// assert_eq!(white, canvas.get_pixel_at(2, 2));
+    /// assert_eq!(Color::default(), canvas.get_pixel_at(2, 1));
+    /// ```
+    pub fn draw_line(&mut self, from: (i32, i32), to: (i32, i32), color: Color) {
+        let (x0, y0) = (i64::from(from.0), i64::from(from.1));
+        let (x1, y1) = (i64::from(to.0), i64::from(to.1));
+        if self.is_outside(x0, y0, x1, y1) {
+            return;
+        }
+        // Walk one pixel per step along the major axis, rounding the minor axis to the nearest pixel
+        let (width, height) = self.signed_size();
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        let (major, minor, major_delta, minor_delta, major_size, minor_size) = if steep {
+            (y0, x0, y1 - y0, x1 - x0, height, width)
+        } else {
+            (x0, y0, x1 - x0, y1 - y0, width, height)
+        };
+        let (run, rise) = (major_delta.abs(), minor_delta.abs());
+        let (major_step, minor_step) = (major_delta.signum(), minor_delta.signum());
+        let minor_offset = |step: i64| {
+            if run == 0 {
+                0
+            } else {
+                ((2 * i128::from(step) * i128::from(rise) + i128::from(run))
+                    / (2 * i128::from(run))) as i64
+            }
+        };
+
+        // Clip the steps to those landing on the canvas along both axes, so far away end points cost nothing
+        let (major_first, major_last) = offsets_inside(major, major_step, major_size.into());
+        let (mut first, mut last) = (major_first.max(0), major_last.min(run));
+        let (minor_first, minor_last) = offsets_inside(minor, minor_step, minor_size.into());
+        if rise == 0 {
+            if minor_first > minor_last {
+                return;
+            }
+        } else {
+            let (run, rise) = (i128::from(run), i128::from(rise));
+            let from = div_ceil((2 * i128::from(minor_first) - 1) * run, 2 * rise);
+            let to = div_ceil((2 * i128::from(minor_last) + 1) * run, 2 * rise) - 1;
+            first = first.max(from.clamp(0, run) as i64);
+            last = last.min(to.clamp(-1, run) as i64);
+        }
+        for step in first..=last {
+            let along = major + major_step * step;
+            let across = minor + minor_step * minor_offset(step);
+            let (x, y) = if steep {
+                (across, along)
+            } else {
+                (along, across)
+            };
+            self.set_pixel_clipped(saturate(x), saturate(y), color);
+        }
+    }
+
+    /// Draws an anti-aliased line between two points with Xiaolin Wu's algorithm, compositing the color over
+    /// the canvas in proportion to how much of each pixel the line covers
+    ///
+    /// Pixel centers lie at integer coordinates
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(4, 3);
+    /// canvas.draw_line_antialiased((0.0, 0.5), (3.0, 0.5), Color::new(1.0, 1.0, 1.0));
+    /// // The line runs between two rows, covering each half
+    /// assert_eq!(Color::new_with_alpha(1.0, 1.0, 1.0, 0.5), canvas.get_pixel_at(1, 0));
+    /// assert_eq!(Color::new_with_alpha(1.0, 1.0, 1.0, 0.5), canvas.get_pixel_at(1, 1));
+    /// assert_eq!(Color::default(), canvas.get_pixel_at(1, 2));
+    /// ```
+    pub fn draw_line_antialiased(&mut self, from: (f32, f32), to: (f32, f32), color: Color) {
+        let (mut x0, mut y0) = from;
+        let (mut x1, mut y1) = to;
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        if steep {
+            swap(&mut x0, &mut y0);
+            swap(&mut x1, &mut y1);
+        }
+        if x0 > x1 {
+            swap(&mut x0, &mut x1);
+            swap(&mut y0, &mut y1);
+        }
+        // Bounds of the canvas along the major and minor axes
+        let (width, height) = self.signed_size();
+        let (major, minor) = if steep {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        if x1 < -1.0 || x0 > major as f32 || y0.max(y1) < -1.0 || y0.min(y1) > minor as f32 {
+            return;
+        }
+        let gradient = if x1 == x0 { 1.0 } else { (y1 - y0) / (x1 - x0) };
+        let mut plot = |x: f32, y: f32, coverage: f32| {
+            let (x, y) = (x as i32, y as i32);
+            if steep {
+                self.plot_coverage(y, x, color, coverage);
+            } else {
+                self.plot_coverage(x, y, color, coverage);
+            }
+        };
+
+        // The end points cover their pixels in proportion to how far the line reaches into them
+        let first_x = x0.round();
+        let first_y = y0 + gradient * (first_x - x0);
+        let gap = 1.0 - fpart(x0 + 0.5);
+        plot(first_x, first_y.floor(), (1.0 - fpart(first_y)) * gap);
+        plot(first_x, first_y.floor() + 1.0, fpart(first_y) * gap);
+
+        let last_x = x1.round();
+        let last_y = y1 + gradient * (last_x - x1);
+        let gap = fpart(x1 + 0.5);
+        plot(last_x, last_y.floor(), (1.0 - fpart(last_y)) * gap);
+        plot(last_x, last_y.floor() + 1.0, fpart(last_y) * gap);
+
+        let start = (first_x as i32 + 1).max(-1);
+        let end = (last_x as i32).min(major.saturating_add(1));
+        for x in start..end {
+            let y = first_y + gradient * (x as f32 - first_x);
+            plot(x as f32, y.floor(), 1.0 - fpart(y));
+            plot(x as f32, y.floor() + 1.0, fpart(y));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_lines {
+    use super::*;
+
+    const WHITE: Color = Color::new(1.0, 1.0, 1.0);
+
+    /// Returns the coordinates of all pixels that are not transparent black
+    fn drawn(canvas: &Canvas) -> Vec<(u32, u32)> {
+        (0..canvas.height)
+            .flat_map(|y| (0..canvas.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| canvas.get_pixel_at(x, y) != Color::default())
+            .collect()
+    }
+
+    #[test]
+    fn horizontal_and_vertical() {
+        let mut canvas = Canvas::new(5, 5);
+        canvas.draw_line((1, 2), (3, 2), WHITE);
+        assert_eq!(vec![(1, 2), (2, 2), (3, 2)], drawn(&canvas));
+
+        let mut canvas = Canvas::new(5, 5);
+        canvas.draw_line((4, 3), (4, 0), WHITE);
+        assert_eq!(vec![(4, 0), (4, 1), (4, 2), (4, 3)], drawn(&canvas));
+    }
+
+    #[test]
+    fn single_pixel() {
+        let mut canvas = Canvas::new(3, 3);
+        canvas.draw_line((1, 1), (1, 1), WHITE);
+        assert_eq!(vec![(1, 1)], drawn(&canvas));
+    }
+
+    #[test]
+    fn shallow_slope() {
+        let mut canvas = Canvas::new(8, 3);
+        canvas.draw_line((0, 0), (7, 2), WHITE);
+        assert_eq!(
+            vec![
+                (0, 0),
+                (1, 0),
+                (2, 1),
+                (3, 1),
+                (4, 1),
+                (5, 1),
+                (6, 2),
+                (7, 2)
+            ],
+            drawn(&canvas)
+        );
+    }
+
+    #[test]
+    fn symmetric() {
+        // Drawing in all octants gives one pixel per step along the major axis
+        for to in [
+            (6, 2),
+            (2, 6),
+            (-2, 6),
+            (-6, 2),
+            (-6, -2),
+            (-2, -6),
+            (2, -6),
+            (6, -2),
+        ] {
+            let mut canvas = Canvas::new(13, 13);
+            canvas.draw_line((6, 6), (6 + to.0, 6 + to.1), WHITE);
+            assert_eq!(7, drawn(&canvas).len(), "{to:?}");
+            assert_eq!(WHITE, canvas.get_pixel_at(6, 6));
+            assert_eq!(
+                WHITE,
+                canvas.get_pixel_at((6 + to.0) as u32, (6 + to.1) as u32)
+            );
+        }
+    }
+
+    #[test]
+    fn clipped() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.draw_line((-2, 1), (10, 1), WHITE);
+        assert_eq!(vec![(0, 1), (1, 1), (2, 1), (3, 1)], drawn(&canvas));
+
+        let mut canvas = Canvas::new(4, 4);
+        canvas.draw_line((-1_000_000, -5), (1_000_000, -5), WHITE);
+        canvas.draw_line((4, 0), (4, 3), WHITE);
+        assert!(drawn(&canvas).is_empty());
+    }
+
+    #[test]
+    fn clipped_matches_unclipped() {
+        // Clipping skips steps without moving the pixels that remain
+        for (from, to) in [
+            ((-7, -3), (12, 9)),
+            ((11, -2), (-4, 10)),
+            ((2, -9), (6, 14)),
+            ((-3, 7), (9, -1)),
+        ] {
+            let mut large = Canvas::new(40, 40);
+            large.draw_line((from.0 + 20, from.1 + 20), (to.0 + 20, to.1 + 20), WHITE);
+            let mut clipped = Canvas::new(8, 8);
+            clipped.draw_line(from, to, WHITE);
+            let expected: Vec<(u32, u32)> = drawn(&large)
+                .into_iter()
+                .filter(|&(x, y)| (20..28).contains(&x) && (20..28).contains(&y))
+                .map(|(x, y)| (x - 20, y - 20))
+                .collect();
+            assert_eq!(expected, drawn(&clipped), "{from:?} {to:?}");
+        }
+    }
+
+    #[test]
+    fn extreme_coordinates() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.draw_line((i32::MIN, 1), (i32::MAX, 1), WHITE);
+        assert_eq!(vec![(0, 1), (1, 1), (2, 1), (3, 1)], drawn(&canvas));
+
+        let mut canvas = Canvas::new(4, 4);
+        canvas.draw_line(
+            (-2_000_000_000, -2_000_000_000),
+            (2_000_000_000, 2_000_000_000),
+            WHITE,
+        );
+        assert_eq!(vec![(0, 0), (1, 1), (2, 2), (3, 3)], drawn(&canvas));
+
+        let mut canvas = Canvas::new(4, 4);
+        canvas.draw_line((i32::MAX, i32::MIN), (i32::MIN, i32::MAX), WHITE);
+        assert!(drawn(&canvas).len() <= 4);
+    }
+
+    #[test]
+    fn antialiased_on_pixel_centers() {
+        let mut canvas = Canvas::new(5, 3);
+        canvas.draw_line_antialiased((0.0, 1.0), (4.0, 1.0), WHITE);
+        for x in 1..4 {
+            assert_eq!(WHITE, canvas.get_pixel_at(x, 1));
+            assert_eq!(Color::default(), canvas.get_pixel_at(x, 0));
+            assert_eq!(Color::default(), canvas.get_pixel_at(x, 2));
+        }
+        // End points are half covered
+        assert_eq!(0.5, canvas.get_pixel_at(0, 1).a());
+        assert_eq!(0.5, canvas.get_pixel_at(4, 1).a());
+    }
+
+    #[test]
+    fn antialiased_coverage_sums_to_one() {
+        let mut canvas = Canvas::new(10, 10);
+        canvas.draw_line_antialiased((1.0, 1.0), (8.0, 4.3), WHITE);
+        for x in 2..8 {
+            let coverage = (0..10).map(|y| canvas.get_pixel_at(x, y).a()).sum::<f32>();
+            assert!((coverage - 1.0).abs() < 1e-5, "{x} {coverage}");
+        }
+    }
+
+    #[test]
+    fn antialiased_steep() {
+        let mut canvas = Canvas::new(3, 6);
+        canvas.draw_line_antialiased((1.25, 5.0), (1.25, 0.0), WHITE);
+        for y in 1..5 {
+            assert_eq!(0.75, canvas.get_pixel_at(1, y).a());
+            assert_eq!(0.25, canvas.get_pixel_at(2, y).a());
+            assert_eq!(0.0, canvas.get_pixel_at(0, y).a());
+        }
+    }
+
+    #[test]
+    fn antialiased_blends() {
+        let mut canvas = Canvas::new(3, 3);
+        canvas.raw_buffer.fill(Color::new(0.0, 0.0, 1.0));
+        canvas.draw_line_antialiased((0.0, 0.5), (2.0, 0.5), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(Color::new(0.5, 0.0, 0.5), canvas.get_pixel_at(1, 0));
+        assert_eq!(Color::new(0.0, 0.0, 1.0), canvas.get_pixel_at(1, 2));
+    }
+
+    #[test]
+    fn antialiased_clipped() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.draw_line_antialiased((-1e9, 2.0), (1e9, 2.0), WHITE);
+        assert_eq!(4, drawn(&canvas).len());
+        canvas.draw_line_antialiased((0.0, 10.0), (3.0, 12.0), WHITE);
+        assert_eq!(4, drawn(&canvas).len());
+    }
+}
+
+impl Canvas {
+    /// Draws the outline of a circle around a pixel with the midpoint circle algorithm
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let white = Color::new(1.0, 1.0, 1.0);
+    /// let mut canvas = Canvas::new(5, 5);
+    /// canvas.draw_circle((2, 2), 2, white);
+    /// assert_eq!(white, canvas.get_pixel_at(2, 0));
+    /// assert_eq!(white, canvas.get_pixel_at(4, 2));
+    /// assert_eq!(Color::default(), canvas.get_pixel_at(2, 2));
+    /// ```
+    pub fn draw_circle(&mut self, center: (i32, i32), radius: i32, color: Color) {
+        let (cx, cy) = (i64::from(center.0), i64::from(center.1));
+        self.circle_octants(center, radius, |canvas, (x, y)| {
+            for (dx, dy) in [(x, y), (y, x), (-y, x), (-x, y)] {
+                canvas.set_pixel_clipped(saturate(cx + dx), saturate(cy + dy), color);
+                canvas.set_pixel_clipped(saturate(cx - dx), saturate(cy - dy), color);
+            }
+        });
+    }
+
+    /// Fills a disc around a pixel, covering the same pixels as [`Canvas::draw_circle`] and those inside
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let white = Color::new(1.0, 1.0, 1.0);
+    /// let mut canvas = Canvas::new(5, 5);
+    /// canvas.fill_circle((2, 2), 2, white);
+    /// assert_eq!(white, canvas.get_pixel_at(2, 2));
+    /// assert_eq!(Color::default(), canvas.get_pixel_at(0, 0));
+    /// ```
+    pub fn fill_circle(&mut self, center: (i32, i32), radius: i32, color: Color) {
+        let (cx, cy) = (i64::from(center.0), i64::from(center.1));
+        self.circle_octants(center, radius, |canvas, (x, y)| {
+            for (dx, dy) in [(x, y), (y, x)] {
+                let (left, right) = (saturate(cx - dx), saturate(cx + dx));
+                canvas.fill_span(saturate(cy + dy), left, right, color);
+                canvas.fill_span(saturate(cy - dy), left, right, color);
+            }
+        });
+    }
+
+    /// Walks the first octant of a circle, calling back with each offset from its center
+    ///
+    /// Only the steps mirroring onto a row or column of the canvas are visited, so huge circles cost no more
+    /// than the part of them that is visible
+    fn circle_octants(
+        &mut self,
+        center: (i32, i32),
+        radius: i32,
+        mut f: impl FnMut(&mut Self, (i64, i64)),
+    ) {
+        let (cx, cy, radius) = (i64::from(center.0), i64::from(center.1), i64::from(radius));
+        if radius < 0 || self.is_outside(cx - radius, cy - radius, cx + radius, cy + radius) {
+            return;
+        }
+        let (width, height) = self.signed_size();
+        let mut ranges = vec![];
+        for (center, size) in [(cx, i64::from(width)), (cy, i64::from(height))] {
+            for (first, last) in [
+                offsets_inside(center, 1, size),
+                offsets_inside(center, -1, size),
+            ] {
+                // Steps whose minor offset lands on the canvas, and those whose major offset does
+                ranges.push((first, last));
+                ranges.push((octant_y(radius, last + 1) + 1, octant_y(radius, first)));
+            }
+        }
+        ranges.sort_unstable();
+        let mut next = 0;
+        for (first, last) in ranges {
+            for y in first.max(next)..=last.min(radius) {
+                let x = octant_x(radius, y);
+                if x < y {
+                    return;
+                }
+                f(self, (x, y));
+            }
+            next = next.max(last.saturating_add(1));
+        }
+    }
+
+    /// Fills a rectangle from its top left pixel, clipped to the canvas
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let red = Color::new(1.0, 0.0, 0.0);
+    /// let mut canvas = Canvas::new(4, 4);
+    /// canvas.fill_rect((-1, 1), 3, 2, red);
+    /// assert_eq!(red, canvas.get_pixel_at(0, 1));
+    /// assert_eq!(red, canvas.get_pixel_at(1, 2));
+    /// assert_eq!(Color::default(), canvas.get_pixel_at(2, 2));
+    /// ```
+    pub fn fill_rect(&mut self, top_left: (i32, i32), width: u32, height: u32, color: Color) {
+        if width == 0 {
+            return;
+        }
+        let clamp = |x: i64| i32::try_from(x).unwrap_or(i32::MAX);
+        let right = clamp(i64::from(top_left.0) + i64::from(width) - 1);
+        let first = top_left.1.max(0);
+        let last = clamp(i64::from(top_left.1) + i64::from(height)).min(self.signed_size().1);
+        for y in first..last {
+            self.fill_span(y, top_left.0, right, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_shapes {
+    use super::*;
+
+    const WHITE: Color = Color::new(1.0, 1.0, 1.0);
+
+    fn count(canvas: &Canvas) -> usize {
+        canvas
+            .raw_buffer
+            .iter()
+            .filter(|&&c| c != Color::default())
+            .count()
+    }
+
+    #[test]
+    fn circle_outline() {
+        let mut canvas = Canvas::new(11, 11);
+        canvas.draw_circle((5, 5), 4, WHITE);
+        for y in 0..11_u32 {
+            for x in 0..11_u32 {
+                let distance = (x as f32 - 5.0).hypot(y as f32 - 5.0);
+                if canvas.get_pixel_at(x, y) == WHITE {
+                    assert!((distance - 4.0).abs() < 0.75, "{x} {y}");
+                } else {
+                    assert!((distance - 4.0).abs() > 0.4, "{x} {y}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn circle_radius_zero() {
+        let mut canvas = Canvas::new(3, 3);
+        canvas.draw_circle((1, 1), 0, WHITE);
+        assert_eq!(1, count(&canvas));
+        canvas.draw_circle((1, 1), -1, WHITE);
+        assert_eq!(1, count(&canvas));
+    }
+
+    #[test]
+    fn filled_circle_covers_outline() {
+        let mut outline = Canvas::new(21, 21);
+        outline.draw_circle((10, 10), 7, WHITE);
+        let mut disc = Canvas::new(21, 21);
+        disc.fill_circle((10, 10), 7, WHITE);
+        for (o, d) in outline.raw_buffer.iter().zip(&disc.raw_buffer) {
+            if *o == WHITE {
+                assert_eq!(WHITE, *d);
+            }
+        }
+        // Close to the area of the disc
+        let area = std::f32::consts::PI * 7.5 * 7.5;
+        assert!((count(&disc) as f32 - area).abs() < area * 0.05);
+    }
+
+    #[test]
+    fn huge_circles() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.draw_circle((0, 0), i32::MAX, WHITE);
+        canvas.fill_circle((0, 0), i32::MAX, WHITE);
+        assert_eq!(16, count(&canvas));
+
+        // The outline passes through the canvas once it is centered a radius away
+        let mut canvas = Canvas::new(4, 4);
+        canvas.draw_circle((i32::MIN + 2, 1), i32::MAX, WHITE);
+        assert_eq!(WHITE, canvas.get_pixel_at(1, 1));
+        assert_eq!(Color::default(), canvas.get_pixel_at(3, 1));
+        let mut canvas = Canvas::new(4, 4);
+        canvas.fill_circle((i32::MIN + 2, 1), i32::MAX, WHITE);
+        assert_eq!(WHITE, canvas.get_pixel_at(1, 1));
+        assert_eq!(Color::default(), canvas.get_pixel_at(3, 1));
+    }
+
+    #[test]
+    fn clipped_circles_match_unclipped() {
+        let mut large = Canvas::new(60, 60);
+        large.draw_circle((33, 41), 23, WHITE);
+        let mut clipped = Canvas::new(10, 10);
+        clipped.draw_circle((3, 11), 23, WHITE);
+        for y in 0..10 {
+            for x in 0..10 {
+                assert_eq!(
+                    large.get_pixel_at(x + 30, y + 30),
+                    clipped.get_pixel_at(x, y),
+                    "{x} {y}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn circles_clipped() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.fill_circle((0, 0), 2, WHITE);
+        assert_eq!(WHITE, canvas.get_pixel_at(0, 0));
+        assert_eq!(WHITE, canvas.get_pixel_at(2, 0));
+        assert_eq!(Color::default(), canvas.get_pixel_at(2, 2));
+        canvas.draw_circle((100, 100), 10, WHITE);
+        canvas.fill_circle((-50, 2), 10, WHITE);
+        assert_eq!(8, count(&canvas));
+    }
+
+    #[test]
+    fn rect() {
+        let mut canvas = Canvas::new(5, 4);
+        canvas.fill_rect((1, 1), 3, 2, WHITE);
+        assert_eq!(6, count(&canvas));
+        for y in 1..3 {
+            for x in 1..4 {
+                assert_eq!(WHITE, canvas.get_pixel_at(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn rect_clipped() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.fill_rect((-10, -10), 100, 100, WHITE);
+        assert_eq!(16, count(&canvas));
+
+        let mut canvas = Canvas::new(4, 4);
+        canvas.fill_rect((2, 3), u32::MAX, u32::MAX, WHITE);
+        assert_eq!(2, count(&canvas));
+        canvas.fill_rect((i32::MAX, i32::MAX), u32::MAX, u32::MAX, WHITE);
+        canvas.fill_rect((0, 0), 0, 4, WHITE);
+        canvas.fill_rect((0, 0), 4, 0, WHITE);
+        assert_eq!(2, count(&canvas));
+    }
+}
+
+impl Canvas {
+    /// Fills a triangle, interpolating the colors of its vertices across it with barycentric coordinates
+    ///
+    /// Pixels whose centers, at integer coordinates, lie inside or on the edges of the triangle are filled
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(5, 5);
+    /// canvas.fill_triangle(
+    ///     [(0.0, 0.0), (4.0, 0.0), (0.0, 4.0)],
+    ///     [Color::new(1.0, 0.0, 0.0), Color::new(0.0, 1.0, 0.0), Color::new(0.0, 0.0, 1.0)],
+    /// );
+    /// assert_eq!(Color::new(0.5, 0.5, 0.0), canvas.get_pixel_at(2, 0));
+    /// assert_eq!(Color::new(0.5, 0.25, 0.25), canvas.get_pixel_at(1, 1));
+    /// assert_eq!(Color::default(), canvas.get_pixel_at(3, 3));
+    /// ```
+    pub fn fill_triangle(&mut self, vertices: [(f32, f32); 3], colors: [Color; 3]) {
+        let [a, b, c] = vertices;
+        let area = edge(a, b, c);
+        if area == 0.0 || !area.is_finite() {
+            return;
+        }
+        let min_x = a.0.min(b.0).min(c.0).ceil().max(0.0);
+        let max_x = a.0.max(b.0).max(c.0).floor().min(self.width as f32 - 1.0);
+        let min_y = a.1.min(b.1).min(c.1).ceil().max(0.0);
+        let max_y = a.1.max(b.1).max(c.1).floor().min(self.height as f32 - 1.0);
+        if min_x > max_x || min_y > max_y {
+            return;
+        }
+        for y in min_y as u32..=max_y as u32 {
+            for x in min_x as u32..=max_x as u32 {
+                let p = (x as f32, y as f32);
+                // Dividing by the signed area makes the weights positive inside whatever the winding
+                let weights = [edge(b, c, p), edge(c, a, p), edge(a, b, p)].map(|e| e / area);
+                if weights.iter().any(|&w| w < 0.0) {
+                    continue;
+                }
+                let tuple: [f32; 4] = std::array::from_fn(|i| {
+                    weights
+                        .iter()
+                        .zip(colors)
+                        .map(|(w, color)| w * color.get_at(i))
+                        .sum()
+                });
+                self.set_pixel_at(x, y, Color::from(tuple));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_triangles {
+    use super::*;
+
+    const WHITE: Color = Color::new(1.0, 1.0, 1.0);
+
+    #[test]
+    fn covers_inside() {
+        let mut canvas = Canvas::new(6, 6);
+        canvas.fill_triangle([(0.0, 0.0), (5.0, 0.0), (0.0, 5.0)], [WHITE; 3]);
+        for y in 0..6_u32 {
+            for x in 0..6_u32 {
+                let expected = if x + y <= 5 { WHITE } else { Color::default() };
+                assert_eq!(expected, canvas.get_pixel_at(x, y), "{x} {y}");
+            }
+        }
+    }
+
+    #[test]
+    fn either_winding() {
+        let mut clockwise = Canvas::new(8, 8);
+        clockwise.fill_triangle([(1.0, 1.0), (6.0, 2.0), (3.0, 6.0)], [WHITE; 3]);
+        let mut counter_clockwise = Canvas::new(8, 8);
+        counter_clockwise.fill_triangle([(1.0, 1.0), (3.0, 6.0), (6.0, 2.0)], [WHITE; 3]);
+        assert_eq!(clockwise.raw_buffer, counter_clockwise.raw_buffer);
+        assert!(clockwise.raw_buffer.contains(&WHITE));
+    }
+
+    #[test]
+    fn vertex_colors() {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let green = Color::new(0.0, 1.0, 0.0);
+        let blue = Color::new_with_alpha(0.0, 0.0, 1.0, 0.0);
+        let mut canvas = Canvas::new(7, 7);
+        canvas.fill_triangle([(0.0, 0.0), (6.0, 0.0), (0.0, 6.0)], [red, green, blue]);
+        assert_eq!(red, canvas.get_pixel_at(0, 0));
+        assert_eq!(green, canvas.get_pixel_at(6, 0));
+        assert_eq!(blue, canvas.get_pixel_at(0, 6));
+        assert_eq!(
+            Color::new_with_alpha(1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0, 2.0 / 3.0),
+            canvas.get_pixel_at(2, 2)
+        );
+    }
+
+    #[test]
+    fn degenerate() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.fill_triangle([(0.0, 0.0), (1.0, 1.0), (3.0, 3.0)], [WHITE; 3]);
+        canvas.fill_triangle([(0.0, 0.0), (f32::NAN, 1.0), (3.0, 0.0)], [WHITE; 3]);
+        assert!(!canvas.raw_buffer.contains(&WHITE));
+    }
+
+    #[test]
+    fn clipped() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.fill_triangle(
+            [(-100.0, -100.0), (100.0, -100.0), (0.0, 100.0)],
+            [WHITE; 3],
+        );
+        assert!(canvas.raw_buffer.iter().all(|&c| c == WHITE));
+
+        let mut canvas = Canvas::new(4, 4);
+        canvas.fill_triangle([(10.0, 10.0), (20.0, 10.0), (10.0, 20.0)], [WHITE; 3]);
+        assert!(!canvas.raw_buffer.contains(&WHITE));
+    }
+}
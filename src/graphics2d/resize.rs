@@ -0,0 +1,425 @@
+use std::f32::consts::PI;
+
+use super::{canvas::Canvas, pixel::Pixel};
+use crate::core3d::color::Color;
+
+/// Reconstruction filter weighting the source pixels around each pixel of a resized canvas
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ResizeFilter {
+    /// Copies the closest source pixel, keeping hard pixel edges
+    Nearest,
+    /// Interpolates linearly between the two closest source pixels along each axis
+    #[default]
+    Bilinear,
+    /// Catmull-Rom cubic, sharper than bilinear with a slight overshoot at edges
+    Bicubic,
+    /// Windowed sinc with three lobes, the sharpest and the one ringing most at edges
+    Lanczos,
+    /// Mitchell-Netravali cubic with B = C = 1/3, balancing blur and ringing
+    Mitchell,
+}
+
+/// Mitchell-Netravali family of cubics, Catmull-Rom for B = 0, C = 0.5
+fn cubic(x: f32, b: f32, c: f32) -> f32 {
+    let x = x.abs();
+    let value = if x < 1.0 {
+        (12.0 - 9.0 * b - 6.0 * c) * x * x * x
+            + (-18.0 + 12.0 * b + 6.0 * c) * x * x
+            + (6.0 - 2.0 * b)
+    } else if x < 2.0 {
+        (-b - 6.0 * c) * x * x * x
+            + (6.0 * b + 30.0 * c) * x * x
+            + (-12.0 * b - 48.0 * c) * x
+            + (8.0 * b + 24.0 * c)
+    } else {
+        0.0
+    };
+    value / 6.0
+}
+
+/// Normalized sinc, 1 at 0 and 0 at every other integer
+fn sinc(x: f32) -> f32 {
+    if x == 0.0 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+impl ResizeFilter {
+    /// Returns the distance in source pixels beyond which the filter has no weight
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::graphics2d::resize::ResizeFilter;
+    /// assert_eq!(1.0, ResizeFilter::Bilinear.support());
+    /// assert_eq!(3.0, ResizeFilter::Lanczos.support());
+    /// ```
+    #[must_use]
+    pub const fn support(self) -> f32 {
+        match self {
+            Self::Nearest => 0.5,
+            Self::Bilinear => 1.0,
+            Self::Bicubic | Self::Mitchell => 2.0,
+            Self::Lanczos => 3.0,
+        }
+    }
+
+    /// Returns the unnormalized weight of a source pixel at distance `x` in source pixels
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::graphics2d::resize::ResizeFilter;
+    /// assert_eq!(1.0, ResizeFilter::Nearest.weight(0.25));
+    /// assert_eq!(0.0, ResizeFilter::Nearest.weight(-0.5));
+    /// assert_eq!(0.75, ResizeFilter::Bilinear.weight(-0.25));
+    /// assert_eq!(0.0, ResizeFilter::Lanczos.weight(3.0));
+    /// ```
+    #[must_use]
+    pub fn weight(self, x: f32) -> f32 {
+        match self {
+            Self::Nearest => f32::from(u8::from(x > -0.5 && x <= 0.5)),
+            Self::Bilinear => (1.0 - x.abs()).max(0.0),
+            Self::Bicubic => cubic(x, 0.0, 0.5),
+            Self::Lanczos if x.abs() < 3.0 => sinc(x) * sinc(x / 3.0),
+            Self::Lanczos => 0.0,
+            Self::Mitchell => cubic(x, 1.0 / 3.0, 1.0 / 3.0),
+        }
+    }
+
+    /// Returns the first source pixel and the normalized weights of the source pixels contributing to each target
+    /// pixel along an axis
+    ///
+    /// When shrinking, the filter is widened to cover every source pixel, averaging them instead of skipping some.
+    /// Weights of pixels beyond the edges are dropped
+    fn contributions(self, source_size: u32, target_size: u32) -> Vec<(usize, Vec<f32>)> {
+        let scale = source_size as f32 / target_size as f32;
+        let filter_scale = if self == Self::Nearest {
+            1.0
+        } else {
+            scale.max(1.0)
+        };
+        let support = self.support() * filter_scale;
+        (0..target_size)
+            .map(|target| {
+                let center = (target as f32 + 0.5) * scale;
+                let start = ((center - support).floor().max(0.0) as u32).min(source_size - 1);
+                let end = ((center + support).ceil() as u32).clamp(start + 1, source_size);
+                let weights: Vec<f32> = (start..end)
+                    .map(|source| self.weight((source as f32 + 0.5 - center) / filter_scale))
+                    .collect();
+                let sum: f32 = weights.iter().sum();
+                if sum == 0.0 {
+                    // Rounding left no pixel under the filter, fall back to the closest one
+                    let closest = (center as u32).min(source_size - 1);
+                    return (closest as usize, vec![1.0]);
+                }
+                (
+                    start as usize,
+                    weights.into_iter().map(|w| w / sum).collect(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests_resize_filter {
+    use super::*;
+
+    const FILTERS: [ResizeFilter; 5] = [
+        ResizeFilter::Nearest,
+        ResizeFilter::Bilinear,
+        ResizeFilter::Bicubic,
+        ResizeFilter::Lanczos,
+        ResizeFilter::Mitchell,
+    ];
+
+    #[test]
+    fn weights() {
+        for filter in FILTERS {
+            assert_eq!(0.0, filter.weight(-filter.support()));
+            assert_eq!(0.0, filter.weight(filter.support() + 0.1));
+            assert_eq!(filter.weight(0.3), filter.weight(-0.3), "{filter:?}");
+        }
+        // Interpolating filters go through the source pixels
+        for filter in [
+            ResizeFilter::Bilinear,
+            ResizeFilter::Bicubic,
+            ResizeFilter::Lanczos,
+        ] {
+            assert!((filter.weight(0.0) - 1.0).abs() < 1e-6);
+            assert!(filter.weight(1.0).abs() < 1e-6);
+        }
+        assert!((ResizeFilter::Mitchell.weight(0.0) - 8.0 / 9.0).abs() < 1e-6);
+        assert!(ResizeFilter::Bicubic.weight(1.5) < 0.0);
+        assert!(ResizeFilter::Lanczos.weight(1.5) < 0.0);
+    }
+
+    #[test]
+    fn contributions_sum_to_one() {
+        for filter in FILTERS {
+            for (source, target) in [(10, 3), (3, 10), (7, 7), (1, 5), (5, 1)] {
+                let contributions = filter.contributions(source, target);
+                assert_eq!(target as usize, contributions.len());
+                for (start, weights) in contributions {
+                    assert!(start + weights.len() <= source as usize);
+                    assert!((weights.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn shrinking_widens_filter() {
+        let contributions = ResizeFilter::Bilinear.contributions(8, 2);
+        assert_eq!(0, contributions[0].0);
+        assert_eq!(6, contributions[0].1.len());
+        let picked: Vec<usize> = ResizeFilter::Nearest
+            .contributions(8, 2)
+            .into_iter()
+            .map(|(start, weights)| start + weights.iter().position(|&w| w == 1.0).unwrap())
+            .collect();
+        assert_eq!(vec![2, 6], picked);
+    }
+}
+
+impl<P: Pixel> Canvas<P> {
+    /// Creates a new canvas of `width` by `height` pixels resampled from this canvas with a filter, all four
+    /// channels including alpha
+    ///
+    /// Colors are weighted by their alpha, so the color of transparent pixels does not bleed into the edges of
+    /// opaque ones
+    ///
+    /// An empty canvas resizes to a canvas of default pixels
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// # use rusty_ray_tracer::graphics2d::resize::ResizeFilter;
+    /// let mut canvas = Canvas::filled(2, 2, Color::new(0.0, 0.0, 0.0));
+    /// canvas.set_pixel_at(0, 0, Color::new(1.0, 1.0, 1.0));
+    /// canvas.set_pixel_at(1, 1, Color::new(1.0, 1.0, 1.0));
+    /// let thumbnail = canvas.resize(1, 1, ResizeFilter::Bilinear);
+    /// assert_eq!(Color::new(0.5, 0.5, 0.5), thumbnail.get_pixel_at(0, 0));
+    ///
+    /// let enlarged = canvas.resize(4, 2, ResizeFilter::Bilinear);
+    /// assert_eq!(Color::new(0.75, 0.75, 0.75), enlarged.get_pixel_at(1, 0));
+    /// ```
+    #[must_use]
+    pub fn resize(&self, width: u32, height: u32, filter: ResizeFilter) -> Self {
+        if self.raw_buffer.is_empty() || width == 0 || height == 0 {
+            return Self::filled(width, height, P::default());
+        }
+        let horizontal = filter.contributions(self.width, width);
+        let vertical = filter.contributions(self.height, height);
+        let source: Vec<[f32; 4]> = self
+            .raw_buffer
+            .iter()
+            .map(|p| p.to_color().premultiply().tuple)
+            .collect();
+
+        let rows: Vec<[f32; 4]> = source
+            .chunks(self.width as usize)
+            .flat_map(|row| {
+                horizontal
+                    .iter()
+                    .map(|(start, weights)| weighted_sum(&row[*start..], 1, weights))
+            })
+            .collect();
+        let raw_buffer = vertical
+            .iter()
+            .flat_map(|(start, weights)| {
+                let rows = &rows[start * width as usize..];
+                (0..width as usize).map(move |x| weighted_sum(&rows[x..], width as usize, weights))
+            })
+            .map(|tuple| P::from_color(Color::from(tuple).unpremultiply()))
+            .collect();
+        Self {
+            width,
+            height,
+            raw_buffer,
+        }
+    }
+}
+
+/// Sums every `stride`th value weighted, starting at the first
+fn weighted_sum(values: &[[f32; 4]], stride: usize, weights: &[f32]) -> [f32; 4] {
+    let mut sum = [0.0; 4];
+    for (value, weight) in values.iter().step_by(stride).zip(weights) {
+        for (total, channel) in sum.iter_mut().zip(value) {
+            *total += weight * channel;
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests_resize {
+    use super::*;
+    use crate::core3d::color_rgb::ColorRGB;
+
+    fn checkerboard(size: u32) -> Canvas {
+        let mut canvas = Canvas::filled(size, size, Color::new(0.0, 0.0, 0.0));
+        for y in 0..size {
+            for x in 0..size {
+                if (x + y) % 2 == 0 {
+                    canvas.set_pixel_at(x, y, Color::new(1.0, 1.0, 1.0));
+                }
+            }
+        }
+        canvas
+    }
+
+    #[test]
+    fn same_size() {
+        let canvas = checkerboard(5);
+        for filter in [
+            ResizeFilter::Nearest,
+            ResizeFilter::Bilinear,
+            ResizeFilter::Bicubic,
+            ResizeFilter::Lanczos,
+        ] {
+            assert_eq!(
+                canvas.raw_buffer,
+                canvas.resize(5, 5, filter).raw_buffer,
+                "{filter:?}"
+            );
+        }
+        // Mitchell does not interpolate, so it softens even at the same size
+        assert!(
+            canvas
+                .resize(5, 5, ResizeFilter::Mitchell)
+                .get_pixel_at(2, 2)
+                .r()
+                < 1.0
+        );
+    }
+
+    #[test]
+    fn flat_areas() {
+        let canvas = Canvas::filled(7, 5, Color::new_with_alpha(0.2, 0.4, 0.8, 0.5));
+        for filter in [
+            ResizeFilter::Nearest,
+            ResizeFilter::Bilinear,
+            ResizeFilter::Bicubic,
+            ResizeFilter::Lanczos,
+            ResizeFilter::Mitchell,
+        ] {
+            for (width, height) in [(3, 2), (14, 10), (1, 1), (20, 3)] {
+                let resized = canvas.resize(width, height, filter);
+                assert_eq!((width, height), (resized.width, resized.height));
+                assert!(resized
+                    .raw_buffer
+                    .iter()
+                    .all(|&c| c == Color::new_with_alpha(0.2, 0.4, 0.8, 0.5)));
+            }
+        }
+    }
+
+    #[test]
+    fn downsample_averages() {
+        let canvas = checkerboard(8);
+        for filter in [
+            ResizeFilter::Bilinear,
+            ResizeFilter::Bicubic,
+            ResizeFilter::Lanczos,
+            ResizeFilter::Mitchell,
+        ] {
+            let thumbnail = canvas.resize(2, 2, filter);
+            for &pixel in &thumbnail.raw_buffer {
+                assert!((pixel.r() - 0.5).abs() < 0.1, "{filter:?} {pixel:?}");
+            }
+        }
+        // Nearest picks single pixels, aliasing the pattern away
+        let nearest = canvas.resize(2, 2, ResizeFilter::Nearest);
+        assert!(nearest
+            .raw_buffer
+            .iter()
+            .all(|&c| c == Color::new(1.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn nearest_upsample() {
+        let canvas = checkerboard(2);
+        let enlarged = canvas.resize(4, 6, ResizeFilter::Nearest);
+        for y in 0..6 {
+            for x in 0..4 {
+                assert_eq!(
+                    canvas.get_pixel_at(x / 2, y / 3),
+                    enlarged.get_pixel_at(x, y)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn ringing() {
+        let mut canvas = Canvas::filled(4, 1, Color::new(0.0, 0.0, 0.0));
+        canvas.fill_rect((2, 0), 2, 1, Color::new(1.0, 1.0, 1.0));
+        let lanczos = canvas.resize(16, 1, ResizeFilter::Lanczos);
+        assert!(lanczos.raw_buffer.iter().any(|c| c.r() < 0.0));
+        let bilinear = canvas.resize(16, 1, ResizeFilter::Bilinear);
+        assert!(bilinear
+            .raw_buffer
+            .iter()
+            .all(|c| (0.0..=1.0).contains(&c.r())));
+    }
+
+    #[test]
+    fn alpha_edges() {
+        // Red hidden behind zero alpha next to opaque green
+        let mut canvas = Canvas::filled(4, 2, Color::new_with_alpha(1.0, 0.0, 0.0, 0.0));
+        canvas.fill_rect((2, 0), 2, 2, Color::new(0.0, 1.0, 0.0));
+        for filter in [
+            ResizeFilter::Bilinear,
+            ResizeFilter::Bicubic,
+            ResizeFilter::Lanczos,
+            ResizeFilter::Mitchell,
+        ] {
+            for (width, height) in [(9, 2), (3, 1)] {
+                let resized = canvas.resize(width, height, filter);
+                // The edge turns partly transparent but keeps the color of the opaque side
+                assert!(resized
+                    .raw_buffer
+                    .iter()
+                    .any(|c| c.a() > 0.0 && c.a() < 1.0));
+                for color in resized.raw_buffer.iter().filter(|c| c.a() != 0.0) {
+                    assert_eq!(0.0, color.r(), "{filter:?} {color:?}");
+                    assert!((color.g() - 1.0).abs() < 1e-5, "{filter:?} {color:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn other_pixel_types() {
+        let depth = Canvas::from_buffer(2, 1, vec![0.0_f32, 1.0]).unwrap();
+        let resized = depth.resize(4, 1, ResizeFilter::Bilinear);
+        for (expected, actual) in [0.0, 0.25, 0.75, 1.0].into_iter().zip(resized.raw_buffer) {
+            assert!((expected - actual).abs() < 1e-5);
+        }
+        let bytes = Canvas::from_buffer(2, 1, vec![[0_u8, 0, 0], [255, 255, 255]]).unwrap();
+        assert_eq!(
+            vec![[128, 128, 128]],
+            bytes.resize(1, 1, ResizeFilter::Bilinear).raw_buffer
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let canvas = Canvas::new(0, 3);
+        let resized = canvas.resize(2, 2, ResizeFilter::Lanczos);
+        assert_eq!(vec![Color::default(); 4], resized.raw_buffer);
+        let canvas = checkerboard(3);
+        assert!(canvas
+            .resize(0, 5, ResizeFilter::Bicubic)
+            .raw_buffer
+            .is_empty());
+        assert_eq!(5, canvas.resize(0, 5, ResizeFilter::Bicubic).height);
+    }
+}
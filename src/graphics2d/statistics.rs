@@ -0,0 +1,421 @@
+use super::{canvas::Canvas, pixel::Pixel};
+use crate::core3d::{color::Color, color_rgb::ColorRGB};
+
+/// Value of a pixel to collect statistics of
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Channel {
+    Red,
+    Green,
+    Blue,
+    Alpha,
+    /// Linear luminance, the brightness as perceived by the eye
+    #[default]
+    Luminance,
+}
+
+impl Channel {
+    /// Returns the value of the channel of a color
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::statistics::Channel;
+    /// let color = Color::new_with_alpha(0.0, 1.0, 0.0, 0.5);
+    /// assert_eq!(1.0, Channel::Green.value(color));
+    /// assert_eq!(0.5, Channel::Alpha.value(color));
+    /// assert!((Channel::Luminance.value(color) - 0.7152).abs() < 1e-3);
+    /// ```
+    #[must_use]
+    pub fn value(self, color: Color) -> f32 {
+        match self {
+            Self::Red => color.r(),
+            Self::Green => color.g(),
+            Self::Blue => color.b(),
+            Self::Alpha => color.a(),
+            Self::Luminance => color.luminance(),
+        }
+    }
+}
+
+/// Counts of values falling into equally wide bins between a minimum and a maximum
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+    pub min: f32,
+    pub max: f32,
+    pub bins: Vec<usize>,
+    /// Number of values below the minimum
+    pub below: usize,
+    /// Number of values above the maximum
+    pub above: usize,
+}
+
+impl Histogram {
+    /// Creates a new empty histogram of `bins` bins covering `min` to `max`
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no bins or if `max` is not greater than `min`
+    #[must_use]
+    pub fn new(min: f32, max: f32, bins: usize) -> Self {
+        assert!(bins > 0, "Histogram needs at least one bin!");
+        assert!(min < max, "Histogram range is empty!");
+        Self {
+            min,
+            max,
+            bins: vec![0; bins],
+            below: 0,
+            above: 0,
+        }
+    }
+
+    /// Counts a value, in the last bin if it equals the maximum
+    ///
+    /// Values that are not a number are ignored
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::graphics2d::statistics::Histogram;
+    /// let mut histogram = Histogram::new(0.0, 1.0, 4);
+    /// for value in [0.0, 0.3, 0.3, 1.0, 2.0, f32::NAN] {
+    ///     histogram.add(value);
+    /// }
+    /// assert_eq!(vec![1, 2, 0, 1], histogram.bins);
+    /// assert_eq!(1, histogram.above);
+    /// assert_eq!(5, histogram.count());
+    /// ```
+    pub fn add(&mut self, value: f32) {
+        if value.is_nan() {
+            return;
+        }
+        if value < self.min {
+            self.below += 1;
+        } else if value > self.max {
+            self.above += 1;
+        } else {
+            let position = (value - self.min) / (self.max - self.min) * self.bins.len() as f32;
+            let index = (position as usize).min(self.bins.len() - 1);
+            self.bins[index] += 1;
+        }
+    }
+
+    /// Returns the number of values counted, including those outside of the range
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.bins.iter().sum::<usize>() + self.below + self.above
+    }
+
+    /// Returns the range of values counted in a bin
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::graphics2d::statistics::Histogram;
+    /// let histogram = Histogram::new(-1.0, 1.0, 4);
+    /// assert_eq!((-0.5, 0.0), histogram.bin_range(1));
+    /// ```
+    #[must_use]
+    pub fn bin_range(&self, index: usize) -> (f32, f32) {
+        let width = (self.max - self.min) / self.bins.len() as f32;
+        (
+            (index as f32).mul_add(width, self.min),
+            ((index + 1) as f32).mul_add(width, self.min),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests_histogram {
+    use super::*;
+
+    #[test]
+    fn add() {
+        let mut histogram = Histogram::new(-1.0, 1.0, 2);
+        for value in [
+            -1.0,
+            -0.5,
+            0.0,
+            0.5,
+            1.0,
+            -2.0,
+            -1.5,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+        ] {
+            histogram.add(value);
+        }
+        assert_eq!(vec![2, 3], histogram.bins);
+        assert_eq!(3, histogram.below);
+        assert_eq!(1, histogram.above);
+        assert_eq!(9, histogram.count());
+    }
+
+    #[test]
+    fn bin_ranges() {
+        let histogram = Histogram::new(0.0, 1.0, 4);
+        let ranges: Vec<_> = (0..4).map(|i| histogram.bin_range(i)).collect();
+        assert_eq!(
+            vec![(0.0, 0.25), (0.25, 0.5), (0.5, 0.75), (0.75, 1.0)],
+            ranges
+        );
+    }
+
+    #[test]
+    #[should_panic = "Histogram needs at least one bin!"]
+    fn no_bins() {
+        let _ = Histogram::new(0.0, 1.0, 0);
+    }
+
+    #[test]
+    #[should_panic = "Histogram range is empty!"]
+    fn empty_range() {
+        let _ = Histogram::new(1.0, 1.0, 4);
+    }
+}
+
+/// Summary of the finite luminance values of a canvas
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LuminanceStatistics {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+}
+
+impl<P: Pixel> Canvas<P> {
+    /// Returns the values of a channel of all pixels, skipping those that are not finite
+    fn finite_values(&self, channel: Channel) -> impl Iterator<Item = f32> + '_ {
+        self.raw_buffer
+            .iter()
+            .map(move |pixel| channel.value(pixel.to_color()))
+            .filter(|value| value.is_finite())
+    }
+
+    /// Returns a histogram of a channel of all pixels with `bins` bins covering `min` to `max`
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no bins or if `max` is not greater than `min`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// # use rusty_ray_tracer::graphics2d::statistics::Channel;
+    /// let mut canvas = Canvas::filled(4, 1, Color::new(0.1, 0.0, 0.0));
+    /// canvas.set_pixel_at(3, 0, Color::new(0.9, 0.0, 0.0));
+    /// let histogram = canvas.histogram(Channel::Red, 2, 0.0, 1.0);
+    /// assert_eq!(vec![3, 1], histogram.bins);
+    /// ```
+    #[must_use]
+    pub fn histogram(&self, channel: Channel, bins: usize, min: f32, max: f32) -> Histogram {
+        let mut histogram = Histogram::new(min, max, bins);
+        for pixel in &self.raw_buffer {
+            histogram.add(channel.value(pixel.to_color()));
+        }
+        histogram
+    }
+
+    /// Returns the minimum, maximum and mean luminance of the pixels, skipping those that are not finite
+    ///
+    /// Returns `None` if there is no pixel with a finite luminance
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::filled(4, 1, Color::new(0.5, 0.5, 0.5));
+    /// canvas.set_pixel_at(0, 0, Color::new(2.5, 2.5, 2.5));
+    /// canvas.set_pixel_at(1, 0, Color::new(f32::NAN, 0.0, 0.0));
+    /// let statistics = canvas.luminance_statistics().unwrap();
+    /// assert!((statistics.max - 2.5).abs() < 1e-4);
+    /// assert!((statistics.mean - 3.5 / 3.0).abs() < 1e-4);
+    /// assert_eq!(None, Canvas::new(0, 0).luminance_statistics());
+    /// ```
+    #[must_use]
+    pub fn luminance_statistics(&self) -> Option<LuminanceStatistics> {
+        let mut count = 0_usize;
+        let mut sum = 0.0_f64;
+        let mut min = f32::INFINITY;
+        let mut max = f32::NEG_INFINITY;
+        for value in self.finite_values(Channel::Luminance) {
+            count += 1;
+            sum += f64::from(value);
+            min = min.min(value);
+            max = max.max(value);
+        }
+        (count > 0).then(|| LuminanceStatistics {
+            min,
+            max,
+            mean: (sum / count as f64) as f32,
+        })
+    }
+
+    /// Returns the value of a channel below which `percent` percent of the pixels lie, interpolating between
+    /// pixels and skipping those that are not finite
+    ///
+    /// The 50th percentile is the median, the 99th shows how bright the brightest highlights are without a few
+    /// fireflies throwing it off. Returns `None` if there is no pixel with a finite value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// # use rusty_ray_tracer::graphics2d::statistics::Channel;
+    /// let mut canvas = Canvas::new(5, 1);
+    /// for x in 0..5 {
+    ///     canvas.set_pixel_at(x, 0, Color::new(x as f32, 0.0, 0.0));
+    /// }
+    /// assert_eq!(Some(2.0), canvas.percentile(Channel::Red, 50.0));
+    /// assert_eq!(Some(3.5), canvas.percentile(Channel::Red, 87.5));
+    /// assert_eq!(Some(4.0), canvas.percentile(Channel::Red, 100.0));
+    /// ```
+    #[must_use]
+    pub fn percentile(&self, channel: Channel, percent: f32) -> Option<f32> {
+        let mut values: Vec<f32> = self.finite_values(channel).collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_unstable_by(f32::total_cmp);
+        let position = (percent.clamp(0.0, 100.0) / 100.0) * (values.len() - 1) as f32;
+        let lower = position.floor() as usize;
+        let upper = (lower + 1).min(values.len() - 1);
+        let t = position - lower as f32;
+        Some((values[upper] - values[lower]).mul_add(t, values[lower]))
+    }
+
+    /// Returns the coordinates of the pixels with a channel that is not a number or infinite, row by row
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(3, 2);
+    /// canvas.set_pixel_at(2, 0, Color::new(0.0, f32::NAN, 0.0));
+    /// canvas.set_pixel_at(1, 1, Color::new(f32::INFINITY, 0.0, 0.0));
+    /// assert_eq!(vec![(2, 0), (1, 1)], canvas.non_finite_pixels());
+    /// ```
+    #[must_use]
+    pub fn non_finite_pixels(&self) -> Vec<(u32, u32)> {
+        let width = self.width.max(1) as usize;
+        self.raw_buffer
+            .iter()
+            .enumerate()
+            .filter(|(_, pixel)| !pixel.to_color().tuple.iter().all(|c| c.is_finite()))
+            .map(|(idx, _)| ((idx % width) as u32, (idx / width) as u32))
+            .collect()
+    }
+
+    /// Returns the number of pixels whose color cannot be displayed, with a color channel below 0 or above 1
+    ///
+    /// Channels that are not a number count as out of gamut
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::filled(4, 1, Color::new(1.0, 0.5, 0.0));
+    /// canvas.set_pixel_at(0, 0, Color::new(1.5, 0.0, 0.0));
+    /// canvas.set_pixel_at(1, 0, Color::new(0.0, -0.1, 0.0));
+    /// assert_eq!(2, canvas.count_out_of_gamut());
+    /// ```
+    #[must_use]
+    pub fn count_out_of_gamut(&self) -> usize {
+        self.raw_buffer
+            .iter()
+            .map(|pixel| pixel.to_color())
+            .filter(|color| {
+                ![color.r(), color.g(), color.b()]
+                    .iter()
+                    .all(|c| (0.0..=1.0).contains(c))
+            })
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests_statistics {
+    use super::*;
+
+    fn fireflies() -> Canvas {
+        let mut canvas = Canvas::filled(10, 10, Color::new(0.2, 0.2, 0.2));
+        canvas.set_pixel_at(3, 4, Color::new(500.0, 500.0, 500.0));
+        canvas.set_pixel_at(7, 1, Color::new(f32::NAN, 0.0, 0.0));
+        canvas.set_pixel_at(0, 9, Color::new(0.0, f32::NEG_INFINITY, 0.0));
+        canvas.set_pixel_at(9, 9, Color::new_with_alpha(0.0, 0.0, 0.0, f32::INFINITY));
+        canvas
+    }
+
+    #[test]
+    fn histogram() {
+        let canvas = fireflies();
+        let histogram = canvas.histogram(Channel::Luminance, 10, 0.0, 1.0);
+        assert_eq!(96, histogram.bins[1] + histogram.bins[2]);
+        assert_eq!(1, histogram.bins[0]);
+        // The alpha of the last pixel does not change its luminance, while the NaN is not counted at all
+        assert_eq!((1, 1), (histogram.below, histogram.above));
+        assert_eq!(99, histogram.count());
+        let alpha = canvas.histogram(Channel::Alpha, 2, 0.0, 1.0);
+        assert_eq!((vec![0, 99], 1), (alpha.bins, alpha.above));
+    }
+
+    #[test]
+    fn luminance() {
+        let canvas = fireflies();
+        let statistics = canvas.luminance_statistics().unwrap();
+        assert_eq!(0.0, statistics.min);
+        assert!((statistics.max - 500.0).abs() < 0.1);
+        assert!((statistics.mean - (96.0 * 0.2 + 500.0) / 98.0).abs() < 1e-3);
+        let lost = Canvas::filled(2, 2, Color::new(f32::NAN, 0.0, 0.0));
+        assert_eq!(None, lost.luminance_statistics());
+    }
+
+    #[test]
+    fn percentiles() {
+        let canvas = fireflies();
+        // The firefly only shows up in the last percent
+        assert!((canvas.percentile(Channel::Luminance, 50.0).unwrap() - 0.2).abs() < 1e-4);
+        assert!((canvas.percentile(Channel::Luminance, 98.0).unwrap() - 0.2).abs() < 1e-4);
+        assert!(canvas.percentile(Channel::Luminance, 100.0).unwrap() > 499.0);
+        assert_eq!(Some(0.0), canvas.percentile(Channel::Blue, 0.0));
+        assert_eq!(Some(0.0), canvas.percentile(Channel::Blue, -10.0));
+        assert_eq!(Some(500.0), canvas.percentile(Channel::Blue, 1000.0));
+        assert_eq!(None, Canvas::new(0, 3).percentile(Channel::Red, 50.0));
+    }
+
+    #[test]
+    fn non_finite() {
+        let canvas = fireflies();
+        assert_eq!(vec![(7, 1), (0, 9), (9, 9)], canvas.non_finite_pixels());
+        assert!(Canvas::filled(3, 3, Color::new(1e30, -1e30, 0.0))
+            .non_finite_pixels()
+            .is_empty());
+        assert!(Canvas::new(0, 0).non_finite_pixels().is_empty());
+    }
+
+    #[test]
+    fn out_of_gamut() {
+        let canvas = fireflies();
+        // The firefly, the NaN and the negative infinity, while alpha is not a color channel
+        assert_eq!(3, canvas.count_out_of_gamut());
+        assert_eq!(0, Canvas::new(5, 5).count_out_of_gamut());
+    }
+
+    #[test]
+    fn other_pixel_types() {
+        let depth = Canvas::from_buffer(4, 1, vec![1.0_f32, 2.0, 3.0, f32::INFINITY]).unwrap();
+        assert_eq!(Some(2.0), depth.percentile(Channel::Red, 50.0));
+        assert_eq!(vec![(3, 0)], depth.non_finite_pixels());
+        let bytes = Canvas::from_buffer(2, 1, vec![[0_u8, 0, 0], [255, 255, 255]]).unwrap();
+        assert_eq!(0, bytes.count_out_of_gamut());
+        assert_eq!(
+            vec![1, 1],
+            bytes.histogram(Channel::Green, 2, 0.0, 1.0).bins
+        );
+    }
+}
@@ -0,0 +1,508 @@
+use super::{canvas::Canvas, pixel::Pixel};
+
+/// Order in which the tiles of a canvas are visited
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TileOrder {
+    /// Row by row from the top left, like the pixels themselves
+    #[default]
+    RowMajor,
+    /// Along the Morton curve, visiting nearby tiles close together in time so they share cached scene data
+    ZOrder,
+    /// Outwards from the center in a square spiral, so progressive renders show the middle of the image first
+    Spiral,
+}
+
+/// Rectangle of pixels covered by a tile, smaller than the tile size at the right and bottom edges
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TileRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Spreads the bits of a value apart, leaving a zero bit between each of them
+const fn spread_bits(value: u32) -> u64 {
+    let mut value = value as u64;
+    value = (value | value << 16) & 0x0000_FFFF_0000_FFFF;
+    value = (value | value << 8) & 0x00FF_00FF_00FF_00FF;
+    value = (value | value << 4) & 0x0F0F_0F0F_0F0F_0F0F;
+    value = (value | value << 2) & 0x3333_3333_3333_3333;
+    (value | value << 1) & 0x5555_5555_5555_5555
+}
+
+/// Returns the position of a tile on the Morton curve, interleaving the bits of its column and row
+const fn morton(column: u32, row: u32) -> u64 {
+    spread_bits(column) | spread_bits(row) << 1
+}
+
+/// Returns the column and row of each tile of a grid in a square spiral walk starting at the center tile
+fn spiral(columns: u32, rows: u32) -> Vec<(u32, u32)> {
+    const DIRECTIONS: [(i64, i64); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+    let total = columns as usize * rows as usize;
+    let mut order = Vec::with_capacity(total);
+    let (mut column, mut row) = ((i64::from(columns) - 1) / 2, (i64::from(rows) - 1) / 2);
+    let visit = |column: i64, row: i64, order: &mut Vec<(u32, u32)>| {
+        if (0..i64::from(columns)).contains(&column) && (0..i64::from(rows)).contains(&row) {
+            order.push((column as u32, row as u32));
+        }
+    };
+    visit(column, row, &mut order);
+    // Legs grow by one every second turn, 1, 1, 2, 2, 3, 3, ... tiles long
+    let mut leg = 0;
+    while order.len() < total {
+        let (dx, dy) = DIRECTIONS[leg % 4];
+        let length = leg as i64 / 2 + 1;
+        // Only the steps of the leg inside the grid are walked, so long grids skip the legs beside them at once
+        let (first, last) = [(column, dx, columns), (row, dy, rows)].into_iter().fold(
+            (1, length),
+            |(first, last), (start, step, size)| match step {
+                1 => (first.max(-start), last.min(i64::from(size) - 1 - start)),
+                -1 => (first.max(start - i64::from(size) + 1), last.min(start)),
+                _ if (0..i64::from(size)).contains(&start) => (first, last),
+                _ => (1, 0),
+            },
+        );
+        for step in first..=last {
+            visit(column + dx * step, row + dy * step, &mut order);
+        }
+        column += dx * length;
+        row += dy * length;
+        leg += 1;
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests_tile_order {
+    use super::*;
+
+    #[test]
+    fn morton_curve() {
+        let order: Vec<u64> = [(0, 0), (1, 0), (0, 1), (1, 1), (2, 0), (0, 2), (3, 3)]
+            .map(|(c, r)| morton(c, r))
+            .to_vec();
+        assert_eq!(vec![0, 1, 2, 3, 4, 8, 15], order);
+        assert_eq!(u64::MAX, morton(u32::MAX, u32::MAX));
+    }
+
+    #[test]
+    fn spiral_square() {
+        let order = spiral(3, 3);
+        let expected = [
+            (1, 1),
+            (2, 1),
+            (2, 2),
+            (1, 2),
+            (0, 2),
+            (0, 1),
+            (0, 0),
+            (1, 0),
+            (2, 0),
+        ];
+        assert_eq!(expected.to_vec(), order);
+    }
+
+    #[test]
+    fn spiral_wide() {
+        let order = spiral(5, 2);
+        assert_eq!((2, 0), order[0]);
+        assert_eq!(10, order.len());
+        let mut sorted = order.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(10, sorted.len());
+        // Tiles closer to the center come first
+        let distance = |(c, r): (u32, u32)| c.abs_diff(2).max(r.abs_diff(0));
+        assert!(order.windows(2).all(|w| distance(w[0]) <= distance(w[1])));
+    }
+
+    #[test]
+    fn spiral_matches_full_walk() {
+        for (columns, rows) in [(1, 6), (7, 2), (4, 4), (9, 5), (3, 10)] {
+            let mut expected = Vec::new();
+            let (mut column, mut row) = ((columns as i64 - 1) / 2, (rows as i64 - 1) / 2);
+            expected.push((column as u32, row as u32));
+            let mut leg = 0;
+            while expected.len() < (columns * rows) as usize {
+                let (dx, dy) = [(1, 0), (0, 1), (-1, 0), (0, -1)][leg % 4];
+                for _ in 0..leg / 2 + 1 {
+                    (column, row) = (column + dx, row + dy);
+                    if (0..columns as i64).contains(&column) && (0..rows as i64).contains(&row) {
+                        expected.push((column as u32, row as u32));
+                    }
+                }
+                leg += 1;
+            }
+            assert_eq!(expected, spiral(columns, rows), "{columns}x{rows}");
+        }
+    }
+
+    #[test]
+    fn spiral_long() {
+        let order = spiral(100_000, 1);
+        assert_eq!(100_000, order.len());
+        assert_eq!([(49_999, 0), (50_000, 0), (49_998, 0)], order[..3]);
+        assert_eq!((99_999, 0), order[order.len() - 1]);
+        let order = spiral(1, 100_001);
+        assert_eq!(100_001, order.len());
+        assert_eq!([(0, 50_000), (0, 50_001), (0, 49_999)], order[..3]);
+    }
+
+    #[test]
+    fn spiral_empty() {
+        assert!(spiral(0, 4).is_empty());
+        assert_eq!(vec![(0, 0)], spiral(1, 1));
+    }
+}
+
+/// Mutable view of the pixels of one tile, borrowing its rows from the canvas
+///
+/// The tiles of a canvas never overlap, so each of them can be handed to its own thread
+#[derive(Debug)]
+pub struct TileMut<'a, P> {
+    pub rect: TileRect,
+    rows: Vec<&'a mut [P]>,
+}
+
+impl<'a, P: Pixel> TileMut<'a, P> {
+    /// Get the pixel at x, y relative to the top left corner of the tile
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates lie outside of the tile
+    #[must_use]
+    pub fn get_pixel_at(&self, x: u32, y: u32) -> P {
+        self.rows[y as usize][x as usize]
+    }
+
+    /// Set the pixel at x, y relative to the top left corner of the tile
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates lie outside of the tile
+    pub fn set_pixel_at(&mut self, x: u32, y: u32, pixel: P) {
+        self.rows[y as usize][x as usize] = pixel;
+    }
+
+    /// Returns the rows of the tile from top to bottom, each as wide as the tile
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [P]> + use<'_, 'a, P> {
+        self.rows.iter_mut().map(|row| &mut **row)
+    }
+}
+
+impl<P: Pixel> Canvas<P> {
+    /// Returns the rectangles of the tiles covering the canvas in the given order, at most `size` by `size`
+    /// pixels each
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tile size is zero
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// # use rusty_ray_tracer::graphics2d::tile::{TileOrder, TileRect};
+    /// let canvas = Canvas::new(100, 50);
+    /// let tiles = canvas.tile_rects(64, TileOrder::RowMajor);
+    /// assert_eq!(2, tiles.len());
+    /// assert_eq!(TileRect { x: 64, y: 0, width: 36, height: 50 }, tiles[1]);
+    /// ```
+    #[must_use]
+    pub fn tile_rects(&self, size: u32, order: TileOrder) -> Vec<TileRect> {
+        assert!(size > 0, "Tile size must not be zero!");
+        let (columns, rows) = (self.width.div_ceil(size), self.height.div_ceil(size));
+        let mut grid: Vec<(u32, u32)> = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (column, row)))
+            .collect();
+        match order {
+            TileOrder::RowMajor => {}
+            TileOrder::ZOrder => grid.sort_unstable_by_key(|&(column, row)| morton(column, row)),
+            TileOrder::Spiral => grid = spiral(columns, rows),
+        }
+        grid.into_iter()
+            .map(|(column, row)| {
+                let (x, y) = (column * size, row * size);
+                TileRect {
+                    x,
+                    y,
+                    width: size.min(self.width - x),
+                    height: size.min(self.height - y),
+                }
+            })
+            .collect()
+    }
+
+    /// Splits the canvas into disjoint mutable tiles of at most `size` by `size` pixels in the given order
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tile size is zero
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// # use rusty_ray_tracer::graphics2d::tile::TileOrder;
+    /// let mut canvas = Canvas::new(4, 4);
+    /// std::thread::scope(|scope| {
+    ///     for mut tile in canvas.tiles_mut(2, TileOrder::ZOrder) {
+    ///         scope.spawn(move || {
+    ///             let shade = tile.rect.x as f32 / 4.0;
+    ///             for row in tile.rows_mut() {
+    ///                 row.fill(Color::new(shade, shade, shade));
+    ///             }
+    ///         });
+    ///     }
+    /// });
+    /// assert_eq!(Color::new(0.5, 0.5, 0.5), canvas.get_pixel_at(3, 1));
+    /// ```
+    pub fn tiles_mut(&mut self, size: u32, order: TileOrder) -> Vec<TileMut<'_, P>> {
+        let rects = self.tile_rects(size, order);
+        if rects.is_empty() {
+            return Vec::new();
+        }
+        let columns = self.width.div_ceil(size) as usize;
+        let grid_index = |x: u32, y: u32| (y / size) as usize * columns + (x / size) as usize;
+        let mut grid: Vec<Option<TileMut<'_, P>>> = vec![];
+        grid.resize_with(rects.len(), || None);
+        for &rect in &rects {
+            grid[grid_index(rect.x, rect.y)] = Some(TileMut {
+                rect,
+                rows: Vec::with_capacity(rect.height as usize),
+            });
+        }
+        for (y, row) in self.raw_buffer.chunks_mut(self.width as usize).enumerate() {
+            for (column, piece) in row.chunks_mut(size as usize).enumerate() {
+                let index = y / size as usize * columns + column;
+                if let Some(tile) = &mut grid[index] {
+                    tile.rows.push(piece);
+                }
+            }
+        }
+        rects
+            .iter()
+            .filter_map(|rect| grid[grid_index(rect.x, rect.y)].take())
+            .collect()
+    }
+
+    /// Copies the pixels of a separately rendered tile buffer back into the tile's rectangle
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer is not the size of the tile
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// # use rusty_ray_tracer::graphics2d::tile::TileOrder;
+    /// let mut canvas = Canvas::new(3, 3);
+    /// let tile = canvas.tile_rects(2, TileOrder::RowMajor)[3];
+    /// let buffer = Canvas::filled(tile.width, tile.height, Color::new(1.0, 0.0, 0.0));
+    /// canvas.merge_tile(tile, &buffer);
+    /// assert_eq!(Color::new(1.0, 0.0, 0.0), canvas.get_pixel_at(2, 2));
+    /// assert_eq!(Color::default(), canvas.get_pixel_at(1, 2));
+    /// ```
+    pub fn merge_tile(&mut self, tile: TileRect, buffer: &Self) {
+        assert!(
+            (buffer.width, buffer.height) == (tile.width, tile.height),
+            "Tile buffer size does not match the tile!"
+        );
+        let (x, y) = (
+            i32::try_from(tile.x).unwrap_or(i32::MAX),
+            i32::try_from(tile.y).unwrap_or(i32::MAX),
+        );
+        self.paste(buffer, x, y);
+    }
+}
+
+#[cfg(test)]
+mod tests_tiles {
+    use super::*;
+    use crate::{core3d::color::Color, graphics2d::fixtures::numbered};
+
+    #[test]
+    fn rects_cover_canvas() {
+        for order in [TileOrder::RowMajor, TileOrder::ZOrder, TileOrder::Spiral] {
+            for (width, height, size) in [(10, 7, 3), (8, 8, 4), (1, 1, 16), (33, 2, 8)] {
+                let canvas = Canvas::new(width, height);
+                let mut covered = vec![0; (width * height) as usize];
+                for rect in canvas.tile_rects(size, order) {
+                    assert!(rect.width <= size && rect.height <= size);
+                    for y in rect.y..rect.y + rect.height {
+                        for x in rect.x..rect.x + rect.width {
+                            covered[(y * width + x) as usize] += 1;
+                        }
+                    }
+                }
+                assert!(covered.iter().all(|&c| c == 1), "{order:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn orders() {
+        let canvas = Canvas::new(8, 8);
+        let origins = |order| -> Vec<(u32, u32)> {
+            canvas
+                .tile_rects(4, order)
+                .iter()
+                .map(|r| (r.x, r.y))
+                .collect()
+        };
+        assert_eq!(
+            vec![(0, 0), (4, 0), (0, 4), (4, 4)],
+            origins(TileOrder::RowMajor)
+        );
+        assert_eq!(
+            vec![(0, 0), (4, 0), (0, 4), (4, 4)],
+            origins(TileOrder::ZOrder)
+        );
+        assert_eq!(
+            vec![(0, 0), (4, 0), (4, 4), (0, 4)],
+            origins(TileOrder::Spiral)
+        );
+
+        let canvas = Canvas::new(16, 8);
+        let z_order: Vec<(u32, u32)> = canvas
+            .tile_rects(4, TileOrder::ZOrder)
+            .iter()
+            .map(|r| (r.x / 4, r.y / 4))
+            .collect();
+        let expected = [
+            (0, 0),
+            (1, 0),
+            (0, 1),
+            (1, 1),
+            (2, 0),
+            (3, 0),
+            (2, 1),
+            (3, 1),
+        ];
+        assert_eq!(expected.to_vec(), z_order);
+    }
+
+    #[test]
+    fn empty() {
+        let mut canvas = Canvas::new(0, 5);
+        assert!(canvas.tile_rects(4, TileOrder::Spiral).is_empty());
+        assert!(canvas.tiles_mut(4, TileOrder::Spiral).is_empty());
+    }
+
+    #[test]
+    #[should_panic = "Tile size must not be zero!"]
+    fn zero_size() {
+        let _ = Canvas::new(4, 4).tile_rects(0, TileOrder::RowMajor);
+    }
+
+    #[test]
+    fn tiles_mut_views() {
+        let mut canvas = numbered(5, 3);
+        let tiles = canvas.tiles_mut(2, TileOrder::RowMajor);
+        assert_eq!(6, tiles.len());
+        assert_eq!(
+            TileRect {
+                x: 4,
+                y: 2,
+                width: 1,
+                height: 1
+            },
+            tiles[5].rect
+        );
+        assert_eq!(14.0, tiles[5].get_pixel_at(0, 0));
+        assert_eq!(8.0, tiles[1].get_pixel_at(1, 1));
+        assert_eq!(6.0, tiles[0].get_pixel_at(1, 1));
+
+        let mut tiles = canvas.tiles_mut(2, TileOrder::Spiral);
+        assert_eq!(
+            TileRect {
+                x: 2,
+                y: 0,
+                width: 2,
+                height: 2
+            },
+            tiles[0].rect
+        );
+        tiles[0].set_pixel_at(1, 0, -1.0);
+        for row in tiles[1].rows_mut() {
+            row.fill(-2.0);
+        }
+        let rect = tiles[1].rect;
+        assert_eq!(-1.0, canvas.get_pixel_at(3, 0));
+        assert_eq!(-2.0, canvas.get_pixel_at(rect.x, rect.y));
+        assert_eq!(0.0, canvas.get_pixel_at(0, 0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn tile_mut_outside() {
+        let mut canvas = numbered(3, 3);
+        let tiles = canvas.tiles_mut(2, TileOrder::RowMajor);
+        let _ = tiles[1].get_pixel_at(1, 0);
+    }
+
+    #[test]
+    fn threads() {
+        let shade =
+            |x: u32, y: u32| Color::new(x as f32 / 37.0, y as f32 / 23.0, (x * y % 7) as f32);
+        let mut sequential = Canvas::new(37, 23);
+        for y in 0..23 {
+            for x in 0..37 {
+                sequential.set_pixel_at(x, y, shade(x, y));
+            }
+        }
+        for order in [TileOrder::RowMajor, TileOrder::ZOrder, TileOrder::Spiral] {
+            let mut parallel = Canvas::new(37, 23);
+            std::thread::scope(|scope| {
+                for mut tile in parallel.tiles_mut(8, order) {
+                    scope.spawn(move || {
+                        let rect = tile.rect;
+                        for y in 0..rect.height {
+                            for x in 0..rect.width {
+                                tile.set_pixel_at(x, y, shade(rect.x + x, rect.y + y));
+                            }
+                        }
+                    });
+                }
+            });
+            assert_eq!(sequential.raw_buffer, parallel.raw_buffer, "{order:?}");
+        }
+    }
+
+    #[test]
+    fn merge_buffers() {
+        let mut canvas = Canvas::filled(7, 5, 0.0_f32);
+        let buffers: Vec<(TileRect, Canvas<f32>)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = canvas
+                .tile_rects(3, TileOrder::Spiral)
+                .into_iter()
+                .map(|rect| {
+                    scope.spawn(move || {
+                        let value = (rect.y * 7 + rect.x) as f32;
+                        (rect, Canvas::filled(rect.width, rect.height, value))
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        for (rect, buffer) in &buffers {
+            canvas.merge_tile(*rect, buffer);
+        }
+        assert_eq!(0.0, canvas.get_pixel_at(2, 2));
+        assert_eq!(3.0, canvas.get_pixel_at(5, 1));
+        assert_eq!(27.0, canvas.get_pixel_at(6, 4));
+        assert_eq!(24.0, canvas.get_pixel_at(4, 3));
+    }
+
+    #[test]
+    #[should_panic = "Tile buffer size does not match the tile!"]
+    fn merge_wrong_size() {
+        let mut canvas = Canvas::new(4, 4);
+        let tile = canvas.tile_rects(4, TileOrder::RowMajor)[0];
+        canvas.merge_tile(tile, &Canvas::new(3, 4));
+    }
+}
@@ -0,0 +1,250 @@
+use crate::{
+    core3d::{color::Color, color_rgb::ColorRGB},
+    graphics2d::canvas::Canvas,
+};
+
+/// Linear white point of the Uncharted 2 curve, the input mapped to 1
+pub const HABLE_WHITE_POINT: f32 = 11.2;
+/// Exposure bias the Uncharted 2 curve is applied with
+const HABLE_EXPOSURE_BIAS: f32 = 2.0;
+
+/// Curve compressing unbounded linear values into the displayable range 0..1
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum ToneMapOperator {
+    /// Keeps values as they are, so everything brighter than 1 is clipped
+    #[default]
+    Linear,
+    /// Maps the luminance `L` to `L / (1 + L)`, never quite reaching white
+    Reinhard,
+    /// Reinhard's curve reaching white at the luminance of the white point
+    ExtendedReinhard { white_point: f32 },
+    /// John Hable's filmic curve from Uncharted 2, applied to each channel
+    Hable,
+    /// Krzysztof Narkowicz's fit of the ACES filmic curve, applied to each channel
+    ACES,
+}
+
+/// Compression of a rendering's linear values into displayable ones, as done by a camera
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ToneMapping {
+    pub operator: ToneMapOperator,
+    /// Exposure adjustment in stops applied before the operator, each stop doubling the brightness
+    pub exposure: f32,
+}
+
+/// Uncharted 2 curve before normalising to its white point
+fn hable_partial(x: f32) -> f32 {
+    const A: f32 = 0.15; // Shoulder strength
+    const B: f32 = 0.50; // Linear strength
+    const C: f32 = 0.10; // Linear angle
+    const D: f32 = 0.20; // Toe strength
+    const E: f32 = 0.02; // Toe numerator
+    const F: f32 = 0.30; // Toe denominator
+    ((x * (A * x + C * B) + D * E) / (x * (A * x + B) + D * F)) - E / F
+}
+
+fn aces(x: f32) -> f32 {
+    (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+}
+
+impl ToneMapping {
+    /// Creates a new tone mapping
+    #[must_use]
+    pub const fn new(operator: ToneMapOperator, exposure: f32) -> Self {
+        Self { operator, exposure }
+    }
+
+    /// Maps a linear color to the range 0..1, keeping its alpha
+    ///
+    /// Negative channels are treated as black
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::tone_mapping::{ToneMapOperator, ToneMapping};
+    /// let reinhard = ToneMapping::new(ToneMapOperator::Reinhard, 0.0);
+    /// assert_eq!(Color::new(0.5, 0.5, 0.5), reinhard.map_color(Color::new(1.0, 1.0, 1.0)));
+    ///
+    /// let brighter = ToneMapping::new(ToneMapOperator::Linear, 1.0);
+    /// assert_eq!(Color::new(0.5, 1.0, 0.0), brighter.map_color(Color::new(0.25, 3.0, -1.0)));
+    /// ```
+    #[must_use]
+    pub fn map_color(&self, color: Color) -> Color {
+        let scale = self.exposure.exp2();
+        let [r, g, b] = [color.r(), color.g(), color.b()].map(|c| (c * scale).max(0.0));
+        let exposed = Color::new(r, g, b);
+        let mapped = match self.operator {
+            ToneMapOperator::Linear => [r, g, b],
+            ToneMapOperator::Reinhard => Self::scale_luminance(&exposed, |l| l / (1.0 + l)),
+            ToneMapOperator::ExtendedReinhard { white_point } => {
+                let white_squared = white_point * white_point;
+                Self::scale_luminance(&exposed, |l| l * (1.0 + l / white_squared) / (1.0 + l))
+            }
+            ToneMapOperator::Hable => {
+                let white = hable_partial(HABLE_WHITE_POINT);
+                [r, g, b].map(|c| hable_partial(c * HABLE_EXPOSURE_BIAS) / white)
+            }
+            ToneMapOperator::ACES => [r, g, b].map(aces),
+        };
+        let [r, g, b] = mapped.map(|c| num::clamp(c, 0.0, 1.0));
+        Color::new_with_alpha(r, g, b, color.a())
+    }
+
+    /// Scales all channels of a color by the ratio of its mapped and original luminance, keeping its hue
+    fn scale_luminance(color: &Color, curve: impl Fn(f32) -> f32) -> [f32; 3] {
+        let luminance = color.luminance();
+        if luminance <= 0.0 {
+            return [0.0; 3];
+        }
+        let ratio = curve(luminance) / luminance;
+        [color.r(), color.g(), color.b()].map(|c| c * ratio)
+    }
+
+    /// Creates a new canvas holding the mapped colors of a canvas
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// # use rusty_ray_tracer::graphics2d::tone_mapping::{ToneMapOperator, ToneMapping};
+    /// let mut canvas = Canvas::new(2, 1);
+    /// canvas.set_pixel_at(1, 0, Color::new(100.0, 100.0, 100.0));
+    /// let mapped = ToneMapping::new(ToneMapOperator::ACES, 0.0).apply(&canvas);
+    /// assert_eq!(Color::new(1.0, 1.0, 1.0), mapped.get_pixel_at(1, 0));
+    /// ```
+    #[must_use]
+    pub fn apply(&self, canvas: &Canvas) -> Canvas {
+        Canvas {
+            width: canvas.width,
+            height: canvas.height,
+            raw_buffer: canvas
+                .raw_buffer
+                .iter()
+                .map(|&c| self.map_color(c))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_tone_mapping {
+    use super::*;
+
+    fn map(operator: ToneMapOperator, value: f32) -> f32 {
+        ToneMapping::new(operator, 0.0)
+            .map_color(Color::new(value, value, value))
+            .r()
+    }
+
+    #[test]
+    fn default_is_clamping() {
+        let tone_mapping = ToneMapping::default();
+        assert_eq!(ToneMapOperator::Linear, tone_mapping.operator);
+        assert_eq!(0.0, tone_mapping.exposure);
+        assert_eq!(
+            Color::new(0.25, 1.0, 0.0),
+            tone_mapping.map_color(Color::new(0.25, 7.0, -2.0))
+        );
+    }
+
+    #[test]
+    fn exposure() {
+        let darker = ToneMapping::new(ToneMapOperator::Linear, -2.0);
+        assert_eq!(
+            Color::new(0.25, 0.5, 1.0),
+            darker.map_color(Color::new(1.0, 2.0, 8.0))
+        );
+        let brighter = ToneMapping::new(ToneMapOperator::Reinhard, 1.0);
+        assert_eq!(
+            Color::new(0.5, 0.5, 0.5),
+            brighter.map_color(Color::new(0.5, 0.5, 0.5))
+        );
+    }
+
+    #[test]
+    fn reinhard() {
+        assert_eq!(0.0, map(ToneMapOperator::Reinhard, 0.0));
+        assert_eq!(0.5, map(ToneMapOperator::Reinhard, 1.0));
+        assert!((map(ToneMapOperator::Reinhard, 3.0) - 0.75).abs() < 1e-6);
+        assert!(map(ToneMapOperator::Reinhard, 1e6) < 1.0);
+    }
+
+    #[test]
+    fn reinhard_keeps_hue() {
+        let tone_mapping = ToneMapping::new(ToneMapOperator::Reinhard, 0.0);
+        let mapped = tone_mapping.map_color(Color::new(0.0, 1.0, 0.0));
+        let expected = 1.0 / (1.0 + Color::new(0.0, 1.0, 0.0).luminance());
+        assert_eq!(Color::new(0.0, expected, 0.0), mapped);
+    }
+
+    #[test]
+    fn extended_reinhard() {
+        let operator = ToneMapOperator::ExtendedReinhard { white_point: 4.0 };
+        assert!((map(operator, 4.0) - 1.0).abs() < 1e-6);
+        assert!((map(operator, 10.0) - 1.0).abs() < 1e-6);
+        assert!(map(operator, 1.0) > map(ToneMapOperator::Reinhard, 1.0));
+    }
+
+    #[test]
+    fn hable() {
+        assert!(map(ToneMapOperator::Hable, 0.0).abs() < 1e-6);
+        assert!(
+            (map(
+                ToneMapOperator::Hable,
+                HABLE_WHITE_POINT / HABLE_EXPOSURE_BIAS
+            ) - 1.0)
+                .abs()
+                < 1e-6
+        );
+        assert!(map(ToneMapOperator::Hable, 0.5) < map(ToneMapOperator::Hable, 1.0));
+    }
+
+    #[test]
+    fn aces() {
+        assert_eq!(0.0, map(ToneMapOperator::ACES, 0.0));
+        assert!((map(ToneMapOperator::ACES, 1.0) - 0.803_797_5).abs() < 1e-6);
+        assert_eq!(1.0, map(ToneMapOperator::ACES, 1000.0));
+    }
+
+    #[test]
+    fn monotonic() {
+        for operator in [
+            ToneMapOperator::Linear,
+            ToneMapOperator::Reinhard,
+            ToneMapOperator::ExtendedReinhard { white_point: 8.0 },
+            ToneMapOperator::Hable,
+            ToneMapOperator::ACES,
+        ] {
+            let values = (0..200)
+                .map(|i| map(operator, i as f32 * 0.1))
+                .collect::<Vec<_>>();
+            assert!(values.windows(2).all(|w| w[0] <= w[1]), "{operator:?}");
+            assert!(
+                values.iter().all(|v| (0.0..=1.0).contains(v)),
+                "{operator:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_alpha() {
+        let color = Color::new_with_alpha(2.0, 2.0, 2.0, 0.25);
+        let mapped = ToneMapping::new(ToneMapOperator::Hable, 0.0).map_color(color);
+        assert_eq!(0.25, mapped.a());
+    }
+
+    #[test]
+    fn apply() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.set_pixel_at(2, 1, Color::new(1.5, 0.5, 0.0));
+        let mapped = ToneMapping::new(ToneMapOperator::Reinhard, 0.0).apply(&canvas);
+        assert_eq!(3, mapped.width);
+        assert_eq!(2, mapped.height);
+        assert_eq!(Color::default(), mapped.get_pixel_at(0, 0));
+        let pixel = mapped.get_pixel_at(2, 1);
+        assert!(pixel.r() < 1.0);
+        assert!((pixel.r() / pixel.g() - 3.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,209 @@
+use crate::core3d::{color::Color, color_rgb::ColorRGB};
+
+/// Largest linear value on the straight segment of the sRGB curve
+const SRGB_LINEAR_THRESHOLD: f32 = 0.003_130_8;
+/// Largest encoded value on the straight segment of the sRGB curve
+const SRGB_ENCODED_THRESHOLD: f32 = 0.040_45;
+const SRGB_LINEAR_SLOPE: f32 = 12.92;
+const SRGB_GAMMA: f32 = 2.4;
+const SRGB_OFFSET: f32 = 0.055;
+
+/// Curve relating the linear light values a canvas holds to the encoded values stored in image files
+///
+/// Canvases are always linear, the curve is applied when writing 8-bit images and undone when reading them
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum TransferFunction {
+    /// Stores the linear values as they are
+    Linear,
+    /// The piecewise sRGB curve most images and displays use
+    #[default]
+    SRGB,
+    /// A pure power curve, encoding `x` as `x^(1 / gamma)`, for a positive and finite gamma
+    Gamma(f32),
+}
+
+impl TransferFunction {
+    /// Returns whether the curve can be applied, which power curves can only with a positive and finite gamma
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::graphics2d::transfer_function::TransferFunction;
+    /// assert!(TransferFunction::Gamma(2.2).is_valid());
+    /// assert!(!TransferFunction::Gamma(0.0).is_valid());
+    /// assert!(!TransferFunction::Gamma(f32::INFINITY).is_valid());
+    /// ```
+    #[must_use]
+    pub fn is_valid(self) -> bool {
+        match self {
+            Self::Linear | Self::SRGB => true,
+            Self::Gamma(gamma) => gamma > 0.0 && gamma.is_finite(),
+        }
+    }
+
+    /// Encodes a linear value, mirroring the curve for negative values
+    ///
+    /// The curve is not checked for every value, callers validate it once with [`TransferFunction::is_valid`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::graphics2d::transfer_function::TransferFunction;
+    /// assert_eq!(0.5, TransferFunction::Linear.encode(0.5));
+    /// assert!((TransferFunction::SRGB.encode(0.5) - 0.735_356_7).abs() < 1e-6);
+    /// assert_eq!(0.5, TransferFunction::Gamma(2.0).encode(0.25));
+    /// ```
+    #[must_use]
+    pub fn encode(self, linear: f32) -> f32 {
+        let magnitude = linear.abs();
+        let encoded = match self {
+            Self::Linear => magnitude,
+            Self::SRGB => {
+                if magnitude <= SRGB_LINEAR_THRESHOLD {
+                    magnitude * SRGB_LINEAR_SLOPE
+                } else {
+                    (1.0 + SRGB_OFFSET) * magnitude.powf(1.0 / SRGB_GAMMA) - SRGB_OFFSET
+                }
+            }
+            Self::Gamma(gamma) => magnitude.powf(1.0 / gamma),
+        };
+        encoded.copysign(linear)
+    }
+
+    /// Decodes an encoded value back to linear, the inverse of [`TransferFunction::encode`]
+    ///
+    /// The curve is not checked for every value, callers validate it once with [`TransferFunction::is_valid`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::graphics2d::transfer_function::TransferFunction;
+    /// assert_eq!(0.5, TransferFunction::Linear.decode(0.5));
+    /// assert!((TransferFunction::SRGB.decode(0.5) - 0.214_041_1).abs() < 1e-6);
+    /// assert_eq!(0.25, TransferFunction::Gamma(2.0).decode(0.5));
+    /// ```
+    #[must_use]
+    pub fn decode(self, encoded: f32) -> f32 {
+        let magnitude = encoded.abs();
+        let linear = match self {
+            Self::Linear => magnitude,
+            Self::SRGB => {
+                if magnitude <= SRGB_ENCODED_THRESHOLD {
+                    magnitude / SRGB_LINEAR_SLOPE
+                } else {
+                    ((magnitude + SRGB_OFFSET) / (1.0 + SRGB_OFFSET)).powf(SRGB_GAMMA)
+                }
+            }
+            Self::Gamma(gamma) => magnitude.powf(gamma),
+        };
+        linear.copysign(encoded)
+    }
+
+    /// Encodes the color channels of a linear color, keeping its alpha linear
+    #[must_use]
+    pub fn encode_color(self, color: Color) -> Color {
+        self.map_color(color, |c| self.encode(c))
+    }
+
+    /// Decodes the color channels of an encoded color, keeping its alpha as it is
+    #[must_use]
+    pub fn decode_color(self, color: Color) -> Color {
+        self.map_color(color, |c| self.decode(c))
+    }
+
+    fn map_color(self, color: Color, f: impl Fn(f32) -> f32) -> Color {
+        if self == Self::Linear {
+            return color;
+        }
+        let [r, g, b] = [color.r(), color.g(), color.b()].map(f);
+        Color::new_with_alpha(r, g, b, color.a())
+    }
+}
+
+#[cfg(test)]
+mod tests_transfer_function {
+    use super::*;
+
+    const ALL: [TransferFunction; 5] = [
+        TransferFunction::Linear,
+        TransferFunction::SRGB,
+        TransferFunction::Gamma(1.0),
+        TransferFunction::Gamma(1.8),
+        TransferFunction::Gamma(2.2),
+    ];
+
+    #[test]
+    fn default_is_srgb() {
+        assert_eq!(TransferFunction::SRGB, TransferFunction::default());
+    }
+
+    #[test]
+    fn end_points() {
+        for transfer in ALL {
+            assert_eq!(0.0, transfer.encode(0.0), "{transfer:?}");
+            assert_eq!(0.0, transfer.decode(0.0), "{transfer:?}");
+            assert!((transfer.encode(1.0) - 1.0).abs() < 1e-6, "{transfer:?}");
+            assert!((transfer.decode(1.0) - 1.0).abs() < 1e-6, "{transfer:?}");
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for transfer in ALL {
+            for i in 0..=1000 {
+                let value = i as f32 / 250.0 - 2.0;
+                let decoded = transfer.decode(transfer.encode(value));
+                assert!(
+                    (decoded - value).abs() <= 1e-5 * value.abs().max(1.0),
+                    "{transfer:?} {value} {decoded}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn srgb_segments() {
+        let srgb = TransferFunction::SRGB;
+        assert_eq!(0.001 * 12.92, srgb.encode(0.001));
+        assert_eq!(0.02 / 12.92, srgb.decode(0.02));
+        // Both segments meet at the thresholds
+        let below = srgb.encode(SRGB_LINEAR_THRESHOLD);
+        let above = srgb.encode(SRGB_LINEAR_THRESHOLD + 1e-7);
+        assert!((below - above).abs() < 1e-5);
+        assert!((srgb.decode(SRGB_ENCODED_THRESHOLD) - SRGB_LINEAR_THRESHOLD).abs() < 1e-5);
+    }
+
+    #[test]
+    fn brightens_midtones() {
+        for transfer in [TransferFunction::SRGB, TransferFunction::Gamma(2.2)] {
+            assert!(transfer.encode(0.18) > 0.4, "{transfer:?}");
+            assert!(transfer.decode(0.5) < 0.25, "{transfer:?}");
+        }
+    }
+
+    #[test]
+    fn negative_values_are_mirrored() {
+        for transfer in ALL {
+            assert_eq!(-transfer.encode(0.3), transfer.encode(-0.3), "{transfer:?}");
+            assert_eq!(-transfer.decode(0.3), transfer.decode(-0.3), "{transfer:?}");
+        }
+    }
+
+    #[test]
+    fn invalid_gammas() {
+        for gamma in [0.0, -2.2, f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            assert!(!TransferFunction::Gamma(gamma).is_valid(), "{gamma}");
+        }
+        assert!(ALL.iter().all(|transfer| transfer.is_valid()));
+    }
+
+    #[test]
+    fn colors_keep_alpha() {
+        let color = Color::new_with_alpha(0.25, 1.0, 0.0, 0.25);
+        let encoded = TransferFunction::Gamma(2.0).encode_color(color);
+        assert_eq!([0.5, 1.0, 0.0, 0.25], encoded.tuple);
+        let decoded = TransferFunction::Gamma(2.0).decode_color(encoded);
+        assert_eq!([0.25, 1.0, 0.0, 0.25], decoded.tuple);
+        assert_eq!(color, TransferFunction::Linear.encode_color(color));
+    }
+}
@@ -0,0 +1,363 @@
+use super::{canvas::Canvas, pixel::Pixel};
+use crate::core3d::color::Color;
+
+impl<P: Pixel> Canvas<P> {
+    /// Creates a new canvas from the `width` by `height` pixels whose top left corner is at x, y
+    ///
+    /// Returns `None` if the rectangle does not lie within the canvas
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(4, 3);
+    /// canvas.set_pixel_at(2, 1, Color::new(1.0, 0.0, 0.0));
+    /// let cropped = canvas.crop(1, 1, 3, 2).unwrap();
+    /// assert_eq!((3, 2), (cropped.width, cropped.height));
+    /// assert_eq!(Color::new(1.0, 0.0, 0.0), cropped.get_pixel_at(1, 0));
+    /// assert!(canvas.crop(2, 0, 3, 1).is_none());
+    /// ```
+    #[must_use]
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Option<Self> {
+        if u64::from(x) + u64::from(width) > u64::from(self.width)
+            || u64::from(y) + u64::from(height) > u64::from(self.height)
+        {
+            return None;
+        }
+        let raw_buffer = (y..y + height)
+            .flat_map(|row| {
+                let start = row as usize * self.width as usize + x as usize;
+                self.raw_buffer[start..start + width as usize]
+                    .iter()
+                    .copied()
+            })
+            .collect();
+        Some(Self {
+            width,
+            height,
+            raw_buffer,
+        })
+    }
+
+    /// Returns the rows of the canvas, a single empty row for a canvas without columns
+    fn rows(&self) -> std::slice::Chunks<'_, P> {
+        self.raw_buffer.chunks(self.width.max(1) as usize)
+    }
+
+    /// Creates a new canvas mirrored from left to right
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(3, 1);
+    /// canvas.set_pixel_at(0, 0, Color::new(1.0, 0.0, 0.0));
+    /// assert_eq!(Color::new(1.0, 0.0, 0.0), canvas.flip_horizontal().get_pixel_at(2, 0));
+    /// ```
+    #[must_use]
+    pub fn flip_horizontal(&self) -> Self {
+        Self {
+            width: self.width,
+            height: self.height,
+            raw_buffer: self
+                .rows()
+                .flat_map(|row| row.iter().rev().copied())
+                .collect(),
+        }
+    }
+
+    /// Creates a new canvas mirrored from top to bottom
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(1, 3);
+    /// canvas.set_pixel_at(0, 0, Color::new(1.0, 0.0, 0.0));
+    /// assert_eq!(Color::new(1.0, 0.0, 0.0), canvas.flip_vertical().get_pixel_at(0, 2));
+    /// ```
+    #[must_use]
+    pub fn flip_vertical(&self) -> Self {
+        Self {
+            width: self.width,
+            height: self.height,
+            raw_buffer: self.rows().rev().flatten().copied().collect(),
+        }
+    }
+
+    /// Creates a new canvas rotated a quarter turn clockwise, swapping width and height
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(3, 2);
+    /// canvas.set_pixel_at(0, 0, Color::new(1.0, 0.0, 0.0));
+    /// let rotated = canvas.rotate_90();
+    /// assert_eq!((2, 3), (rotated.width, rotated.height));
+    /// assert_eq!(Color::new(1.0, 0.0, 0.0), rotated.get_pixel_at(1, 0));
+    /// ```
+    #[must_use]
+    pub fn rotate_90(&self) -> Self {
+        let raw_buffer = (0..self.width)
+            .flat_map(|x| (0..self.height).rev().map(move |y| (x, y)))
+            .map(|(x, y)| self.get_pixel_at(x, y))
+            .collect();
+        Self {
+            width: self.height,
+            height: self.width,
+            raw_buffer,
+        }
+    }
+
+    /// Creates a new canvas rotated half a turn
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(3, 2);
+    /// canvas.set_pixel_at(0, 0, Color::new(1.0, 0.0, 0.0));
+    /// assert_eq!(Color::new(1.0, 0.0, 0.0), canvas.rotate_180().get_pixel_at(2, 1));
+    /// ```
+    #[must_use]
+    pub fn rotate_180(&self) -> Self {
+        Self {
+            width: self.width,
+            height: self.height,
+            raw_buffer: self.raw_buffer.iter().rev().copied().collect(),
+        }
+    }
+
+    /// Creates a new canvas rotated a quarter turn counterclockwise, swapping width and height
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(3, 2);
+    /// canvas.set_pixel_at(0, 0, Color::new(1.0, 0.0, 0.0));
+    /// let rotated = canvas.rotate_270();
+    /// assert_eq!((2, 3), (rotated.width, rotated.height));
+    /// assert_eq!(Color::new(1.0, 0.0, 0.0), rotated.get_pixel_at(0, 2));
+    /// ```
+    #[must_use]
+    pub fn rotate_270(&self) -> Self {
+        let raw_buffer = (0..self.width)
+            .rev()
+            .flat_map(|x| (0..self.height).map(move |y| (x, y)))
+            .map(|(x, y)| self.get_pixel_at(x, y))
+            .collect();
+        Self {
+            width: self.height,
+            height: self.width,
+            raw_buffer,
+        }
+    }
+
+    /// Combines the pixels of another canvas placed with its top left corner at signed coordinates x, y into this
+    /// canvas, skipping the pixels falling outside
+    fn paste_with(&mut self, source: &Self, x: i32, y: i32, combine: impl Fn(P, P) -> P) {
+        let left = i64::from(x).max(0);
+        let right = (i64::from(x) + i64::from(source.width)).min(i64::from(self.width));
+        if left >= right {
+            return;
+        }
+        let length = (right - left) as usize;
+        let source_left = (left - i64::from(x)) as usize;
+        for (source_y, source_row) in source.rows().enumerate() {
+            let target_y = i64::from(y) + source_y as i64;
+            if !(0..i64::from(self.height)).contains(&target_y) {
+                continue;
+            }
+            let start = target_y as usize * self.width as usize + left as usize;
+            let target_row = &mut self.raw_buffer[start..start + length];
+            for (target, &pixel) in target_row.iter_mut().zip(&source_row[source_left..]) {
+                *target = combine(pixel, *target);
+            }
+        }
+    }
+
+    /// Copies the pixels of another canvas into this one with its top left corner at signed coordinates x, y,
+    /// skipping the pixels falling outside
+    ///
+    /// Sub-images can be pasted by cropping them first
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::new(4, 4);
+    /// let sticker = Canvas::filled(2, 2, Color::new(1.0, 0.0, 0.0));
+    /// canvas.paste(&sticker, 3, -1);
+    /// assert_eq!(Color::new(1.0, 0.0, 0.0), canvas.get_pixel_at(3, 0));
+    /// assert_eq!(Color::default(), canvas.get_pixel_at(3, 1));
+    /// ```
+    pub fn paste(&mut self, source: &Self, x: i32, y: i32) {
+        self.paste_with(source, x, y, |pixel, _| pixel);
+    }
+}
+
+impl Canvas {
+    /// Composites the straight alpha pixels of another canvas over this one with its top left corner at signed
+    /// coordinates x, y, skipping the pixels falling outside
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rusty_ray_tracer::core3d::color::Color;
+    /// # use rusty_ray_tracer::graphics2d::canvas::Canvas;
+    /// let mut canvas = Canvas::filled(2, 1, Color::new(0.0, 0.0, 1.0));
+    /// let overlay = Canvas::filled(1, 1, Color::new_with_alpha(1.0, 0.0, 0.0, 0.5));
+    /// canvas.paste_over(&overlay, 1, 0);
+    /// assert_eq!(Color::new(0.0, 0.0, 1.0), canvas.get_pixel_at(0, 0));
+    /// assert_eq!(Color::new(0.5, 0.0, 0.5), canvas.get_pixel_at(1, 0));
+    /// ```
+    pub fn paste_over(&mut self, source: &Self, x: i32, y: i32) {
+        self.paste_with(source, x, y, Color::over);
+    }
+}
+
+#[cfg(test)]
+mod tests_crop {
+    use crate::graphics2d::fixtures::numbered;
+
+    #[test]
+    fn crop() {
+        let canvas = numbered(4, 3);
+        assert_eq!(
+            vec![5.0, 6.0, 9.0, 10.0],
+            canvas.crop(1, 1, 2, 2).unwrap().raw_buffer
+        );
+        assert_eq!(
+            canvas.raw_buffer,
+            canvas.crop(0, 0, 4, 3).unwrap().raw_buffer
+        );
+        assert!(canvas.crop(4, 3, 0, 0).unwrap().raw_buffer.is_empty());
+        assert!(canvas.crop(0, 1, 4, 3).is_none());
+        assert!(canvas.crop(u32::MAX, 0, 2, 1).is_none());
+    }
+
+    #[test]
+    fn flip() {
+        let canvas = numbered(3, 2);
+        assert_eq!(
+            vec![2.0, 1.0, 0.0, 5.0, 4.0, 3.0],
+            canvas.flip_horizontal().raw_buffer
+        );
+        assert_eq!(
+            vec![3.0, 4.0, 5.0, 0.0, 1.0, 2.0],
+            canvas.flip_vertical().raw_buffer
+        );
+        assert_eq!(
+            canvas.raw_buffer,
+            canvas.flip_vertical().flip_vertical().raw_buffer
+        );
+        assert_eq!(
+            canvas.rotate_180().raw_buffer,
+            canvas.flip_horizontal().flip_vertical().raw_buffer
+        );
+        assert!(numbered(0, 0).flip_horizontal().raw_buffer.is_empty());
+        assert!(numbered(0, 3).flip_vertical().raw_buffer.is_empty());
+    }
+
+    #[test]
+    fn rotate() {
+        let canvas = numbered(3, 2);
+        let clockwise = canvas.rotate_90();
+        assert_eq!((2, 3), (clockwise.width, clockwise.height));
+        assert_eq!(vec![3.0, 0.0, 4.0, 1.0, 5.0, 2.0], clockwise.raw_buffer);
+        assert_eq!(
+            vec![2.0, 5.0, 1.0, 4.0, 0.0, 3.0],
+            canvas.rotate_270().raw_buffer
+        );
+        assert_eq!(
+            canvas.rotate_180().raw_buffer,
+            clockwise.rotate_90().raw_buffer
+        );
+        assert_eq!(canvas.raw_buffer, clockwise.rotate_270().raw_buffer);
+        assert_eq!(
+            canvas.raw_buffer,
+            canvas
+                .rotate_90()
+                .rotate_90()
+                .rotate_90()
+                .rotate_90()
+                .raw_buffer
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_paste {
+    use super::*;
+
+    #[test]
+    fn paste_inside() {
+        let mut canvas = Canvas::filled(4, 3, 0.0);
+        let source = Canvas::from_buffer(2, 2, vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+        canvas.paste(&source, 1, 1);
+        let expected = [0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 2.0, 0.0, 0.0, 3.0, 4.0, 0.0];
+        assert_eq!(expected.to_vec(), canvas.raw_buffer);
+    }
+
+    #[test]
+    fn paste_clipped() {
+        let source = Canvas::from_buffer(2, 2, vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+        let mut canvas = Canvas::filled(2, 2, 0.0);
+        canvas.paste(&source, -1, -1);
+        assert_eq!(vec![4.0, 0.0, 0.0, 0.0], canvas.raw_buffer);
+
+        let mut canvas = Canvas::filled(2, 2, 0.0);
+        canvas.paste(&source, 1, 1);
+        assert_eq!(vec![0.0, 0.0, 0.0, 1.0], canvas.raw_buffer);
+
+        let mut canvas = Canvas::filled(2, 2, 0.0);
+        for (x, y) in [
+            (2, 0),
+            (-2, 0),
+            (0, 2),
+            (0, -2),
+            (i32::MAX, i32::MAX),
+            (i32::MIN, i32::MIN),
+        ] {
+            canvas.paste(&source, x, y);
+        }
+        assert_eq!(vec![0.0; 4], canvas.raw_buffer);
+    }
+
+    #[test]
+    fn paste_larger() {
+        let source = Canvas::from_buffer(3, 3, (1..=9).map(|i| i as f32).collect()).unwrap();
+        let mut canvas = Canvas::filled(1, 1, 0.0);
+        canvas.paste(&source, -1, -1);
+        assert_eq!(vec![5.0], canvas.raw_buffer);
+    }
+
+    #[test]
+    fn paste_sub_image() {
+        let source = Canvas::from_buffer(3, 2, (1..=6).map(|i| i as f32).collect()).unwrap();
+        let mut canvas = Canvas::filled(2, 1, 0.0);
+        canvas.paste(&source.crop(1, 1, 2, 1).unwrap(), 0, 0);
+        assert_eq!(vec![5.0, 6.0], canvas.raw_buffer);
+    }
+
+    #[test]
+    fn paste_over() {
+        let mut canvas = Canvas::filled(2, 2, Color::new(1.0, 1.0, 1.0));
+        let mut overlay = Canvas::new(2, 2);
+        overlay.set_pixel_at(0, 0, Color::new(0.0, 0.0, 0.0));
+        overlay.set_pixel_at(1, 0, Color::new_with_alpha(0.0, 0.0, 0.0, 0.25));
+        canvas.paste_over(&overlay, 0, 0);
+        assert_eq!(Color::new(0.0, 0.0, 0.0), canvas.get_pixel_at(0, 0));
+        assert_eq!(Color::new(0.75, 0.75, 0.75), canvas.get_pixel_at(1, 0));
+        assert_eq!(Color::new(1.0, 1.0, 1.0), canvas.get_pixel_at(0, 1));
+    }
+}
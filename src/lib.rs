@@ -39,14 +39,14 @@ mod projectile_tests {
         wind: Vector,
     }
 
-    const SCALE: u16 = 8; // 512
-    const CANVAS_WIDTH: u16 = 2 * SCALE;
-    const CANVAS_HEIGHT: u16 = SCALE;
+    const SCALE: u32 = 8; // 512
+    const CANVAS_WIDTH: u32 = 2 * SCALE;
+    const CANVAS_HEIGHT: u32 = SCALE;
     const POSITION_TO_CANVAS_SCALE: f32 = 0.66 * (SCALE as f32);
-    fn map_projectile_position_to_canvas(point: Point) -> (u16, u16) {
+    fn map_projectile_position_to_canvas(point: Point) -> (u32, u32) {
         (
-            (point.x() * POSITION_TO_CANVAS_SCALE) as u16,
-            (CANVAS_HEIGHT - 1) - (point.y() * POSITION_TO_CANVAS_SCALE) as u16,
+            (point.x() * POSITION_TO_CANVAS_SCALE) as u32,
+            (CANVAS_HEIGHT - 1) - (point.y() * POSITION_TO_CANVAS_SCALE) as u32,
         )
     }
 
@@ -26,7 +26,7 @@ impl CanvasWorld {
 }
 
 #[given(expr = r"c ← canvas\({int}, {int}\)")]
-fn a_canvas(world: &mut CanvasWorld, width: u16, height: u16) {
+fn a_canvas(world: &mut CanvasWorld, width: u32, height: u32) {
     let world_canvas = &mut world.c;
     *world_canvas = Canvas::new(width, height);
 }
@@ -38,12 +38,12 @@ fn a_color(world: &mut CanvasWorld, name: String, color: CaptureColor) {
 }
 
 #[then(expr = r"c.width = {int}")]
-fn dim_width_equals(world: &mut CanvasWorld, dimension: u16) {
+fn dim_width_equals(world: &mut CanvasWorld, dimension: u32) {
     assert_eq!(dimension, world.c.width);
 }
 
 #[then(expr = r"c.height = {int}")]
-fn dim_height_equals(world: &mut CanvasWorld, dimension: u16) {
+fn dim_height_equals(world: &mut CanvasWorld, dimension: u32) {
     assert_eq!(dimension, world.c.height);
 }
 
@@ -60,7 +60,7 @@ fn every_pixel_equals(world: &mut CanvasWorld) {
 }
 
 #[when(expr = r"write_pixel\(c, {int}, {int}, {word}\)")]
-fn write_pixel(world: &mut CanvasWorld, x: u16, y: u16, color_name: String) {
+fn write_pixel(world: &mut CanvasWorld, x: u32, y: u32, color_name: String) {
     let color = *world.get_color(&color_name);
     world.c.set_pixel_at(x, y, color);
 }